@@ -0,0 +1,29 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use csip::parser::Parser;
+
+const MESSAGE: &[u8] = b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+Via: SIP/2.0/UDP bigbox3.site3.atlanta.com;branch=z9hG4bK77ef4c2312983.1\r\n\
+Max-Forwards: 70\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+CSeq: 314159 INVITE\r\n\
+Contact: <sip:alice@pc33.atlanta.com>\r\n\
+Record-Route: <sip:p1.example.com;lr>\r\n\
+Route: <sip:p2.example.com;lr>\r\n\
+Allow: INVITE, ACK, CANCEL, OPTIONS, BYE\r\n\
+Supported: replaces, timer\r\n\
+User-Agent: csip\r\n\
+Subject: Lunch\r\n\
+Content-Length: 0\r\n\
+\r\n";
+
+fn parse_15_headers(c: &mut Criterion) {
+    c.bench_function("parse 15 headers", |b| {
+        b.iter(|| Parser::parse(black_box(MESSAGE)).unwrap())
+    });
+}
+
+criterion_group!(benches, parse_15_headers);
+criterion_main!(benches);
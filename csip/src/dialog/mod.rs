@@ -1,14 +1,27 @@
 use tokio::sync::mpsc;
 
 use crate::Endpoint;
-use crate::error::{DialogError, Result};
-use crate::message::headers::{CallId, Contact, From, Header, Headers, To};
-use crate::message::{Method, Params, ReasonPhrase, Scheme, StatusCode, Uri};
+use crate::error::{DialogError, Error, Result};
+use crate::message::headers::{
+    CSeq, CallId, Contact, From, Header, Headers, MaxForwards, Refresher, Route, SessionExpires,
+    Supported, To,
+};
+use crate::message::{
+    CodeClass, Method, NameAddr, Params, ReasonPhrase, Request, Scheme, StatusCode, Uri,
+};
+use crate::parser::HeaderParser;
 use crate::transaction::Role;
-use crate::transport::incoming::IncomingRequest;
+use crate::transport::incoming::{IncomingRequest, IncomingResponse};
+use crate::transport::outgoing::OutgoingRequest;
 use crate::ua::UserAgent;
 
-/**
+mod session_timer;
+mod subscription;
+
+pub use session_timer::{MIN_SESSION_INTERVAL, SessionRefresh, SessionTimer, TIMER_OPTION_TAG};
+pub use subscription::{REFER_EVENT, Subscription, SubscriptionState};
+
+/*
  * Example of SIP Dialog establishment and termination
  * (INVITE):
  *
@@ -21,6 +34,13 @@ use crate::ua::UserAgent;
  *     |<--- 200 OK -----------|    // Confirms termination → Dialog terminated
  */
 
+/// The largest initial local sequence number of a dialog.
+///
+/// RFC 3261 section 8.1.1.5 only asks for a value below 2**31,
+/// a smaller one leaves room for many requests before the
+/// sequence number is exhausted.
+const MAX_INITIAL_CSEQ: u32 = u16::MAX as u32;
+
 /// Returns `true` if this method can establish a dialog
 const fn can_establish_a_dialog(method: &Method) -> bool {
         matches!(method, Method::Invite)
@@ -36,10 +56,13 @@ pub struct Dialog {
     from: From,
     to: To,
     contact: Contact,
+    remote_target: Uri,
     secure: bool,
     route_set: Vec<RouteSet>,
     role: Role,
     usages: Vec<Box<dyn DialogUsage>>,
+    subscriptions: Vec<Subscription>,
+    session_timer: Option<SessionTimer>,
     receiver: mpsc::Receiver<DialogMessage>,
 }
 
@@ -63,6 +86,10 @@ impl Dialog {
         let local_seq_num = None;
 
         let route_set = RouteSet::from_headers(all_headers);
+        let remote_target = all_headers
+            .iter()
+            .find_map(Header::as_contact)
+            .map_or_else(|| from.uri().clone(), |contact| contact.uri.uri().clone());
         let secure = request.incoming_info.transport.transport.is_secure()
             && request.request.req_line.uri.scheme == Scheme::Sips;
 
@@ -89,16 +116,284 @@ impl Dialog {
             from,
             to,
             contact,
+            remote_target,
             secure,
             route_set,
             role: Role::UAS,
             usages: Vec::new(),
+            subscriptions: Vec::new(),
+            session_timer: None,
+            receiver,
+        };
+
+        Ok(dialog)
+    }
+
+    /// Creates a dialog on the UAC side from the sent `request`
+    /// and a response received for it, as described in RFC 3261
+    /// section 12.1.2.
+    ///
+    /// A `101-199` response with a `To` tag creates an early
+    /// dialog, a `2xx` response creates a confirmed dialog and
+    /// discards the other early dialogs created by the same
+    /// `request`.
+    pub fn create_uac(
+        ua: &UserAgent,
+        request: &Request,
+        response: &IncomingResponse,
+    ) -> Result<Self> {
+        if !can_establish_a_dialog(&request.req_line.method) {
+            return Err(DialogError::InvalidMethod.into());
+        }
+        let code = response.status();
+        let state = match code.class() {
+            CodeClass::Provisional if code != StatusCode::Trying => DialogState::Early,
+            CodeClass::Success => DialogState::Established,
+            _ => return Err(Error::InvalidStatusCode),
+        };
+        let response_headers = &response.incoming_info.mandatory_headers;
+
+        let Some(contact) = request.headers.iter().find_map(Header::as_contact).cloned() else {
+            return Err(Error::MissingHeader(Contact::NAME));
+        };
+        let (remote_target, route_set) = Self::uac_target_and_route_set(response)?;
+
+        let from = response_headers.from.clone();
+        let to = response_headers.to.clone();
+
+        if matches!(state, DialogState::Early) && to.tag().is_none() {
+            return Err(DialogError::MissingTagInToHeader.into());
+        }
+
+        let secure = response.incoming_info.transport.transport.is_secure()
+            && request.req_line.uri.scheme == Scheme::Sips;
+
+        let dialog_id = DialogId::from_response(response);
+
+        let (sender, receiver) = mpsc::channel(10);
+
+        match state {
+            DialogState::Early => ua.add_early_dialog(dialog_id.clone(), sender),
+            DialogState::Established => {
+                ua.add_dialog(dialog_id.clone(), sender);
+                ua.confirm_dialog(&dialog_id);
+            }
+        }
+
+        let mut dialog = Self {
+            endpoint: ua.endpoint().clone(),
+            id: dialog_id,
+            state,
+            // The remote sequence number MUST be empty.
+            remote_cseq: 0,
+            local_seq_num: Some(response_headers.cseq.cseq),
+            from,
+            to,
+            contact,
+            remote_target,
+            secure,
+            route_set,
+            role: Role::UAC,
+            usages: Vec::new(),
+            subscriptions: Vec::new(),
+            session_timer: None,
             receiver,
         };
+        if matches!(dialog.state, DialogState::Established) {
+            dialog.start_session_timer_from(response);
+        }
 
         Ok(dialog)
     }
 
+    /// Confirms an early UAC dialog with the `2xx` `response`
+    /// received for it.
+    ///
+    /// The remote target and route set are recomputed from the
+    /// response and the other early dialogs created by the same
+    /// request are discarded.
+    pub fn confirm(&mut self, ua: &UserAgent, response: &IncomingResponse) -> Result<()> {
+        if response.status().class() != CodeClass::Success {
+            return Err(Error::InvalidStatusCode);
+        }
+        let response_headers = &response.incoming_info.mandatory_headers;
+        let remote_tag = response_headers.to.tag().as_deref().unwrap_or_default();
+
+        if response_headers.call_id != self.id.call_id || remote_tag != self.id.remote_tag {
+            return Err(DialogError::ResponseMismatch.into());
+        }
+
+        let (remote_target, route_set) = Self::uac_target_and_route_set(response)?;
+        self.remote_target = remote_target;
+        self.route_set = route_set;
+        self.state = DialogState::Established;
+        self.start_session_timer_from(response);
+
+        ua.confirm_dialog(&self.id);
+
+        Ok(())
+    }
+
+    /// Starts the session timer negotiated by the
+    /// `Session-Expires` of a `2xx` response, where `role` is
+    /// the role of the local user agent in the transaction that
+    /// negotiated it.
+    ///
+    /// Must be called again after each successful session
+    /// refresh, which restarts the timer.
+    pub fn start_session_timer(&mut self, session_expires: &SessionExpires, role: Role) {
+        let now = self.endpoint.timer().now();
+        self.session_timer = Some(SessionTimer::new(session_expires, role, now));
+    }
+
+    /// Returns the session timer of the dialog, if any.
+    pub fn session_timer(&self) -> Option<&SessionTimer> {
+        self.session_timer.as_ref()
+    }
+
+    /// Waits for the session timer of the dialog to fire.
+    ///
+    /// If the local user agent is the refresher, this returns
+    /// the re-`INVITE` to refresh the session at half the
+    /// session interval. If the session is not refreshed in time
+    /// this returns the `BYE` to tear it down and the timer is
+    /// stopped. Never completes if there is no session timer.
+    pub async fn session_refresh(&mut self) -> Result<SessionRefresh> {
+        let Some(timer) = &self.session_timer else {
+            return std::future::pending().await;
+        };
+        let (deadline, is_refresh) = timer.next_deadline();
+        let interval = timer.interval();
+
+        self.endpoint.timer().sleep_until(deadline).await;
+
+        if is_refresh {
+            let mut request = self.create_request(Method::Invite).await?;
+            let mut supported = Supported::default();
+            supported.add_tag(TIMER_OPTION_TAG);
+            let session_expires =
+                SessionExpires::new(interval.as_secs() as u32).with_refresher(Refresher::Uac);
+
+            let headers = &mut request.request.headers;
+            headers.push(Header::Supported(supported));
+            headers.push(Header::SessionExpires(session_expires));
+            if let Some(timer) = &mut self.session_timer {
+                timer.on_refresh_sent();
+            }
+
+            Ok(SessionRefresh::Refresh(request))
+        } else {
+            let request = self.create_request(Method::Bye).await?;
+            self.session_timer = None;
+
+            Ok(SessionRefresh::Expired(request))
+        }
+    }
+
+    fn start_session_timer_from(&mut self, response: &IncomingResponse) {
+        let session_expires = response
+            .headers()
+            .iter()
+            .find_map(Header::as_session_expires);
+
+        if let Some(session_expires) = session_expires {
+            let now = self.endpoint.timer().now();
+            self.session_timer = Some(SessionTimer::new(session_expires, Role::UAC, now));
+        }
+    }
+
+    /// Returns the dialog id.
+    pub fn id(&self) -> &DialogId {
+        &self.id
+    }
+
+    /// Returns `true` if the dialog is in the early state.
+    pub fn is_early(&self) -> bool {
+        matches!(self.state, DialogState::Early)
+    }
+
+    // The remote target MUST be set to the URI from the Contact
+    // header field of the response and the route set MUST be
+    // set to the list of URIs in the Record-Route header field
+    // from the response, taken in reverse order.
+    fn uac_target_and_route_set(response: &IncomingResponse) -> Result<(Uri, Vec<RouteSet>)> {
+        let all_headers = response.headers();
+        let Some(remote_target) = all_headers
+            .iter()
+            .find_map(Header::as_contact)
+            .map(|contact| contact.uri.uri().clone())
+        else {
+            return Err(Error::MissingHeader(Contact::NAME));
+        };
+        let mut route_set = RouteSet::from_headers(all_headers);
+        route_set.reverse();
+
+        Ok((remote_target, route_set))
+    }
+
+    /// Creates a new request within the dialog, as described in
+    /// RFC 3261 section 12.2.1.1.
+    ///
+    /// The Request-URI is set to the remote target, the route
+    /// set is added as `Route` headers and the local sequence
+    /// number is incremented, except for `ACK` and `CANCEL`,
+    /// which reuse it.
+    ///
+    /// Returns [`DialogError::CSeqExhausted`] if the local
+    /// sequence number is already
+    /// [`MAX_CSEQ`](crate::message::headers::MAX_CSEQ).
+    pub async fn create_request(&mut self, method: Method) -> Result<OutgoingRequest> {
+        let (from, to) = self.local_and_remote();
+        let cseq = match self.local_seq_num {
+            // ACK and CANCEL reuse the sequence number of the INVITE
+            // (RFC 3261 sections 13.2.2.4 and 9.1).
+            Some(cseq) if matches!(method, Method::Ack | Method::Cancel) => {
+                CSeq::new(cseq, method.clone())
+            }
+            Some(cseq) => CSeq::new(cseq, method.clone())
+                .next()
+                .ok_or(DialogError::CSeqExhausted)?,
+            None => CSeq::new(rand::random_range(1..=MAX_INITIAL_CSEQ), method.clone()),
+        };
+        self.local_seq_num = Some(cseq.cseq);
+
+        let mut headers = Headers::with_capacity(7 + self.route_set.len());
+        headers.extend(self.route_set.iter().map(|route| {
+            Header::Route(Route {
+                name_addr: NameAddr::new(route.uri.clone()),
+                param: route.params.clone(),
+            })
+        }));
+        headers.push(Header::From(from));
+        headers.push(Header::To(to));
+        headers.push(Header::CallId(self.id.call_id.clone()));
+        headers.push(Header::CSeq(cseq));
+        headers.push(Header::MaxForwards(MaxForwards::new(70)));
+        headers.push(Header::Contact(self.contact.clone()));
+
+        let request = Request::with_headers(method, self.remote_target.clone(), headers);
+
+        if let Some(subscription) = Subscription::from_request(&request) {
+            self.subscriptions.push(subscription);
+        }
+
+        self.endpoint.create_outgoing_request(request, None).await
+    }
+
+    /// Returns the local (`From`) and remote (`To`) headers to
+    /// be used in requests sent within the dialog.
+    fn local_and_remote(&self) -> (From, To) {
+        match self.role {
+            Role::UAC => (self.from.clone(), self.to.clone()),
+            Role::UAS => (self.to.clone().into(), self.from.clone().into()),
+        }
+    }
+
+    /// Returns the remote target of the dialog.
+    pub fn remote_target(&self) -> &Uri {
+        &self.remote_target
+    }
+
     pub async fn receive(&mut self, request: IncomingRequest) -> Result<()> {
         // Check CSeq.
         let request_cseq = request.incoming_info.mandatory_headers.cseq.cseq;
@@ -113,6 +408,7 @@ impl Dialog {
             return Ok(());
         }
         self.remote_cseq = request_cseq;
+        self.notify_subscription(&request);
         let mut request = Some(request);
 
         for usage in self.usages.iter() {
@@ -126,6 +422,24 @@ impl Dialog {
         Ok(())
     }
 
+    /// Returns the subscription of this dialog that `notify`
+    /// belongs to, updating its state from the `NOTIFY`.
+    pub fn notify_subscription(&mut self, notify: &Request) -> Option<&Subscription> {
+        let subscription = self
+            .subscriptions
+            .iter_mut()
+            .find(|subscription| subscription.matches(notify))?;
+        subscription.on_notify(notify);
+
+        Some(subscription)
+    }
+
+    /// Returns the subscriptions created by requests sent
+    /// within this dialog.
+    pub fn subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
     pub async fn register_usage<U>(&mut self, usage: U)
     where
         U: DialogUsage,
@@ -177,6 +491,23 @@ impl DialogId {
             remote_tag,
         })
     }
+
+    /// Returns the id of the UAC dialog created by `response`.
+    pub(crate) fn from_response(response: &IncomingResponse) -> Self {
+        let headers = &response.incoming_info.mandatory_headers;
+
+        Self {
+            call_id: headers.call_id.clone(),
+            local_tag: headers.from.tag().clone().unwrap_or_default(),
+            remote_tag: headers.to.tag().clone().unwrap_or_default(),
+        }
+    }
+
+    /// Returns `true` if both dialogs were created by the same
+    /// request, that is, they only differ in the remote tag.
+    pub(crate) fn is_same_request(&self, other: &DialogId) -> bool {
+        self.call_id == other.call_id && self.local_tag == other.local_tag
+    }
 }
 
 struct RouteSet {
@@ -201,3 +532,436 @@ impl RouteSet {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::endpoint::EndpointBuilder;
+    use crate::message::SipMessage;
+    use crate::message::headers::MAX_CSEQ;
+    use crate::parser::Parser;
+    use crate::test_utils::create_test_endpoint;
+    use crate::test_utils::transport::MockTransport;
+    use crate::testing::MockTimer;
+    use crate::transport::incoming::IncomingInfo;
+    use crate::transport::{Packet, Transport, TransportMessage};
+
+    const INVITE: &[u8] = b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+Max-Forwards: 70\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+CSeq: 314159 INVITE\r\n\
+Contact: <sip:alice@pc33.atlanta.com>\r\n\
+Content-Length: 0\r\n\r\n";
+
+    fn incoming_response(status: &str, to_tag: &str) -> IncomingResponse {
+        incoming_response_with(status, to_tag, "")
+    }
+
+    fn incoming_response_with(status: &str, to_tag: &str, extra_headers: &str) -> IncomingResponse {
+        let src = format!(
+            "SIP/2.0 {status}\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+Record-Route: <sip:p2.biloxi.com;lr>\r\n\
+Record-Route: <sip:p1.atlanta.com;lr>\r\n\
+To: Bob <sip:bob@biloxi.com>;tag={to_tag}\r\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+CSeq: 314159 INVITE\r\n\
+Contact: <sip:bob@192.0.2.4>\r\n\
+{extra_headers}\
+Content-Length: 0\r\n\r\n"
+        );
+        let transport = Transport::new(MockTransport::new_udp());
+        let (msg, mandatory_headers) = Parser::new(&src)
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+        let SipMessage::Response(response) = msg else {
+            unreachable!("expected a response");
+        };
+        let packet = Packet::new(Bytes::from(src), transport.local_addr());
+
+        IncomingResponse {
+            response,
+            incoming_info: Box::new(IncomingInfo {
+                transport: TransportMessage { packet, transport },
+                mandatory_headers,
+            }),
+        }
+    }
+
+    fn create_ua() -> UserAgent {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        endpoint.transports().register_transport(transport).unwrap();
+
+        UserAgent::new(endpoint)
+    }
+
+    fn invite_request() -> Request {
+        let SipMessage::Request(request) = Parser::parse(INVITE).unwrap() else {
+            unreachable!("expected a request");
+        };
+
+        request
+    }
+
+    fn create_uac_dialog() -> Dialog {
+        let ua = create_ua();
+        let response = incoming_response("200 OK", "a6c85cf");
+
+        Dialog::create_uac(&ua, &invite_request(), &response).unwrap()
+    }
+
+    #[test]
+    fn test_create_uac_reverses_route_set() {
+        let dialog = create_uac_dialog();
+        let routes: Vec<_> = dialog
+            .route_set
+            .iter()
+            .map(|route| route.uri.to_string())
+            .collect();
+
+        assert_eq!(routes, ["sip:p1.atlanta.com;lr", "sip:p2.biloxi.com;lr"]);
+    }
+
+    #[test]
+    fn test_create_uac_extracts_remote_target_and_tags() {
+        let dialog = create_uac_dialog();
+
+        assert_eq!(dialog.remote_target().to_string(), "sip:bob@192.0.2.4");
+        assert_eq!(dialog.id.local_tag, "1928301774");
+        assert_eq!(dialog.id.remote_tag, "a6c85cf");
+        assert_eq!(dialog.local_seq_num, Some(314159));
+        assert!(matches!(dialog.role, Role::UAC));
+    }
+
+    #[tokio::test]
+    async fn test_create_request_increments_cseq() {
+        let mut dialog = create_uac_dialog();
+
+        let first = dialog.create_request(Method::Bye).await.unwrap();
+        let second = dialog.create_request(Method::Bye).await.unwrap();
+
+        let cseq = |request: &OutgoingRequest| {
+            request
+                .headers
+                .iter()
+                .find_map(Header::as_c_seq)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(cseq(&first), CSeq::new(314160, Method::Bye));
+        assert_eq!(cseq(&second), CSeq::new(314161, Method::Bye));
+    }
+
+    #[tokio::test]
+    async fn test_ack_and_cancel_reuse_cseq() {
+        let mut dialog = create_uac_dialog();
+
+        let ack = dialog.create_request(Method::Ack).await.unwrap();
+        let cancel = dialog.create_request(Method::Cancel).await.unwrap();
+
+        let cseq = |request: &OutgoingRequest| {
+            request
+                .headers
+                .iter()
+                .find_map(Header::as_c_seq)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(cseq(&ack), CSeq::new(314159, Method::Ack));
+        assert_eq!(cseq(&cancel), CSeq::new(314159, Method::Cancel));
+        assert_eq!(dialog.local_seq_num, Some(314159));
+    }
+
+    #[tokio::test]
+    async fn test_uas_request_keeps_display_names() {
+        let mut dialog = create_uac_dialog();
+        dialog.role = Role::UAS;
+
+        let request = dialog.create_request(Method::Bye).await.unwrap();
+        let from = request.headers.iter().find_map(Header::as_from).unwrap();
+        let to = request.headers.iter().find_map(Header::as_to).unwrap();
+
+        assert_eq!(from.display(), Some("Bob"));
+        assert_eq!(from.tag().as_deref(), Some("a6c85cf"));
+        assert_eq!(to.display(), Some("Alice"));
+        assert_eq!(to.tag().as_deref(), Some("1928301774"));
+    }
+
+    #[tokio::test]
+    async fn test_initial_cseq_is_bounded() {
+        let mut dialog = create_uac_dialog();
+        dialog.local_seq_num = None;
+
+        dialog.create_request(Method::Bye).await.unwrap();
+
+        let cseq = dialog.local_seq_num.unwrap();
+        assert!((1..=MAX_INITIAL_CSEQ).contains(&cseq));
+    }
+
+    #[tokio::test]
+    async fn test_create_request_fails_when_cseq_is_exhausted() {
+        let mut dialog = create_uac_dialog();
+        dialog.local_seq_num = Some(MAX_CSEQ);
+
+        let result = dialog.create_request(Method::Bye).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::DialogError(DialogError::CSeqExhausted))
+        ));
+        assert_eq!(dialog.local_seq_num, Some(MAX_CSEQ));
+    }
+
+    #[tokio::test]
+    async fn test_create_request_uses_dialog_state() {
+        let mut dialog = create_uac_dialog();
+
+        let request = dialog.create_request(Method::Bye).await.unwrap();
+        let headers = &request.headers;
+        let routes: Vec<_> = headers
+            .iter()
+            .filter_map(Header::as_route)
+            .map(|route| route.name_addr.uri.to_string())
+            .collect();
+        let from = headers.iter().find_map(Header::as_from).unwrap();
+        let to = headers.iter().find_map(Header::as_to).unwrap();
+        let call_id = headers.iter().find_map(Header::as_call_id).unwrap();
+
+        assert_eq!(request.req_line.uri.to_string(), "sip:bob@192.0.2.4");
+        assert_eq!(routes, ["sip:p1.atlanta.com;lr", "sip:p2.biloxi.com;lr"]);
+        assert_eq!(from.tag().as_deref(), Some("1928301774"));
+        assert_eq!(to.tag().as_deref(), Some("a6c85cf"));
+        assert_eq!(call_id, &dialog.id.call_id);
+    }
+
+    #[tokio::test]
+    async fn test_forked_provisional_responses_create_early_dialogs() {
+        let ua = create_ua();
+        let request = invite_request();
+
+        let mut dialog_a =
+            Dialog::create_uac(&ua, &request, &incoming_response("180 Ringing", "a")).unwrap();
+        let mut dialog_b =
+            Dialog::create_uac(&ua, &request, &incoming_response("180 Ringing", "b")).unwrap();
+
+        assert!(dialog_a.is_early() && dialog_b.is_early());
+        assert_ne!(dialog_a.id(), dialog_b.id());
+        assert_eq!(ua.early_dialogs().len(), 2);
+
+        dialog_a
+            .confirm(&ua, &incoming_response("200 OK", "a"))
+            .unwrap();
+
+        assert!(!dialog_a.is_early());
+        assert!(ua.early_dialogs().is_empty());
+        assert!(
+            dialog_b.receiver.recv().await.is_none(),
+            "the other early dialog must be discarded"
+        );
+    }
+
+    #[test]
+    fn test_confirm_rejects_response_for_other_dialog() {
+        let ua = create_ua();
+        let request = invite_request();
+        let mut dialog =
+            Dialog::create_uac(&ua, &request, &incoming_response("180 Ringing", "a")).unwrap();
+
+        let result = dialog.confirm(&ua, &incoming_response("200 OK", "b"));
+
+        assert!(result.is_err());
+        assert!(dialog.is_early());
+    }
+
+    fn notify_request(event: &str, state: &str) -> Request {
+        let src = format!(
+            "NOTIFY sip:alice@pc33.atlanta.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP 192.0.2.4;branch=z9hG4bKnashds8\r\n\
+To: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+From: Bob <sip:bob@biloxi.com>;tag=a6c85cf\r\n\
+Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+CSeq: 1 NOTIFY\r\n\
+Event: {event}\r\n\
+Subscription-State: {state}\r\n\
+Content-Length: 0\r\n\r\n"
+        );
+        let SipMessage::Request(request) = Parser::parse(src.as_bytes()).unwrap() else {
+            unreachable!("expected a request");
+        };
+
+        request
+    }
+
+    #[tokio::test]
+    async fn test_refer_creates_implicit_subscription_correlated_by_notify() {
+        let mut dialog = create_uac_dialog();
+
+        dialog.create_request(Method::Refer).await.unwrap();
+        let subscription = dialog.notify_subscription(&notify_request("refer", "active"));
+
+        let subscription = subscription.expect("NOTIFY must match the REFER subscription");
+        assert_eq!(subscription.event(), REFER_EVENT);
+        assert_eq!(subscription.id(), Some("314160"));
+        assert_eq!(subscription.state(), SubscriptionState::Active);
+
+        let notify = notify_request("refer;id=314160", "terminated;reason=noresource");
+        let subscription = dialog.notify_subscription(&notify).unwrap();
+        assert_eq!(subscription.state(), SubscriptionState::Terminated);
+    }
+
+    #[tokio::test]
+    async fn test_notify_for_other_subscription_is_not_correlated() {
+        let mut dialog = create_uac_dialog();
+
+        dialog.create_request(Method::Refer).await.unwrap();
+        dialog.create_request(Method::Bye).await.unwrap();
+
+        assert_eq!(dialog.subscriptions().len(), 1);
+        assert!(
+            dialog
+                .notify_subscription(&notify_request("refer;id=1", "active"))
+                .is_none()
+        );
+        assert!(
+            dialog
+                .notify_subscription(&notify_request("presence", "active"))
+                .is_none()
+        );
+        assert_eq!(
+            dialog.subscriptions()[0].state(),
+            SubscriptionState::Pending
+        );
+    }
+
+    fn create_uac_dialog_with_session_timer(session_expires: &str) -> Dialog {
+        let ua = create_ua();
+        let response = incoming_response_with(
+            "200 OK",
+            "a6c85cf",
+            &format!("Session-Expires: {session_expires}\r\n"),
+        );
+
+        Dialog::create_uac(&ua, &invite_request(), &response).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refresher_sends_reinvite_before_session_interval() {
+        let mut dialog = create_uac_dialog_with_session_timer("90;refresher=uac");
+        let start = tokio::time::Instant::now();
+
+        let timer = dialog.session_timer().unwrap();
+        assert!(timer.is_refresher());
+        assert_eq!(timer.interval(), std::time::Duration::from_secs(90));
+
+        let SessionRefresh::Refresh(request) = dialog.session_refresh().await.unwrap() else {
+            panic!("the refresher must refresh the session");
+        };
+        let session_expires = request
+            .headers
+            .iter()
+            .find_map(Header::as_session_expires)
+            .unwrap();
+
+        assert_eq!(start.elapsed(), std::time::Duration::from_secs(45));
+        assert_eq!(request.method(), Method::Invite);
+        assert_eq!(session_expires.delta(), 90);
+        assert_eq!(session_expires.refresher(), Some(Refresher::Uac));
+
+        let SessionRefresh::Expired(request) = dialog.session_refresh().await.unwrap() else {
+            panic!("an unrefreshed session must expire");
+        };
+        assert_eq!(start.elapsed(), std::time::Duration::from_secs(90));
+        assert_eq!(request.method(), Method::Bye);
+        assert!(dialog.session_timer().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_non_refresher_tears_down_session_before_expiry() {
+        let mut dialog = create_uac_dialog_with_session_timer("90;refresher=uas");
+        let start = tokio::time::Instant::now();
+
+        assert!(!dialog.session_timer().unwrap().is_refresher());
+
+        let SessionRefresh::Expired(request) = dialog.session_refresh().await.unwrap() else {
+            panic!("the non refresher must not refresh the session");
+        };
+        assert_eq!(start.elapsed(), std::time::Duration::from_secs(60));
+        assert_eq!(request.method(), Method::Bye);
+    }
+
+    #[tokio::test]
+    async fn test_session_refresh_is_driven_by_the_endpoint_timer() {
+        let timer = MockTimer::new();
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timer(timer.clone())
+            .build();
+        let transport = Transport::new(MockTransport::new_udp());
+        endpoint.transports().register_transport(transport).unwrap();
+        let ua = UserAgent::new(endpoint);
+        let response =
+            incoming_response_with("200 OK", "a6c85cf", "Session-Expires: 90;refresher=uac\r\n");
+        let dialog = Dialog::create_uac(&ua, &invite_request(), &response).unwrap();
+        // Waits for the next session timer event of the dialog.
+        let spawn_refresh = |mut dialog: Dialog| {
+            tokio::spawn(async move {
+                let refresh = dialog.session_refresh().await.unwrap();
+                (dialog, refresh)
+            })
+        };
+        let settle = || async {
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+        };
+
+        let refresh = spawn_refresh(dialog);
+        timer.advance(Duration::from_secs(45) - Duration::from_millis(1));
+        settle().await;
+        assert!(!refresh.is_finished(), "refreshed before half the interval");
+
+        timer.advance(Duration::from_millis(1));
+        let (dialog, SessionRefresh::Refresh(request)) = refresh.await.unwrap() else {
+            panic!("the refresher must refresh the session");
+        };
+        assert_eq!(request.method(), Method::Invite);
+
+        let expiry = spawn_refresh(dialog);
+        timer.advance(Duration::from_secs(45) - Duration::from_millis(1));
+        settle().await;
+        assert!(!expiry.is_finished(), "expired before the interval");
+
+        timer.advance(Duration::from_millis(1));
+        let (dialog, SessionRefresh::Expired(request)) = expiry.await.unwrap() else {
+            panic!("an unrefreshed session must expire");
+        };
+        assert_eq!(request.method(), Method::Bye);
+        assert!(dialog.session_timer().is_none());
+    }
+
+    #[test]
+    fn test_short_session_interval_is_raised_to_the_minimum() {
+        let dialog = create_uac_dialog_with_session_timer("30;refresher=uac");
+
+        assert_eq!(
+            dialog.session_timer().unwrap().interval(),
+            MIN_SESSION_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_no_session_timer_without_session_expires() {
+        let dialog = create_uac_dialog();
+
+        assert!(dialog.session_timer().is_none());
+    }
+}
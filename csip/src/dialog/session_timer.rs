@@ -0,0 +1,108 @@
+//! Session timers (RFC 4028) of an established dialog.
+
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::message::headers::{Refresher, SessionExpires};
+use crate::transaction::Role;
+use crate::transport::outgoing::OutgoingRequest;
+
+/// The option tag of the session timer extension.
+pub const TIMER_OPTION_TAG: &str = "timer";
+
+/// The smallest session interval allowed by `RFC4028`, a
+/// shorter `Session-Expires` is raised to it.
+pub const MIN_SESSION_INTERVAL: Duration = Duration::from_secs(90);
+
+/// The upper bound of the time before the session expiration
+/// at which the side that is not the refresher sends a `BYE`.
+const MAX_BYE_ADVANCE: Duration = Duration::from_secs(32);
+
+/// A session timer negotiated with `Session-Expires`.
+#[derive(Debug, Clone)]
+pub struct SessionTimer {
+    interval: Duration,
+    refresher: bool,
+    started_at: Instant,
+    refresh_sent: bool,
+}
+
+impl SessionTimer {
+    /// Starts a session timer at `started_at` from the
+    /// `Session-Expires` of the `2xx` response, where `role` is
+    /// the role of the local user agent in the transaction that
+    /// negotiated it.
+    ///
+    /// Without a `refresher` parameter the client of the
+    /// transaction refreshes the session. An interval below
+    /// [`MIN_SESSION_INTERVAL`] is raised to it.
+    pub fn new(session_expires: &SessionExpires, role: Role, started_at: Instant) -> Self {
+        let refresher = match session_expires.refresher().unwrap_or(Refresher::Uac) {
+            Refresher::Uac => role == Role::UAC,
+            Refresher::Uas => role == Role::UAS,
+        };
+
+        Self {
+            interval: session_expires.interval().max(MIN_SESSION_INTERVAL),
+            refresher,
+            started_at,
+            refresh_sent: false,
+        }
+    }
+
+    /// Returns the negotiated session interval.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns `true` if the local user agent must refresh the
+    /// session.
+    pub fn is_refresher(&self) -> bool {
+        self.refresher
+    }
+
+    /// Returns when the session must be refreshed, at half the
+    /// session interval, or `None` if the local user agent is
+    /// not the refresher.
+    pub fn refresh_at(&self) -> Option<Instant> {
+        self.refresher.then(|| self.started_at + self.interval / 2)
+    }
+
+    /// Returns when the session is torn down if it is not
+    /// refreshed.
+    ///
+    /// The side that is not the refresher tears it down the
+    /// smaller of 32 seconds and a third of the interval before
+    /// the session expires.
+    pub fn expires_at(&self) -> Instant {
+        if self.refresher {
+            self.started_at + self.interval
+        } else {
+            let advance = MAX_BYE_ADVANCE.min(self.interval / 3);
+            self.started_at + self.interval - advance
+        }
+    }
+
+    /// Returns the next deadline of the timer and whether it is
+    /// the refresh.
+    pub(super) fn next_deadline(&self) -> (Instant, bool) {
+        match self.refresh_at() {
+            Some(refresh_at) if !self.refresh_sent => (refresh_at, true),
+            _ => (self.expires_at(), false),
+        }
+    }
+
+    pub(super) fn on_refresh_sent(&mut self) {
+        self.refresh_sent = true;
+    }
+}
+
+/// What a dialog must do when its session timer fires.
+pub enum SessionRefresh {
+    /// The session must be refreshed by sending the re-`INVITE`.
+    Refresh(OutgoingRequest),
+    /// The session expired and must be torn down by sending the
+    /// `BYE`.
+    Expired(OutgoingRequest),
+}
@@ -0,0 +1,142 @@
+//! Subscriptions created by `SUBSCRIBE` (RFC 6665) and `REFER`
+//! (RFC 3515) requests.
+
+use crate::message::headers::{CallId, Header, Headers};
+use crate::message::{Method, Request};
+
+/// The event package of the implicit subscription created by
+/// a `REFER` request.
+pub const REFER_EVENT: &str = "refer";
+
+/// The name of the `Subscription-State` header.
+const SUBSCRIPTION_STATE: &str = "Subscription-State";
+
+/// The state of a subscription, as reported by the
+/// `Subscription-State` header of the `NOTIFY` requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionState {
+    /// The subscription request was sent, no `NOTIFY` was
+    /// received yet or the subscription is not yet authorized.
+    Pending,
+    /// The subscription was accepted.
+    Active,
+    /// The subscription has ended.
+    Terminated,
+}
+
+/// A subscription of the local user agent, created by a
+/// `SUBSCRIBE` or `REFER` request sent within a dialog.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    call_id: CallId,
+    local_tag: String,
+    event: String,
+    id: Option<String>,
+    // `REFER` creates the subscription implicitly, its first
+    // `NOTIFY` may omit the `id` parameter.
+    implicit: bool,
+    state: SubscriptionState,
+}
+
+impl Subscription {
+    /// Creates the subscription for a `SUBSCRIBE` or `REFER`
+    /// request sent by the local user agent.
+    ///
+    /// Returns `None` if the method does not create a
+    /// subscription or the request lacks the headers needed to
+    /// correlate `NOTIFY` requests.
+    pub fn from_request(request: &Request) -> Option<Self> {
+        let method = request.method();
+        if !method.is_subscription_creating() {
+            return None;
+        }
+        let headers = &request.headers;
+        let call_id = headers.iter().find_map(Header::as_call_id)?.clone();
+        let local_tag = headers.iter().find_map(Header::as_from)?.tag().clone()?;
+        let event = headers.iter().find_map(Header::as_event);
+
+        let (event, id, implicit) = match (method, event) {
+            // The `id` of the `NOTIFY` is the `CSeq` of the `REFER`.
+            (Method::Refer, _) => {
+                let cseq = headers.iter().find_map(Header::as_c_seq)?;
+                (REFER_EVENT.into(), Some(cseq.cseq.to_string()), true)
+            }
+            (_, Some(event)) => (event.package().into(), event.id().map(Into::into), false),
+            (_, None) => return None,
+        };
+
+        Some(Self {
+            call_id,
+            local_tag,
+            event,
+            id,
+            implicit,
+            state: SubscriptionState::Pending,
+        })
+    }
+
+    /// Returns the event package of the subscription.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Returns the `id` parameter of the subscription.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the current state of the subscription.
+    pub fn state(&self) -> SubscriptionState {
+        self.state
+    }
+
+    /// Returns `true` if `notify` is a `NOTIFY` request for this
+    /// subscription.
+    pub fn matches(&self, notify: &Request) -> bool {
+        if notify.method() != Method::Notify {
+            return false;
+        }
+        let headers = &notify.headers;
+        let Some(event) = headers.iter().find_map(Header::as_event) else {
+            return false;
+        };
+        let same_dialog = headers.iter().find_map(Header::as_call_id) == Some(&self.call_id)
+            && headers
+                .iter()
+                .find_map(Header::as_to)
+                .and_then(|to| to.tag().as_deref())
+                == Some(self.local_tag.as_str());
+        let same_id = match (event.id(), self.id()) {
+            (None, Some(_)) => self.implicit,
+            (id, expected) => id == expected,
+        };
+
+        same_dialog && event.package() == self.event && same_id
+    }
+
+    /// Updates the state of the subscription from the
+    /// `Subscription-State` header of a matching `NOTIFY`.
+    pub(crate) fn on_notify(&mut self, notify: &Request) {
+        if let Some(state) = subscription_state(&notify.headers) {
+            self.state = state;
+        }
+    }
+}
+
+fn subscription_state(headers: &Headers) -> Option<SubscriptionState> {
+    let header = headers
+        .iter()
+        .filter_map(Header::as_raw_header)
+        .find(|header| header.name.eq_ignore_ascii_case(SUBSCRIPTION_STATE))?;
+    let value = header.data.split(';').next()?.trim();
+
+    if value.eq_ignore_ascii_case("active") {
+        Some(SubscriptionState::Active)
+    } else if value.eq_ignore_ascii_case("pending") {
+        Some(SubscriptionState::Pending)
+    } else if value.eq_ignore_ascii_case("terminated") {
+        Some(SubscriptionState::Terminated)
+    } else {
+        None
+    }
+}
@@ -1,12 +1,25 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
+use bytes::Bytes;
+use tokio::sync::mpsc;
 use utils::DnsResolver;
 
-use super::{Endpoint, EndpointHandler};
+use super::{Endpoint, EndpointHandler, UnhandledMessage};
+use crate::MediaType;
 use crate::endpoint::EndpointInner;
 use crate::message::headers::{Header, Headers};
+use crate::message::{BodyCodecs, HostPort, ReasonPhrase, StatusCode};
 use crate::transaction::manager::TransactionManager;
-use crate::transport::TransportManager;
+use crate::transaction::{Timer, TimerConfig, TokioTimer};
+use crate::transport::keepalive::KeepAliveConfig;
+use crate::transport::retry::SendRetryConfig;
+use crate::transport::{TransportManager, TransportType};
+
+/// Default size above which requests are not sent over UDP.
+const DEFAULT_UDP_SIZE_THRESHOLD: usize = 1300;
 
 /// EndpointBuilder for creating a new SIP `Endpoint`.
 pub struct EndpointBuilder {
@@ -16,6 +29,16 @@ pub struct EndpointBuilder {
     transports: Option<TransportManager>,
     capabilities: Headers,
     handler: Option<Box<dyn EndpointHandler>>,
+    udp_size_threshold: usize,
+    timers: TimerConfig,
+    timer: Arc<dyn Timer>,
+    advertised: HashMap<(TransportType, SocketAddr), HostPort>,
+    keepalive: Option<KeepAliveConfig>,
+    send_retry: SendRetryConfig,
+    reasons: HashMap<StatusCode, ReasonPhrase>,
+    codecs: BodyCodecs,
+    udp_addrs: Vec<SocketAddr>,
+    unhandled: Option<mpsc::Sender<UnhandledMessage>>,
 }
 
 impl EndpointBuilder {
@@ -38,6 +61,16 @@ impl EndpointBuilder {
             handler: None,
             transaction: None,
             transports: Default::default(),
+            udp_size_threshold: DEFAULT_UDP_SIZE_THRESHOLD,
+            timers: TimerConfig::default(),
+            timer: Arc::new(TokioTimer),
+            advertised: HashMap::new(),
+            keepalive: None,
+            send_retry: SendRetryConfig::default(),
+            reasons: HashMap::new(),
+            codecs: BodyCodecs::new(),
+            udp_addrs: Vec::new(),
+            unhandled: None,
         }
     }
 
@@ -106,6 +139,217 @@ impl EndpointBuilder {
         self
     }
 
+    /// Sets the size in bytes above which requests are sent over TCP
+    /// instead of UDP.
+    ///
+    /// Defaults to `1300`, as defined in RFC 3261 section 18.1.1.
+    pub fn with_udp_size_threshold(mut self, size: usize) -> Self {
+        self.udp_size_threshold = size;
+
+        self
+    }
+
+    /// Sets the transaction timer values (`T1`, `T2` and `T4`)
+    /// and the retransmission jitter and cap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use csip::*;
+    /// # use csip::transaction::TimerConfig;
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_timers(TimerConfig {
+    ///         t1: Duration::from_millis(100),
+    ///         ..Default::default()
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn with_timers(mut self, timers: TimerConfig) -> Self {
+        self.timers = timers;
+
+        self
+    }
+
+    /// Sets the clock the transaction timers are scheduled on,
+    /// [`TokioTimer`] by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::*;
+    /// # use csip::transaction::TokioTimer;
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_timer(TokioTimer)
+    ///     .build();
+    /// ```
+    pub fn with_timer(mut self, timer: impl Timer) -> Self {
+        self.timer = Arc::new(timer);
+
+        self
+    }
+
+    /// Sets the address advertised in the `Via` sent-by and
+    /// `From` of requests sent over the `transport` bound to
+    /// `bind`, instead of the address it is bound to.
+    ///
+    /// This is needed when the endpoint is behind a NAT or
+    /// has more than one interface. A `bind` address with an
+    /// unspecified IP, like `0.0.0.0:5060`, also applies to the
+    /// connections accepted on that port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::*;
+    /// # use csip::transport::TransportType;
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_udp_address("192.168.0.10:5060".parse().unwrap())
+    ///     .with_advertised_address(
+    ///         TransportType::Udp,
+    ///         "192.168.0.10:5060".parse().unwrap(),
+    ///         "203.0.113.10:5060".parse().unwrap(),
+    ///     )
+    ///     .build();
+    /// ```
+    pub fn with_advertised_address(
+        mut self,
+        transport: TransportType,
+        bind: SocketAddr,
+        address: HostPort,
+    ) -> Self {
+        self.advertised.insert((transport, bind), address);
+
+        self
+    }
+
+    /// Adds an address to bind a UDP transport to when
+    /// [`Endpoint::start_transports`] is called.
+    ///
+    /// This function can be called multiple times, for example
+    /// with an IPv4 and an IPv6 address. Requests sent over UDP
+    /// use the transport of the same address family as their
+    /// destination.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::*;
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_udp_address("0.0.0.0:5060".parse().unwrap())
+    ///     .with_udp_address("[::]:5060".parse().unwrap())
+    ///     .build();
+    /// ```
+    pub fn with_udp_address(mut self, addr: SocketAddr) -> Self {
+        self.udp_addrs.push(addr);
+
+        self
+    }
+
+    /// Enables keep-alive pings on the reliable connections
+    /// opened by the endpoint.
+    ///
+    /// Disabled by default. See [`KeepAliveConfig`].
+    pub fn with_keepalive(mut self, config: KeepAliveConfig) -> Self {
+        self.keepalive = Some(config);
+
+        self
+    }
+
+    /// Sets the channel that receives the messages no handler
+    /// took, instead of only logging them.
+    ///
+    /// Without an [`EndpointHandler`] every request and every
+    /// response that does not match a transaction is sent to
+    /// `sink`, which is useful for debugging and catch-all
+    /// handling. With a handler, `sink` receives the requests
+    /// it hands back with [`Endpoint::report_unhandled`] and,
+    /// unless it overrides
+    /// [`handle_response`](EndpointHandler::handle_response),
+    /// the responses that do not match a transaction.
+    ///
+    /// The endpoint never waits for the sink: messages received
+    /// while it is full are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::*;
+    /// let (sink, mut unhandled) = tokio::sync::mpsc::channel(16);
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_unhandled_sink(sink)
+    ///     .build();
+    /// ```
+    pub fn with_unhandled_sink(mut self, sink: mpsc::Sender<UnhandledMessage>) -> Self {
+        self.unhandled = Some(sink);
+
+        self
+    }
+
+    /// Sets how sends failing with a transient I/O error, such
+    /// as `WouldBlock` on a full socket buffer, are retried.
+    ///
+    /// Only sends over unreliable transports, such as UDP, are
+    /// retried.
+    ///
+    /// See [`SendRetryConfig`] for the defaults.
+    pub fn with_send_retry(mut self, config: SendRetryConfig) -> Self {
+        self.send_retry = config;
+
+        self
+    }
+
+    /// Sets the default reason phrase used for responses
+    /// with the status `code`, instead of
+    /// [`StatusCode::reason`].
+    ///
+    /// A reason given explicitly when creating a response
+    /// still takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::*;
+    /// # use csip::message::StatusCode;
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_reason_phrase(StatusCode::BusyHere, "User Busy")
+    ///     .build();
+    /// ```
+    pub fn with_reason_phrase(mut self, code: StatusCode, reason: impl Into<ReasonPhrase>) -> Self {
+        self.reasons.insert(code, reason.into());
+
+        self
+    }
+
+    /// Registers a codec for message bodies of `media_type`,
+    /// see [`BodyCodecs::register`].
+    ///
+    /// Bodies are then decoded with [`Endpoint::decode_body`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use csip::*;
+    /// let endpoint = endpoint::EndpointBuilder::new()
+    ///     .with_body_codec(
+    ///         MediaType::new("application", "pidf+xml"),
+    ///         |body| Ok(String::from_utf8_lossy(body).into_owned()),
+    ///         |xml: &String| Ok(Bytes::from(xml.clone())),
+    ///     )
+    ///     .build();
+    /// ```
+    pub fn with_body_codec<T, D, E>(mut self, media_type: MediaType, decode: D, encode: E) -> Self
+    where
+        T: Any,
+        D: Fn(&[u8]) -> crate::Result<T> + Send + Sync + 'static,
+        E: Fn(&T) -> crate::Result<Bytes> + Send + Sync + 'static,
+    {
+        self.codecs.register(media_type, decode, encode);
+
+        self
+    }
+
     /// Finalize the EndpointBuilder into a `Endpoint`.
     pub fn build(self) -> Endpoint {
         log::trace!("Creating endpoint...");
@@ -122,6 +366,18 @@ impl EndpointBuilder {
                 capabilities: self.capabilities,
                 resolver: self.resolver,
                 handler: self.handler,
+                udp_size_threshold: self.udp_size_threshold,
+                timers: self.timers,
+                timer: self.timer,
+                advertised: self.advertised,
+                keepalive: self.keepalive,
+                send_retry: self.send_retry,
+                reasons: self.reasons,
+                codecs: self.codecs,
+                udp_addrs: self.udp_addrs,
+                unhandled: self.unhandled,
+                stateless: Default::default(),
+                shutdown: Default::default(),
             }),
         };
 
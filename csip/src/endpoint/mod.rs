@@ -1,43 +1,105 @@
 #![warn(missing_docs)]
 //! SIP Endpoint
 
+use std::any::Any;
 use std::borrow::Cow;
-use std::net::{IpAddr, SocketAddr};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub use builder::EndpointBuilder;
 use bytes::Bytes;
+pub use stateless::MAX_STATELESS_RESPONSES;
+use stateless::{Lookup, StatelessResponses};
 use tokio::net::ToSocketAddrs;
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 use utils::DnsResolver;
 use uuid::Uuid;
 
+use crate::MediaType;
 use crate::error::TransactionError;
 use crate::message::headers::{
-    CSeq, CallId, Contact, From, Header, Headers, MaxForwards, Route, To, Via,
+    CSeq, CallId, Contact, DEFAULT_VIA_TTL, From, Header, Headers, MaxForwards, MimeVersion, Route,
+    To, Unsupported, Via,
 };
 use crate::message::{
-    CodeClass, DomainName, Host, HostPort, MandatoryHeaders, NameAddr, ReasonPhrase, Request,
-    RequestLine, Response, SipBody, SipMessage, SipUri, StatusCode, StatusLine, Uri, UriBuilder,
+    BodyCodecs, CodeClass, DomainName, Host, HostPort, NameAddr, ReasonPhrase, Request, Response,
+    SipBody, SipMessage, SipUri, StatusCode, StatusLine, Uri, UriBuilder,
+};
+use crate::transaction::manager::{ServerLookup, TransactionKey, TransactionManager};
+use crate::transaction::{
+    ClientTransaction, ServerTransaction, Timer, TimerConfig, TransactionMessage,
 };
-use crate::transaction::manager::{TransactionKey, TransactionManager};
-use crate::transaction::{ClientTransaction, ServerTransaction, TransactionMessage};
 use crate::transport::incoming::{IncomingInfo, IncomingRequest, IncomingResponse};
+use crate::transport::keepalive::KeepAliveConfig;
 use crate::transport::outgoing::{Encode, OutgoingRequest, OutgoingResponse, TargetTransportInfo};
+use crate::transport::retry::SendRetryConfig;
 use crate::transport::tcp::TcpListener;
 use crate::transport::udp::UdpTransport;
 use crate::transport::ws::WebSocketListener;
-use crate::transport::{SipTransport, Transport, TransportManager, TransportMessage};
-use crate::{Method, Result};
+use crate::transport::{
+    SipTransport, Transport, TransportManager, TransportMessage, TransportType,
+};
+use crate::{Method, Result, find_map_mut_header};
 
 mod builder;
+mod stateless;
 
 /// A trait which provides a way to extend the SIP endpoint functionalities.
 #[async_trait::async_trait]
 #[allow(unused_variables)]
 pub trait EndpointHandler: Sync + Send + 'static {
     /// Called when an inbound SIP request is received.
+    ///
+    /// Requests the handler does not take can be handed back
+    /// with [`Endpoint::report_unhandled`].
     async fn handle(&self, request: IncomingRequest, endpoint: &Endpoint);
+
+    /// Called when an inbound SIP response does not match any transaction.
+    ///
+    /// By default the response is handed back with
+    /// [`Endpoint::report_unhandled`].
+    async fn handle_response(&self, response: IncomingResponse, endpoint: &Endpoint) {
+        endpoint.report_unhandled(UnhandledMessage::Response(response));
+    }
+
+    /// Called when a transport is added to the endpoint, such
+    /// as a new TCP connection.
+    async fn on_transport_created(&self, transport: &Transport, endpoint: &Endpoint) {}
+
+    /// Called when a transport is removed from the endpoint,
+    /// such as when a TCP connection is closed.
+    ///
+    /// Flow-based services (RFC 5626) can use this to tear
+    /// down the dialogs bound to the connection.
+    async fn on_transport_closed(&self, transport: &Transport, endpoint: &Endpoint) {}
+
+    /// Called before an outgoing request is encoded.
+    ///
+    /// The request can be modified, for example to add headers or
+    /// a signature. Retransmissions reuse the encoded request and
+    /// are not passed to this method again.
+    async fn on_outgoing_request(&self, request: &mut OutgoingRequest, endpoint: &Endpoint) {}
+
+    /// Called before an outgoing response is encoded.
+    ///
+    /// The response can be modified, for example to add a `Server`
+    /// header. Retransmissions reuse the encoded response and are
+    /// not passed to this method again.
+    async fn on_outgoing_response(&self, response: &mut OutgoingResponse, endpoint: &Endpoint) {}
+}
+
+/// A message received by the endpoint that no handler took,
+/// see [`EndpointBuilder::with_unhandled_sink`].
+pub enum UnhandledMessage {
+    /// A request that no transaction absorbed.
+    Request(Box<IncomingRequest>),
+    /// A response that matched no transaction.
+    Response(IncomingResponse),
 }
 
 struct EndpointInner {
@@ -53,6 +115,30 @@ struct EndpointInner {
     resolver: DnsResolver,
     /// The list of services registered.
     handler: Option<Box<dyn EndpointHandler>>,
+    /// Requests larger than this size are sent over TCP instead of UDP.
+    udp_size_threshold: usize,
+    /// The transaction timer values.
+    timers: TimerConfig,
+    /// The clock the transaction timers are scheduled on.
+    timer: Arc<dyn Timer>,
+    /// The address advertised for each transport type and bound address.
+    advertised: HashMap<(TransportType, SocketAddr), HostPort>,
+    /// The keep-alive settings for reliable connections.
+    keepalive: Option<KeepAliveConfig>,
+    /// The retry settings for transient send errors.
+    send_retry: SendRetryConfig,
+    /// The reason phrases that replace the default ones.
+    reasons: HashMap<StatusCode, ReasonPhrase>,
+    /// The registered message body codecs.
+    codecs: BodyCodecs,
+    /// The addresses UDP transports are bound to on start.
+    udp_addrs: Vec<SocketAddr>,
+    /// The responses sent without a transaction.
+    stateless: StatelessResponses,
+    /// Receives the messages no handler took.
+    unhandled: Option<mpsc::Sender<UnhandledMessage>>,
+    /// Cancelled when the endpoint starts shutting down.
+    shutdown: CancellationToken,
     // user_agent: UserAgent
 }
 
@@ -82,6 +168,89 @@ impl Endpoint {
         &self.inner.name
     }
 
+    /// Get the transaction timer values.
+    pub fn timers(&self) -> &TimerConfig {
+        &self.inner.timers
+    }
+
+    /// Get the clock the transaction timers are scheduled on.
+    pub fn timer(&self) -> &Arc<dyn Timer> {
+        &self.inner.timer
+    }
+
+    /// Get the keep-alive settings, if keep-alive is enabled.
+    pub fn keepalive(&self) -> Option<&KeepAliveConfig> {
+        self.inner.keepalive.as_ref()
+    }
+
+    /// Get the retry settings for transient send errors.
+    pub fn send_retry(&self) -> &SendRetryConfig {
+        &self.inner.send_retry
+    }
+
+    /// Returns the default reason phrase for `code`.
+    ///
+    /// This is the phrase configured with
+    /// [`EndpointBuilder::with_reason_phrase`], or
+    /// [`StatusCode::reason`] if none.
+    pub fn reason_phrase(&self, code: StatusCode) -> ReasonPhrase {
+        self.inner
+            .reasons
+            .get(&code)
+            .cloned()
+            .unwrap_or_else(|| code.reason())
+    }
+
+    /// Returns the address to advertise for `transport`.
+    ///
+    /// This is the address configured with
+    /// [`EndpointBuilder::with_advertised_address`] for the
+    /// type and bound address of `transport`, or for the
+    /// unspecified IP on the same port, or
+    /// [`Transport::local_name`] if none.
+    pub fn local_name(&self, transport: &Transport) -> HostPort {
+        let tp_type = transport.transport_type();
+        let local = transport.local_addr();
+        let unspecified = match local.ip() {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let advertised = &self.inner.advertised;
+
+        advertised
+            .get(&(tp_type, local))
+            .or_else(|| advertised.get(&(tp_type, SocketAddr::new(unspecified, local.port()))))
+            .cloned()
+            .unwrap_or_else(|| transport.local_name())
+    }
+
+    /// Get the message body codecs registered with
+    /// [`EndpointBuilder::with_body_codec`].
+    pub fn body_codecs(&self) -> &BodyCodecs {
+        &self.inner.codecs
+    }
+
+    /// Decodes the body of `request` into `T` with the codec
+    /// registered for its `Content-Type`.
+    ///
+    /// Returns `None` if the request has no body, no
+    /// `Content-Type`, or no codec for `T` matches it.
+    pub fn decode_body<T: Any>(&self, request: &Request) -> Option<Result<T>> {
+        let body = request.body.as_ref()?;
+        let content_type = request
+            .headers
+            .iter()
+            .find_map(|header| header.as_content_type())?;
+
+        self.inner.codecs.decode(content_type.media_type(), body)
+    }
+
+    /// Get the capability headers (`Allow`, `Supported`,
+    /// `Accept`, ...) configured for the endpoint.
+    pub fn capabilities(&self) -> &Headers {
+        &self.inner.capabilities
+    }
+
     pub async fn respond(
         &self,
         request: &IncomingRequest,
@@ -93,6 +262,69 @@ impl Endpoint {
         self.send_outgoing_response(&mut response).await
     }
 
+    /// Responds to `request` without creating a server
+    /// transaction, as a stateless UAS (RFC 3261 section 8.2.7).
+    ///
+    /// The response is built by `create` and sent to the address
+    /// derived from the top `Via`. It is kept for `64*T1`, so
+    /// retransmissions of the request received in that window
+    /// are answered with the same response and `create` is not
+    /// called again. At most [`MAX_STATELESS_RESPONSES`]
+    /// responses are kept, the oldest are dropped first.
+    ///
+    /// An `ACK` is never answered, nothing is sent and `create`
+    /// is not called.
+    pub async fn respond_stateless<F>(&self, request: &IncomingRequest, create: F) -> Result<()>
+    where
+        F: FnOnce(&Endpoint, &IncomingRequest) -> OutgoingResponse,
+    {
+        // https://www.rfc-editor.org/rfc/rfc3261#section-17.2.1
+        // An ACK shares the transaction key of its INVITE, it must
+        // not retransmit the response cached for the INVITE.
+        if request.request.req_line.method == Method::Ack {
+            return Ok(());
+        }
+        let key = TransactionKey::from_request(request);
+        let expires = Instant::now() + self.inner.timers.t1 * 64;
+
+        match self.inner.stateless.get_or_reserve(&key, expires) {
+            Lookup::Sent(mut response) => {
+                log::debug!("Retransmitting stateless response for {key}");
+                return self.send_outgoing_response(&mut response).await;
+            }
+            Lookup::Pending => {
+                log::debug!("Response for {key} is being sent, ignoring retransmission");
+                return Ok(());
+            }
+            Lookup::Reserved => (),
+        }
+        let mut response = create(self, request);
+        if let Err(err) = self.send_outgoing_response(&mut response).await {
+            self.inner.stateless.release(&key);
+            return Err(err);
+        }
+        self.inner.stateless.complete(&key, &response);
+
+        Ok(())
+    }
+
+    /// Like [`Endpoint::respond`], but the response carries
+    /// `body` with a `Content-Type` of `media_type`, as a `200`
+    /// answering an `INVITE` with SDP.
+    pub async fn respond_with_body(
+        &self,
+        request: &IncomingRequest,
+        code: StatusCode,
+        phrase: Option<ReasonPhrase>,
+        media_type: MediaType,
+        body: impl Into<SipBody>,
+    ) -> Result<()> {
+        let mut response = self.create_outgoing_response(request, code, phrase);
+        response.set_body_with_type(body, media_type);
+
+        self.send_outgoing_response(&mut response).await
+    }
+
     /// Creates a new SIP response based on an incoming
     /// request.
     ///
@@ -100,6 +332,10 @@ impl Endpoint {
     /// and reason phrase. It also sets the necessary headers from request,
     /// including `Call-ID`, `From`, `To`, `CSeq`, `Via` and
     /// `Record-Route` headers.
+    ///
+    /// The endpoint [`capabilities`](Self::capabilities) are
+    /// also appended to `2xx` responses to `OPTIONS` and to
+    /// `405` and `420` responses.
     pub fn create_outgoing_response(
         &self,
         request: &IncomingRequest,
@@ -145,11 +381,16 @@ impl Endpoint {
         headers.push(Header::To(to));
 
         // `CSeq` header.
-        headers.push(Header::CSeq(mandatory_headers.cseq));
+        headers.push(Header::CSeq(mandatory_headers.cseq.clone()));
+
+        // Capability headers.
+        if self.should_advertise_capabilities(request, code) {
+            headers.extend(self.inner.capabilities.iter().cloned());
+        }
 
         let reason = match reason {
-            None => code.reason(),
-            Some(reason) => reason.into(),
+            None => self.reason_phrase(code),
+            Some(reason) => reason,
         };
         let status_line = StatusLine::new(code, reason);
         let response = Response::with_headers(status_line, headers);
@@ -158,36 +399,134 @@ impl Endpoint {
         OutgoingResponse {
             response,
             target_info: TargetTransportInfo {
-                target: request.incoming_info.transport.packet.source,
+                target: Self::response_target(request),
                 transport: request.incoming_info.transport.transport.clone(),
             },
             encoded: Bytes::new(),
         }
     }
 
+    /// Returns the option tags in `required` that are not listed in
+    /// the endpoint `Supported` capability.
+    ///
+    /// `required` are the tags of the `Require` headers of a
+    /// request, or of the `Proxy-Require` headers when acting
+    /// as a proxy.
+    pub fn unsupported_option_tags<'a, I>(&self, required: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let supported = self.capabilities().iter().find_map(|h| h.as_supported());
+        let is_supported = |tag: &str| supported.is_some_and(|s| s.contains(tag));
+
+        required
+            .into_iter()
+            .filter(|tag| !is_supported(tag))
+            .map(String::from)
+            .collect()
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-8.2.2.3
+    /// Creates a `420 Bad Extension` response listing the
+    /// `unsupported` option tags in an `Unsupported` header.
+    pub fn create_bad_extension_response(
+        &self,
+        request: &IncomingRequest,
+        unsupported: Vec<String>,
+    ) -> OutgoingResponse {
+        let mut response = self.create_outgoing_response(request, StatusCode::BadExtension, None);
+        response
+            .headers_mut()
+            .push(Header::Unsupported(Unsupported::from(unsupported)));
+
+        response
+    }
+
+    /// Returns `true` if `method` is listed in the endpoint
+    /// `Allow` capability, or if the endpoint has none.
+    ///
+    /// `ACK` and `CANCEL` are always allowed, as they belong to
+    /// the transaction of another request.
+    pub fn is_method_allowed(&self, method: Method) -> bool {
+        let allow = self.capabilities().iter().find_map(|h| h.as_allow());
+
+        matches!(method, Method::Ack | Method::Cancel)
+            || allow.is_none_or(|allow| allow.contains(method))
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-8.2.1
+    /// Creates a `405 Method Not Allowed` response, carrying the
+    /// endpoint `Allow` capability, for a request whose method
+    /// is not supported.
+    pub fn create_method_not_allowed_response(
+        &self,
+        request: &IncomingRequest,
+    ) -> OutgoingResponse {
+        self.create_outgoing_response(request, StatusCode::MethodNotAllowed, None)
+    }
+
     pub fn new_server_transaction(&self, request: IncomingRequest) -> ServerTransaction {
         ServerTransaction::new(request, self.clone())
     }
 
+    /// Returns a new server transaction for `request`, or passes
+    /// the request to its existing one.
+    ///
+    /// An `ACK` that must be handled by the TU is returned in
+    /// [`ServerLookup::Unmatched`], an `ACK` never creates a
+    /// transaction.
+    ///
+    /// See [`TransactionManager::find_or_create_server`].
+    pub async fn find_or_create_server_transaction(
+        &self,
+        request: IncomingRequest,
+    ) -> ServerLookup {
+        self.transactions()
+            .find_or_create_server(request, self)
+            .await
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-17.1.1.3
+    /// Creates the `ACK` for a non-2xx final `response` to the
+    /// INVITE `outgoing`.
+    ///
+    /// The `ACK` reuses the Request-URI, the top `Via`, `From`,
+    /// `Call-ID`, the `CSeq` number and the `Route` headers of the
+    /// INVITE, the `To` is copied from the response.
     pub(crate) fn create_ack_request(
         &self,
         outgoing: &OutgoingRequest,
         response: &IncomingResponse,
     ) -> OutgoingRequest {
         assert!(
-            matches!(response.status().as_u16(), 300..699),
+            matches!(response.status().as_u16(), 300..=699),
             "message must be a 300-699 final response"
         );
-        let target = outgoing.request.req_line.uri.clone();
-        // Clone: Via, To, From, Max-Forwards, Call-ID and CSeq from response.
-        let headers = MandatoryHeaders {
-            cseq: CSeq {
-                method: Method::Ack,
-                ..response.incoming_info.mandatory_headers.cseq
-            },
-            ..response.incoming_info.mandatory_headers.clone()
+        let invite = &outgoing.request;
+        let target = invite.req_line.uri.clone();
+        let to = &response.incoming_info.mandatory_headers.to;
+
+        let mut headers = Headers::with_capacity(invite.headers.len());
+        let mut has_via = false;
+        for header in invite.headers.iter() {
+            let header = match header {
+                // The ACK contains a single Via, the top Via of the INVITE.
+                Header::Via(_) if !has_via => {
+                    has_via = true;
+                    header.clone()
+                }
+                Header::To(_) => Header::To(to.clone()),
+                Header::CSeq(cseq) => Header::CSeq(CSeq {
+                    method: Method::Ack,
+                    ..*cseq
+                }),
+                Header::From(_) | Header::CallId(_) | Header::MaxForwards(_) | Header::Route(_) => {
+                    header.clone()
+                }
+                _ => continue,
+            };
+            headers.push(header);
         }
-        .into_headers();
 
         let request = Request::with_headers(Method::Ack, target, headers);
         let target_info = outgoing.target_info.clone();
@@ -202,7 +541,19 @@ impl Endpoint {
     /// Send the request.
     pub async fn send_outgoing_request(&self, request: &mut OutgoingRequest) -> Result<()> {
         if request.encoded.is_empty() {
+            if let Some(handler) = &self.inner.handler {
+                handler.on_outgoing_request(request, self).await;
+            }
+            self.ensure_via_transport(request);
+            self.ensure_mime_version(&mut request.request.headers);
             request.encoded = request.encode()?;
+
+            if self.exceeds_udp_size_threshold(request)
+                && self.switch_to_tcp_transport(request).await
+            {
+                self.ensure_via_transport(request);
+                request.encoded = request.encode()?;
+            }
         }
 
         log::debug!(
@@ -212,10 +563,9 @@ impl Endpoint {
             request.target_info.target
         );
 
-        request
-            .target_info
-            .transport
-            .send_msg(&request.encoded, &request.target_info.target)
+        let TargetTransportInfo { target, transport } = &request.target_info;
+        self.send_retry()
+            .send(transport, || transport.send_msg(&request.encoded, target))
             .await?;
 
         Ok(())
@@ -223,8 +573,13 @@ impl Endpoint {
 
     pub async fn send_outgoing_response(&self, response: &mut OutgoingResponse) -> Result<()> {
         if response.encoded.is_empty() {
+            if let Some(handler) = &self.inner.handler {
+                handler.on_outgoing_response(response, self).await;
+            }
+            self.ensure_mime_version(response.headers_mut());
             response.encoded = response.encode()?;
         }
+        self.ensure_response_connection(response).await?;
         log::debug!(
             "Sending Response {} {} to /{}",
             response.status().as_u16(),
@@ -232,15 +587,163 @@ impl Endpoint {
             response.target_info.target
         );
 
-        response
-            .target_info
-            .transport
-            .send_msg(&response.encoded, &response.target_info.target)
-            .await?;
+        let TargetTransportInfo { target, transport } = &response.target_info;
+
+        // https://www.rfc-editor.org/rfc/rfc3261#section-18.2.2
+        // Responses to a multicast maddr use the ttl of the Via.
+        if target.ip().is_multicast() {
+            let ttl = response
+                .headers()
+                .iter()
+                .find_map(|h| h.as_via())
+                .map_or(DEFAULT_VIA_TTL, Via::ttl);
+
+            self.send_retry()
+                .send(transport, || {
+                    transport.send_multicast(&response.encoded, target, ttl.into())
+                })
+                .await?;
+        } else {
+            self.send_retry()
+                .send(transport, || transport.send_msg(&response.encoded, target))
+                .await?;
+        }
 
         Ok(())
     }
 
+    // https://www.rfc-editor.org/rfc/rfc3261#section-18.2.2
+    // Over reliable transports, the response goes back over the
+    // connection the request came in on. If that connection is
+    // gone, a new one is opened to the `received` address and the
+    // sent-by port of the top Via.
+    async fn ensure_response_connection(&self, response: &mut OutgoingResponse) -> Result<()> {
+        let transport = &response.target_info.transport;
+        if !transport.is_reliable() || self.transports().contains(&transport.key())? {
+            return Ok(());
+        }
+        let Some(via) = response.headers().iter().find_map(|h| h.as_via()) else {
+            return Ok(());
+        };
+        log::debug!(
+            "Connection to {} is gone, reconnecting to send the response",
+            response.target_info.target
+        );
+        let (target, transport) = self.get_outbound_addr(via, transport).await?;
+        response.target_info = TargetTransportInfo { target, transport };
+
+        Ok(())
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-18.2.2
+    // Over unreliable transports, a Via with an maddr parameter
+    // sends the response to that address, using the sent-by port.
+    //
+    // Responses are created synchronously, so a domain name maddr
+    // can not be resolved here: the response goes to the source
+    // of the request instead.
+    fn response_target(request: &IncomingRequest) -> SocketAddr {
+        let info = &request.incoming_info;
+        let via = &info.mandatory_headers.via;
+
+        if info.transport.transport.is_reliable() {
+            return info.transport.packet.source;
+        }
+        match &via.maddr {
+            Some(Host::IpAddr(ip)) => SocketAddr::new(*ip, via.sent_by.port.unwrap_or(5060)),
+            Some(Host::DomainName(domain)) => {
+                log::warn!(
+                    "Via maddr {domain} is not an IP address, responding to {}",
+                    info.transport.packet.source
+                );
+                info.transport.packet.source
+            }
+            None => info.transport.packet.source,
+        }
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-11.2
+    // Allow, Accept, Accept-Encoding, Accept-Language, and Supported
+    // header fields SHOULD be present in a 200 (OK) response to an
+    // OPTIONS request.
+    fn should_advertise_capabilities(&self, request: &IncomingRequest, code: StatusCode) -> bool {
+        match code {
+            StatusCode::MethodNotAllowed | StatusCode::BadExtension => true,
+            code => {
+                request.request.req_line.method == Method::Options
+                    && code.class() == CodeClass::Success
+            }
+        }
+    }
+
+    fn exceeds_udp_size_threshold(&self, request: &OutgoingRequest) -> bool {
+        request.target_info.transport.transport_type() == TransportType::Udp
+            && request.encoded.len() > self.inner.udp_size_threshold
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-18.1.1
+    // If a request is within 200 bytes of the path MTU, or if it is larger
+    // than 1300 bytes and the path MTU is unknown, the request MUST be sent
+    // using an RFC 2914 congestion controlled transport protocol, such as TCP.
+    // If this causes a change in the transport protocol from the one
+    // indicated in the top Via, the value in the top Via MUST be changed.
+    async fn switch_to_tcp_transport(&self, request: &mut OutgoingRequest) -> bool {
+        let target = request.target_info.target;
+
+        match self
+            .transports()
+            .get_or_create_transport(TransportType::Tcp, target, self)
+            .await
+        {
+            Ok(transport) => {
+                log::debug!(
+                    "Request size {} exceeds {} bytes, sending over TCP",
+                    request.encoded.len(),
+                    self.inner.udp_size_threshold
+                );
+                request.target_info.transport = transport;
+                true
+            }
+            Err(err) => {
+                // If the connection fails, the request is sent over UDP.
+                log::debug!("Failed to switch to TCP transport, using UDP: {}", err);
+                false
+            }
+        }
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-18.1.1
+    // The topmost Via must contain the transport the request is actually sent
+    // over, otherwise the responses would be routed to the wrong transport.
+    fn ensure_via_transport(&self, request: &mut OutgoingRequest) {
+        let transport = request.target_info.transport.transport_type();
+
+        if let Some(via) = find_map_mut_header!(request.request.headers, Via)
+            && via.transport != transport
+        {
+            log::warn!(
+                "Topmost Via transport {} does not match {}, rewriting it",
+                via.transport,
+                transport
+            );
+            via.transport = transport;
+        }
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc3261#section-7.4.1
+    // Multipart bodies are MIME entities, so the message carries the
+    // MIME-Version header the receiver needs to interpret them.
+    fn ensure_mime_version(&self, headers: &mut Headers) {
+        let is_multipart = headers
+            .iter()
+            .filter_map(|h| h.as_content_type())
+            .any(|c| c.media_type().is_multipart());
+
+        if is_multipart && !headers.iter().any(|h| h.as_mime_version().is_some()) {
+            headers.push(Header::MimeVersion(MimeVersion::default()));
+        }
+    }
+
     // https://www.rfc-editor.org/rfc/rfc3261#section-8.1.1
     // A valid SIP request formulated by a UAC MUST, at a minimum, contain
     // the following header fields: To, From, CSeq, Call-ID, Max-Forwards,
@@ -270,7 +773,7 @@ impl Endpoint {
         }
 
         if !exists_via {
-            let sent_by = transport.local_addr().into();
+            let sent_by = self.local_name(transport);
             let transport = transport.transport_type();
             let branch = crate::generate_branch();
             let via = Via::new_with_transport(transport, sent_by, Some(branch));
@@ -279,7 +782,7 @@ impl Endpoint {
         }
 
         if !exists_from {
-            let host = transport.local_addr().into();
+            let host = self.local_name(transport);
             let uri = UriBuilder::new()
                 .with_host(host)
                 .with_scheme(request.req_line.uri.scheme)
@@ -299,7 +802,7 @@ impl Endpoint {
         }
 
         if !exists_cseq {
-            let cseq = CSeq::new(1, request.req_line.method);
+            let cseq = CSeq::new(1, request.req_line.method.clone());
 
             headers[3] = Some(Header::CSeq(cseq));
         }
@@ -397,12 +900,24 @@ impl Endpoint {
         })
     }
 
+    /// Starts a UDP transport on each address added with
+    /// [`EndpointBuilder::with_udp_address`].
+    pub async fn start_transports(&self) -> Result<()> {
+        for addr in &self.inner.udp_addrs {
+            self.start_udp_transport(addr).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn start_udp_transport<A: ToSocketAddrs>(&self, addr: A) -> Result<()> {
         let udp = UdpTransport::bind(addr).await?;
         log::info!("SIP UDP transport started, bound to: {}", udp.local_addr());
-        self.transports()
-            .register_transport(Transport::new(udp.clone()))?;
-        tokio::spawn(udp.receive_datagram(self.clone()));
+        let transport = Transport::new(udp.clone());
+        self.add_transport(transport.clone()).await?;
+        // Keeps receiving while the endpoint shuts down, until the
+        // transport itself is shut down.
+        tokio::spawn(udp.receive_datagram(transport, self.clone()));
         Ok(())
     }
 
@@ -412,7 +927,7 @@ impl Endpoint {
             "SIP TCP listener ready for incoming connections at: {}",
             tcp.local_addr()
         );
-        tokio::spawn(tcp.accept_clients(self.clone()));
+        tokio::spawn(self.until_shutdown(tcp.accept_clients(self.clone())));
         Ok(())
     }
 
@@ -422,11 +937,95 @@ impl Endpoint {
             "SIP WS listener ready for incoming connections at: {}",
             ws.local_addr()
         );
-        tokio::spawn(ws.accept_clients(self.clone()));
+        tokio::spawn(self.until_shutdown(ws.accept_clients(self.clone())));
         Ok(())
     }
 
+    // Registers `transport` and notifies the handler.
+    pub(crate) async fn add_transport(&self, transport: Transport) -> Result<()> {
+        self.transports().register_transport(transport.clone())?;
+
+        if let Some(handler) = &self.inner.handler {
+            handler.on_transport_created(&transport, self).await;
+        }
+
+        Ok(())
+    }
+
+    // Removes `transport`, stops its read loop and notifies the
+    // handler, once, if it was registered.
+    pub(crate) async fn close_transport(&self, transport: &Transport) -> Result<()> {
+        transport.shutdown();
+
+        let removed = self.transports().remove_transport(&transport.key())?;
+        if removed.is_none() {
+            return Ok(());
+        }
+
+        if let Some(handler) = &self.inner.handler {
+            handler.on_transport_closed(transport, self).await;
+        }
+
+        Ok(())
+    }
+
+    /// Gracefully shuts down the endpoint.
+    ///
+    /// Stops accepting new connections and new requests, waits up to
+    /// `timeout` for the active transactions to terminate and then shuts
+    /// down all transports, calling [`EndpointHandler::on_transport_closed`]
+    /// for each one.
+    ///
+    /// Messages for the active transactions, such as retransmissions,
+    /// `ACK`s and responses, are still processed while waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ShutdownTimeout`] if there are still active
+    /// transactions when `timeout` elapses. The transports are closed anyway.
+    ///
+    /// [`Error::ShutdownTimeout`]: crate::Error::ShutdownTimeout
+    pub async fn shutdown(&self, timeout: Duration) -> Result<()> {
+        log::info!("Shutting down endpoint...");
+        self.inner.shutdown.cancel();
+
+        let mut result = Ok(());
+        if let Some(transactions) = &self.inner.transaction {
+            let drained = tokio::time::timeout(timeout, transactions.wait_until_empty()).await;
+            if drained.is_err() {
+                let pending = transactions.len();
+                log::warn!("Shutdown timed out with {} active transactions", pending);
+                result = Err(crate::Error::ShutdownTimeout(pending));
+            }
+        }
+        // Closed one by one, so the handler sees each transport close.
+        for transport in self.transports().all()? {
+            self.close_transport(&transport).await?;
+        }
+
+        result
+    }
+
+    /// Returns `true` if [`Endpoint::shutdown`] has been called.
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.shutdown.is_cancelled()
+    }
+
+    fn until_shutdown<F>(&self, fut: F) -> impl Future<Output = ()> + use<F>
+    where
+        F: Future,
+    {
+        let shutdown = self.inner.shutdown.clone();
+
+        async move {
+            shutdown.run_until_cancelled_owned(fut).await;
+        }
+    }
+
     pub(crate) fn receive_transport_message(&self, message: TransportMessage) {
+        let transport = &message.transport;
+        transport.record_received(message.packet.data.len());
+
         tokio::spawn({
             let endpoint = self.clone();
             async move {
@@ -437,10 +1036,9 @@ impl Endpoint {
         });
     }
 
-    async fn process_transport_message(self, message: TransportMessage) -> Result<()> {
-        match message.parse() {
-            Ok(SipMessage::Request(request)) => {
-                let mut headers: MandatoryHeaders = (&request.headers).try_into()?;
+    pub(crate) async fn process_transport_message(self, message: TransportMessage) -> Result<()> {
+        match message.parse_with_mandatory_headers() {
+            Ok((SipMessage::Request(request), mut headers)) => {
                 // 4. Server Behavior
                 // the server MUST insert a "received" parameter containing the source
                 // IP address that the request came from.
@@ -452,11 +1050,11 @@ impl Endpoint {
                 self.process_request(IncomingRequest {
                     request,
                     incoming_info: Box::new(info),
+                    endpoint: Some(self.clone()),
                 })
                 .await?;
             }
-            Ok(SipMessage::Response(res)) => {
-                let mut headers: MandatoryHeaders = res.headers().try_into()?;
+            Ok((SipMessage::Response(res), mut headers)) => {
                 // 4. Server Behavior
                 // the server MUST insert a "received" parameter containing the source
                 // IP address that the request came from.
@@ -499,29 +1097,45 @@ impl Endpoint {
         via: &Via,
         transport: &Transport,
     ) -> Result<(SocketAddr, Transport)> {
+        let (protocol, sent_by) = via.sent_by_target();
+        // Filled in by `sent_by_target`.
+        let sent_by_port = sent_by.port.unwrap();
+        // A Via without `received` was not stamped by the transport
+        // layer, fall back to the sent-by host.
+        let received = async {
+            match via.received {
+                Some(ip) => Ok(ip),
+                None => self.lookup_address(&sent_by.host).await,
+            }
+        };
+
         if transport.is_reliable() {
             // Tcp, TLS, etc..
-            return Ok((transport.remote_addr().unwrap(), transport.clone()));
+            if let Some(remote) = transport.remote_addr()
+                && self.transports().contains(&transport.key())?
+            {
+                return Ok((remote, transport.clone()));
+            }
+            // The connection is gone, open a new one to the
+            // `received` address and the sent-by port.
+            let addr = SocketAddr::new(received.await?, sent_by_port);
+            let transport = self
+                .transports()
+                .get_or_create_transport(protocol, addr, self)
+                .await?;
+
+            return Ok((addr, transport));
         }
 
         if let Some(maddr) = &via.maddr {
-            let port = via.sent_by.port.unwrap_or(5060);
             let ip = self.lookup_address(maddr).await?;
-            let addr = SocketAddr::new(ip, port);
+            let addr = SocketAddr::new(ip, sent_by_port);
 
-            return Ok((addr, transport.clone()));
-        } else if let Some(rport) = via.rport {
-            let ip = via.received.unwrap();
-            let addr = SocketAddr::new(ip, rport);
-            return Ok((addr, transport.clone()));
-        } else {
-            let ip = via
-                .received
-                .expect("Missing received parameter on 'Via' header");
-            let port = via.sent_by.port.unwrap_or(5060);
-            let addr = SocketAddr::new(ip, port);
             return Ok((addr, transport.clone()));
         }
+        let port = via.rport.unwrap_or(sent_by_port);
+
+        Ok((SocketAddr::new(received.await?, port), transport.clone()))
     }
 
     pub(crate) async fn process_response(&self, response: IncomingResponse) -> Result<()> {
@@ -536,13 +1150,14 @@ impl Endpoint {
             None => Some(response),
         };
 
-        if let Some(response) = response {
-            log::info!(
-                "Response ({} {}) from /{} was unhandled",
-                response.status().as_u16(),
-                response.reason().as_str(),
-                response.incoming_info.transport.packet.source
-            );
+        let Some(response) = response else {
+            return Ok(());
+        };
+
+        if let Some(handler) = &self.inner.handler {
+            handler.handle_response(response, self).await;
+        } else {
+            self.report_unhandled(UnhandledMessage::Response(response));
         }
         Ok(())
     }
@@ -573,20 +1188,64 @@ impl Endpoint {
             return Ok(());
         };
 
-        if let Some(handler) = &self.inner.handler {
-            handler.handle(msg, self).await;
-        } else {
+        // Only the ACK of a 2xx reaches here without being a new
+        // request, it completes a dialog started before the shutdown.
+        if self.is_shutting_down() && msg.request.method() != Method::Ack {
             log::debug!(
-                "Request ({}, cseq={}) from /{} was unhandled",
+                "Dropping request ({}) from /{}, endpoint is shutting down",
                 msg.request.method(),
-                msg.incoming_info.mandatory_headers.cseq.cseq,
                 msg.incoming_info.transport.packet.source
             );
+            return Ok(());
+        }
+
+        if let Some(handler) = &self.inner.handler {
+            handler.handle(msg, self).await;
+        } else {
+            self.report_unhandled(UnhandledMessage::Request(Box::new(msg)));
         }
 
         Ok(())
     }
 
+    /// Reports a received message that no handler took.
+    ///
+    /// The message is sent to the sink set with
+    /// [`EndpointBuilder::with_unhandled_sink`], or only logged
+    /// without one. An [`EndpointHandler`] calls this for the
+    /// requests it ignores.
+    pub fn report_unhandled(&self, msg: UnhandledMessage) {
+        let Some(sink) = &self.inner.unhandled else {
+            match msg {
+                UnhandledMessage::Request(request) => log::debug!(
+                    "Request ({}, cseq={}) from /{} was unhandled",
+                    request.request.method(),
+                    request.incoming_info.mandatory_headers.cseq.cseq,
+                    request.incoming_info.transport.packet.source
+                ),
+                UnhandledMessage::Response(response) => log::info!(
+                    "Response ({} {}) from /{} was unhandled",
+                    response.status().as_u16(),
+                    response.reason().as_str(),
+                    response.incoming_info.transport.packet.source
+                ),
+            }
+            return;
+        };
+
+        // Never waits for the sink, a slow consumer must not stall
+        // the processing of received messages.
+        match sink.try_send(msg) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                log::warn!("Unhandled message sink is full, dropping message");
+            }
+            Err(TrySendError::Closed(_)) => {
+                log::debug!("Unhandled message sink is closed, dropping message");
+            }
+        }
+    }
+
     pub(crate) fn transactions(&self) -> &TransactionManager {
         self.inner
             .transaction
@@ -594,6 +1253,26 @@ impl Endpoint {
             .expect("Transaction Manager not set")
     }
 
+    /// Returns `true` if a transaction is registered under `key`.
+    pub(crate) fn has_transaction(&self, key: &TransactionKey) -> bool {
+        self.inner
+            .transaction
+            .as_ref()
+            .is_some_and(|tsx_layer| tsx_layer.get_entry(key).is_some())
+    }
+
+    /// Returns the key of the INVITE server transaction cancelled
+    /// by the `CANCEL` request `cancel`, if it is still active.
+    ///
+    /// A `CANCEL` that matches no transaction should be answered
+    /// with a `481` (RFC 3261 section 9.2).
+    pub fn find_cancelled_invite(&self, cancel: &IncomingRequest) -> Option<TransactionKey> {
+        self.inner
+            .transaction
+            .as_ref()?
+            .find_cancelled_invite(cancel)
+    }
+
     pub(crate) fn register_transaction(
         &self,
         key: TransactionKey,
@@ -606,3 +1285,1123 @@ impl Endpoint {
         &self.inner.transport
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::message::MandatoryHeaders;
+    use crate::message::headers::{Allow, ContentType, Require, Supported};
+    use crate::test_utils::transport::MockTransport;
+    use crate::test_utils::{create_test_endpoint, create_test_request};
+    use crate::transport::{Packet, TransportFactory, TransportStats};
+
+    fn create_endpoint_with_allow() -> Endpoint {
+        let mut allow = Allow::new();
+        allow.push(Method::Invite);
+        allow.push(Method::Options);
+
+        EndpointBuilder::new()
+            .with_capability(Header::Allow(allow))
+            .build()
+    }
+
+    #[test]
+    fn test_options_response_carries_capabilities() {
+        let endpoint = create_endpoint_with_allow();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Options, transport);
+
+        let response = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+        let allow = response
+            .headers()
+            .iter()
+            .find_map(|h| h.as_allow())
+            .unwrap();
+
+        assert_eq!(allow.to_string(), "Allow: INVITE, OPTIONS");
+    }
+
+    #[test]
+    fn test_method_not_allowed_response_carries_capabilities() {
+        let endpoint = create_endpoint_with_allow();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Bye, transport);
+
+        let ok = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+        let not_allowed =
+            endpoint.create_outgoing_response(&request, StatusCode::MethodNotAllowed, None);
+
+        assert!(!ok.headers().iter().any(|h| h.as_allow().is_some()));
+        assert!(not_allowed.headers().iter().any(|h| h.as_allow().is_some()));
+    }
+
+    #[test]
+    fn test_unsupported_method_yields_method_not_allowed() {
+        let endpoint = create_endpoint_with_allow();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Subscribe, transport);
+
+        assert!(!endpoint.is_method_allowed(Method::Subscribe));
+        assert!(endpoint.is_method_allowed(Method::Options));
+        assert!(endpoint.is_method_allowed(Method::Ack));
+
+        let response = endpoint.create_method_not_allowed_response(&request);
+        let allow = response
+            .headers()
+            .iter()
+            .find_map(|h| h.as_allow())
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::MethodNotAllowed);
+        assert_eq!(allow.to_string(), "Allow: INVITE, OPTIONS");
+    }
+
+    #[test]
+    fn test_bad_extension_response_lists_unsupported_tags() {
+        let mut supported = Supported::default();
+        supported.add_tag("100rel");
+        supported.add_tag("timer");
+        let endpoint = EndpointBuilder::new()
+            .with_capability(Header::Supported(supported))
+            .build();
+        let transport = Transport::new(MockTransport::new_udp());
+        let mut request = create_test_request(Method::Invite, transport);
+        let mut require = Require::default();
+        require.add_tag("100rel");
+        require.add_tag("foo");
+        require.add_tag("timer");
+        require.add_tag("bar");
+        request.request.headers.push(Header::Require(require));
+
+        let required = request
+            .request
+            .headers
+            .iter()
+            .filter_map(|h| h.as_require())
+            .flat_map(|r| r.iter());
+        let unsupported = endpoint.unsupported_option_tags(required);
+        assert_eq!(unsupported, ["foo", "bar"]);
+
+        let response = endpoint.create_bad_extension_response(&request, unsupported);
+        let header = response
+            .headers()
+            .iter()
+            .find_map(|h| h.as_unsupported())
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BadExtension);
+        assert_eq!(header.to_string(), "Unsupported: foo, bar");
+    }
+
+    #[tokio::test]
+    async fn test_rewrites_via_transport_mismatch_on_send() {
+        let endpoint = create_test_endpoint();
+        let udp = Transport::new(MockTransport::new_udp());
+        let tcp = MockTransport::new_tcp();
+        let request = create_test_request(Method::Options, udp).request;
+        let mut outgoing = OutgoingRequest {
+            request,
+            target_info: TargetTransportInfo {
+                target: tcp.local_addr(),
+                transport: Transport::new(tcp.clone()),
+            },
+            encoded: Bytes::new(),
+        };
+
+        endpoint.send_outgoing_request(&mut outgoing).await.unwrap();
+
+        let sent = tcp.get_last_sent_request().unwrap();
+        let via = sent.headers.iter().find_map(|h| h.as_via()).unwrap();
+        assert_eq!(via.transport, TransportType::Tcp);
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_request_keeps_from_without_tag() {
+        // Proxies forward requests as received, a tag must not be
+        // added to their From.
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let target = Some((transport.clone(), transport.local_addr()));
+
+        let mut request = create_test_request(Method::Options, transport).request;
+        let from = find_map_mut_header!(request.headers, From).unwrap();
+        from.set_tag(None);
+
+        let outgoing = endpoint
+            .create_outgoing_request(request, target)
+            .await
+            .unwrap();
+        let from = outgoing.request.headers.iter().find_map(|h| h.as_from());
+        assert!(from.unwrap().tag().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_response_to_multicast_maddr_uses_via_ttl() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let mut request = create_test_request(Method::Options, Transport::new(mock.clone()));
+        let via: Via = "SIP/2.0/UDP 10.0.0.1:5070;branch=z9hG4bK776asdhds;ttl=16;maddr=224.0.1.75"
+            .parse()
+            .unwrap();
+        request
+            .request
+            .headers
+            .replace_first(Header::Via(via.clone()));
+        request.incoming_info.mandatory_headers.via = via;
+
+        let mut response = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+        assert_eq!(
+            response.target_info.target,
+            "224.0.1.75:5070".parse().unwrap()
+        );
+
+        endpoint
+            .send_outgoing_response(&mut response)
+            .await
+            .unwrap();
+        assert_eq!(mock.last_multicast_ttl(), Some(16));
+    }
+
+    #[tokio::test]
+    async fn test_domain_maddr_responds_to_source() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let mut request = create_test_request(Method::Options, Transport::new(mock.clone()));
+        let via: Via = "SIP/2.0/UDP 10.0.0.1:5070;branch=z9hG4bK776asdhds;maddr=sip.example.com"
+            .parse()
+            .unwrap();
+        request.incoming_info.mandatory_headers.via = via;
+
+        let response = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+
+        assert_eq!(
+            response.target_info.target,
+            request.incoming_info.transport.packet.source
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unicast_response_is_not_multicast() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let request = create_test_request(Method::Options, Transport::new(mock.clone()));
+
+        let mut response = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+        endpoint
+            .send_outgoing_response(&mut response)
+            .await
+            .unwrap();
+
+        assert_eq!(mock.sent_count(), 1);
+        assert_eq!(mock.last_multicast_ttl(), None);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Sdp {
+        version: u32,
+    }
+
+    #[test]
+    fn test_decode_body_with_registered_codec() {
+        let endpoint = Endpoint::builder()
+            .with_body_codec(
+                MediaType::new("application", "sdp"),
+                |body| {
+                    let body = std::str::from_utf8(body)?;
+                    let version = body
+                        .strip_prefix("v=")
+                        .and_then(|v| v.trim_end().parse().ok())
+                        .ok_or_else(|| crate::Error::Other("invalid sdp".into()))?;
+                    Ok(Sdp { version })
+                },
+                |sdp: &Sdp| Ok(Bytes::from(format!("v={}\r\n", sdp.version))),
+            )
+            .build();
+        let transport = Transport::new(MockTransport::new_udp());
+        let mut request = create_test_request(Method::Invite, transport).request;
+
+        request.set_body_with_type("v=0\r\n", ContentType::new_sdp().media_type().clone());
+        let sdp = endpoint.decode_body::<Sdp>(&request).unwrap().unwrap();
+        assert_eq!(sdp, Sdp { version: 0 });
+
+        request.set_body_with_type("v=x\r\n", ContentType::new_sdp().media_type().clone());
+        assert!(endpoint.decode_body::<Sdp>(&request).unwrap().is_err());
+
+        request.set_body_with_type("v=0\r\n", MediaType::new("text", "plain"));
+        assert!(endpoint.decode_body::<Sdp>(&request).is_none());
+        assert!(endpoint.decode_body::<String>(&request).is_none());
+
+        let sdp_type = MediaType::new("application", "sdp");
+        let body = endpoint
+            .body_codecs()
+            .encode(&sdp_type, &Sdp { version: 0 })
+            .unwrap()
+            .unwrap();
+        assert_eq!(&body[..], b"v=0\r\n");
+    }
+
+    #[test]
+    fn test_reason_phrase_override() {
+        let endpoint = Endpoint::builder()
+            .with_reason_phrase(StatusCode::BusyHere, "User Busy")
+            .build();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Invite, transport);
+
+        let response = endpoint.create_outgoing_response(&request, StatusCode::BusyHere, None);
+        assert_eq!(response.reason().as_str(), "User Busy");
+
+        let response = endpoint.create_outgoing_response(
+            &request,
+            StatusCode::BusyHere,
+            Some("Try Later".into()),
+        );
+        assert_eq!(response.reason().as_str(), "Try Later");
+
+        let response = endpoint.create_outgoing_response(&request, StatusCode::Decline, None);
+        assert_eq!(response.reason().as_str(), "Decline");
+    }
+
+    #[tokio::test]
+    async fn test_generated_via_uses_advertised_address() {
+        let advertised: HostPort = "203.0.113.10:5060".parse().unwrap();
+        let transport = Transport::new(MockTransport::new_udp());
+        let endpoint = Endpoint::builder()
+            .with_advertised_address(
+                TransportType::Udp,
+                transport.local_addr(),
+                advertised.clone(),
+            )
+            .build();
+        let target = Some((transport.clone(), transport.local_addr()));
+        let uri = Uri::from_str("sip:bob@127.0.0.1:5070").unwrap();
+
+        let outgoing = endpoint
+            .create_outgoing_request(Request::new(Method::Options, uri), target)
+            .await
+            .unwrap();
+        let via = outgoing.request.headers.iter().find_map(|h| h.as_via());
+
+        assert_eq!(via.unwrap().sent_by, advertised);
+        assert_eq!(endpoint.local_name(&transport), advertised);
+    }
+
+    #[test]
+    fn test_advertised_address_is_per_bound_address() {
+        let public: HostPort = "203.0.113.10:5060".parse().unwrap();
+        let private: HostPort = "198.51.100.20:5060".parse().unwrap();
+        let first = Transport::new(
+            MockTransport::new_udp().with_local_addr("10.0.0.1:5060".parse().unwrap()),
+        );
+        let second = Transport::new(
+            MockTransport::new_udp().with_local_addr("192.168.1.1:5060".parse().unwrap()),
+        );
+        let unconfigured = Transport::new(
+            MockTransport::new_udp().with_local_addr("172.16.0.1:5060".parse().unwrap()),
+        );
+        let endpoint = Endpoint::builder()
+            .with_advertised_address(TransportType::Udp, first.local_addr(), public.clone())
+            .with_advertised_address(TransportType::Udp, second.local_addr(), private.clone())
+            .build();
+
+        assert_eq!(endpoint.local_name(&first), public);
+        assert_eq!(endpoint.local_name(&second), private);
+        assert_eq!(
+            endpoint.local_name(&unconfigured),
+            HostPort::from(unconfigured.local_addr())
+        );
+    }
+
+    #[test]
+    fn test_advertised_address_of_unspecified_bind_address() {
+        let advertised: HostPort = "203.0.113.10:5060".parse().unwrap();
+        let endpoint = Endpoint::builder()
+            .with_advertised_address(
+                TransportType::Tcp,
+                "0.0.0.0:5060".parse().unwrap(),
+                advertised.clone(),
+            )
+            .build();
+        let accepted = Transport::new(
+            MockTransport::new_tcp()
+                .with_local_addr("10.0.0.1:5060".parse().unwrap())
+                .with_remote_addr("10.0.0.2:49152".parse().unwrap()),
+        );
+
+        assert_eq!(endpoint.local_name(&accepted), advertised);
+    }
+
+    #[test]
+    fn test_local_name_falls_back_to_bound_address() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+
+        assert_eq!(
+            endpoint.local_name(&transport),
+            HostPort::from(transport.local_addr())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_closing_transport_notifies_handler() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use tokio::sync::Notify;
+
+        #[derive(Default)]
+        struct Lifecycle {
+            created: AtomicUsize,
+            closed: Notify,
+        }
+
+        struct LifecycleHandler(Arc<Lifecycle>);
+
+        #[async_trait::async_trait]
+        impl EndpointHandler for LifecycleHandler {
+            async fn handle(&self, _request: IncomingRequest, _endpoint: &Endpoint) {}
+
+            async fn on_transport_created(&self, _transport: &Transport, _endpoint: &Endpoint) {
+                self.0.created.fetch_add(1, Ordering::SeqCst);
+            }
+
+            async fn on_transport_closed(&self, _transport: &Transport, _endpoint: &Endpoint) {
+                self.0.closed.notify_one();
+            }
+        }
+
+        let lifecycle = Arc::new(Lifecycle::default());
+        let endpoint = Endpoint::builder()
+            .with_handler(LifecycleHandler(lifecycle.clone()))
+            .build();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr();
+        tokio::spawn(listener.accept_clients(endpoint.clone()));
+
+        let client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        drop(client);
+
+        tokio::time::timeout(Duration::from_secs(1), lifecycle.closed.notified())
+            .await
+            .expect("on_transport_closed was not called");
+        assert_eq!(lifecycle.created.load(Ordering::SeqCst), 1);
+        assert_eq!(endpoint.transports().transport_count().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_closing_transport_shuts_connection_down() {
+        use tokio::io::AsyncReadExt;
+
+        let endpoint = create_test_endpoint();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr();
+        tokio::spawn(listener.accept_clients(endpoint.clone()));
+
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let transport = loop {
+            if let Some(transport) = endpoint.transports().all().unwrap().pop() {
+                break transport;
+            }
+            tokio::task::yield_now().await;
+        };
+        endpoint.close_transport(&transport).await.unwrap();
+
+        let mut buf = [0; 1];
+        let read = tokio::time::timeout(Duration::from_secs(1), client.read(&mut buf))
+            .await
+            .expect("connection was not shut down");
+        assert_eq!(read.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_adds_mime_version_to_multipart_body() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let mut outgoing = create_outgoing_with_body(Transport::new(mock.clone()), 10);
+        let media_type = MediaType::from_static("multipart/mixed;boundary=unique").unwrap();
+        outgoing
+            .request
+            .headers
+            .push(Header::ContentType(ContentType::new(media_type)));
+
+        endpoint.send_outgoing_request(&mut outgoing).await.unwrap();
+
+        let sent = mock.get_last_sent_request().unwrap();
+        let mime_version = sent.headers.iter().find_map(|h| h.as_mime_version());
+        assert_eq!(mime_version, Some(&MimeVersion::new(1, 0)));
+    }
+
+    fn create_outgoing_with_body(transport: Transport, body_size: usize) -> OutgoingRequest {
+        let mut request = create_test_request(Method::Options, transport.clone()).request;
+        request.body = Some(vec![b'a'; body_size].as_slice().into());
+
+        OutgoingRequest {
+            request,
+            target_info: TargetTransportInfo {
+                target: transport.local_addr(),
+                transport,
+            },
+            encoded: Bytes::new(),
+        }
+    }
+
+    fn create_endpoint_with_transports() -> (Endpoint, MockTransport, MockTransport) {
+        let endpoint = Endpoint::builder().with_udp_size_threshold(1300).build();
+        let udp = MockTransport::new_udp();
+        let tcp = MockTransport::new_tcp();
+
+        endpoint
+            .transports()
+            .register_transport(Transport::new(udp.clone()))
+            .unwrap();
+        endpoint
+            .transports()
+            .register_transport(Transport::new(tcp.clone()))
+            .unwrap();
+
+        (endpoint, udp, tcp)
+    }
+
+    #[tokio::test]
+    async fn test_keeps_udp_for_requests_within_size_threshold() {
+        let (endpoint, udp, tcp) = create_endpoint_with_transports();
+        let mut outgoing = create_outgoing_with_body(Transport::new(udp.clone()), 100);
+
+        endpoint.send_outgoing_request(&mut outgoing).await.unwrap();
+
+        assert!(outgoing.encoded.len() <= 1300);
+        assert_eq!(udp.sent_count(), 1);
+        assert_eq!(tcp.sent_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_switches_to_tcp_for_requests_exceeding_size_threshold() {
+        let (endpoint, udp, tcp) = create_endpoint_with_transports();
+        let mut outgoing = create_outgoing_with_body(Transport::new(udp.clone()), 1300);
+
+        endpoint.send_outgoing_request(&mut outgoing).await.unwrap();
+
+        assert!(outgoing.encoded.len() > 1300);
+        assert_eq!(udp.sent_count(), 0);
+        assert_eq!(tcp.sent_count(), 1);
+
+        let sent = tcp.get_last_sent_request().unwrap();
+        let via = sent.headers.iter().find_map(|h| h.as_via()).unwrap();
+        assert_eq!(via.transport, TransportType::Tcp);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_waits_for_pending_server_transaction() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Options, transport);
+        let transaction = ServerTransaction::new(request, endpoint.clone());
+
+        let shutdown = tokio::spawn({
+            let endpoint = endpoint.clone();
+            async move { endpoint.shutdown(Duration::from_secs(60)).await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(endpoint.is_shutting_down());
+        assert!(!shutdown.is_finished());
+
+        // Over UDP the transaction stays in Completed until Timer J fires.
+        transaction.send_final_status(StatusCode::Ok).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(!shutdown.is_finished());
+
+        assert!(shutdown.await.unwrap().is_ok());
+        assert!(endpoint.transactions().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_times_out_with_pending_server_transaction() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Options, transport);
+        let _transaction = ServerTransaction::new(request, endpoint.clone());
+
+        let result = endpoint.shutdown(Duration::from_secs(32)).await;
+
+        assert!(matches!(result, Err(crate::Error::ShutdownTimeout(1))));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drops_only_new_requests() {
+        let (sink, mut unhandled) = mpsc::channel(4);
+        let endpoint = Endpoint::builder()
+            .with_transaction(Default::default())
+            .with_unhandled_sink(sink)
+            .build();
+        let transport = Transport::new(MockTransport::new_udp());
+        endpoint.shutdown(Duration::from_secs(1)).await.unwrap();
+
+        for method in [Method::Options, Method::Ack] {
+            let request = create_test_request(method, transport.clone());
+            endpoint.process_request(request).await.unwrap();
+        }
+
+        assert!(matches!(
+            unhandled.try_recv(),
+            Ok(UnhandledMessage::Request(request)) if request.method() == Method::Ack
+        ));
+        assert!(unhandled.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_answers_retransmissions() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let request = create_test_request(Method::Options, Transport::new(mock.clone()));
+        let transaction = ServerTransaction::new(request.clone(), endpoint.clone());
+        transaction.send_final_status(StatusCode::Ok).await.unwrap();
+
+        let shutdown = tokio::spawn({
+            let endpoint = endpoint.clone();
+            async move { endpoint.shutdown(Duration::from_secs(60)).await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        endpoint.process_request(request).await.unwrap();
+        tokio::task::yield_now().await;
+
+        assert_eq!(mock.sent_count(), 2);
+        assert!(shutdown.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_shuts_connections_down() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use tokio::io::AsyncReadExt;
+
+        struct CloseCounter(Arc<AtomicUsize>);
+
+        #[async_trait::async_trait]
+        impl EndpointHandler for CloseCounter {
+            async fn handle(&self, _request: IncomingRequest, _endpoint: &Endpoint) {}
+
+            async fn on_transport_closed(&self, _transport: &Transport, _endpoint: &Endpoint) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let closed = Arc::new(AtomicUsize::new(0));
+        let endpoint = Endpoint::builder()
+            .with_handler(CloseCounter(closed.clone()))
+            .build();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr();
+        tokio::spawn(listener.accept_clients(endpoint.clone()));
+
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        while endpoint.transports().transport_count().unwrap() == 0 {
+            tokio::task::yield_now().await;
+        }
+        endpoint.shutdown(Duration::from_secs(1)).await.unwrap();
+
+        let mut buf = [0; 1];
+        let read = tokio::time::timeout(Duration::from_secs(1), client.read(&mut buf))
+            .await
+            .expect("connection was not shut down");
+        assert_eq!(read.unwrap(), 0);
+        assert_eq!(endpoint.transports().transport_count().unwrap(), 0);
+        assert_eq!(closed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_response_hook_adds_server_header() {
+        use crate::message::headers::Server;
+
+        struct ServerHeaderHandler;
+
+        #[async_trait::async_trait]
+        impl EndpointHandler for ServerHeaderHandler {
+            async fn handle(&self, _request: IncomingRequest, _endpoint: &Endpoint) {}
+
+            async fn on_outgoing_response(
+                &self,
+                response: &mut OutgoingResponse,
+                _endpoint: &Endpoint,
+            ) {
+                response
+                    .headers_mut()
+                    .push(Header::Server(Server::new("csip-test")));
+            }
+        }
+
+        let endpoint = Endpoint::builder()
+            .with_handler(ServerHeaderHandler)
+            .build();
+        let mock = MockTransport::new_udp();
+        let invite = create_test_request(Method::Invite, Transport::new(mock.clone()));
+        let options = create_test_request(Method::Options, Transport::new(mock.clone()));
+
+        for (request, code) in [(invite, StatusCode::Ringing), (options, StatusCode::Ok)] {
+            endpoint.respond(&request, code, None).await.unwrap();
+
+            let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+                panic!("no response was sent");
+            };
+            let servers: Vec<_> = response
+                .headers()
+                .iter()
+                .filter_map(Header::as_server)
+                .collect();
+            assert_eq!(servers.len(), 1);
+            assert_eq!(servers[0].to_string(), "Server: csip-test");
+        }
+        assert_eq!(mock.sent_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_udp_transport_is_selected_by_address_family() {
+        let endpoint = Endpoint::builder()
+            .with_udp_address("127.0.0.1:0".parse().unwrap())
+            .with_udp_address("[::1]:0".parse().unwrap())
+            .build();
+        endpoint.start_transports().await.unwrap();
+        assert_eq!(endpoint.transports().transport_count().unwrap(), 2);
+
+        let transports = endpoint.transports();
+        let v6_target = "[::1]:5060".parse().unwrap();
+        let v4_target = "127.0.0.1:5060".parse().unwrap();
+        let v6 = transports
+            .get_or_create_transport(TransportType::Udp, v6_target, &endpoint)
+            .await
+            .unwrap();
+        let v4 = transports
+            .get_or_create_transport(TransportType::Udp, v4_target, &endpoint)
+            .await
+            .unwrap();
+
+        assert!(v6.local_addr().is_ipv6());
+        assert!(v4.local_addr().is_ipv4());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_send_is_retried_after_transient_error() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp().with_transient_failures(1);
+        let request = create_test_request(Method::Options, Transport::new(mock.clone()));
+
+        endpoint
+            .respond(&request, StatusCode::Ok, None)
+            .await
+            .unwrap();
+
+        assert_eq!(mock.sent_count(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stateless_retransmission_reuses_response() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let request = create_test_request(Method::Options, Transport::new(mock.clone()));
+        let created = AtomicU32::new(0);
+        let create = |endpoint: &Endpoint, request: &IncomingRequest| {
+            created.fetch_add(1, Ordering::SeqCst);
+            endpoint.create_outgoing_response(request, StatusCode::Ok, None)
+        };
+
+        endpoint.respond_stateless(&request, create).await.unwrap();
+        endpoint.respond_stateless(&request, create).await.unwrap();
+
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+        assert_eq!(mock.sent_count(), 2);
+
+        // Past the window the request is processed again.
+        tokio::time::advance(endpoint.timers().t1 * 64).await;
+        endpoint.respond_stateless(&request, create).await.unwrap();
+
+        assert_eq!(created.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stateless_ack_is_not_answered() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let invite = create_test_request(Method::Invite, Transport::new(mock.clone()));
+        endpoint
+            .respond_stateless(&invite, |endpoint, request| {
+                endpoint.create_outgoing_response(request, StatusCode::BusyHere, None)
+            })
+            .await
+            .unwrap();
+
+        let mut ack = invite.clone();
+        ack.request.req_line.method = Method::Ack;
+        ack.incoming_info.mandatory_headers.cseq.method = Method::Ack;
+        endpoint
+            .respond_stateless(&ack, |_, _| panic!("an ACK must not be answered"))
+            .await
+            .unwrap();
+
+        assert_eq!(mock.sent_count(), 1);
+    }
+
+    /// Returns an `OPTIONS` request and a `200` response to it
+    /// that matches no transaction.
+    fn unhandled_request_and_response(endpoint: &Endpoint) -> (IncomingRequest, IncomingResponse) {
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Options, transport.clone());
+        let outgoing = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+        let response = IncomingResponse {
+            incoming_info: Box::new(IncomingInfo {
+                mandatory_headers: MandatoryHeaders::from_headers(outgoing.headers()).unwrap(),
+                transport: request.incoming_info.transport.clone(),
+            }),
+            response: outgoing.response,
+        };
+
+        (request, response)
+    }
+
+    #[tokio::test]
+    async fn test_unhandled_messages_are_sent_to_sink() {
+        let (sink, mut unhandled) = mpsc::channel(4);
+        let endpoint = Endpoint::builder()
+            .with_transaction(Default::default())
+            .with_unhandled_sink(sink)
+            .build();
+        let (request, response) = unhandled_request_and_response(&endpoint);
+
+        endpoint.process_request(request).await.unwrap();
+        endpoint.process_response(response).await.unwrap();
+
+        assert!(matches!(
+            unhandled.recv().await,
+            Some(UnhandledMessage::Request(request)) if request.method() == Method::Options
+        ));
+        assert!(matches!(
+            unhandled.recv().await,
+            Some(UnhandledMessage::Response(response)) if response.status() == StatusCode::Ok
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_messages_ignored_by_handler_are_sent_to_sink() {
+        struct IgnoringHandler;
+
+        #[async_trait::async_trait]
+        impl EndpointHandler for IgnoringHandler {
+            async fn handle(&self, request: IncomingRequest, endpoint: &Endpoint) {
+                endpoint.report_unhandled(UnhandledMessage::Request(Box::new(request)));
+            }
+        }
+
+        let (sink, mut unhandled) = mpsc::channel(4);
+        let endpoint = Endpoint::builder()
+            .with_transaction(Default::default())
+            .with_handler(IgnoringHandler)
+            .with_unhandled_sink(sink)
+            .build();
+        let (request, response) = unhandled_request_and_response(&endpoint);
+
+        endpoint.process_request(request).await.unwrap();
+        endpoint.process_response(response).await.unwrap();
+
+        assert!(matches!(
+            unhandled.recv().await,
+            Some(UnhandledMessage::Request(request)) if request.method() == Method::Options
+        ));
+        assert!(matches!(
+            unhandled.recv().await,
+            Some(UnhandledMessage::Response(response)) if response.status() == StatusCode::Ok
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_messages_taken_by_handler_are_not_sent_to_sink() {
+        struct TakingHandler;
+
+        #[async_trait::async_trait]
+        impl EndpointHandler for TakingHandler {
+            async fn handle(&self, _request: IncomingRequest, _endpoint: &Endpoint) {}
+
+            async fn handle_response(&self, _response: IncomingResponse, _endpoint: &Endpoint) {}
+        }
+
+        let (sink, mut unhandled) = mpsc::channel(4);
+        let endpoint = Endpoint::builder()
+            .with_transaction(Default::default())
+            .with_handler(TakingHandler)
+            .with_unhandled_sink(sink)
+            .build();
+        let (request, response) = unhandled_request_and_response(&endpoint);
+
+        endpoint.process_request(request).await.unwrap();
+        endpoint.process_response(response).await.unwrap();
+
+        assert!(unhandled.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_full_unhandled_sink_drops_messages() {
+        let (sink, mut unhandled) = mpsc::channel(1);
+        let endpoint = Endpoint::builder().with_unhandled_sink(sink).build();
+        let transport = Transport::new(MockTransport::new_udp());
+
+        for _ in 0..2 {
+            let request = create_test_request(Method::Options, transport.clone());
+            endpoint.process_request(request).await.unwrap();
+        }
+
+        assert!(unhandled.recv().await.is_some());
+        assert!(unhandled.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_when_retry_is_disabled() {
+        let endpoint = Endpoint::builder()
+            .with_send_retry(SendRetryConfig {
+                retries: 0,
+                ..Default::default()
+            })
+            .build();
+        let mock = MockTransport::new_udp().with_transient_failures(1);
+        let request = create_test_request(Method::Options, Transport::new(mock.clone()));
+
+        let result = endpoint.respond(&request, StatusCode::Ok, None).await;
+
+        assert!(matches!(result, Err(crate::Error::Io(_))));
+        assert_eq!(mock.sent_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_respond_with_body_sets_content_type_and_length() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let request = create_test_request(Method::Invite, Transport::new(mock.clone()));
+        let sdp = "v=0\r\no=bob 2890844527 2890844527 IN IP4 192.0.2.4\r\ns=-\r\n";
+
+        endpoint
+            .respond_with_body(
+                &request,
+                StatusCode::Ok,
+                None,
+                MediaType::new("application", "sdp"),
+                sdp,
+            )
+            .await
+            .unwrap();
+
+        let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+            panic!("no response was sent");
+        };
+        let content_type = response
+            .headers()
+            .iter()
+            .find_map(Header::as_content_type)
+            .unwrap();
+        let content_length = response
+            .headers()
+            .iter()
+            .find_map(Header::as_content_length)
+            .unwrap();
+        assert_eq!(
+            content_type.media_type(),
+            &MediaType::new("application", "sdp")
+        );
+        assert_eq!(content_length.clen() as usize, sdp.len());
+        assert_eq!(response.body().map(|body| &body[..]), Some(sdp.as_bytes()));
+    }
+
+    #[tokio::test]
+    async fn test_outbound_addr_falls_back_to_sent_by() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let via = Via::from_str("SIP/2.0/UDP 10.0.0.1:5080;branch=z9hG4bK1").unwrap();
+
+        let (addr, _) = endpoint.get_outbound_addr(&via, &transport).await.unwrap();
+
+        assert_eq!(addr, "10.0.0.1:5080".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_response_reconnects_when_connection_is_gone() {
+        #[derive(Default)]
+        struct TcpFactory(Arc<Mutex<Option<MockTransport>>>);
+
+        #[async_trait::async_trait]
+        impl TransportFactory for TcpFactory {
+            fn transport_type(&self) -> TransportType {
+                TransportType::Tcp
+            }
+
+            async fn create(&self, addr: SocketAddr, _endpoint: &Endpoint) -> Result<Transport> {
+                let mock = MockTransport::new_tcp().with_remote_addr(addr);
+                *self.0.lock().unwrap() = Some(mock.clone());
+
+                Ok(Transport::new(mock))
+            }
+        }
+
+        let factory = TcpFactory::default();
+        let created = factory.0.clone();
+        let endpoint = create_test_endpoint();
+        endpoint.transports().register_factory(factory).unwrap();
+        let dropped = MockTransport::new_tcp().with_remote_addr("10.0.0.1:49152".parse().unwrap());
+        let connection = Transport::new(dropped.clone());
+        endpoint.add_transport(connection.clone()).await.unwrap();
+        let mut request = create_test_request(Method::Options, connection.clone());
+        let mut via = Via::from_str("SIP/2.0/TCP client.atlanta.com:5080;branch=z9hG4bK1").unwrap();
+        via.received = Some("10.0.0.1".parse().unwrap());
+        request.incoming_info.mandatory_headers.via = via;
+
+        endpoint.close_transport(&connection).await.unwrap();
+        endpoint
+            .respond(&request, StatusCode::Ok, None)
+            .await
+            .unwrap();
+
+        let reconnected = created
+            .lock()
+            .unwrap()
+            .clone()
+            .expect("no connection was opened");
+        assert_eq!(dropped.sent_count(), 0);
+        assert_eq!(reconnected.sent_count(), 1);
+        assert_eq!(
+            reconnected.get_last_sent_response().unwrap().status(),
+            StatusCode::Ok
+        );
+        assert_eq!(
+            Transport::new(reconnected).remote_addr(),
+            Some("10.0.0.1:5080".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outbound_addr_reuses_open_connection() {
+        let endpoint = create_test_endpoint();
+        let remote = "10.0.0.1:49152".parse().unwrap();
+        let open = Transport::new(MockTransport::new_tcp().with_remote_addr(remote));
+        endpoint
+            .transports()
+            .register_transport(open.clone())
+            .unwrap();
+        let via = Via::from_str("SIP/2.0/TCP client.atlanta.com:5080;branch=z9hG4bK1").unwrap();
+
+        let (addr, transport) = endpoint.get_outbound_addr(&via, &open).await.unwrap();
+
+        assert_eq!(addr, remote);
+        assert_eq!(transport.key(), open.key());
+    }
+
+    #[tokio::test]
+    async fn test_transport_stats_count_received_packets() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        endpoint
+            .transports()
+            .register_transport(transport.clone())
+            .unwrap();
+        let source = "127.0.0.1:5070".parse().unwrap();
+        let garbage = Bytes::from_static(b"not a sip message\r\n\r\n");
+
+        endpoint.receive_transport_message(TransportMessage {
+            transport: transport.clone(),
+            packet: Packet::new(garbage.clone(), source),
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let expected = TransportStats {
+            messages_received: 1,
+            bytes_received: garbage.len() as u64,
+            parse_errors: 1,
+            ..Default::default()
+        };
+        assert_eq!(transport.stats(), expected);
+        assert_eq!(endpoint.transports().stats(), expected);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_packets_across_transports_are_processed_concurrently() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use tokio::sync::Notify;
+
+        const PACKETS: usize = 2000;
+
+        #[derive(Default)]
+        struct Throughput {
+            processed: AtomicUsize,
+            done: Notify,
+            release: Notify,
+        }
+
+        struct ThroughputHandler(Arc<Throughput>);
+
+        #[async_trait::async_trait]
+        impl EndpointHandler for ThroughputHandler {
+            async fn handle(&self, request: IncomingRequest, _endpoint: &Endpoint) {
+                if request.incoming_info.mandatory_headers.call_id.id() == "slow" {
+                    // Stalls until every other packet has been handled.
+                    self.0.release.notified().await;
+                    return;
+                }
+                if self.0.processed.fetch_add(1, Ordering::SeqCst) + 1 == PACKETS - 1 {
+                    self.0.done.notify_one();
+                }
+            }
+        }
+
+        let handler = Arc::new(Throughput::default());
+        let endpoint = EndpointBuilder::new()
+            .with_handler(ThroughputHandler(handler.clone()))
+            .build();
+        let transports = [
+            Transport::new(MockTransport::new_udp()),
+            Transport::new(
+                MockTransport::new_tcp().with_remote_addr("127.0.0.1:5070".parse().unwrap()),
+            ),
+        ];
+        for transport in &transports {
+            endpoint
+                .transports()
+                .register_transport(transport.clone())
+                .unwrap();
+        }
+        let source = "127.0.0.1:5070".parse().unwrap();
+        let done = handler.done.notified();
+
+        for n in 0..PACKETS {
+            let call_id = if n == 0 {
+                "slow".to_owned()
+            } else {
+                n.to_string()
+            };
+            let transport = &transports[n % transports.len()];
+            let packet = format!(
+                "MESSAGE sip:bob@127.0.0.1 SIP/2.0\r\n\
+                 Via: SIP/2.0/{} 127.0.0.1:5070;branch=z9hG4bK{n}\r\n\
+                 From: <sip:alice@127.0.0.1>;tag={n}\r\n\
+                 To: <sip:bob@127.0.0.1>\r\n\
+                 Call-ID: {call_id}\r\n\
+                 CSeq: 1 MESSAGE\r\n\
+                 Content-Length: 0\r\n\r\n",
+                transport.transport_type()
+            );
+            endpoint.receive_transport_message(TransportMessage {
+                transport: transport.clone(),
+                packet: Packet::new(Bytes::from(packet), source),
+            });
+        }
+
+        tokio::time::timeout(Duration::from_secs(10), done)
+            .await
+            .expect("packets were blocked behind the stalled request");
+        assert_eq!(handler.processed.load(Ordering::SeqCst), PACKETS - 1);
+        let received: u64 = transports
+            .iter()
+            .map(|transport| transport.stats().messages_received)
+            .sum();
+        assert_eq!(received, PACKETS as u64);
+
+        handler.release.notify_one();
+    }
+}
@@ -0,0 +1,250 @@
+//! Responses sent without a server transaction.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use tokio::time::Instant;
+
+use crate::message::Response;
+use crate::transaction::manager::TransactionKey;
+use crate::transport::outgoing::{OutgoingResponse, TargetTransportInfo};
+
+/// The maximum number of stateless responses kept at once, the
+/// oldest ones are dropped first when the limit is reached.
+pub const MAX_STATELESS_RESPONSES: usize = 4096;
+
+/// The responses sent statelessly, kept for a while to answer
+/// retransmissions of their requests.
+pub(crate) struct StatelessResponses {
+    state: Mutex<State>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<TransactionKey, Entry>,
+    /// The keys in the order they expire.
+    expiry: VecDeque<(Instant, TransactionKey)>,
+}
+
+struct Entry {
+    expires: Instant,
+    /// `None` while the response is being created.
+    sent: Option<SentResponse>,
+}
+
+struct SentResponse {
+    response: Response,
+    target_info: TargetTransportInfo,
+    encoded: Bytes,
+}
+
+/// The result of [`StatelessResponses::get_or_reserve`].
+pub(crate) enum Lookup {
+    /// The response sent to a previous copy of the request.
+    Sent(OutgoingResponse),
+    /// A previous copy of the request is still being answered.
+    Pending,
+    /// The key was reserved, the caller must create the response
+    /// and [`complete`](StatelessResponses::complete) or
+    /// [`release`](StatelessResponses::release) it.
+    Reserved,
+}
+
+impl Default for StatelessResponses {
+    fn default() -> Self {
+        Self::with_capacity(MAX_STATELESS_RESPONSES)
+    }
+}
+
+impl StatelessResponses {
+    /// Creates an empty set keeping at most `capacity` responses.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            state: Default::default(),
+            capacity,
+        }
+    }
+
+    /// Returns the response sent to the request with `key`, if
+    /// it is still within its window, or reserves `key` until
+    /// `expires` if there is none.
+    ///
+    /// The reservation is done under the same lock as the lookup,
+    /// so only one copy of a request creates its response.
+    pub(crate) fn get_or_reserve(&self, key: &TransactionKey, expires: Instant) -> Lookup {
+        let mut state = self.state.lock().expect("Lock failed");
+        state.remove_expired(Instant::now());
+
+        match state.entries.get(key) {
+            Some(Entry {
+                sent: Some(sent), ..
+            }) => {
+                return Lookup::Sent(OutgoingResponse {
+                    response: sent.response.clone(),
+                    target_info: sent.target_info.clone(),
+                    encoded: sent.encoded.clone(),
+                });
+            }
+            Some(Entry { sent: None, .. }) => return Lookup::Pending,
+            None => (),
+        }
+
+        while state.entries.len() >= self.capacity.max(1) {
+            state.remove_oldest();
+        }
+        state.entries.insert(
+            key.clone(),
+            Entry {
+                expires,
+                sent: None,
+            },
+        );
+        state.expiry.push_back((expires, key.clone()));
+
+        Lookup::Reserved
+    }
+
+    /// Keeps `response`, sent to the request with the reserved
+    /// `key`, until the reservation expires.
+    pub(crate) fn complete(&self, key: &TransactionKey, response: &OutgoingResponse) {
+        let mut state = self.state.lock().expect("Lock failed");
+
+        if let Some(entry) = state.entries.get_mut(key) {
+            entry.sent = Some(SentResponse {
+                response: response.response.clone(),
+                target_info: response.target_info.clone(),
+                encoded: response.encoded.clone(),
+            });
+        }
+    }
+
+    /// Drops the reservation of `key`, when no response could be
+    /// sent.
+    pub(crate) fn release(&self, key: &TransactionKey) {
+        let mut state = self.state.lock().expect("Lock failed");
+
+        if let Some(Entry { sent: None, .. }) = state.entries.get(key) {
+            state.entries.remove(key);
+        }
+    }
+}
+
+impl State {
+    fn remove_expired(&mut self, now: Instant) {
+        while self
+            .expiry
+            .front()
+            .is_some_and(|(expires, _)| *expires <= now)
+        {
+            self.remove_oldest();
+        }
+    }
+
+    fn remove_oldest(&mut self) {
+        let Some((expires, key)) = self.expiry.pop_front() else {
+            return;
+        };
+        // The key may have been released and reserved again
+        // since, with a later expiry.
+        if self
+            .entries
+            .get(&key)
+            .is_some_and(|entry| entry.expires == expires)
+        {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::message::{Method, StatusCode};
+    use crate::test_utils::transport::MockTransport;
+    use crate::test_utils::{create_test_endpoint, create_test_request};
+    use crate::transport::Transport;
+
+    const WINDOW: Duration = Duration::from_secs(32);
+
+    fn key_and_response(cseq: u32) -> (TransactionKey, OutgoingResponse) {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let mut request = create_test_request(Method::Options, transport);
+        request.incoming_info.mandatory_headers.cseq.cseq = cseq;
+        request.incoming_info.mandatory_headers.via.branch = Some(format!("z9hG4bK{cseq}"));
+        let response = endpoint.create_outgoing_response(&request, StatusCode::Ok, None);
+
+        (TransactionKey::from_request(&request), response)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_second_copy_waits_for_the_first() {
+        let responses = StatelessResponses::default();
+        let (key, response) = key_and_response(1);
+        let expires = Instant::now() + WINDOW;
+
+        assert!(matches!(
+            responses.get_or_reserve(&key, expires),
+            Lookup::Reserved
+        ));
+        assert!(matches!(
+            responses.get_or_reserve(&key, expires),
+            Lookup::Pending
+        ));
+
+        responses.complete(&key, &response);
+        assert!(matches!(
+            responses.get_or_reserve(&key, expires),
+            Lookup::Sent(_)
+        ));
+
+        tokio::time::advance(WINDOW).await;
+        assert!(matches!(
+            responses.get_or_reserve(&key, expires),
+            Lookup::Reserved
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_oldest_response_is_dropped_at_capacity() {
+        let responses = StatelessResponses::with_capacity(2);
+        let expires = Instant::now() + WINDOW;
+        let keys: Vec<_> = (1..=3).map(|cseq| key_and_response(cseq).0).collect();
+
+        for key in &keys {
+            assert!(matches!(
+                responses.get_or_reserve(key, expires),
+                Lookup::Reserved
+            ));
+        }
+
+        assert_eq!(responses.state.lock().unwrap().entries.len(), 2);
+        assert!(matches!(
+            responses.get_or_reserve(&keys[2], expires),
+            Lookup::Pending
+        ));
+        assert!(matches!(
+            responses.get_or_reserve(&keys[0], expires),
+            Lookup::Reserved
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_released_key_can_be_reserved_again() {
+        let responses = StatelessResponses::default();
+        let (key, _) = key_and_response(1);
+        let expires = Instant::now() + WINDOW;
+
+        responses.get_or_reserve(&key, expires);
+        responses.release(&key);
+
+        assert!(matches!(
+            responses.get_or_reserve(&key, expires),
+            Lookup::Reserved
+        ));
+    }
+}
@@ -20,8 +20,12 @@ pub type Result<T> = std::result::Result<T, Error>;
 // }
 
 impl std::convert::From<Utf8Error> for Error {
-    fn from(value: Utf8Error) -> Self {
-        todo!()
+    /// The conversion has no access to the parser, so the
+    /// resulting error carries no position.
+    fn from(_: Utf8Error) -> Self {
+        let kind = ParseErrorKind::Scanner(ScannerError::InvalidUtf8);
+
+        ParseError::new(kind, Position::default(), 0).into()
     }
 }
 
@@ -63,9 +67,21 @@ pub enum Error {
     #[error("Invalid Status Code")]
     InvalidStatusCode,
 
+    #[error("Invalid Method")]
+    InvalidMethod,
+
+    #[error("Shutdown timed out with {0} transactions still active")]
+    ShutdownTimeout(usize),
+
     #[error("Fmt Error")]
     FmtError(std::fmt::Error),
 
+    #[error("Unsupported content encoding '{0}'")]
+    UnsupportedContentEncoding(String),
+
+    #[error("Decoded body exceeds the limit of {0} bytes")]
+    DecodedBodyTooLarge(usize),
+
     #[error("Internal error: {0}")]
     Other(String),
 }
@@ -80,20 +96,95 @@ impl Error {
 pub struct ParseError {
     pub kind: ParseErrorKind,
     pub position: Position,
+    /// Byte offset in the buffer where parsing stopped.
+    pub offset: usize,
+    /// The part of the message being parsed, if a whole message was parsed.
+    pub section: Option<MessageSection>,
+    /// A copy of the line where parsing stopped, without the line
+    /// terminator, if a whole message was parsed.
+    pub source_line: Option<String>,
 }
 
 impl ParseError {
-    pub fn new(kind: ParseErrorKind, position: Position) -> Self {
-        Self { kind, position }
+    pub fn new(kind: ParseErrorKind, position: Position, offset: usize) -> Self {
+        Self {
+            kind,
+            position,
+            offset,
+            section: None,
+            source_line: None,
+        }
+    }
+
+    /// Returns the line where the error occurred, starting from 1.
+    pub fn line(&self) -> usize {
+        self.position.line
+    }
+
+    /// Returns the column where the error occurred.
+    pub fn column(&self) -> usize {
+        self.position.column
+    }
+
+    pub(crate) fn with_section(mut self, section: MessageSection) -> Self {
+        self.section = Some(section);
+        self
+    }
+
+    // Copies the line of `input` that contains the error offset.
+    pub(crate) fn with_source_line(mut self, input: &[u8]) -> Self {
+        let offset = self.offset.min(input.len());
+        let start = input[..offset]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let end = input[offset..]
+            .iter()
+            .position(|&b| b == b'\r' || b == b'\n')
+            .map_or(input.len(), |i| offset + i);
+
+        self.source_line = Some(String::from_utf8_lossy(&input[start..end]).into_owned());
+        self
+    }
+
+    /// Returns the name of the header being parsed when the error occurred.
+    pub fn header_name(&self) -> Option<&str> {
+        match &self.section {
+            Some(MessageSection::Header(name)) => Some(name),
+            _ => None,
+        }
     }
 }
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+        write!(f, "Failed to parse {:?}", self.kind)?;
+        match &self.section {
+            Some(MessageSection::StartLine) => write!(f, " in start line")?,
+            Some(MessageSection::Header(name)) => write!(f, " in '{}' header", name)?,
+            Some(MessageSection::Body) => write!(f, " in body")?,
+            None => (),
+        }
+        write!(
+            f,
+            " at line {}, column {} (offset {})",
+            self.position.line, self.position.column, self.offset
+        )
     }
 }
 
+/// The part of a SIP message where a [`ParseError`] occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageSection {
+    /// The Request-Line or Status-Line.
+    StartLine,
+    /// A header, with the name as it appears in the message.
+    Header(String),
+    /// Anything after the last header, starting with the empty
+    /// line that ends the header block.
+    Body,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ParseErrorKind {
     StatusCode,
@@ -114,6 +205,12 @@ pub enum DialogError {
 
     #[error("Missing To tag in 'To' header")]
     MissingTagInToHeader,
+
+    #[error("Response does not belong to the dialog")]
+    ResponseMismatch,
+
+    #[error("Local CSeq number exceeds the maximum")]
+    CSeqExhausted,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -124,6 +221,12 @@ pub enum TransactionError {
     AckCannotCreateTransaction,
     #[error("Failed to send request: {0}")]
     FailedToSendMessage(String),
+    #[error("Peer does not support reliable provisional responses")]
+    ReliableProvisionalNotSupported,
+    #[error("The previous reliable provisional response is not acknowledged yet")]
+    ReliableProvisionalPending,
+    #[error("No PRACK received for the reliable provisional response")]
+    ReliableProvisionalTimeout,
     #[error("Timeout reached after send message")]
     Timeout, //     #[error("The transaction is no longer valid")]
              // Invalid,
@@ -7,11 +7,15 @@
 pub mod endpoint;
 pub mod message;
 pub mod parser;
+pub mod proxy;
 pub mod transaction;
 pub mod transport;
 pub mod dialog;
 pub mod ua;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 pub(crate) mod error;
 
 pub mod macros;
@@ -40,7 +44,7 @@ pub(crate) const RFC3261_BRANCH_ID: &str = "z9hG4bK";
 
 use rand::distr::{Alphanumeric, SampleString};
 
-use crate::message::Params;
+use crate::message::{Param, Params};
 
 pub(crate) fn generate_branch() -> String {
     generate_branch_n(8)
@@ -63,7 +67,7 @@ pub(crate) fn generate_random_str(n: usize) -> String {
 
 #[inline(always)]
 pub(crate) fn is_valid_port(v: u16) -> bool {
-    matches!(v, 0..=65535)
+    v != 0
 }
 
 /// Represents a quality value (q-value) used in SIP
@@ -74,85 +78,203 @@ pub(crate) fn is_valid_port(v: u16) -> bool {
 /// q-value is typically used to indicate the preference
 /// of certain SIP headers.
 ///
+/// The value is kept in thousandths, the precision allowed
+/// by RFC 3261, so q-values compare and sort numerically.
+///
 /// # Examples
 ///
 /// ```
 /// use csip::Q;
 ///
 /// let q_value = "0.5".parse();
-/// assert_eq!(q_value, Ok(Q(0, 5)));
+/// assert_eq!(q_value, Ok(Q::from_thousandths(500)));
+/// assert!(Q::from_thousandths(500) > "0.25".parse().unwrap());
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
-pub struct Q(pub u8, pub u8);
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
+pub struct Q(u16);
 
 impl Q {
-    pub fn new(a: u8, b: u8) -> Self {
-        Self(a, b)
+    /// The highest preference, `1.0`.
+    pub const MAX: Q = Q(1000);
+    /// The lowest preference, `0`.
+    pub const MIN: Q = Q(0);
+
+    /// Creates a `Q` from a value in thousandths, clamped to
+    /// `1000`.
+    pub const fn from_thousandths(value: u16) -> Self {
+        if value > 1000 { Self::MAX } else { Self(value) }
+    }
+
+    /// Returns the value in thousandths.
+    pub const fn thousandths(&self) -> u16 {
+        self.0
+    }
+
+    /// Creates a `Q` from its integer part and the digits of
+    /// its fractional part, so `Q::new(0, 25)` is `0.25`.
+    /// Values above `1.0` are clamped.
+    #[deprecated(note = "use `Q::from_thousandths` or parse the q-value")]
+    pub fn new(int: u8, frac: u8) -> Self {
+        let frac = match frac {
+            0..=9 => u16::from(frac) * 100,
+            10..=99 => u16::from(frac) * 10,
+            _ => u16::from(frac),
+        };
+
+        Self::from_thousandths((u16::from(int) * 1000).saturating_add(frac))
+    }
+
+    /// Returns the integer part, `0` or `1`, what the first
+    /// field of the former `Q(u8, u8)` held.
+    #[deprecated(note = "use `Q::thousandths`")]
+    pub const fn int_part(&self) -> u8 {
+        (self.0 / 1000) as u8
+    }
+
+    /// Returns the digits of the fractional part without the
+    /// trailing zeros, what the second field of the former
+    /// `Q(u8, u8)` held, so `0.25` gives `25`.
+    #[deprecated(note = "use `Q::thousandths`")]
+    pub const fn frac_part(&self) -> u16 {
+        let mut frac = self.0 % 1000;
+        while frac != 0 && frac.is_multiple_of(10) {
+            frac /= 10;
+        }
+        frac
+    }
+}
+
+impl Default for Q {
+    /// A missing q-value means `1.0`.
+    fn default() -> Self {
+        Self::MAX
     }
 }
-impl From<u8> for Q {
-    fn from(value: u8) -> Self {
-        Self(value, 0)
+
+impl TryFrom<u8> for Q {
+    type Error = ParseQError;
+
+    /// Converts `0` or `1`, rejecting any other value.
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Q::MIN),
+            1 => Ok(Q::MAX),
+            _ => Err(ParseQError),
+        }
     }
 }
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ParseQError;
 
 impl From<ParseQError> for Error {
-    fn from(value: ParseQError) -> Self {
-        todo!()
-        // Self::ParseError(SipParserError {
-        //     message: format!("{:?}", value),
-        // })
+    fn from(_: ParseQError) -> Self {
+        let kind = crate::error::ParseErrorKind::Param;
+
+        crate::error::ParseError::new(kind, utils::Position::default(), 0).into()
     }
 }
 
 impl FromStr for Q {
     type Err = ParseQError;
 
+    /*
+     * qvalue = ( "0" [ "." 0*3DIGIT ] )
+     *          / ( "1" [ "." 0*3("0") ] )
+     */
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s.rsplit_once('.') {
-            Some((a, b)) => {
-                let a = a.parse().map_err(|_| ParseQError)?;
-                let b = b.parse().map_err(|_| ParseQError)?;
-                Ok(Q(a, b))
-            }
-            None => match s.parse() {
-                Ok(n) => Ok(Q(n, 0)),
-                Err(_) => Err(ParseQError),
-            },
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseQError);
+        }
+        let frac = frac
+            .bytes()
+            .chain(std::iter::repeat(b'0'))
+            .take(3)
+            .fold(0, |acc, b| acc * 10 + u16::from(b - b'0'));
+
+        match int {
+            "0" => Ok(Q(frac)),
+            "1" if frac == 0 => Ok(Q::MAX),
+            _ => Err(ParseQError),
         }
     }
 }
 
 impl fmt::Display for Q {
+    /// Writes the canonical three-digit form, e.g. `0.500`.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, ";q={}.{}", self.0, self.1)
+        write!(f, "{}.{:03}", self.0 / 1000, self.0 % 1000)
     }
 }
 
 /// This type reprents an MIME type that indicates an
 /// content format.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Type and subtype compare case-insensitively.
+#[derive(Debug, Clone, Eq)]
 pub struct MimeType {
     pub mtype: String,
     pub subtype: String,
 }
 
+impl MimeType {
+    /// Returns `true` if `self` and `other` are equal,
+    /// treating `*` on either side as a wildcard.
+    pub fn matches(&self, other: &MimeType) -> bool {
+        let eq = |a: &str, b: &str| a == "*" || b == "*" || a.eq_ignore_ascii_case(b);
+
+        eq(&self.mtype, &other.mtype) && eq(&self.subtype, &other.subtype)
+    }
+}
+
+impl PartialEq for MimeType {
+    fn eq(&self, other: &Self) -> bool {
+        self.mtype.eq_ignore_ascii_case(&other.mtype)
+            && self.subtype.eq_ignore_ascii_case(&other.subtype)
+    }
+}
+
 /// The `media-type` that appears in `Accept` and
 /// `Content-Type` SIP headers.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Two media types are equal if their type, subtype and
+/// parameters are equal, regardless of parameter order and
+/// of the case of type, subtype and parameter names.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::MediaType;
+/// let plain = MediaType::from_static("text/plain;charset=utf-8").unwrap();
+///
+/// assert!(plain.matches(&MediaType::new("text", "*")));
+/// assert_eq!(plain.charset(), Some("utf-8"));
+/// ```
+#[derive(Debug, Clone, Eq)]
 pub struct MediaType {
     pub mimetype: MimeType,
     pub param: Option<Params>,
 }
 
+impl PartialEq for MediaType {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |a: &Param, b: &Param| {
+            a.name().eq_ignore_ascii_case(b.name()) && a.value() == b.value()
+        };
+
+        self.mimetype == other.mimetype
+            && self.params().count() == other.params().count()
+            && self.params().all(|a| other.params().any(|b| same(a, b)))
+    }
+}
+
 impl fmt::Display for MediaType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let MediaType { mimetype, param } = self;
         write!(f, "{}/{}", mimetype.mtype, mimetype.subtype)?;
         if let Some(param) = &param {
-            write!(f, ";{}", param)?;
+            write!(f, "{}", param)?;
         }
         Ok(())
     }
@@ -194,6 +316,50 @@ impl MediaType {
             param,
         }
     }
+
+    fn params(&self) -> impl Iterator<Item = &Param> {
+        self.param.iter().flat_map(Params::iter)
+    }
+
+    /// Returns `true` if the type and subtype match `other`,
+    /// treating `*` on either side as a wildcard.
+    ///
+    /// Parameters are ignored.
+    pub fn matches(&self, other: &MediaType) -> bool {
+        self.mimetype.matches(&other.mimetype)
+    }
+
+    /// Returns `true` if this is a `multipart/*` media type.
+    pub fn is_multipart(&self) -> bool {
+        self.mimetype.mtype.eq_ignore_ascii_case("multipart")
+    }
+
+    /// Returns the `charset` parameter, if present.
+    pub fn charset(&self) -> Option<&str> {
+        self.get_param("charset")
+    }
+
+    /// Returns the `boundary` parameter of a multipart
+    /// body, if present.
+    pub fn boundary(&self) -> Option<&str> {
+        self.get_param("boundary")
+    }
+
+    /// Returns the value of the parameter `name`, matched
+    /// case-insensitively, without surrounding quotes.
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        let param = self
+            .params()
+            .find(|p| p.name().eq_ignore_ascii_case(name))?;
+        let value = param.value()?;
+
+        Some(
+            value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value),
+        )
+    }
 }
 
 pub(crate) fn get_local_name(addr: &SocketAddr) -> String {
@@ -202,3 +368,88 @@ pub(crate) fn get_local_name(addr: &SocketAddr) -> String {
 
     local_name
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_q_value() {
+        assert_eq!("1".parse(), Ok(Q::MAX));
+        assert_eq!("1.000".parse(), Ok(Q::MAX));
+        assert_eq!("0".parse(), Ok(Q::MIN));
+        assert_eq!("0.5".parse(), Ok(Q::from_thousandths(500)));
+        assert_eq!("0.05".parse(), Ok(Q::from_thousandths(50)));
+        assert_eq!("0.125".parse(), Ok(Q::from_thousandths(125)));
+
+        for invalid in ["", ".5", "1.5", "1.001", "2", "5.123", "0.9999", "0.x"] {
+            assert_eq!(invalid.parse::<Q>(), Err(ParseQError), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_q_from_u8_rejects_values_above_one() {
+        assert_eq!(Q::try_from(0), Ok(Q::MIN));
+        assert_eq!(Q::try_from(1), Ok(Q::MAX));
+        assert_eq!(Q::try_from(2), Err(ParseQError));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_new_reads_fraction_digits() {
+        assert_eq!(Q::new(0, 5), Q::from_thousandths(500));
+        assert_eq!(Q::new(0, 25), Q::from_thousandths(250));
+        assert_eq!(Q::new(0, 125), Q::from_thousandths(125));
+        assert_eq!(Q::new(1, 0), Q::MAX);
+        assert_eq!(Q::new(2, 0), Q::MAX);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_parts_match_former_fields() {
+        let q: Q = "0.25".parse().unwrap();
+
+        assert_eq!((q.int_part(), q.frac_part()), (0, 25));
+        assert_eq!((Q::MAX.int_part(), Q::MAX.frac_part()), (1, 0));
+        assert_eq!(Q::new(q.int_part(), q.frac_part() as u8), q);
+    }
+
+    #[test]
+    fn test_media_type_matches_wildcards() {
+        let plain = MediaType::from_static("text/plain;charset=utf-8").unwrap();
+
+        assert!(plain.matches(&MediaType::new("text", "*")));
+        assert!(plain.matches(&MediaType::new("*", "*")));
+        assert!(plain.matches(&MediaType::new("TEXT", "Plain")));
+        assert!(!plain.matches(&MediaType::new("application", "*")));
+        assert_eq!(plain.charset(), Some("utf-8"));
+    }
+
+    #[test]
+    fn test_media_type_boundary() {
+        let mixed = MediaType::from_static("multipart/mixed;boundary=\"unique-42\"").unwrap();
+
+        assert!(mixed.is_multipart());
+        assert_eq!(mixed.boundary(), Some("unique-42"));
+        assert_eq!(mixed.charset(), None);
+    }
+
+    #[test]
+    fn test_media_type_equality_ignores_case_and_param_order() {
+        let a = MediaType::from_static("Text/Plain;charset=utf-8;format=flowed").unwrap();
+        let b = MediaType::from_static("text/plain;format=flowed;CHARSET=utf-8").unwrap();
+        let c = MediaType::from_static("text/plain;charset=utf-8").unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.to_string(), "Text/Plain;charset=utf-8;format=flowed");
+    }
+
+    #[test]
+    fn test_display_q_value() {
+        assert_eq!(Q::MAX.to_string(), "1.000");
+        assert_eq!(Q::MIN.to_string(), "0.000");
+        assert_eq!(Q::from_thousandths(50).to_string(), "0.050");
+        assert_eq!(Q::from_thousandths(500).to_string(), "0.500");
+    }
+}
@@ -5,6 +5,8 @@ macro_rules! lookup_table {
             $(
                 let mut i = 0;
                 while i < $slice.len() {
+                    // Readers built on these tables skip UTF-8 validation.
+                    assert!($slice[i].is_ascii());
                     arr[$slice[i] as usize] = true;
                     i += 1;
                 }
@@ -46,7 +48,7 @@ macro_rules! parse_param {
                         let _ = $scanner.next_byte();
                         let param = $func($scanner)?;
                         $(
-                            if param.0 == $name {
+                            if param.0.eq_ignore_ascii_case($name) {
                                 $var = param.1.map(|p| p.into());
                                 $scanner.skip_ws();
                                 continue;
@@ -102,10 +104,16 @@ macro_rules! headers {
 }
 
 macro_rules! try_parse_hdr {
-    ($header:ident, $scanner:ident) => {{
-        let Ok(header) = $header::parse($scanner) else {
+    ($header:ident, $scanner:ident) => {
+        $crate::macros::try_parse_hdr!($header::parse, $scanner)
+    };
+    ($header:ident::$func:ident, $scanner:ident) => {{
+        let Ok(header) = $header::$func($scanner) else {
             let position = *$scanner.position();
-            return Err(ParseError::new($crate::error::ParseErrorKind::Header, position).into());
+            let offset = $scanner.offset();
+            return Err(
+                ParseError::new($crate::error::ParseErrorKind::Header, position, offset).into(),
+            );
         };
         header
     }};
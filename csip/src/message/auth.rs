@@ -1,7 +1,7 @@
 //! SIP Auth types
 use std::fmt;
 
-use super::Params;
+use super::{Param, Params};
 
 /// The cnonce parameter used in Digest authentication.
 pub const CNONCE: &str = "cnonce";
@@ -82,6 +82,122 @@ pub enum Challenge {
     },
 }
 
+impl DigestChallenge {
+    /// Creates a new `DigestChallenge` for the given `realm`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::{Challenge, DigestChallenge};
+    /// let digest = DigestChallenge::new("atlanta.com")
+    ///     .with_nonce("84a4cc6f3082121f32b42a2187831a9e")
+    ///     .with_algorithm("MD5")
+    ///     .with_qop("auth");
+    ///
+    /// assert_eq!(
+    ///     Challenge::Digest(digest).to_string(),
+    ///     "Digest realm=\"atlanta.com\", nonce=\"84a4cc6f3082121f32b42a2187831a9e\", \
+    ///      algorithm=MD5, qop=\"auth\""
+    /// );
+    /// ```
+    pub fn new(realm: impl Into<String>) -> Self {
+        Self {
+            realm: Some(realm.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the `domain` parameter.
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the `nonce` parameter.
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the `opaque` parameter.
+    pub fn with_opaque(mut self, opaque: impl Into<String>) -> Self {
+        self.opaque = Some(opaque.into());
+        self
+    }
+
+    /// Sets the `stale` parameter.
+    pub fn with_stale(mut self, stale: bool) -> Self {
+        self.stale = Some(if stale { "true" } else { "false" }.into());
+        self
+    }
+
+    /// Sets the `algorithm` parameter.
+    pub fn with_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.algorithm = Some(algorithm.into());
+        self
+    }
+
+    /// Sets the `qop` parameter, e.g. `"auth"` or `"auth,auth-int"`.
+    pub fn with_qop(mut self, qop: impl Into<String>) -> Self {
+        self.qop = Some(qop.into());
+        self
+    }
+}
+
+/// Writes the comma separated `auth-param` list of an
+/// authentication scheme.
+///
+/// Values are written as `quoted-string` when `quoted` is
+/// `true`, otherwise as a token.
+struct AuthParams<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    first: bool,
+}
+
+impl<'a, 'f> AuthParams<'a, 'f> {
+    fn new(f: &'a mut fmt::Formatter<'f>, scheme: &str) -> Result<Self, fmt::Error> {
+        write!(f, "{scheme}")?;
+        Ok(Self { f, first: true })
+    }
+
+    fn param(&mut self, name: &str, value: &Option<String>, quoted: bool) -> fmt::Result {
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let sep = self.separator();
+
+        if quoted {
+            write!(self.f, "{sep}{name}=\"")?;
+            for c in value.chars() {
+                if matches!(c, '"' | '\\') {
+                    write!(self.f, "\\")?;
+                }
+                write!(self.f, "{c}")?;
+            }
+            write!(self.f, "\"")
+        } else {
+            write!(self.f, "{sep}{name}={value}")
+        }
+    }
+
+    /// Writes the `auth-param` of an unknown scheme as it was
+    /// parsed, with the quotes of its value, if any.
+    fn other(&mut self, Param { name, value }: &Param) -> fmt::Result {
+        let sep = self.separator();
+        write!(self.f, "{sep}{name}")?;
+        if let Some(value) = value {
+            write!(self.f, "={value}")?;
+        }
+        Ok(())
+    }
+
+    fn separator(&mut self) -> &'static str {
+        let sep = if self.first { " " } else { ", " };
+        self.first = false;
+        sep
+    }
+}
+
 impl fmt::Display for Challenge {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -94,35 +210,21 @@ impl fmt::Display for Challenge {
                 algorithm,
                 qop,
             }) => {
-                write!(f, "Digest ")?;
-                if let Some(realm) = realm {
-                    write!(f, "realm={realm}, ")?;
-                }
-                if let Some(domain) = domain {
-                    write!(f, "domain={domain}, ")?;
-                }
-                if let Some(nonce) = nonce {
-                    write!(f, "nonce={nonce}, ")?;
-                }
-                if let Some(opaque) = opaque {
-                    write!(f, "opaque={opaque}, ")?;
-                }
-                if let Some(stale) = stale {
-                    write!(f, "stale={stale}, ")?;
-                }
-                if let Some(algorithm) = algorithm {
-                    write!(f, "algorithm={algorithm}, ")?;
-                }
-                if let Some(qop) = qop {
-                    write!(f, "qop={qop}")?;
-                }
-
-                Ok(())
+                // digest-cln  =  realm / domain / nonce / opaque / stale
+                //                / algorithm / qop-options / auth-param
+                let mut params = AuthParams::new(f, DIGEST)?;
+                params.param(REALM, realm, true)?;
+                params.param(DOMAIN, domain, true)?;
+                params.param(NONCE, nonce, true)?;
+                params.param(OPAQUE, opaque, true)?;
+                params.param(STALE, stale, false)?;
+                params.param(ALGORITHM, algorithm, false)?;
+                params.param(QOP, qop, true)
+            }
+            Challenge::Other { scheme, param } => {
+                let mut params = AuthParams::new(f, scheme)?;
+                param.iter().try_for_each(|p| params.other(p))
             }
-            Challenge::Other {
-                scheme: _,
-                param: _,
-            } => todo!(),
         }
     }
 }
@@ -169,6 +271,92 @@ pub enum Credential {
     },
 }
 
+impl DigestCredential {
+    /// Creates a new `DigestCredential` for the given `username`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::{Credential, DigestCredential};
+    /// let digest = DigestCredential::new("bob")
+    ///     .with_realm("biloxi.com")
+    ///     .with_nonce("dcd98b7102dd2f0e8b11d0f600bfb0c093")
+    ///     .with_uri("sip:bob@biloxi.com")
+    ///     .with_response("6629fae49393a05397450978507c4ef1")
+    ///     .with_qop("auth")
+    ///     .with_nc(1)
+    ///     .with_cnonce("0a4f113b");
+    ///
+    /// assert_eq!(
+    ///     Credential::Digest(digest).to_string(),
+    ///     "Digest username=\"bob\", realm=\"biloxi.com\", \
+    ///      nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"sip:bob@biloxi.com\", \
+    ///      response=\"6629fae49393a05397450978507c4ef1\", qop=auth, nc=00000001, \
+    ///      cnonce=\"0a4f113b\""
+    /// );
+    /// ```
+    pub fn new(username: impl Into<String>) -> Self {
+        Self {
+            username: Some(username.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the `realm` parameter.
+    pub fn with_realm(mut self, realm: impl Into<String>) -> Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    /// Sets the `nonce` parameter.
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the `uri` parameter.
+    pub fn with_uri(mut self, uri: impl Into<String>) -> Self {
+        self.uri = Some(uri.into());
+        self
+    }
+
+    /// Sets the `response` parameter.
+    pub fn with_response(mut self, response: impl Into<String>) -> Self {
+        self.response = Some(response.into());
+        self
+    }
+
+    /// Sets the `algorithm` parameter.
+    pub fn with_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.algorithm = Some(algorithm.into());
+        self
+    }
+
+    /// Sets the `cnonce` parameter.
+    pub fn with_cnonce(mut self, cnonce: impl Into<String>) -> Self {
+        self.cnonce = Some(cnonce.into());
+        self
+    }
+
+    /// Sets the `opaque` parameter.
+    pub fn with_opaque(mut self, opaque: impl Into<String>) -> Self {
+        self.opaque = Some(opaque.into());
+        self
+    }
+
+    /// Sets the `qop` parameter.
+    pub fn with_qop(mut self, qop: impl Into<String>) -> Self {
+        self.qop = Some(qop.into());
+        self
+    }
+
+    /// Sets the `nc` parameter, written as 8 hexadecimal digits.
+    pub fn with_nc(mut self, nc: u32) -> Self {
+        self.nc = Some(format!("{nc:08x}"));
+        self
+    }
+}
+
 impl fmt::Display for Credential {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -184,44 +372,26 @@ impl fmt::Display for Credential {
                 qop,
                 nc,
             }) => {
-                write!(f, "Digest ")?;
-                if let Some(username) = username {
-                    write!(f, "username={username}")?;
-                }
-                if let Some(realm) = realm {
-                    write!(f, "realm={realm}, ")?;
-                }
-                if let Some(nonce) = nonce {
-                    write!(f, "nonce={nonce}, ")?;
-                }
-                if let Some(uri) = uri {
-                    write!(f, "uri={uri}, ")?;
-                }
-                if let Some(response) = response {
-                    write!(f, "response={response}, ")?;
-                }
-                if let Some(algorithm) = algorithm {
-                    write!(f, "algorithm={algorithm}, ")?;
-                }
-                if let Some(cnonce) = cnonce {
-                    write!(f, "cnonce={cnonce}, ")?;
-                }
-                if let Some(qop) = qop {
-                    write!(f, "qop={qop}, ")?;
-                }
-                if let Some(nc) = nc {
-                    write!(f, "nc={nc}, ")?;
-                }
-                if let Some(opaque) = opaque {
-                    write!(f, "opaque={opaque}, ")?;
-                }
-
-                Ok(())
+                // dig-resp  =  username / realm / nonce / digest-uri
+                //              / dresponse / algorithm / cnonce
+                //              / opaque / message-qop
+                //              / nonce-count / auth-param
+                let mut params = AuthParams::new(f, DIGEST)?;
+                params.param(USERNAME, username, true)?;
+                params.param(REALM, realm, true)?;
+                params.param(NONCE, nonce, true)?;
+                params.param(URI, uri, true)?;
+                params.param(RESPONSE, response, true)?;
+                params.param(ALGORITHM, algorithm, false)?;
+                params.param(QOP, qop, false)?;
+                params.param(NC, nc, false)?;
+                params.param(CNONCE, cnonce, true)?;
+                params.param(OPAQUE, opaque, true)
+            }
+            Credential::Other { scheme, param } => {
+                let mut params = AuthParams::new(f, scheme)?;
+                param.iter().try_for_each(|p| params.other(p))
             }
-            Credential::Other {
-                scheme: _,
-                param: _,
-            } => todo!(),
         }
     }
 }
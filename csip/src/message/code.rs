@@ -20,7 +20,7 @@ pub enum CodeClass {
 }
 
 /// Status Code enum for SIP messages.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
 #[repr(u16)]
 pub enum StatusCode {
     ///`Trying` status code.
@@ -473,3 +473,23 @@ impl TryFrom<&[u8]> for StatusCode {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_status_code_has_a_reason() {
+        let codes: Vec<_> = (100..=699).filter_map(StatusCode::from_u16).collect();
+
+        assert_eq!(codes.len(), 77);
+        for code in codes {
+            let reason = code.reason();
+            let digits = code.as_u16().to_string();
+
+            assert!(!reason.as_str().is_empty(), "{code:?}");
+            assert_ne!(reason.as_str(), "Unknown", "{code:?}");
+            assert_eq!(StatusCode::try_from(digits.as_bytes()), Ok(code));
+        }
+    }
+}
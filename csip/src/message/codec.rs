@@ -0,0 +1,111 @@
+use std::any::{Any, TypeId};
+
+use bytes::Bytes;
+
+use crate::MediaType;
+use crate::error::Result;
+use crate::message::SipBody;
+
+type DecodeFn = dyn Fn(&[u8]) -> Result<Box<dyn Any>> + Send + Sync;
+type EncodeFn = dyn Fn(&dyn Any) -> Result<Bytes> + Send + Sync;
+
+struct BodyCodec {
+    media_type: MediaType,
+    // The type produced by `decode` and accepted by `encode`.
+    type_id: TypeId,
+    decode: Box<DecodeFn>,
+    encode: Box<EncodeFn>,
+}
+
+/// A registry of message body codecs keyed by [`MediaType`].
+///
+/// A codec converts a body of a given media type from and to
+/// an application type, such as a parsed SDP session. The
+/// media type of a codec can contain wildcards, as in
+/// `application/*`, see [`MediaType::matches`].
+///
+/// # Examples
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use csip::MediaType;
+/// # use csip::message::BodyCodecs;
+/// let mut codecs = BodyCodecs::new();
+/// codecs.register(
+///     MediaType::new("text", "plain"),
+///     |body| Ok(String::from_utf8_lossy(body).into_owned()),
+///     |text: &String| Ok(Bytes::from(text.clone())),
+/// );
+///
+/// let text: String = codecs
+///     .decode(&MediaType::new("text", "plain"), b"hello")
+///     .unwrap()
+///     .unwrap();
+///
+/// assert_eq!(text, "hello");
+/// ```
+#[derive(Default)]
+pub struct BodyCodecs {
+    codecs: Vec<BodyCodec>,
+}
+
+impl BodyCodecs {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the codec for bodies of `media_type` decoded to
+    /// and encoded from `T`.
+    ///
+    /// When several codecs for the same `T` match a media type,
+    /// the first registered is used.
+    pub fn register<T, D, E>(&mut self, media_type: MediaType, decode: D, encode: E)
+    where
+        T: Any,
+        D: Fn(&[u8]) -> Result<T> + Send + Sync + 'static,
+        E: Fn(&T) -> Result<Bytes> + Send + Sync + 'static,
+    {
+        self.codecs.push(BodyCodec {
+            media_type,
+            type_id: TypeId::of::<T>(),
+            decode: Box::new(move |body| Ok(Box::new(decode(body)?))),
+            encode: Box::new(move |value| {
+                encode(value.downcast_ref().expect("checked by the type id"))
+            }),
+        });
+    }
+
+    /// Returns `true` if no codec has been registered.
+    pub fn is_empty(&self) -> bool {
+        self.codecs.is_empty()
+    }
+
+    fn find<T: Any>(&self, media_type: &MediaType) -> Option<&BodyCodec> {
+        self.codecs.iter().find(|codec| {
+            codec.type_id == TypeId::of::<T>() && codec.media_type.matches(media_type)
+        })
+    }
+
+    /// Decodes a `body` of `media_type` into `T`.
+    ///
+    /// Returns `None` if no codec for `T` matches the media
+    /// type.
+    pub fn decode<T: Any>(&self, media_type: &MediaType, body: &[u8]) -> Option<Result<T>> {
+        let codec = self.find::<T>(media_type)?;
+        let decoded = (codec.decode)(body)
+            .map(|value| *value.downcast::<T>().expect("checked by the type id"));
+
+        Some(decoded)
+    }
+
+    /// Encodes `value` into a body of `media_type`.
+    ///
+    /// Returns `None` if no codec for `T` matches the media
+    /// type.
+    pub fn encode<T: Any>(&self, media_type: &MediaType, value: &T) -> Option<Result<SipBody>> {
+        let codec = self.find::<T>(media_type)?;
+
+        Some((codec.encode)(value).map(SipBody::new))
+    }
+}
@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+
+use bytes::Bytes;
+
+use crate::error::{Error, Result};
+use crate::message::SipBody;
+use crate::message::headers::ContentEncoding;
+
+/// The `gzip` content coding (RFC 1952).
+pub const GZIP: &str = "gzip";
+
+/// The `identity` content coding, the body is not encoded.
+pub const IDENTITY: &str = "identity";
+
+/// The maximum size, in bytes, of a body decoded by
+/// [`decode_body`].
+///
+/// A small compressed body can expand to a huge one, so the
+/// decoded size is bounded. SIP bodies are rarely larger than a
+/// few kilobytes.
+pub const MAX_DECODED_BODY_LEN: usize = 1024 * 1024;
+
+/// Removes the content codings listed in `encoding` from
+/// `body`, in the reverse order they were applied.
+///
+/// Only `identity` and, with the `gzip` feature, `gzip` are
+/// supported, any other coding is an
+/// [`Error::UnsupportedContentEncoding`]. A body that decodes to
+/// more than [`MAX_DECODED_BODY_LEN`] bytes is an
+/// [`Error::DecodedBodyTooLarge`].
+pub fn decode_body<'a>(
+    encoding: Option<&ContentEncoding>,
+    body: &'a [u8],
+) -> Result<Cow<'a, [u8]>> {
+    decode_body_with_limit(encoding, body, MAX_DECODED_BODY_LEN)
+}
+
+/// Same as [`decode_body`], but the decoded body is limited to
+/// `max_len` bytes.
+#[cfg_attr(not(feature = "gzip"), allow(unused_variables))]
+pub fn decode_body_with_limit<'a>(
+    encoding: Option<&ContentEncoding>,
+    body: &'a [u8],
+    max_len: usize,
+) -> Result<Cow<'a, [u8]>> {
+    let mut body = Cow::Borrowed(body);
+    let Some(encoding) = encoding else {
+        return Ok(body);
+    };
+
+    for coding in encoding.iter().rev() {
+        body = match coding {
+            coding if coding.eq_ignore_ascii_case(IDENTITY) => body,
+            #[cfg(feature = "gzip")]
+            coding if coding.eq_ignore_ascii_case(GZIP) => {
+                Cow::Owned(gzip::decode(&body, max_len)?)
+            }
+            other => return Err(Error::UnsupportedContentEncoding(other.into())),
+        };
+    }
+
+    Ok(body)
+}
+
+/// Applies the content `coding` to `body`.
+///
+/// See [`decode_body`] for the supported codings.
+pub fn encode_body(coding: &str, body: &[u8]) -> Result<SipBody> {
+    let encoded = match coding {
+        coding if coding.eq_ignore_ascii_case(IDENTITY) => Bytes::copy_from_slice(body),
+        #[cfg(feature = "gzip")]
+        coding if coding.eq_ignore_ascii_case(GZIP) => gzip::encode(body)?.into(),
+        other => return Err(Error::UnsupportedContentEncoding(other.into())),
+    };
+
+    Ok(SipBody::new(encoded))
+}
+
+#[cfg(feature = "gzip")]
+mod gzip {
+    use std::io::{Read, Write};
+
+    use flate2::Compression;
+    use flate2::read::GzDecoder;
+    use flate2::write::GzEncoder;
+
+    use crate::error::{Error, Result};
+
+    pub(super) fn decode(body: &[u8], max_len: usize) -> Result<Vec<u8>> {
+        let mut decoded = Vec::with_capacity(body.len().saturating_mul(2).min(max_len));
+        // Reading one byte past the limit tells a body of exactly
+        // `max_len` bytes from a larger one.
+        let limit = u64::try_from(max_len).unwrap_or(u64::MAX).saturating_add(1);
+        GzDecoder::new(body).take(limit).read_to_end(&mut decoded)?;
+
+        if decoded.len() > max_len {
+            return Err(Error::DecodedBodyTooLarge(max_len));
+        }
+
+        Ok(decoded)
+    }
+
+    pub(super) fn encode(body: &[u8]) -> Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::with_capacity(body.len()), Compression::default());
+        encoder.write_all(body)?;
+
+        Ok(encoder.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_is_not_decoded() {
+        let encoding = ContentEncoding::from([IDENTITY]);
+        let body = decode_body(Some(&encoding), b"hello").unwrap();
+
+        assert!(matches!(body, Cow::Borrowed(b"hello")));
+    }
+
+    #[test]
+    fn test_unknown_coding_is_an_error() {
+        let encoding = ContentEncoding::from(["compress"]);
+        let result = decode_body(Some(&encoding), b"hello");
+
+        assert!(matches!(result, Err(Error::UnsupportedContentEncoding(c)) if c == "compress"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_round_trip() {
+        let xml = b"<?xml version=\"1.0\"?><presence entity=\"sip:alice@atlanta.com\"/>";
+        let encoded = encode_body(GZIP, xml).unwrap();
+        let encoding = ContentEncoding::from([GZIP]);
+
+        assert_ne!(&encoded[..], &xml[..]);
+        assert_eq!(decode_body(Some(&encoding), &encoded).unwrap(), &xml[..]);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_decoded_size_is_limited() {
+        let encoded = encode_body(GZIP, &vec![0; 4096]).unwrap();
+        let encoding = ContentEncoding::from([GZIP]);
+
+        assert_eq!(
+            decode_body_with_limit(Some(&encoding), &encoded, 4096)
+                .unwrap()
+                .len(),
+            4096
+        );
+        assert!(matches!(
+            decode_body_with_limit(Some(&encoding), &encoded, 4095),
+            Err(Error::DecodedBodyTooLarge(4095))
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_bomb_is_rejected() {
+        let encoded = encode_body(GZIP, &vec![0; MAX_DECODED_BODY_LEN + 1]).unwrap();
+        let encoding = ContentEncoding::from([GZIP]);
+
+        assert!(encoded.len() < 4096);
+        assert!(matches!(
+            decode_body(Some(&encoding), &encoded),
+            Err(Error::DecodedBodyTooLarge(MAX_DECODED_BODY_LEN))
+        ));
+    }
+}
@@ -126,7 +126,7 @@ impl fmt::Display for Coding {
 
         write!(f, "{}", coding)?;
         if let Some(q) = q {
-            write!(f, ";q={}.{}", q.0, q.1)?;
+            write!(f, ";{}={}", Q_PARAM, q)?;
         }
         if let Some(param) = param {
             write!(f, ";{}", param)?;
@@ -174,15 +174,15 @@ mod tests {
 
         let coding = accept_encoding.get(0).unwrap();
         assert_eq!(coding.coding, "gzip");
-        assert_eq!(coding.q, Some(Q(1, 0)));
+        assert_eq!(coding.q, Some(Q::MAX));
 
         let coding = accept_encoding.get(1).unwrap();
         assert_eq!(coding.coding, "identity");
-        assert_eq!(coding.q, Some(Q(0, 5)));
+        assert_eq!(coding.q, Some(Q::from_thousandths(500)));
 
         let coding = accept_encoding.get(2).unwrap();
         assert_eq!(coding.coding, "*");
-        assert_eq!(coding.q, Some(Q(0, 0)));
+        assert_eq!(coding.q, Some(Q::MIN));
     }
 
     #[test]
@@ -63,6 +63,7 @@ impl HeaderParser for AcceptLanguage {
 
     fn parse(parser: &mut Parser) -> Result<Self> {
         let languages = comma_separated_header_value!(parser => {
+            // SAFETY: `is_lang` only accepts ASCII bytes.
             let language = unsafe { parser.read_while_as_str_unchecked(is_lang) };
             let mut q_param = None;
             let param = parse_header_param!(parser, Q_PARAM = q_param);
@@ -116,7 +117,7 @@ impl fmt::Display for Language {
         let Language { language, q, param } = self;
         write!(f, "{}", language)?;
         if let Some(q) = q {
-            write!(f, "{}", q)?;
+            write!(f, ";{}={}", Q_PARAM, q)?;
         }
         if let Some(param) = param {
             write!(f, ";{}", param)?;
@@ -155,11 +156,11 @@ mod tests {
 
         let lang = accept_language.get(1).unwrap();
         assert_eq!(lang.language, "en-gb");
-        assert_eq!(lang.q, Some(Q(0, 8)));
+        assert_eq!(lang.q, Some(Q::from_thousandths(800)));
 
         let lang = accept_language.get(2).unwrap();
         assert_eq!(lang.language, "en");
-        assert_eq!(lang.q, Some(Q(0, 7)));
+        assert_eq!(lang.q, Some(Q::from_thousandths(700)));
 
         let src = b"*\r\n";
         let mut parser = Parser::new(src);
@@ -1,10 +1,49 @@
 use std::{fmt, str};
 
+use itertools::Itertools;
+
 use crate::error::Result;
-use crate::macros::parse_header_param;
+use crate::macros::{comma_separated_header_value, parse_header_param};
 use crate::message::Params;
 use crate::parser::{HeaderParser, Parser};
 
+/// A single `<uri>;params` entry of an `Alert-Info` header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AlertInfoUri {
+    uri: String,
+    params: Option<Params>,
+}
+
+impl AlertInfoUri {
+    /// Creates a new `AlertInfoUri` with the given uri and params.
+    pub fn new(uri: &str, params: Option<Params>) -> Self {
+        Self {
+            uri: uri.into(),
+            params,
+        }
+    }
+
+    /// Returns the uri of this entry.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the params of this entry, if any.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+}
+
+impl fmt::Display for AlertInfoUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.uri)?;
+        if let Some(params) = &self.params {
+            write!(f, "{}", params)?;
+        }
+        Ok(())
+    }
+}
+
 /// The `Alert-Info` SIP header.
 ///
 /// Specifies an alternative ring tone.
@@ -12,7 +51,7 @@ use crate::parser::{HeaderParser, Parser};
 /// # Examples
 ///
 /// ```
-/// # use csip::header::AlertInfo;
+/// # use csip::message::headers::AlertInfo;
 /// let info = AlertInfo::new("http://www.alert.com/sounds/moo.wav");
 ///
 /// assert_eq!(
@@ -21,29 +60,39 @@ use crate::parser::{HeaderParser, Parser};
 /// );
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct AlertInfo {
-    url: String,
-    params: Option<Params>,
-}
+pub struct AlertInfo(Vec<AlertInfoUri>);
 
 impl AlertInfo {
-    /// Creates a new `AlertInfo` header.
+    /// Creates a new `AlertInfo` header with a single uri.
     pub fn new(url: &str) -> Self {
-        Self {
-            url: url.into(),
-            params: None,
-        }
+        Self(vec![AlertInfoUri::new(url, None)])
     }
 
-    /// Creates a new `AlertInfo` header with the specified
-    /// url and params.
+    /// Creates a new `AlertInfo` header with a single uri and
+    /// the specified params.
     pub fn from_parts(url: String, params: Option<Params>) -> Self {
-        Self { url, params }
+        Self(vec![AlertInfoUri { uri: url, params }])
     }
 
-    /// Set the url for this header.
+    /// Set the url of the first uri of this header.
+    #[deprecated(
+        note = "`Alert-Info` holds a list of uris, use `AlertInfo::push` or `AlertInfo::new`"
+    )]
     pub fn set_url(&mut self, url: &str) {
-        self.url = url.into();
+        match self.0.first_mut() {
+            Some(first) => first.uri = url.into(),
+            None => self.0.push(AlertInfoUri::new(url, None)),
+        }
+    }
+
+    /// Appends an uri to this header.
+    pub fn push(&mut self, uri: AlertInfoUri) {
+        self.0.push(uri);
+    }
+
+    /// Returns an iterator over the uris in this header.
+    pub fn iter(&self) -> impl Iterator<Item = &AlertInfoUri> {
+        self.0.iter()
     }
 }
 
@@ -51,26 +100,25 @@ impl HeaderParser for AlertInfo {
     const NAME: &'static str = "Alert-Info";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        parser.skip_ws();
+        let infos = comma_separated_header_value!(parser => {
+            parser.next_byte()?;
+            let uri = parser.read_until(b'>');
+            parser.next_byte()?;
 
-        parser.next_byte()?;
-        let url = parser.read_until(b'>');
-        parser.next_byte()?;
+            let uri = str::from_utf8(uri)?.into();
+            let params = parse_header_param!(parser);
+            parser.skip_ws();
 
-        let url = str::from_utf8(url)?.into();
-        let params = parse_header_param!(parser);
+            AlertInfoUri { uri, params }
+        });
 
-        Ok(AlertInfo { url, params })
+        Ok(AlertInfo(infos))
     }
 }
 
 impl fmt::Display for AlertInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: <{}>", AlertInfo::NAME, self.url)?;
-        if let Some(params) = &self.params {
-            write!(f, "{}", params)?;
-        }
-        Ok(())
+        write!(f, "{}: {}", AlertInfo::NAME, self.0.iter().format(", "))
     }
 }
 
@@ -88,7 +136,8 @@ mod tests {
         let alert_info = alert_info.unwrap();
 
         assert_eq!(scanner.remaining(), b"\r\n");
-        assert_eq!(alert_info.url, "http://www.example.com/sounds/moo.wav");
+        let info = alert_info.iter().next().unwrap();
+        assert_eq!(info.uri(), "http://www.example.com/sounds/moo.wav");
 
         let src = b"<http://example.com/ringtones/premium.wav>;purpose=ringtone\r\n";
         let mut scanner = Parser::new(src);
@@ -96,10 +145,46 @@ mod tests {
         let alert_info = alert_info.unwrap();
 
         assert_eq!(scanner.remaining(), b"\r\n");
-        assert_eq!(alert_info.url, "http://example.com/ringtones/premium.wav");
+        let info = alert_info.iter().next().unwrap();
+        assert_eq!(info.uri(), "http://example.com/ringtones/premium.wav");
         assert_eq!(
-            alert_info.params.unwrap().get_named("purpose"),
+            info.params().unwrap().get_named("purpose"),
             Some("ringtone")
         );
     }
+
+    #[test]
+    fn test_parse_multiple_uris() {
+        let src = b"<http://a.com/ring1.wav>;purpose=ring, <http://b.com/ring2.wav>;x=y\r\n";
+        let mut scanner = Parser::new(src);
+        let alert_info = AlertInfo::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+
+        let uris: Vec<_> = alert_info.iter().collect();
+        assert_eq!(uris.len(), 2);
+        assert_eq!(uris[0].uri(), "http://a.com/ring1.wav");
+        assert_eq!(uris[0].params().unwrap().get_named("purpose"), Some("ring"));
+        assert_eq!(uris[1].uri(), "http://b.com/ring2.wav");
+        assert_eq!(uris[1].params().unwrap().get_named("x"), Some("y"));
+
+        assert_eq!(
+            alert_info.to_string(),
+            "Alert-Info: <http://a.com/ring1.wav>;purpose=ring, <http://b.com/ring2.wav>;x=y"
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_set_url_replaces_first_uri() {
+        let mut info = AlertInfo::from_parts("http://a.com/a.wav".into(), None);
+        info.push(AlertInfoUri::new("http://a.com/b.wav", None));
+
+        info.set_url("http://a.com/c.wav");
+
+        assert_eq!(
+            info.to_string(),
+            "Alert-Info: <http://a.com/c.wav>, <http://a.com/b.wav>"
+        );
+    }
 }
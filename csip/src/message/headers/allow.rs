@@ -42,6 +42,11 @@ impl Allow {
         self.0.get(index)
     }
 
+    /// Returns `true` if `method` is listed in the header.
+    pub fn contains(&self, method: Method) -> bool {
+        self.0.contains(&method)
+    }
+
     /// Returns the number of `SipMethods` in the header.
     pub fn len(&self) -> usize {
         self.0.len()
@@ -52,11 +57,7 @@ impl HeaderParser for Allow {
     const NAME: &'static str = "Allow";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let allow = comma_separated_header_value!(parser => {
-            let b_method = parser.alphabetic();
-
-            Method::from(b_method)
-        });
+        let allow = comma_separated_header_value!(parser => parser.parse_method()?);
 
         Ok(Allow(allow))
     }
@@ -2,22 +2,29 @@ use std::{fmt, str};
 
 use crate::error::{ParseErrorKind as ErrorKind, Result};
 use crate::macros::comma_separated;
-use crate::message::{CNONCE, NC, NEXTNONCE, Param, QOP, RSPAUTH};
-use crate::parser::{HeaderParser, Parser};
+use crate::message::uri::write_quoted_string;
+use crate::message::{CNONCE, NC, NEXTNONCE, QOP, RSPAUTH};
+use crate::parser::{HeaderParser, Parser, unquote};
 
 /// The `Authentication-Info` SIP header.
 ///
-/// Provides additional authentication information.
+/// Provides additional authentication information, sent by
+/// the server after a successful digest authentication
+/// (`RFC2617` section 3.2.3).
+///
+/// The values are stored unquoted, a UAC can check
+/// [`rspauth`](Self::rspauth) for mutual authentication and use
+/// [`nextnonce`](Self::nextnonce) in its next request.
 ///
 /// # Examples
 ///
 /// ```
-/// # use csip::header::AuthenticationInfo;
+/// # use csip::message::headers::AuthenticationInfo;
 /// let mut auth = AuthenticationInfo::default();
 /// auth.set_nextnonce(Some("5ccc069c403ebaf9f0171e9517f40e41"));
 ///
 /// assert_eq!(
-///     "Authentication-Info: nextnonce=5ccc069c403ebaf9f0171e9517f40e41",
+///     "Authentication-Info: nextnonce=\"5ccc069c403ebaf9f0171e9517f40e41\"",
 ///     auth.to_string()
 /// );
 /// ```
@@ -35,17 +42,56 @@ impl<'a> AuthenticationInfo {
     pub fn set_nextnonce(&mut self, nextnonce: Option<&'a str>) {
         self.nextnonce = nextnonce.map(|n| n.into());
     }
+
+    /// Returns the nonce the server wants the client to use
+    /// for the next authentication response.
+    pub fn nextnonce(&self) -> Option<&str> {
+        self.nextnonce.as_deref()
+    }
+
+    /// Returns the quality of protection applied by the server
+    /// to the response.
+    pub fn qop(&self) -> Option<&str> {
+        self.qop.as_deref()
+    }
+
+    /// Returns the response digest computed by the server,
+    /// which proves that it knows the user's secret.
+    pub fn rspauth(&self) -> Option<&str> {
+        self.rspauth.as_deref()
+    }
+
+    /// Returns the client nonce of the request being answered.
+    pub fn cnonce(&self) -> Option<&str> {
+        self.cnonce.as_deref()
+    }
+
+    /// Returns the nonce count of the request being answered,
+    /// as sent in the `nc` hexadecimal value.
+    pub fn nc(&self) -> Option<u32> {
+        self.nc
+            .as_deref()
+            .and_then(|nc| u32::from_str_radix(nc, 16).ok())
+    }
 }
 
 impl HeaderParser for AuthenticationInfo {
     const NAME: &'static str = "Authentication-Info";
 
+    /*
+     * Authentication-Info  =  "Authentication-Info" HCOLON ainfo
+     *                         *(COMMA ainfo)
+     * ainfo                =  nextnonce / message-qop
+     *                         / response-auth / cnonce
+     *                         / nonce-count
+     */
     fn parse(parser: &mut Parser) -> Result<Self> {
         let mut auth_info = AuthenticationInfo::default();
 
         comma_separated!(parser => {
-            let Param {name, value} = parser.parse_ref_param()?.into();
-            match name.as_ref() {
+            let (name, value) = parser.parse_ref_param()?;
+            let value = value.map(unquote);
+            match name {
                 NEXTNONCE => auth_info.nextnonce = value,
                 QOP => auth_info.qop = value,
                 RSPAUTH => auth_info.rspauth = value,
@@ -63,20 +109,27 @@ impl fmt::Display for AuthenticationInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: ", AuthenticationInfo::NAME)?;
 
-        if let Some(nextnonce) = &self.nextnonce {
-            write!(f, "nextnonce={}", nextnonce)?;
-        }
-        if let Some(qop) = &self.qop {
-            write!(f, ", qop={}", qop)?;
-        }
-        if let Some(rspauth) = &self.rspauth {
-            write!(f, ", rspauth={}", rspauth)?;
-        }
-        if let Some(cnonce) = &self.cnonce {
-            write!(f, ", cnonce={}", cnonce)?;
-        }
-        if let Some(nc) = &self.nc {
-            write!(f, ", nc={}", nc)?;
+        let params = [
+            (NEXTNONCE, &self.nextnonce, true),
+            (QOP, &self.qop, false),
+            (RSPAUTH, &self.rspauth, true),
+            (CNONCE, &self.cnonce, true),
+            (NC, &self.nc, false),
+        ];
+        let params = params
+            .into_iter()
+            .filter_map(|(name, value, quoted)| Some((name, value.as_deref()?, quoted)));
+
+        for (i, (name, value, quoted)) in params.enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}=")?;
+            if quoted {
+                write_quoted_string(f, value)?;
+            } else {
+                f.write_str(value)?;
+            }
         }
 
         Ok(())
@@ -94,10 +147,7 @@ mod tests {
         let auth_info = AuthenticationInfo::parse(&mut scanner).unwrap();
 
         assert_eq!(scanner.remaining(), b"\r\n");
-        assert_eq!(
-            auth_info.nextnonce,
-            Some("\"47364c23432d2e131a5fb210812c\"".into())
-        );
+        assert_eq!(auth_info.nextnonce(), Some("47364c23432d2e131a5fb210812c"));
 
         let src = b"nextnonce=\"5ccc069c403ebaf9f0171e9517f40e41\", \
         cnonce=\"0a4f113b\", nc=00000001, \
@@ -108,15 +158,33 @@ mod tests {
 
         assert_eq!(scanner.remaining(), b"\r\n");
         assert_eq!(
-            auth_info.nextnonce,
-            Some("\"5ccc069c403ebaf9f0171e9517f40e41\"".into())
+            auth_info.nextnonce(),
+            Some("5ccc069c403ebaf9f0171e9517f40e41")
+        );
+        assert_eq!(auth_info.cnonce(), Some("0a4f113b"));
+        assert_eq!(auth_info.nc(), Some(1));
+        assert_eq!(auth_info.qop(), Some("auth"));
+        assert_eq!(
+            auth_info.rspauth(),
+            Some("6629fae49393a05397450978507c4ef1")
         );
-        assert_eq!(auth_info.cnonce, Some("\"0a4f113b\"".into()));
-        assert_eq!(auth_info.nc, Some("00000001".into()));
-        assert_eq!(auth_info.qop, Some("\"auth\"".into()));
+    }
+
+    #[test]
+    fn test_parse_mutual_auth_fields() {
+        let src = b"nextnonce=\"abc\", qop=auth, rspauth=\"def\", cnonce=\"ghi\", nc=00000001\r\n";
+        let mut scanner = Parser::new(src);
+        let auth_info = AuthenticationInfo::parse(&mut scanner).unwrap();
+
+        assert_eq!(auth_info.nextnonce(), Some("abc"));
+        assert_eq!(auth_info.qop(), Some("auth"));
+        assert_eq!(auth_info.rspauth(), Some("def"));
+        assert_eq!(auth_info.cnonce(), Some("ghi"));
+        assert_eq!(auth_info.nc(), Some(1));
         assert_eq!(
-            auth_info.rspauth,
-            Some("\"6629fae49393a05397450978507c4ef1\"".into())
+            auth_info.to_string(),
+            "Authentication-Info: nextnonce=\"abc\", qop=auth, rspauth=\"def\", \
+             cnonce=\"ghi\", nc=00000001"
         );
     }
 }
@@ -11,8 +11,8 @@ use crate::parser::{HeaderParser, Parser};
 /// # Examples
 ///
 /// ```
-/// # use csip::header::Authorization;
-/// # use csip::message::auth::{Credential, DigestCredential};
+/// # use csip::message::headers::Authorization;
+/// # use csip::message::{Credential, DigestCredential};
 /// let auth = Authorization(Credential::Digest(DigestCredential {
 ///     username: Some("Alice".into()),
 ///     realm: Some("atlanta.com".into()),
@@ -22,9 +22,9 @@ use crate::parser::{HeaderParser, Parser};
 /// }));
 ///
 /// assert_eq!(
-///     "Authorization: Digest username=Alice, realm=atlanta.com, \
-///             nonce=84a4cc6f3082121f32b42a2187831a9e, \
-///             response=7587245234b3434cc3412213e5f113a5432",
+///     "Authorization: Digest username=\"Alice\", realm=\"atlanta.com\", \
+///      nonce=\"84a4cc6f3082121f32b42a2187831a9e\", \
+///      response=\"7587245234b3434cc3412213e5f113a5432\"",
 ///     auth.to_string()
 /// );
 /// ```
@@ -71,16 +71,62 @@ mod tests {
         assert_eq!(scanner.remaining(), b"\r\n");
 
         assert_matches!(auth.credential(), &Credential::Digest( DigestCredential { ref username, ref realm, ref nonce, ref response, ..}) => {
-            assert_eq!(username, &Some("\"Alice\"".into()));
-            assert_eq!(realm, &Some("\"atlanta.com\"".into()));
+            assert_eq!(username, &Some("Alice".into()));
+            assert_eq!(realm, &Some("atlanta.com".into()));
             assert_eq!(
                 nonce,
-                &Some("\"84a4cc6f3082121f32b42a2187831a9e\"".into())
+                &Some("84a4cc6f3082121f32b42a2187831a9e".into())
             );
             assert_eq!(
                 response,
-                &Some("\"7587245234b3434cc3412213e5f113a5432\"".into())
+                &Some("7587245234b3434cc3412213e5f113a5432".into())
             );
         });
     }
+
+    #[test]
+    fn test_credential_round_trip() {
+        let digest = DigestCredential::new("bob")
+            .with_realm("biloxi.com")
+            .with_nonce("dcd98b7102dd2f0e8b11d0f600bfb0c093")
+            .with_uri("sip:bob@biloxi.com")
+            .with_response("6629fae49393a05397450978507c4ef1")
+            .with_algorithm("MD5")
+            .with_qop("auth")
+            .with_nc(1)
+            .with_cnonce("0a4f113b")
+            .with_opaque("5ccc069c403ebaf9f0171e9517f40e41");
+        let auth = Authorization(Credential::Digest(digest));
+        let encoded = auth.to_string();
+
+        assert_eq!(
+            encoded,
+            "Authorization: Digest username=\"bob\", realm=\"biloxi.com\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"sip:bob@biloxi.com\", \
+             response=\"6629fae49393a05397450978507c4ef1\", algorithm=MD5, qop=auth, \
+             nc=00000001, cnonce=\"0a4f113b\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""
+        );
+
+        let value = encoded.strip_prefix("Authorization: ").unwrap();
+        let mut scanner = Parser::new(value.as_bytes());
+        let parsed = Authorization::parse(&mut scanner).unwrap();
+
+        assert_eq!(parsed, auth);
+    }
+
+    #[test]
+    fn test_other_credential_round_trip() {
+        let src = b"Bearer abc";
+        let mut scanner = Parser::new(src);
+        let auth = Authorization::parse(&mut scanner).unwrap();
+        let encoded = auth.to_string();
+
+        assert_eq!(encoded, "Authorization: Bearer abc");
+
+        let value = encoded.strip_prefix("Authorization: ").unwrap();
+        let mut scanner = Parser::new(value.as_bytes());
+        let parsed = Authorization::parse(&mut scanner).unwrap();
+
+        assert_eq!(parsed, auth);
+    }
 }
@@ -60,9 +60,17 @@ impl HeaderParser for CallId {
     }
 }
 
+impl CallId {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl fmt::Display for CallId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", CallId::NAME, self.0)
+        write!(f, "{}: ", CallId::NAME)?;
+        self.fmt_value(f)
     }
 }
 
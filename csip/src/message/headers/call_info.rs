@@ -1,12 +1,98 @@
 use std::{fmt, str};
 
+use itertools::Itertools;
+
 use crate::error::Result;
-use crate::macros::parse_header_param;
+use crate::macros::{comma_separated_header_value, parse_header_param};
 use crate::message::Params;
 use crate::parser::{HeaderParser, Parser};
 
 const PURPOSE: &str = "purpose";
 
+/// The `purpose` parameter of a `Call-Info` entry.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Purpose {
+    /// `icon`, an image suitable as an iconic representation.
+    Icon,
+    /// `info`, a general description of the caller or callee.
+    Info,
+    /// `card`, a business card.
+    Card,
+    /// Other purpose value (`token`).
+    Other(String),
+}
+
+impl Purpose {
+    /// Returns the purpose value as str.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Icon => "icon",
+            Self::Info => "info",
+            Self::Card => "card",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl From<&str> for Purpose {
+    fn from(s: &str) -> Self {
+        match s {
+            s if s.eq_ignore_ascii_case("icon") => Self::Icon,
+            s if s.eq_ignore_ascii_case("info") => Self::Info,
+            s if s.eq_ignore_ascii_case("card") => Self::Card,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
+/// A single `<uri>;purpose=..` entry of a `Call-Info` header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CallInfoUri {
+    uri: String,
+    purpose: Option<Purpose>,
+    params: Option<Params>,
+}
+
+impl CallInfoUri {
+    /// Creates a new `CallInfoUri` with the given uri,
+    /// purpose and params.
+    pub fn new(uri: &str, purpose: Option<Purpose>, params: Option<Params>) -> Self {
+        Self {
+            uri: uri.into(),
+            purpose,
+            params,
+        }
+    }
+
+    /// Returns the uri of this entry.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the `purpose` parameter of this entry, if any.
+    pub fn purpose(&self) -> Option<&Purpose> {
+        self.purpose.as_ref()
+    }
+
+    /// Returns the other params of this entry, if any.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+}
+
+impl fmt::Display for CallInfoUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.uri)?;
+        if let Some(purpose) = &self.purpose {
+            write!(f, ";{}={}", PURPOSE, purpose.as_str())?;
+        }
+        if let Some(params) = &self.params {
+            write!(f, "{}", params)?;
+        }
+        Ok(())
+    }
+}
+
 /// The `Call-Info` SIP header.
 ///
 /// Provides aditional information aboute the caller or
@@ -15,7 +101,7 @@ const PURPOSE: &str = "purpose";
 /// # Examples
 ///
 /// ```
-/// # use csip::header::CallInfo;
+/// # use csip::message::headers::CallInfo;
 /// let mut info = CallInfo::new("http://www.example.com/alice/");
 ///
 /// assert_eq!(
@@ -24,69 +110,81 @@ const PURPOSE: &str = "purpose";
 /// );
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct CallInfo {
-    url: String,
-    purpose: Option<String>,
-    params: Option<Params>,
-}
+pub struct CallInfo(Vec<CallInfoUri>);
 
 impl CallInfo {
-    /// Creates a new `CallInfo` header.
+    /// Creates a new `CallInfo` header with a single uri.
     pub fn new(url: &str) -> Self {
-        Self {
-            url: url.into(),
-            purpose: None,
-            params: None,
-        }
+        Self(vec![CallInfoUri::new(url, None, None)])
     }
 
-    /// Creates a new `CallInfo` header with the given url,
-    /// params and purpose.
+    /// Creates a new `CallInfo` header with a single uri and
+    /// the given params and purpose.
     pub fn from_parts(url: String, purpose: Option<&str>, params: Option<Params>) -> Self {
-        Self {
-            url,
-            purpose: purpose.map(|p| p.into()),
+        Self(vec![CallInfoUri {
+            uri: url,
+            purpose: purpose.map(Purpose::from),
             params,
-        }
+        }])
     }
 
-    /// Set the url for this header.
+    /// Set the url of the first uri of this header.
+    #[deprecated(
+        note = "`Call-Info` holds a list of uris, use `CallInfo::push` or `CallInfo::new`"
+    )]
     pub fn set_url(&mut self, url: &str) {
-        self.url = url.into();
+        match self.0.first_mut() {
+            Some(first) => first.uri = url.into(),
+            None => self.0.push(CallInfoUri::new(url, None, None)),
+        }
+    }
+
+    /// Appends an uri to this header.
+    pub fn push(&mut self, uri: CallInfoUri) {
+        self.0.push(uri);
+    }
+
+    /// Returns an iterator over the uris in this header.
+    pub fn iter(&self) -> impl Iterator<Item = &CallInfoUri> {
+        self.0.iter()
     }
 }
 
 impl HeaderParser for CallInfo {
     const NAME: &'static str = "Call-Info";
 
+    /*
+     * Call-Info   =  "Call-Info" HCOLON info *(COMMA info)
+     * info        =  LAQUOT absoluteURI RAQUOT *( SEMI info-param)
+     * info-param  =  ( "purpose" EQUAL ( "icon" / "info"
+     *                / "card" / token ) ) / generic-param
+     */
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let mut purpose: Option<String> = None;
-        // must be an '<'
-        parser.next_byte()?;
-        let url = parser.read_until(b'>');
-        // must be an '>'
-        parser.next_byte()?;
-        let url = str::from_utf8(url)?;
-        let params = parse_header_param!(parser, PURPOSE = purpose);
-
-        Ok(CallInfo {
-            url: url.into(),
-            params,
-            purpose,
-        })
+        let infos = comma_separated_header_value!(parser => {
+            let mut purpose: Option<&str> = None;
+            // must be an '<'
+            parser.next_byte()?;
+            let uri = parser.read_until(b'>');
+            // must be an '>'
+            parser.next_byte()?;
+            let uri = str::from_utf8(uri)?.into();
+            let params = parse_header_param!(parser, PURPOSE = purpose);
+            parser.skip_ws();
+
+            CallInfoUri {
+                uri,
+                purpose: purpose.map(Purpose::from),
+                params,
+            }
+        });
+
+        Ok(CallInfo(infos))
     }
 }
 
 impl fmt::Display for CallInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: <{}>", CallInfo::NAME, self.url)?;
-        if let Some(purpose) = &self.purpose {
-            write!(f, ";{}", purpose)?;
-        }
-        if let Some(params) = &self.params {
-            write!(f, "{}", params)?;
-        }
-        Ok(())
+        write!(f, "{}: {}", CallInfo::NAME, self.0.iter().format(", "))
     }
 }
 
@@ -100,16 +198,55 @@ mod tests {
         ;purpose=icon\r\n";
         let mut scanner = Parser::new(src);
         let info = CallInfo::parse(&mut scanner).unwrap();
+        let info = info.iter().next().unwrap();
 
         assert_eq!(scanner.remaining(), b"\r\n");
-        assert_eq!(info.url, "http://wwww.example.com/alice/photo.jpg");
-        assert_eq!(info.purpose, Some("icon".into()));
+        assert_eq!(info.uri(), "http://wwww.example.com/alice/photo.jpg");
+        assert_eq!(info.purpose(), Some(&Purpose::Icon));
 
         let src = b"<http://www.example.com/alice/> ;purpose=info\r\n";
         let mut scanner = Parser::new(src);
         let info = CallInfo::parse(&mut scanner).unwrap();
+        let info = info.iter().next().unwrap();
+
+        assert_eq!(info.uri(), "http://www.example.com/alice/");
+        assert_eq!(info.purpose(), Some(&Purpose::Info));
+    }
+
+    #[test]
+    fn test_parse_multiple_uris() {
+        let src = b"<http://www.example.com/alice/photo.jpg> ;purpose=icon, \
+        <http://www.example.com/alice/> ;purpose=info\r\n";
+        let mut scanner = Parser::new(src);
+        let call_info = CallInfo::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+
+        let infos: Vec<_> = call_info.iter().collect();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].uri(), "http://www.example.com/alice/photo.jpg");
+        assert_eq!(infos[0].purpose(), Some(&Purpose::Icon));
+        assert_eq!(infos[1].uri(), "http://www.example.com/alice/");
+        assert_eq!(infos[1].purpose(), Some(&Purpose::Info));
+
+        assert_eq!(
+            call_info.to_string(),
+            "Call-Info: <http://www.example.com/alice/photo.jpg>;purpose=icon, \
+            <http://www.example.com/alice/>;purpose=info"
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_set_url_replaces_first_uri() {
+        let mut info = CallInfo::from_parts("http://a.com/a.jpg".into(), Some("icon"), None);
+        info.push(CallInfoUri::new("http://a.com/b", None, None));
+
+        info.set_url("http://a.com/c.jpg");
 
-        assert_eq!(info.url, "http://www.example.com/alice/");
-        assert_eq!(info.purpose, Some("info".into()));
+        assert_eq!(
+            info.to_string(),
+            "Call-Info: <http://a.com/c.jpg>;purpose=icon, <http://a.com/b>"
+        );
     }
 }
@@ -1,10 +1,11 @@
 use core::fmt;
+use std::net::SocketAddr;
 
 use crate::Q;
 use crate::error::Result;
 use crate::macros::parse_header_param;
-use crate::message::headers::{EXPIRES_PARAM, Q_PARAM};
-use crate::message::{Params, SipUri};
+use crate::message::headers::{EXPIRES_PARAM, Expires, Q_PARAM};
+use crate::message::{HostPort, Params, SipUri};
 use crate::parser::{HeaderParser, Parser};
 
 /// The `Contact` SIP header.
@@ -59,6 +60,71 @@ impl Contact {
             param: None,
         }
     }
+
+    /// Returns the q-value of this contact, `1.0` if the
+    /// `q` param is absent.
+    pub fn q(&self) -> Q {
+        self.q.unwrap_or_default()
+    }
+
+    /// Returns the expiration of this contact, the `expires`
+    /// param or else the `Expires` header value of the message.
+    pub fn expires_or(&self, expires: Option<Expires>) -> Option<u32> {
+        self.expires.or(expires.map(|expires| expires.as_u32()))
+    }
+
+    /// Returns `true` if this contact is being removed, that is,
+    /// it expires in `0` seconds.
+    ///
+    /// The `expires` param takes precedence over the `Expires`
+    /// header of the message.
+    pub fn is_removal(&self, expires: Option<Expires>) -> bool {
+        self.expires_or(expires) == Some(0)
+    }
+
+    /// Returns a copy of this contact whose URI points at
+    /// `addr`, keeping the user part and the parameters.
+    ///
+    /// A registrar uses this for far-end NAT traversal: the
+    /// `Contact` of a client behind a NAT holds a private
+    /// address, so the binding is stored with the source address
+    /// of the request instead (see [`Via::is_behind_nat`]).
+    ///
+    /// [`Via::is_behind_nat`]: crate::message::headers::Via::is_behind_nat
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::Contact;
+    /// let contact = Contact::from_str("<sip:alice@192.168.1.2:5060>;expires=3600").unwrap();
+    /// let rewritten = contact.rewrite_target("203.0.113.7:40312".parse().unwrap());
+    ///
+    /// assert_eq!(
+    ///     rewritten.to_string(),
+    ///     "Contact: <sip:alice@203.0.113.7:40312>;expires=3600"
+    /// );
+    /// ```
+    pub fn rewrite_target(&self, addr: SocketAddr) -> Contact {
+        let mut contact = self.clone();
+        let uri = match &mut contact.uri {
+            SipUri::Uri(uri) => uri,
+            SipUri::NameAddr(name_addr) => &mut name_addr.uri,
+        };
+        uri.host_port = HostPort::from(addr);
+        // The maddr would take precedence over the new host.
+        uri.maddr_param = None;
+
+        contact
+    }
+
+    /// Sorts `contacts` by q-value, highest first.
+    ///
+    /// This is the order in which a forking proxy should try
+    /// the targets. Contacts with equal q-values keep their
+    /// relative order.
+    pub fn sort_by_q(contacts: &mut [Contact]) {
+        contacts.sort_by_key(|contact| std::cmp::Reverse(contact.q()));
+    }
 }
 
 impl HeaderParser for Contact {
@@ -83,17 +149,16 @@ impl HeaderParser for Contact {
     }
 }
 
-impl fmt::Display for Contact {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: ", Contact::NAME)?;
-
+impl Contact {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.uri)?;
 
         if let Some(q) = self.q {
-            write!(f, "{}", q)?;
+            write!(f, ";{}={}", Q_PARAM, q)?;
         }
         if let Some(expires) = self.expires {
-            write!(f, "{}", expires)?;
+            write!(f, ";{}={}", EXPIRES_PARAM, expires)?;
         }
         if let Some(param) = &self.param {
             write!(f, "{}", param)?;
@@ -102,6 +167,13 @@ impl fmt::Display for Contact {
     }
 }
 
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", Contact::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::{IpAddr, Ipv4Addr};
@@ -190,7 +262,7 @@ mod tests {
                 },
             );
             assert_eq!(addr.uri.scheme, Scheme::Sip);
-            assert_eq!(q, Some(Q(0, 7)));
+            assert_eq!(q, Some(Q::from_thousandths(700)));
             assert_eq!(expires, Some(3600));
         });
 
@@ -292,4 +364,56 @@ mod tests {
             assert_eq!(uri.scheme, Scheme::Sip);
         });
     }
+
+    #[test]
+    fn test_sort_by_q() {
+        let mut contacts = [
+            "<sip:a@example.com>;q=0.8",
+            "<sip:b@example.com>",
+            "<sip:c@example.com>;q=1.0",
+            "<sip:d@example.com>;q=0.25",
+        ]
+        .map(|c| Contact::from_str(c).unwrap());
+
+        assert_eq!(contacts[1].q(), Q::MAX);
+
+        Contact::sort_by_q(&mut contacts);
+
+        let order: Vec<_> = contacts
+            .iter()
+            .map(|c| c.uri.uri().user.as_ref().unwrap().user.as_str())
+            .collect();
+        assert_eq!(order, ["b", "c", "a", "d"]);
+    }
+
+    #[test]
+    fn test_rewrite_target_keeps_user_and_params() {
+        let contact =
+            Contact::from_str("\"Alice\" <sip:alice@192.168.1.2:5062;transport=tcp>;q=0.5")
+                .unwrap();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)), 40312);
+
+        let rewritten = contact.rewrite_target(addr);
+
+        let uri = rewritten.uri.uri();
+        assert_eq!(uri.user, contact.uri.uri().user);
+        assert_eq!(uri.host_port, HostPort::from(addr));
+        assert_eq!(
+            uri.transport_param,
+            Some(crate::transport::TransportType::Tcp)
+        );
+        assert_eq!(rewritten.uri.display(), Some("Alice"));
+        assert_eq!(rewritten.q, contact.q);
+        assert_eq!(contact.uri.uri().host_port.to_string(), "192.168.1.2:5062");
+    }
+
+    #[test]
+    fn test_display_params() {
+        let contact = Contact::from_str("<sip:a@example.com>;q=0.25;expires=60").unwrap();
+
+        assert_eq!(
+            contact.to_string(),
+            "Contact: <sip:a@example.com>;q=0.250;expires=60"
+        );
+    }
 }
@@ -5,6 +5,9 @@ use crate::macros::parse_header_param;
 use crate::message::Params;
 use crate::parser::{HeaderParser, Parser};
 
+/// The handling parameter of the `Content-Disposition` header.
+const HANDLING_PARAM: &str = "handling";
+
 /// The `Content-Disposition` SIP header.
 ///
 /// Describes how the `message-body` is to be interpreted by
@@ -13,36 +16,71 @@ use crate::parser::{HeaderParser, Parser};
 /// # Examples
 ///
 /// ```
-/// # use csip::header::ContentDisposition;
+/// # use csip::message::headers::ContentDisposition;
 /// let c_disp = ContentDisposition::new("session");
 ///
 /// assert_eq!("Content-Disposition: session", c_disp.to_string());
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ContentDisposition {
-    _type: String,
+    disp_type: DispositionType,
+    handling: Option<Handling>,
     params: Option<Params>,
 }
 
-impl<'a> ContentDisposition {
+impl ContentDisposition {
     /// Creates a new `ContentDisposition` instance.
-    pub fn new(_type: &'a str) -> Self {
+    pub fn new(disp_type: impl Into<DispositionType>) -> Self {
         Self {
-            _type: _type.into(),
+            disp_type: disp_type.into(),
+            handling: None,
             params: None,
         }
     }
+
+    /// Returns the disposition type.
+    pub fn disp_type(&self) -> &DispositionType {
+        &self.disp_type
+    }
+
+    /// Returns how the body must be handled by the receiver.
+    ///
+    /// If the `handling` parameter is missing, `required` is
+    /// assumed (RFC 3261 section 20.11).
+    pub fn handling(&self) -> &Handling {
+        self.handling.as_ref().unwrap_or(&Handling::Required)
+    }
+
+    /// Set the handling parameter.
+    pub fn set_handling(&mut self, handling: Option<Handling>) {
+        self.handling = handling;
+    }
+
+    /// Returns the other parameters of the header.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
 }
 
 impl HeaderParser for ContentDisposition {
     const NAME: &'static str = "Content-Disposition";
 
+    /*
+     * Content-Disposition   =  "Content-Disposition" HCOLON
+     *                          disp-type *( SEMI disp-param )
+     * disp-param            =  handling-param / generic-param
+     * handling-param        =  "handling" EQUAL
+     *                          ( "optional" / "required"
+     *                          / other-handling )
+     */
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let _type = parser.parse_token()?;
-        let params = parse_header_param!(parser);
+        let disp_type = parser.parse_token()?;
+        let mut handling: Option<String> = None;
+        let params = parse_header_param!(parser, HANDLING_PARAM = handling);
 
         Ok(ContentDisposition {
-            _type: _type.into(),
+            disp_type: disp_type.into(),
+            handling: handling.as_deref().map(Handling::from),
             params,
         })
     }
@@ -50,16 +88,98 @@ impl HeaderParser for ContentDisposition {
 
 impl fmt::Display for ContentDisposition {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", ContentDisposition::NAME, self._type)?;
+        write!(
+            f,
+            "{}: {}",
+            ContentDisposition::NAME,
+            self.disp_type.as_str()
+        )?;
 
-        if let Some(param) = &self.params {
-            write!(f, ";{}", param)?;
+        if let Some(handling) = &self.handling {
+            write!(f, ";{}={}", HANDLING_PARAM, handling.as_str())?;
+        }
+        if let Some(params) = &self.params {
+            write!(f, "{}", params)?;
         }
 
         Ok(())
     }
 }
 
+/// The disposition type of a `Content-Disposition` header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DispositionType {
+    /// `render` the body to the user.
+    Render,
+    /// The body is a session description.
+    Session,
+    /// The body is an image to be shown as an icon.
+    Icon,
+    /// The body is an alert, such as a ringing sound.
+    Alert,
+    /// Other disposition type (`disp-extension-token`).
+    Other(String),
+}
+
+impl DispositionType {
+    /// Returns the disposition type as str.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Render => "render",
+            Self::Session => "session",
+            Self::Icon => "icon",
+            Self::Alert => "alert",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl From<&str> for DispositionType {
+    fn from(s: &str) -> Self {
+        match s {
+            s if s.eq_ignore_ascii_case("render") => Self::Render,
+            s if s.eq_ignore_ascii_case("session") => Self::Session,
+            s if s.eq_ignore_ascii_case("icon") => Self::Icon,
+            s if s.eq_ignore_ascii_case("alert") => Self::Alert,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
+/// The value of the `handling` parameter of a
+/// `Content-Disposition` header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Handling {
+    /// The body can be ignored if it is not understood.
+    Optional,
+    /// The body must be understood, otherwise the request
+    /// is rejected with `415 Unsupported Media Type`.
+    Required,
+    /// Other handling value (`other-handling`).
+    Other(String),
+}
+
+impl Handling {
+    /// Returns the handling value as str.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Optional => "optional",
+            Self::Required => "required",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl From<&str> for Handling {
+    fn from(s: &str) -> Self {
+        match s {
+            s if s.eq_ignore_ascii_case("optional") => Self::Optional,
+            s if s.eq_ignore_ascii_case("required") => Self::Required,
+            other => Self::Other(other.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,23 +190,38 @@ mod tests {
         let mut scanner = Parser::new(src);
         let disp = ContentDisposition::parse(&mut scanner);
         let disp = disp.unwrap();
-        assert_eq!(disp._type, "session");
+        assert_eq!(disp.disp_type, DispositionType::Session);
+        assert_eq!(disp.handling(), &Handling::Required);
 
         let src = b"session;handling=optional\r\n";
         let mut scanner = Parser::new(src);
         let disp = ContentDisposition::parse(&mut scanner);
         let disp = disp.unwrap();
-        assert_eq!(disp._type, "session");
-        assert_eq!(disp.params.unwrap().get_named("handling"), Some("optional"));
+        assert_eq!(disp.disp_type, DispositionType::Session);
+        assert_eq!(disp.handling(), &Handling::Optional);
 
         let src = b"attachment; filename=smime.p7s;handling=required\r\n";
         let mut scanner = Parser::new(src);
         let disp = ContentDisposition::parse(&mut scanner);
         let disp = disp.unwrap();
-        assert_eq!(disp._type, "attachment");
+        assert_eq!(disp.disp_type, DispositionType::Other("attachment".into()));
+        assert_eq!(disp.handling(), &Handling::Required);
         let params = disp.params.unwrap();
 
         assert_eq!(params.get_named("filename"), Some("smime.p7s"));
-        assert_eq!(params.get_named("handling"), Some("required"));
+    }
+
+    #[test]
+    fn test_parse_session_handling_required() {
+        let src = b"session;handling=required\r\n";
+        let mut scanner = Parser::new(src);
+        let disp = ContentDisposition::parse(&mut scanner).unwrap();
+
+        assert_eq!(disp.disp_type(), &DispositionType::Session);
+        assert_eq!(disp.handling(), &Handling::Required);
+        assert_eq!(
+            disp.to_string(),
+            "Content-Disposition: session;handling=required"
+        );
     }
 }
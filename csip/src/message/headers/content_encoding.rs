@@ -35,6 +35,12 @@ impl ContentEncoding {
         self.0.get(index).map(|s| s.as_ref())
     }
 
+    /// Returns an iterator over the content encodings, in the
+    /// order they were applied.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.0.iter().map(|s| s.as_ref())
+    }
+
     /// Return the number of content encodings.
     pub fn len(&self) -> usize {
         self.0.len()
@@ -52,14 +58,23 @@ impl HeaderParser for ContentEncoding {
     }
 }
 
+impl ContentEncoding {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().format(", "))
+    }
+}
+
 impl fmt::Display for ContentEncoding {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}: {}",
-            ContentEncoding::NAME,
-            self.0.iter().format(", ")
-        )
+        write!(f, "{}: ", ContentEncoding::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
+impl<'a> FromIterator<&'a str> for ContentEncoding {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self(iter.into_iter().map(String::from).collect())
     }
 }
 
@@ -26,6 +26,7 @@ impl HeaderParser for ContentLanguage {
     const NAME: &'static str = "Content-Language";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
+        // SAFETY: `is_lang` only accepts ASCII bytes.
         let languages = comma_separated_header_value!(parser => unsafe {
             parser.read_while_as_str_unchecked(is_lang).into()
         });
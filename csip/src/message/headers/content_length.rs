@@ -45,9 +45,17 @@ impl HeaderParser for ContentLength {
     }
 }
 
+impl ContentLength {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl fmt::Display for ContentLength {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", ContentLength::NAME, self.0)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", ContentLength::NAME)?;
+        self.fmt_value(f)
     }
 }
 
@@ -59,9 +59,17 @@ impl HeaderParser for ContentType {
     }
 }
 
+impl ContentType {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl fmt::Display for ContentType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", ContentType::NAME, self.0)
+        write!(f, "{}: ", ContentType::NAME)?;
+        self.fmt_value(f)
     }
 }
 
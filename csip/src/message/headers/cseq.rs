@@ -1,10 +1,14 @@
 use core::fmt;
 use std::str::{self, FromStr};
 
-use crate::error::Result;
+use crate::error::{ParseErrorKind as ErrorKind, Result};
 use crate::message::Method;
 use crate::parser::{HeaderParser, Parser};
 
+/// The largest sequence number allowed in a `CSeq`, as the
+/// number must be less than 2**31 (RFC 3261 section 8.1.1.5).
+pub const MAX_CSEQ: u32 = (1 << 31) - 1;
+
 /// The `CSeq` SIP header.
 ///
 /// Ensures order and tracking of SIP Transaction within a
@@ -18,7 +22,7 @@ use crate::parser::{HeaderParser, Parser};
 ///
 /// assert_eq!("CSeq: 1 OPTIONS", cseq.to_string());
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct CSeq {
     /// The CSeq number.
     pub cseq: u32,
@@ -56,6 +60,26 @@ impl CSeq {
     pub fn method(&self) -> &Method {
         &self.method
     }
+
+    /// Returns the `CSeq` with the next sequence number and
+    /// the same method, or `None` if the number would exceed
+    /// [`MAX_CSEQ`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::Method;
+    /// # use csip::message::headers::{CSeq, MAX_CSEQ};
+    /// let cseq = CSeq::new(1, Method::Bye);
+    ///
+    /// assert_eq!(cseq.next(), Some(CSeq::new(2, Method::Bye)));
+    /// assert_eq!(CSeq::new(MAX_CSEQ, Method::Bye).next(), None);
+    /// ```
+    pub fn next(&self) -> Option<Self> {
+        let cseq = self.cseq.checked_add(1).filter(|&cseq| cseq <= MAX_CSEQ)?;
+
+        Some(Self::new(cseq, self.method.clone()))
+    }
 }
 
 impl HeaderParser for CSeq {
@@ -63,10 +87,12 @@ impl HeaderParser for CSeq {
 
     fn parse(parser: &mut Parser) -> Result<CSeq> {
         let cseq = parser.read_u32()?;
+        if cseq > MAX_CSEQ {
+            return parser.parse_error(ErrorKind::Header);
+        }
 
         parser.skip_ws();
-        let b_method = parser.alphabetic();
-        let method = Method::from(b_method);
+        let method = parser.parse_method()?;
 
         Ok(CSeq { cseq, method })
     }
@@ -85,4 +111,37 @@ mod tests {
         assert_eq!(c_length.method, Method::Invite);
         assert_eq!(c_length.cseq, 4711);
     }
+
+    #[test]
+    fn test_parse_max_cseq() {
+        let cseq = CSeq::from_str("2147483647 BYE").unwrap();
+        assert_eq!(cseq, CSeq::new(MAX_CSEQ, Method::Bye));
+
+        let value = cseq.to_string();
+        let value = value.strip_prefix("CSeq: ").unwrap();
+        assert_eq!(CSeq::from_str(value).unwrap(), cseq);
+
+        assert!(CSeq::from_str("2147483648 BYE").is_err());
+    }
+
+    #[test]
+    fn test_next_increments_cseq() {
+        let cseq = CSeq::new(4711, Method::Invite);
+
+        assert_eq!(cseq.next(), Some(CSeq::new(4712, Method::Invite)));
+        assert_eq!(
+            CSeq::new(MAX_CSEQ - 1, Method::Invite).next().unwrap().cseq,
+            MAX_CSEQ
+        );
+        assert_eq!(CSeq::new(MAX_CSEQ, Method::Invite).next(), None);
+    }
+
+    #[test]
+    fn test_long_method_is_not_truncated() {
+        let method = "A".repeat(64);
+        let src = format!("4711 {method}\r\n");
+        let cseq = CSeq::parse(&mut Parser::new(src.as_bytes())).unwrap();
+
+        assert_eq!(cseq.method.as_str(), method);
+    }
 }
@@ -1,45 +1,250 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fmt, str};
 
-use crate::error::Result;
+use crate::error::{ParseErrorKind as ErrorKind, Result};
 use crate::parser::{HeaderParser, Parser};
 
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const GMT: &str = "GMT";
+
+const SECS_PER_DAY: u64 = 86_400;
+
 /// The `Date` SIP header.
 ///
 /// Reflects the time when the request or response is first
-/// sent.
+/// sent. The date is always in GMT, as defined in RFC 1123.
 ///
 /// # Examples
 ///
 /// ```
-/// # use csip::{header::Date};
-/// let date = Date::new("Sat, 13 Nov 2010 23:29:00 GMT");
+/// # use csip::message::headers::Date;
+/// let date = Date::new(2010, 11, 13, 23, 29, 0).unwrap();
 ///
 /// assert_eq!("Date: Sat, 13 Nov 2010 23:29:00 GMT", date.to_string());
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone)]
-#[repr(transparent)]
-pub struct Date(String);
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
 
 impl Date {
     /// Create a new `Date` instance.
-    pub fn new(d: &str) -> Self {
-        Self(d.into())
+    ///
+    /// Returns `None` if any of the fields is out of range.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        let valid = (1970..=9999).contains(&year)
+            && (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second < 60;
+
+        valid.then_some(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Returns the current date.
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Returns the year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month, starting at `1` for January.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Returns the hour.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Returns the minute.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Returns the second.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Returns the day of week name (e.g. `"Sun"`).
+    pub fn weekday(&self) -> &'static str {
+        // 1970-01-01 was a Thursday.
+        let days = self.days_since_epoch();
+        WEEKDAYS[((days + 3) % 7) as usize]
+    }
+
+    /// Converts this date into a [`SystemTime`].
+    pub fn to_system_time(&self) -> SystemTime {
+        let secs = self.days_since_epoch() * SECS_PER_DAY
+            + self.hour as u64 * 3600
+            + self.minute as u64 * 60
+            + self.second as u64;
+
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn days_since_epoch(&self) -> u64 {
+        let days_before_year: u64 = (1970..self.year).map(days_in_year).sum();
+        let days_before_month: u64 = (1..self.month)
+            .map(|month| days_in_month(self.year, month) as u64)
+            .sum();
+
+        days_before_year + days_before_month + (self.day as u64 - 1)
+    }
+}
+
+impl From<SystemTime> for Date {
+    fn from(time: SystemTime) -> Self {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut days = secs / SECS_PER_DAY;
+        let secs_of_day = secs % SECS_PER_DAY;
+
+        let mut year = 1970;
+        while days >= days_in_year(year) {
+            days -= days_in_year(year);
+            year += 1;
+        }
+        let mut month = 1;
+        while days >= days_in_month(year, month) as u64 {
+            days -= days_in_month(year, month) as u64;
+            month += 1;
+        }
+
+        Self {
+            year,
+            month,
+            day: days as u8 + 1,
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day % 3600 / 60) as u8,
+            second: (secs_of_day % 60) as u8,
+        }
+    }
+}
+
+impl From<Date> for SystemTime {
+    fn from(date: Date) -> Self {
+        date.to_system_time()
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_year(year: u16) -> u64 {
+    if is_leap_year(year) { 366 } else { 365 }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
     }
 }
 
 impl HeaderParser for Date {
     const NAME: &'static str = "Date";
 
+    /*
+     * Date          =  "Date" HCOLON SIP-date
+     * SIP-date      =  rfc1123-date
+     * rfc1123-date  =  wkday "," SP date1 SP time SP "GMT"
+     * date1         =  2DIGIT SP month SP 4DIGIT
+     * time          =  2DIGIT ":" 2DIGIT ":" 2DIGIT
+     */
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let date = parser.read_until_new_line_as_str()?;
+        let weekday = str::from_utf8(parser.alphabetic())?;
+        parser.must_read(b',')?;
+        parser.skip_ws();
+
+        let day = parser.read_u32()?;
+        parser.skip_ws();
+        let month = str::from_utf8(parser.alphabetic())?;
+        parser.skip_ws();
+        let year = parser.read_u32()?;
+        parser.skip_ws();
 
-        Ok(Date(date.into()))
+        let hour = parser.read_u32()?;
+        parser.must_read(b':')?;
+        let minute = parser.read_u32()?;
+        parser.must_read(b':')?;
+        let second = parser.read_u32()?;
+        parser.skip_ws();
+
+        // RFC 3261 - 20.17 Date
+        // RFC 3261 restricts the time zone in SIP-date to "GMT".
+        let zone = parser.alphabetic();
+        if zone != GMT.as_bytes() {
+            return parser.parse_error(ErrorKind::Header);
+        }
+
+        let Some(month) = MONTHS.iter().position(|m| *m == month) else {
+            return parser.parse_error(ErrorKind::Header);
+        };
+        let date = Date::new(
+            year.try_into().unwrap_or_default(),
+            month as u8 + 1,
+            day.try_into().unwrap_or_default(),
+            hour.try_into().unwrap_or(u8::MAX),
+            minute.try_into().unwrap_or(u8::MAX),
+            second.try_into().unwrap_or(u8::MAX),
+        );
+
+        match date {
+            Some(date) if date.weekday() == weekday => Ok(date),
+            _ => parser.parse_error(ErrorKind::Header),
+        }
     }
 }
 
 impl fmt::Display for Date {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", Date::NAME, self.0)
+        write!(
+            f,
+            "{}: {}, {:02} {} {:04} {:02}:{:02}:{:02} {}",
+            Date::NAME,
+            self.weekday(),
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second,
+            GMT
+        )
     }
 }
 
@@ -54,6 +259,33 @@ mod tests {
         let date = Date::parse(&mut scanner).unwrap();
 
         assert_eq!(scanner.remaining(), b"\r\n");
-        assert_eq!(date.0, "Sat, 13 Nov 2010 23:29:00 GMT");
+        assert_eq!(date, Date::new(2010, 11, 13, 23, 29, 0).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let src = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let date = Date::from_bytes(src.as_bytes()).unwrap();
+
+        assert_eq!(date.to_string(), format!("Date: {src}"));
+        assert_eq!(Date::from(date.to_system_time()), date);
+        assert_eq!(
+            date.to_system_time(),
+            UNIX_EPOCH + Duration::from_secs(784111777)
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_gmt_zone() {
+        let src = b"Sun, 06 Nov 1994 08:49:37 PST\r\n";
+
+        assert!(Date::from_bytes(src).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_date() {
+        assert!(Date::from_bytes(b"Sun, 31 Feb 1994 08:49:37 GMT").is_err());
+        assert!(Date::from_bytes(b"Mon, 06 Nov 1994 08:49:37 GMT").is_err());
+        assert!(Date::from_bytes(b"Sun, 06 Nov 1994 25:49:37 GMT").is_err());
     }
 }
@@ -7,18 +7,39 @@ use crate::macros::{comma_separated_header_value, parse_header_param};
 use crate::message::Params;
 use crate::parser::{HeaderParser, Parser};
 
+/// A single `<uri>;params` entry of an `Error-Info` header.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ErrorInfoUri {
-    url: String,
+    uri: String,
     params: Option<Params>,
 }
 
+impl ErrorInfoUri {
+    /// Creates a new `ErrorInfoUri` with the given uri and params.
+    pub fn new(uri: &str, params: Option<Params>) -> Self {
+        Self {
+            uri: uri.into(),
+            params,
+        }
+    }
+
+    /// Returns the uri of this entry.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the params of this entry, if any.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+}
+
 impl fmt::Display for ErrorInfoUri {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.url)?;
+        write!(f, "<{}>", self.uri)?;
 
         if let Some(param) = &self.params {
-            write!(f, ";{}", param)?;
+            write!(f, "{}", param)?;
         }
 
         Ok(())
@@ -32,21 +53,37 @@ impl fmt::Display for ErrorInfoUri {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ErrorInfo(Vec<ErrorInfoUri>);
 
+impl ErrorInfo {
+    /// Creates a new `ErrorInfo` header with a single uri.
+    pub fn new(uri: &str) -> Self {
+        Self(vec![ErrorInfoUri::new(uri, None)])
+    }
+
+    /// Appends an uri to this header.
+    pub fn push(&mut self, uri: ErrorInfoUri) {
+        self.0.push(uri);
+    }
+
+    /// Returns an iterator over the uris in this header.
+    pub fn iter(&self) -> impl Iterator<Item = &ErrorInfoUri> {
+        self.0.iter()
+    }
+}
+
 impl HeaderParser for ErrorInfo {
     const NAME: &'static str = "Error-Info";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
         let infos = comma_separated_header_value!(parser => {
             parser.next_byte()?;
-            let url = parser.read_until(b'>');
+            let uri = parser.read_until(b'>');
             parser.next_byte()?;
 
-            let url = str::from_utf8(url)?;
+            let uri = str::from_utf8(uri)?.into();
             let params = parse_header_param!(parser);
-            ErrorInfoUri {
-                url: url.into(),
-                params
-            }
+            parser.skip_ws();
+
+            ErrorInfoUri { uri, params }
         });
 
         Ok(ErrorInfo(infos))
@@ -55,7 +92,7 @@ impl HeaderParser for ErrorInfo {
 
 impl fmt::Display for ErrorInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0.iter().format(", "))
+        write!(f, "{}: {}", ErrorInfo::NAME, self.0.iter().format(", "))
     }
 }
 
@@ -70,7 +107,7 @@ mod tests {
         let err_info = ErrorInfo::parse(&mut scanner).unwrap();
         assert_eq!(scanner.remaining(), b"\r\n");
 
-        let err = err_info.0.get(0).unwrap();
-        assert_eq!(err.url, "sip:not-in-service-recording@atlanta.com");
+        let err = err_info.iter().next().unwrap();
+        assert_eq!(err.uri(), "sip:not-in-service-recording@atlanta.com");
     }
 }
@@ -0,0 +1,135 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::macros::parse_header_param;
+use crate::message::Params;
+use crate::parser::{HeaderParser, Parser};
+
+/// The id parameter of the `Event` header.
+const ID_PARAM: &str = "id";
+
+/// The `Event` SIP header.
+///
+/// Names the event package of a subscription or notification,
+/// as defined in `RFC6665`.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::message::headers::Event;
+/// let event = Event::new("refer").with_id("93809824");
+///
+/// assert_eq!("Event: refer;id=93809824", event.to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Event {
+    package: String,
+    id: Option<String>,
+    params: Option<Params>,
+}
+
+impl Event {
+    /// Creates a new `Event` header for the given event package.
+    pub fn new(package: impl Into<String>) -> Self {
+        Self {
+            package: package.into(),
+            id: None,
+            params: None,
+        }
+    }
+
+    /// Sets the `id` parameter, that tells apart subscriptions
+    /// to the same package in a dialog.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Returns the event package, e.g. `refer`.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Returns the `id` parameter.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the other parameters of the header.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+}
+
+impl HeaderParser for Event {
+    const NAME: &'static str = "Event";
+    const SHORT_NAME: &'static str = "o";
+
+    /*
+     * Event       =  ( "Event" / "o" ) HCOLON event-type
+     *                *( SEMI event-param )
+     * event-param =  generic-param / ( "id" EQUAL token )
+     */
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let package = parser.parse_token()?;
+        let mut id: Option<String> = None;
+        let params = parse_header_param!(parser, ID_PARAM = id);
+
+        Ok(Event {
+            package: package.into(),
+            id,
+            params,
+        })
+    }
+}
+
+impl Event {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.package)?;
+
+        if let Some(id) = &self.id {
+            write!(f, ";{}={}", ID_PARAM, id)?;
+        }
+        if let Some(params) = &self.params {
+            write!(f, "{}", params)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", Event::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = b"refer;id=93809824;foo=bar\r\n";
+        let mut scanner = Parser::new(src);
+        let event = Event::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(event.package(), "refer");
+        assert_eq!(event.id(), Some("93809824"));
+        assert_eq!(event.params().and_then(|p| p.get_named("foo")), Some("bar"));
+    }
+
+    #[test]
+    fn test_parse_without_id() {
+        let src = b"presence\r\n";
+        let mut scanner = Parser::new(src);
+        let event = Event::parse(&mut scanner).unwrap();
+
+        assert_eq!(event.package(), "presence");
+        assert_eq!(event.id(), None);
+        assert_eq!(event.to_string(), "Event: presence");
+    }
+}
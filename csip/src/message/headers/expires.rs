@@ -30,6 +30,14 @@ impl Expires {
     pub const fn as_u32(&self) -> u32 {
         self.0
     }
+
+    /// Returns `true` if the value is `0`.
+    ///
+    /// In a `REGISTER` it removes the bindings and in a
+    /// `SUBSCRIBE` it terminates the subscription.
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
 }
 
 impl HeaderParser for Expires {
@@ -60,4 +68,10 @@ mod tests {
         assert_eq!(scanner.remaining(), b"\r\n");
         assert_eq!(expires.0, 5);
     }
+
+    #[test]
+    fn test_is_zero() {
+        assert!(Expires::new(0).is_zero());
+        assert!(!Expires::new(5).is_zero());
+    }
 }
@@ -3,7 +3,7 @@ use std::str::{self, FromStr};
 
 use crate::error::Result;
 use crate::macros::parse_header_param;
-use crate::message::headers::TAG_PARAM;
+use crate::message::headers::{TAG_PARAM, To};
 use crate::message::{Params, SipUri, Uri};
 use crate::parser::{HeaderParser, Parser};
 
@@ -37,9 +37,9 @@ use crate::parser::{HeaderParser, Parser};
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct From {
-    uri: SipUri,
-    tag: Option<String>,
-    params: Option<Params>,
+    pub(crate) uri: SipUri,
+    pub(crate) tag: Option<String>,
+    pub(crate) params: Option<Params>,
 }
 
 impl FromStr for From {
@@ -61,6 +61,11 @@ impl From {
         }
     }
 
+    /// Get the SIP URI of the `From` header.
+    pub fn sip_uri(&self) -> &SipUri {
+        &self.uri
+    }
+
     /// Get the URI of the `From` header, if available.
     pub fn uri(&self) -> &Uri {
         self.uri.uri()
@@ -75,6 +80,23 @@ impl From {
     pub fn tag(&self) -> &Option<String> {
         &self.tag
     }
+
+    /// Set the tag parameter.
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+    }
+}
+
+impl std::convert::From<To> for From {
+    /// Converts a `To` header into a `From` header with the
+    /// same URI, tag and parameters.
+    fn from(to: To) -> Self {
+        Self {
+            uri: to.uri,
+            tag: to.tag,
+            params: to.params,
+        }
+    }
 }
 
 impl HeaderParser for From {
@@ -90,11 +112,12 @@ impl HeaderParser for From {
     }
 }
 
-impl fmt::Display for From {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl From {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.uri {
-            SipUri::Uri(uri) => write!(f, "{}: {}", From::NAME, uri)?,
-            SipUri::NameAddr(name_addr) => write!(f, "{}: {}", From::NAME, name_addr)?,
+            SipUri::Uri(uri) => write!(f, "{}", uri)?,
+            SipUri::NameAddr(name_addr) => write!(f, "{}", name_addr)?,
         }
         if let Some(tag) = &self.tag {
             write!(f, ";tag={}", tag)?;
@@ -107,10 +130,17 @@ impl fmt::Display for From {
     }
 }
 
+impl fmt::Display for From {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", From::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::message::{DisplayName, DomainName, Host, HostPort, Scheme};
+    use crate::message::{DisplayName, DomainName, Host, HostPort, NameAddr, Scheme};
 
     // FromHeader inputs
 
@@ -216,4 +246,54 @@ mod tests {
             assert_eq!(tag, Some("hyh8".into()));
          });
     }
+
+    #[test]
+    fn test_decode_encoded_word_display_name() {
+        let src = b"\"=?UTF-8?B?Sm/Do28gU2lsdmE=?=\" <sip:joao@example.com>;tag=1\r\n";
+        let mut scanner = Parser::new(src);
+        let from = From::parse(&mut scanner).unwrap();
+        let display = from
+            .sip_uri()
+            .name_addr()
+            .unwrap()
+            .display
+            .as_ref()
+            .unwrap();
+
+        assert_eq!(display.as_str(), "=?UTF-8?B?Sm/Do28gU2lsdmE=?=");
+        assert_eq!(display.decode(), "João Silva");
+
+        let display = DisplayName::new("=?iso-8859-1?q?Jo=E3o?= =?UTF-8?Q?_Silva?=");
+        assert_eq!(display.decode(), "João Silva");
+
+        let display = DisplayName::new("=?UTF-8?X?broken?= Bob");
+        assert_eq!(display.decode(), "=?UTF-8?X?broken?= Bob");
+    }
+
+    #[test]
+    fn test_display_name_with_spaces_is_quoted() {
+        let uri = SipUri::NameAddr(NameAddr {
+            display: Some(DisplayName::new("Alice \"Al\" Liddell")),
+            uri: "sip:alice@wonderland.com".parse().unwrap(),
+        });
+        let from = From::new(uri);
+
+        assert_eq!(
+            from.to_string(),
+            "From: \"Alice \\\"Al\\\" Liddell\" <sip:alice@wonderland.com>"
+        );
+
+        let src = b"Anonymous <sip:c8oqz84zk7z@privacy.org>\r\n";
+        let from = From::parse(&mut Parser::new(src)).unwrap();
+        assert_eq!(
+            from.to_string(),
+            "From: Anonymous <sip:c8oqz84zk7z@privacy.org>"
+        );
+
+        let reparsed = From::parse(&mut Parser::new(
+            b"\"Alice \\\"Al\\\" Liddell\" <sip:alice@wonderland.com>\r\n",
+        ))
+        .unwrap();
+        assert_eq!(reparsed.display(), Some("Alice \"Al\" Liddell"));
+    }
 }
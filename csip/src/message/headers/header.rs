@@ -2,7 +2,9 @@ use std::fmt;
 
 use enum_as_inner::EnumAsInner;
 
+use crate::error::Result;
 use crate::message::headers::*;
+use crate::parser::{HeaderParser, Parser};
 
 /// A SIP Header.
 ///
@@ -47,16 +49,22 @@ pub enum Header {
     Date(Date),
     /// `Error-Info` Header
     ErrorInfo(ErrorInfo),
+    /// `Event` Header
+    Event(Event),
     /// `Expires` Header
     Expires(Expires),
     /// `From` Header
     From(From),
     /// `In-Reply-To` Header
     InReplyTo(InReplyTo),
+    /// `Max-Breadth` Header
+    MaxBreadth(MaxBreadth),
     /// `Max-Fowards` Header
     MaxForwards(MaxForwards),
     /// `Min-Expires` Header
     MinExpires(MinExpires),
+    /// `Min-SE` Header
+    MinSe(MinSe),
     /// `MIME-Version` Header
     MimeVersion(MimeVersion),
     /// `Organization` Header
@@ -69,6 +77,8 @@ pub enum Header {
     ProxyAuthorization(ProxyAuthorization),
     /// `Proxy-Require` Header
     ProxyRequire(ProxyRequire),
+    /// `RAck` Header
+    RAck(RAck),
     /// `Retry-After` Header
     RetryAfter(RetryAfter),
     /// `Route` Header
@@ -79,8 +89,12 @@ pub enum Header {
     ReplyTo(ReplyTo),
     /// `Require` Header
     Require(Require),
+    /// `RSeq` Header
+    RSeq(RSeq),
     /// `Server` Header
     Server(Server),
+    /// `Session-Expires` Header
+    SessionExpires(SessionExpires),
     /// `Subject` Header
     Subject(Subject),
     /// `Supported` Header
@@ -132,6 +146,73 @@ impl fmt::Display for RawHeader {
     }
 }
 
+impl Header {
+    /// Parses a header from a `name: value` line, such as
+    /// `Subject: Lunch`, in its full or compact form.
+    ///
+    /// The line may end with a line break. It is an error if
+    /// anything follows it, or if a comma-separated list header,
+    /// such as `Via` or `Contact`, holds more than one value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::Header;
+    /// let header = Header::from_bytes(b"s: Lunch").unwrap();
+    ///
+    /// assert_eq!(header.to_string(), "Subject: Lunch");
+    /// ```
+    pub fn from_bytes(src: &[u8]) -> Result<Header> {
+        Parser::new(src).parse_header()
+    }
+
+    /// Returns the compact form of the header name, if the
+    /// header has one (RFC 3261 section 7.3.3).
+    pub fn compact_name(&self) -> Option<&'static str> {
+        let name = match self {
+            Header::CallId(_) => CallId::SHORT_NAME,
+            Header::Contact(_) => Contact::SHORT_NAME,
+            Header::ContentEncoding(_) => ContentEncoding::SHORT_NAME,
+            Header::ContentLength(_) => ContentLength::SHORT_NAME,
+            Header::ContentType(_) => ContentType::SHORT_NAME,
+            Header::Event(_) => Event::SHORT_NAME,
+            Header::From(_) => From::SHORT_NAME,
+            Header::SessionExpires(_) => SessionExpires::SHORT_NAME,
+            Header::Subject(_) => Subject::SHORT_NAME,
+            Header::Supported(_) => Supported::SHORT_NAME,
+            Header::To(_) => To::SHORT_NAME,
+            Header::Via(_) => Via::SHORT_NAME,
+            _ => return None,
+        };
+
+        Some(name)
+    }
+
+    // Writes the header with its compact name, if it has one.
+    pub(crate) fn fmt_compact(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(name) = self.compact_name() else {
+            return write!(f, "{self}");
+        };
+        write!(f, "{name}: ")?;
+
+        match self {
+            Header::CallId(h) => h.fmt_value(f),
+            Header::Contact(h) => h.fmt_value(f),
+            Header::ContentEncoding(h) => h.fmt_value(f),
+            Header::ContentLength(h) => h.fmt_value(f),
+            Header::ContentType(h) => h.fmt_value(f),
+            Header::Event(h) => h.fmt_value(f),
+            Header::From(h) => h.fmt_value(f),
+            Header::SessionExpires(h) => h.fmt_value(f),
+            Header::Subject(h) => h.fmt_value(f),
+            Header::Supported(h) => h.fmt_value(f),
+            Header::To(h) => h.fmt_value(f),
+            Header::Via(h) => h.fmt_value(f),
+            _ => unreachable!("{} has no compact name", self),
+        }
+    }
+}
+
 macro_rules! impl_header_display {
     ( $($variant:ident),* $(,)? ) => {
         impl fmt::Display for Header {
@@ -163,23 +244,29 @@ impl_header_display!(
     CSeq,
     Date,
     ErrorInfo,
+    Event,
     Expires,
     From,
     InReplyTo,
+    MaxBreadth,
     MaxForwards,
     MinExpires,
+    MinSe,
     MimeVersion,
     Organization,
     Priority,
     ProxyAuthenticate,
     ProxyAuthorization,
     ProxyRequire,
+    RAck,
     RetryAfter,
     Route,
     RecordRoute,
     ReplyTo,
     Require,
+    RSeq,
     Server,
+    SessionExpires,
     Subject,
     Supported,
     Timestamp,
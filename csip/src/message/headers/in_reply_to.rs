@@ -14,13 +14,32 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct InReplyTo(Vec<CallId>);
 
+impl InReplyTo {
+    /// Creates a new `InReplyTo` header with the given
+    /// `Call-IDs`.
+    pub fn new(ids: Vec<CallId>) -> Self {
+        Self(ids)
+    }
+
+    /// Returns an iterator over the referenced `Call-IDs`.
+    pub fn iter(&self) -> impl Iterator<Item = &CallId> {
+        self.0.iter()
+    }
+
+    /// Returns `true` if the given `Call-ID` is referenced
+    /// by this header.
+    pub fn contains(&self, call_id: &CallId) -> bool {
+        self.0.contains(call_id)
+    }
+}
+
 impl HeaderParser for InReplyTo {
     const NAME: &'static str = "In-Reply-To";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
         let ids = comma_separated_header_value!(parser => {
             let id = parser.not_comma_or_newline();
-            let id = str::from_utf8(id)?;
+            let id = str::from_utf8(id)?.trim_end();
 
             CallId::from(id)
         });
@@ -31,7 +50,12 @@ impl HeaderParser for InReplyTo {
 
 impl fmt::Display for InReplyTo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", InReplyTo::NAME, self.0.iter().format(", "))
+        write!(
+            f,
+            "{}: {}",
+            InReplyTo::NAME,
+            self.0.iter().map(CallId::id).format(", ")
+        )
     }
 }
 
@@ -46,13 +70,24 @@ mod tests {
         let in_reply_to = InReplyTo::parse(&mut scanner).unwrap();
         assert_eq!(scanner.remaining(), b"\r\n");
 
+        let ids: Vec<_> = in_reply_to.iter().map(CallId::id).collect();
         assert_eq!(
-            in_reply_to.0.get(0).unwrap().id(),
-            "70710@saturn.bell-tel.com"
+            ids,
+            ["70710@saturn.bell-tel.com", "17320@saturn.bell-tel.com"]
         );
+        assert!(in_reply_to.contains(&CallId::from("17320@saturn.bell-tel.com")));
+    }
+
+    #[test]
+    fn test_display() {
+        let in_reply_to = InReplyTo::new(vec![
+            CallId::from("70710@saturn.bell-tel.com"),
+            CallId::from("17320@saturn.bell-tel.com"),
+        ]);
+
         assert_eq!(
-            in_reply_to.0.get(1).unwrap().id(),
-            "17320@saturn.bell-tel.com"
+            in_reply_to.to_string(),
+            "In-Reply-To: 70710@saturn.bell-tel.com, 17320@saturn.bell-tel.com"
         );
     }
 }
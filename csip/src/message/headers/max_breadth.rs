@@ -0,0 +1,125 @@
+use std::{fmt, str};
+
+use crate::error::Result;
+use crate::parser::{HeaderParser, Parser};
+
+/// The `Max-Breadth` SIP header.
+///
+/// Limits the number of concurrent branches a request may
+/// be forked into along its path (RFC 5393).
+///
+/// # Examples
+/// ```
+/// # use csip::message::headers::MaxBreadth;
+///
+/// let max = MaxBreadth::new(60);
+///
+/// assert_eq!("Max-Breadth: 60", max.to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
+pub struct MaxBreadth(u32);
+
+impl MaxBreadth {
+    /// Creates a new `MaxBreadth` header with the given
+    /// breadth.
+    pub const fn new(breadth: u32) -> Self {
+        Self(breadth)
+    }
+
+    /// Returns the internal `MaxBreadth` value.
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc5393#section-5.3.2
+    /// Divides the breadth among `branches` parallel branches,
+    /// returning the `Max-Breadth` of each branch.
+    ///
+    /// Each branch gets at least `1`, so `None` is returned if
+    /// there are more branches than the breadth allows, and the
+    /// request must be rejected with `440 Max-Breadth Exceeded`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use csip::message::headers::MaxBreadth;
+    /// let branches = MaxBreadth::new(5).split(2).unwrap();
+    ///
+    /// assert_eq!(branches, [MaxBreadth::new(3), MaxBreadth::new(2)]);
+    /// assert!(MaxBreadth::new(1).split(2).is_none());
+    /// ```
+    pub fn split(self, branches: u32) -> Option<Vec<MaxBreadth>> {
+        if branches > self.0 {
+            return None;
+        }
+        if branches == 0 {
+            return Some(Vec::new());
+        }
+        let share = self.0 / branches;
+        let remainder = self.0 % branches;
+        let split = (0..branches)
+            .map(|branch| MaxBreadth(share + u32::from(branch < remainder)))
+            .collect();
+
+        Some(split)
+    }
+}
+
+impl Default for MaxBreadth {
+    /// Returns `60`, the value a proxy inserts in a request
+    /// that has no `Max-Breadth` before forking it.
+    fn default() -> Self {
+        Self(60)
+    }
+}
+
+impl HeaderParser for MaxBreadth {
+    const NAME: &'static str = "Max-Breadth";
+
+    fn parse(parser: &mut Parser) -> Result<MaxBreadth> {
+        let breadth = parser.read_u32()?;
+
+        Ok(MaxBreadth(breadth))
+    }
+}
+
+impl fmt::Display for MaxBreadth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", MaxBreadth::NAME, self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = b"60\r\n";
+        let mut scanner = Parser::new(src);
+        let max_breadth = MaxBreadth::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(max_breadth.as_u32(), 60);
+    }
+
+    #[test]
+    fn test_split_among_branches() {
+        let split = MaxBreadth::new(60).split(7).unwrap();
+
+        assert_eq!(split.len(), 7);
+        assert_eq!(split.iter().map(MaxBreadth::as_u32).sum::<u32>(), 60);
+        assert!(split.iter().all(|b| b.as_u32() >= 8));
+        assert_eq!(
+            MaxBreadth::new(3).split(3).unwrap(),
+            [MaxBreadth::new(1); 3]
+        );
+    }
+
+    #[test]
+    fn test_split_beyond_breadth_is_exceeded() {
+        assert!(MaxBreadth::new(2).split(3).is_none());
+        assert!(MaxBreadth::new(0).split(1).is_none());
+        assert_eq!(MaxBreadth::new(0).split(0), Some(Vec::new()));
+    }
+}
@@ -1,32 +1,84 @@
 use std::{fmt, str};
 
-use crate::error::Result;
+use crate::error::{ParseErrorKind, Result};
 use crate::parser::{HeaderParser, Parser};
 
 /// The `MIME-Version` SIP header.
 ///
 /// Indicate what version of the `MIME` protocol was used to
 /// construct the message.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::message::headers::MimeVersion;
+/// let mime_version = MimeVersion::new(1, 0);
+///
+/// assert_eq!("MIME-Version: 1.0", mime_version.to_string());
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct MimeVersion {
     major: u8,
     minor: u8,
 }
 
+impl MimeVersion {
+    /// Creates a new `MimeVersion` header.
+    pub fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    /// Returns the major version number.
+    pub fn major(&self) -> u8 {
+        self.major
+    }
+
+    /// Returns the minor version number.
+    pub fn minor(&self) -> u8 {
+        self.minor
+    }
+}
+
+impl Default for MimeVersion {
+    fn default() -> Self {
+        Self::new(1, 0)
+    }
+}
+
 impl HeaderParser for MimeVersion {
     const NAME: &'static str = "MIME-Version";
 
+    /*
+     * MIME-Version  =  "MIME-Version" HCOLON 1*DIGIT "." 1*DIGIT
+     */
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let (major, _, minor) = (
-            parser.next_byte()? - b'0',
-            parser.must_read(b'.')?,
-            parser.next_byte()? - b'0',
-        );
+        let major = parse_version_number(parser)?;
+        parser.must_read(b'.')?;
+        let minor = parse_version_number(parser)?;
 
         Ok(MimeVersion { major, minor })
     }
 }
 
+fn parse_version_number(parser: &mut Parser) -> Result<u8> {
+    let mut number: Option<u8> = None;
+
+    while let Some(&digit) = parser.peek_byte().filter(|b| b.is_ascii_digit()) {
+        parser.next_byte()?;
+        let value = number.unwrap_or(0).checked_mul(10);
+        let value = value.and_then(|n| n.checked_add(digit - b'0'));
+        let Some(value) = value else {
+            return parser.parse_error(ParseErrorKind::Header);
+        };
+        number = Some(value);
+    }
+
+    match number {
+        Some(number) => Ok(number),
+        None => parser.parse_error(ParseErrorKind::Header),
+    }
+}
+
 impl fmt::Display for MimeVersion {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {}.{}", MimeVersion::NAME, self.major, self.minor)
@@ -45,5 +97,15 @@ mod tests {
 
         assert_eq!(mime_version.major, 1);
         assert_eq!(mime_version.minor, 0);
+        assert_eq!(mime_version.to_string(), "MIME-Version: 1.0");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_version() {
+        for src in [&b"x"[..], b"1.x", b"1", b".0", b"256.0"] {
+            let mut scanner = Parser::new(src);
+
+            assert!(MimeVersion::parse(&mut scanner).is_err());
+        }
     }
 }
@@ -0,0 +1,87 @@
+use std::fmt;
+
+use crate::error::Result;
+use crate::macros::parse_header_param;
+use crate::message::Params;
+use crate::parser::{HeaderParser, Parser};
+
+/// The `Min-SE` SIP header.
+///
+/// Indicates the minimum session interval, in seconds, accepted
+/// for a session timer, as defined in `RFC4028`.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::message::headers::MinSe;
+/// let min_se = MinSe::new(90);
+///
+/// assert_eq!("Min-SE: 90", min_se.to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MinSe {
+    delta: u32,
+    params: Option<Params>,
+}
+
+impl MinSe {
+    /// Creates a new `Min-SE` header with the minimum session
+    /// interval in seconds.
+    pub fn new(delta: u32) -> Self {
+        Self {
+            delta,
+            params: None,
+        }
+    }
+
+    /// Returns the minimum session interval in seconds.
+    pub fn delta(&self) -> u32 {
+        self.delta
+    }
+
+    /// Returns the parameters of the header.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+}
+
+impl HeaderParser for MinSe {
+    const NAME: &'static str = "Min-SE";
+
+    /*
+     * Min-SE  =  "Min-SE" HCOLON delta-seconds *(SEMI generic-param)
+     */
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let delta = parser.read_u32()?;
+        let params = parse_header_param!(parser);
+
+        Ok(MinSe { delta, params })
+    }
+}
+
+impl fmt::Display for MinSe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", MinSe::NAME, self.delta)?;
+
+        if let Some(params) = &self.params {
+            write!(f, "{}", params)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = b"90\r\n";
+        let mut scanner = Parser::new(src);
+        let min_se = MinSe::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(min_se.delta(), 90);
+    }
+}
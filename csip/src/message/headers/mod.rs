@@ -23,24 +23,30 @@ mod content_type;
 mod cseq;
 mod date;
 mod error_info;
+mod event;
 mod expires;
 mod from;
 mod header;
 mod in_reply_to;
+mod max_breadth;
 mod max_fowards;
 mod mime_version;
 mod min_expires;
+mod min_se;
 mod organization;
 mod priority;
 mod proxy_authenticate;
 mod proxy_authorization;
 mod proxy_require;
+mod rack;
 mod record_route;
 mod reply_to;
 mod require;
 mod retry_after;
 mod route;
+mod rseq;
 mod server;
+mod session_expires;
 mod subject;
 mod supported;
 mod timestamp;
@@ -59,46 +65,52 @@ use std::vec::Splice;
 pub use accept::Accept;
 pub use accept_encoding::*;
 pub use accept_language::*;
-pub use alert_info::AlertInfo;
+pub use alert_info::{AlertInfo, AlertInfoUri};
 pub use allow::Allow;
 pub use authentication_info::AuthenticationInfo;
 pub use authorization::Authorization;
 pub use call_id::CallId;
-pub use call_info::CallInfo;
+pub use call_info::{CallInfo, CallInfoUri, Purpose};
 pub use contact::Contact;
-pub use content_disposition::ContentDisposition;
+pub use content_disposition::{ContentDisposition, DispositionType, Handling};
 pub use content_encoding::ContentEncoding;
 pub use content_language::ContentLanguage;
 pub use content_length::ContentLength;
 pub use content_type::ContentType;
-pub use cseq::CSeq;
+pub use cseq::{CSeq, MAX_CSEQ};
 pub use date::Date;
-pub use error_info::ErrorInfo;
+pub use error_info::{ErrorInfo, ErrorInfoUri};
+pub use event::Event;
 pub use expires::Expires;
 pub use from::From;
 pub use header::*;
 pub use in_reply_to::InReplyTo;
+pub use max_breadth::MaxBreadth;
 pub use max_fowards::MaxForwards;
 pub use mime_version::MimeVersion;
 pub use min_expires::MinExpires;
+pub use min_se::MinSe;
 pub use organization::Organization;
 pub use priority::Priority;
 pub use proxy_authenticate::ProxyAuthenticate;
 pub use proxy_authorization::ProxyAuthorization;
 pub use proxy_require::ProxyRequire;
+pub use rack::RAck;
 pub use record_route::RecordRoute;
 pub use reply_to::ReplyTo;
 pub use require::Require;
 pub use retry_after::RetryAfter;
 pub use route::Route;
-pub use server::Server;
+pub use rseq::RSeq;
+pub use server::{Server, ServerVal};
+pub use session_expires::{Refresher, SessionExpires};
 pub use subject::Subject;
 pub use supported::Supported;
 pub use timestamp::Timestamp;
 pub use to::To;
 pub use unsupported::Unsupported;
 pub use user_agent::UserAgent;
-pub use via::Via;
+pub use via::{DEFAULT_VIA_TTL, Via};
 pub use warning::Warning;
 pub use www_authenticate::WWWAuthenticate;
 
@@ -172,6 +184,73 @@ impl Headers {
         self.0.insert(index, header);
     }
 
+    /// Removes all headers matching the predicate `f`,
+    /// returning the number of headers removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Headers, Header, Expires, ContentLength};
+    /// let mut headers = Headers::from([
+    ///     Header::Expires(Expires::new(10)),
+    ///     Header::ContentLength(ContentLength::new(0)),
+    /// ]);
+    ///
+    /// assert_eq!(headers.remove_all(|h| matches!(h, Header::Expires(_))), 1);
+    /// assert_eq!(headers.len(), 1);
+    /// ```
+    pub fn remove_all<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&Header) -> bool,
+    {
+        let len = self.0.len();
+        self.0.retain(|h| !f(h));
+
+        len - self.0.len()
+    }
+
+    /// Replaces the first header of the same kind as
+    /// `header`, returning the replaced one.
+    ///
+    /// If no header of that kind is present, `header` is
+    /// pushed to the end of the collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Headers, Header, Expires};
+    /// let mut headers = Headers::from([Header::Expires(Expires::new(10))]);
+    /// let old = headers.replace_first(Header::Expires(Expires::new(20)));
+    ///
+    /// assert_eq!(old, Some(Header::Expires(Expires::new(10))));
+    /// assert_eq!(headers[0], Header::Expires(Expires::new(20)));
+    /// ```
+    pub fn replace_first(&mut self, header: Header) -> Option<Header> {
+        let kind = std::mem::discriminant(&header);
+
+        match self
+            .0
+            .iter_mut()
+            .find(|h| std::mem::discriminant(*h) == kind)
+        {
+            Some(old) => Some(std::mem::replace(old, header)),
+            None => {
+                self.0.push(header);
+                None
+            }
+        }
+    }
+
+    /// Retains only the headers specified by the predicate
+    /// `f`, preserving their order.
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Header) -> bool,
+    {
+        self.0.retain(f);
+    }
+
     /// Constructs a new, empty  collection of `Headers`
     /// with at least the specified capacity.
     #[inline]
@@ -213,6 +292,29 @@ impl Headers {
         self.0.splice(range, replace_with)
     }
 
+    /// Returns a value that displays the headers with the
+    /// compact form of their names, as `v:` for `Via`.
+    ///
+    /// Headers without a compact form are displayed unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Headers, Header, CallId, MaxForwards};
+    /// let headers = Headers::from([
+    ///     Header::CallId(CallId::new("a84b4c76e66710".into())),
+    ///     Header::MaxForwards(MaxForwards::new(70)),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     headers.compact().to_string(),
+    ///     "i: a84b4c76e66710\r\nMax-Forwards: 70\r\n"
+    /// );
+    /// ```
+    pub fn compact(&self) -> CompactHeaders<'_> {
+        CompactHeaders(self)
+    }
+
     /// Returns an iterator over headers.
     #[inline]
     pub fn iter(&self) -> core::slice::Iter<'_, Header> {
@@ -225,6 +327,31 @@ impl Headers {
         self.0.iter_mut()
     }
 
+    /// Returns an iterator over the `branch` parameter of
+    /// every `Via` header, from the topmost to the last.
+    ///
+    /// `Via` headers without a branch are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Headers, Header, Via};
+    /// let headers = Headers::from([
+    ///     Header::Via(Via::new_udp("10.0.0.1:5060".parse().unwrap(), Some("z9hG4bK1".into()))),
+    ///     Header::Via(Via::new_udp("10.0.0.2:5060".parse().unwrap(), None)),
+    ///     Header::Via(Via::new_udp("10.0.0.3:5060".parse().unwrap(), Some("z9hG4bK3".into()))),
+    /// ]);
+    ///
+    /// let branches: Vec<&str> = headers.via_branches().collect();
+    /// assert_eq!(branches, ["z9hG4bK1", "z9hG4bK3"]);
+    /// ```
+    pub fn via_branches(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .iter()
+            .filter_map(|h| h.as_via())
+            .filter_map(|via| via.branch.as_deref())
+    }
+
     /// Moves all the elements of `other` into `self`,
     /// leaving `other` empty.
     ///
@@ -255,9 +382,60 @@ impl Headers {
         self.0.push(hdr);
     }
 
+    /// Inserts a header before all the others.
+    ///
+    /// This is how a proxy adds its `Via`, which must be
+    /// the topmost one. Takes `O(n)` time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Headers, Header, Expires, ContentLength};
+    /// let mut headers = Headers::from([Header::ContentLength(ContentLength::new(0))]);
+    /// headers.push_front(Header::Expires(Expires::new(10)));
+    ///
+    /// assert_eq!(headers[0], Header::Expires(Expires::new(10)));
+    /// ```
+    pub fn push_front(&mut self, hdr: Header) {
+        self.0.insert(0, hdr);
+    }
+
     /// Insert a header on the front
+    #[deprecated(note = "use `Headers::push_front`")]
     pub fn prepend_header(&mut self, hdr: Header) {
-        self.0.insert(0, hdr);
+        self.push_front(hdr);
+    }
+
+    /// Inserts `hdr` before the first header matching the
+    /// predicate `f`, returning the index it was inserted at.
+    ///
+    /// If no header matches, `hdr` is pushed to the end of
+    /// the collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Headers, Header, Expires, ContentLength, MaxForwards};
+    /// let mut headers = Headers::from([
+    ///     Header::Expires(Expires::new(10)),
+    ///     Header::ContentLength(ContentLength::new(0)),
+    /// ]);
+    /// let index = headers.insert_before_first(
+    ///     |h| matches!(h, Header::ContentLength(_)),
+    ///     Header::MaxForwards(MaxForwards::new(70)),
+    /// );
+    ///
+    /// assert_eq!(index, 1);
+    /// assert_eq!(headers[1], Header::MaxForwards(MaxForwards::new(70)));
+    /// ```
+    pub fn insert_before_first<F>(&mut self, f: F, hdr: Header) -> usize
+    where
+        F: FnMut(&Header) -> bool,
+    {
+        let index = self.0.iter().position(f).unwrap_or(self.0.len());
+        self.0.insert(index, hdr);
+
+        index
     }
 
     /// Returns the number of headers in the collection.
@@ -326,6 +504,32 @@ impl IntoIterator for Headers {
     }
 }
 
+impl<'a> IntoIterator for &'a Headers {
+    type Item = &'a Header;
+
+    type IntoIter = core::slice::Iter<'a, Header>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Headers {
+    type Item = &'a mut Header;
+
+    type IntoIter = core::slice::IterMut<'a, Header>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl Extend<Header> for Headers {
+    fn extend<I: IntoIterator<Item = Header>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
 impl<'a> Index<usize> for Headers {
     type Output = Header;
 
@@ -381,6 +585,20 @@ impl fmt::Display for Headers {
     }
 }
 
+/// Displays [`Headers`] with the compact form of the header
+/// names, see [`Headers::compact`].
+pub struct CompactHeaders<'a>(&'a Headers);
+
+impl fmt::Display for CompactHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for hdr in self.0.iter() {
+            hdr.fmt_compact(f)?;
+            write!(f, "\r\n")?;
+        }
+        Ok(())
+    }
+}
+
 impl Default for Headers {
     fn default() -> Self {
         Self::new()
@@ -397,6 +615,41 @@ impl<'a> std::convert::From<Vec<Header>> for Headers {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_headers_compose_with_iterators() {
+        let source = Headers::from([
+            Header::Expires(Expires::new(10)),
+            Header::ContentLength(ContentLength::new(0)),
+            Header::MaxForwards(MaxForwards::new(70)),
+        ]);
+
+        let mut headers: Headers = source
+            .iter()
+            .filter(|h| !matches!(h, Header::ContentLength(_)))
+            .cloned()
+            .collect();
+        assert_eq!(headers.len(), 2);
+
+        fn extend_with(target: &mut impl Extend<Header>, header: Header) {
+            target.extend(std::iter::once(header));
+        }
+        extend_with(&mut headers, Header::Expires(Expires::new(20)));
+
+        for header in &mut headers {
+            if let Header::Expires(expires) = header {
+                *expires = Expires::new(expires.as_u32() + 1);
+            }
+        }
+        let expires: Vec<u32> = (&headers)
+            .into_iter()
+            .filter_map(|h| h.as_expires().map(Expires::as_u32))
+            .collect();
+        assert_eq!(expires, [11, 21]);
+
+        let owned: Vec<Header> = headers.into_iter().collect();
+        assert_eq!(owned[1], Header::MaxForwards(MaxForwards::new(70)));
+    }
+
     #[test]
     fn test_retrieves_header_by_index_correctly() {
         let mut headers = Headers::new();
@@ -477,4 +730,167 @@ mod tests {
         assert_eq!(headers.len(), 1);
         assert!(headers.capacity() >= 5);
     }
+
+    #[test]
+    fn test_removes_all_via_headers() {
+        let via = Via::new_udp("127.0.0.1:5060".parse().unwrap(), None);
+        let mut headers = Headers::from([
+            Header::Via(via.clone()),
+            Header::Expires(Expires::new(10)),
+            Header::Via(via),
+        ]);
+
+        let removed = headers.remove_all(|h| matches!(h, Header::Via(_)));
+
+        assert_eq!(removed, 2);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0], Header::Expires(Expires::new(10)));
+    }
+
+    #[test]
+    fn test_replaces_max_forwards_header() {
+        let mut headers = Headers::from([
+            Header::Expires(Expires::new(10)),
+            Header::MaxForwards(MaxForwards::new(70)),
+        ]);
+
+        let old = headers.replace_first(Header::MaxForwards(MaxForwards::new(69)));
+
+        assert_eq!(old, Some(Header::MaxForwards(MaxForwards::new(70))));
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[1], Header::MaxForwards(MaxForwards::new(69)));
+    }
+
+    #[test]
+    fn test_replace_first_pushes_when_missing() {
+        let mut headers = Headers::new();
+
+        let old = headers.replace_first(Header::MaxForwards(MaxForwards::new(70)));
+
+        assert!(old.is_none());
+        assert_eq!(headers.len(), 1);
+    }
+
+    #[test]
+    fn test_push_front_puts_via_ahead_of_existing_vias() {
+        let via1 = Via::new_udp("10.0.0.1:5060".parse().unwrap(), Some("z9hG4bK1".into()));
+        let via2 = Via::new_udp("10.0.0.2:5060".parse().unwrap(), Some("z9hG4bK2".into()));
+        let proxy = Via::new_udp("10.0.0.9:5060".parse().unwrap(), Some("z9hG4bK9".into()));
+        let mut headers = Headers::from([
+            Header::Via(via1),
+            Header::Via(via2),
+            Header::MaxForwards(MaxForwards::new(70)),
+        ]);
+
+        headers.push_front(Header::Via(proxy));
+
+        let branches: Vec<_> = headers.via_branches().collect();
+        assert_eq!(branches, ["z9hG4bK9", "z9hG4bK1", "z9hG4bK2"]);
+        assert_eq!(headers.len(), 4);
+    }
+
+    #[test]
+    fn test_insert_before_first_keeps_via_block_together() {
+        let via = Via::new_udp("10.0.0.1:5060".parse().unwrap(), Some("z9hG4bK1".into()));
+        let proxy = Via::new_udp("10.0.0.9:5060".parse().unwrap(), Some("z9hG4bK9".into()));
+        let mut headers =
+            Headers::from([Header::MaxForwards(MaxForwards::new(70)), Header::Via(via)]);
+
+        let index =
+            headers.insert_before_first(|h| matches!(h, Header::Via(_)), Header::Via(proxy));
+
+        assert_eq!(index, 1);
+        assert_eq!(
+            headers.via_branches().collect::<Vec<_>>(),
+            ["z9hG4bK9", "z9hG4bK1"]
+        );
+
+        let index = headers.insert_before_first(
+            |h| matches!(h, Header::Expires(_)),
+            Header::Expires(Expires::new(10)),
+        );
+        assert_eq!(index, 3);
+    }
+
+    #[test]
+    fn test_retains_headers_matching_predicate() {
+        let mut headers = Headers::from([
+            Header::Expires(Expires::new(10)),
+            Header::ContentLength(ContentLength::new(0)),
+        ]);
+
+        headers.retain(|h| matches!(h, Header::ContentLength(_)));
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0], Header::ContentLength(ContentLength::new(0)));
+    }
+
+    #[test]
+    fn test_informational_headers_round_trip() {
+        let texts = [
+            "Lunch",
+            "Need more boxes",
+            "trailing whitespace \t ",
+            "  leading whitespace",
+            "the \"quoted\" \\ text",
+            "Softphone Beta1.5 (Linux)",
+            "caf\u{e9} \u{2603}",
+        ];
+        let mut lines: Vec<String> = Vec::new();
+        for text in texts {
+            for name in ["Subject", "Organization", "Server", "User-Agent"] {
+                lines.push(format!("{name}: {text}"));
+            }
+            let quoted = text.replace('\\', "\\\\").replace('"', "\\\"");
+            lines.push(format!("Warning: 399 example.com \"{quoted}\""));
+        }
+        for priority in ["emergency", "Urgent", "normal", "non-urgent", "x-custom"] {
+            lines.push(format!("Priority: {priority}"));
+        }
+        lines.extend(
+            [
+                "Date: Sat, 13 Nov 2010 23:29:00 GMT",
+                "Timestamp: 54",
+                "Timestamp: 54.5 0.25",
+                "In-Reply-To: 70710@saturn.bell-tel.com, 17320@saturn.bell-tel.com",
+                "Retry-After: 18000;duration=3600",
+                "Retry-After: 120 (I'm in a meeting)",
+                "MIME-Version: 1.0",
+                "Call-Info: <http://www.example.com/alice/photo.jpg>;purpose=icon",
+                "Alert-Info: <http://www.example.com/sounds/moo.wav>",
+                "Error-Info: <sip:not-in-service-recording@atlanta.com>",
+            ]
+            .map(String::from),
+        );
+
+        for line in lines {
+            let header = Header::from_bytes(line.as_bytes()).unwrap();
+            let encoded = header.to_string();
+
+            assert_eq!(
+                Header::from_bytes(encoded.as_bytes()).unwrap(),
+                header,
+                "{line:?} encoded as {encoded:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_header_from_bytes_rejects_leftovers() {
+        assert!(Header::from_bytes(b"Subject: Lunch\r\n").is_ok());
+        assert!(Header::from_bytes(b"Via: SIP/2.0/UDP a.com, SIP/2.0/UDP b.com").is_err());
+        assert!(Header::from_bytes(b"Call-ID: a84b4c76\r\nTo: <sip:bob@b.com>").is_err());
+
+        let header = Header::from_bytes(b"Subject: Lunch\x0c \t").unwrap();
+        assert_eq!(header.to_string(), "Subject: Lunch\x0c");
+    }
+
+    #[test]
+    fn test_compact_names_of_event_and_session_expires() {
+        let event = Header::from_bytes(b"o: refer").unwrap();
+        let session_expires = Header::from_bytes(b"x: 1800").unwrap();
+
+        assert_eq!(event.compact_name(), Some("o"));
+        assert_eq!(session_expires.compact_name(), Some("x"));
+    }
 }
@@ -10,11 +10,23 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Organization(String);
 
+impl Organization {
+    /// Creates a new `Organization` header with the given value.
+    pub fn new(s: &str) -> Self {
+        Self(s.into())
+    }
+
+    /// Returns the organization name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl HeaderParser for Organization {
     const NAME: &'static str = "Organization";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let organization = parser.read_until_new_line_as_str()?;
+        let organization = parser.read_folded_text()?;
 
         Ok(Organization(organization.into()))
     }
@@ -7,22 +7,73 @@ use crate::parser::{HeaderParser, Parser};
 ///
 /// Indicates the urgency of the request as received by the
 /// client.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::message::headers::Priority;
+/// let priority = Priority::Emergency;
+///
+/// assert_eq!("Priority: emergency", priority.to_string());
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Priority(String);
+pub enum Priority {
+    /// `emergency` priority.
+    Emergency,
+    /// `urgent` priority.
+    Urgent,
+    /// `normal` priority.
+    Normal,
+    /// `non-urgent` priority.
+    NonUrgent,
+    /// Other priority value (`other-priority`).
+    Other(String),
+}
+
+impl Priority {
+    /// Returns the priority value as str.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Emergency => "emergency",
+            Self::Urgent => "urgent",
+            Self::Normal => "normal",
+            Self::NonUrgent => "non-urgent",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl From<&str> for Priority {
+    fn from(s: &str) -> Self {
+        match s {
+            s if s.eq_ignore_ascii_case("emergency") => Self::Emergency,
+            s if s.eq_ignore_ascii_case("urgent") => Self::Urgent,
+            s if s.eq_ignore_ascii_case("normal") => Self::Normal,
+            s if s.eq_ignore_ascii_case("non-urgent") => Self::NonUrgent,
+            other => Self::Other(other.into()),
+        }
+    }
+}
 
 impl HeaderParser for Priority {
     const NAME: &'static str = "Priority";
 
+    /*
+     * Priority        =  "Priority" HCOLON priority-value
+     * priority-value  =  "emergency" / "urgent" / "normal"
+     *                    / "non-urgent" / other-priority
+     * other-priority  =  token
+     */
     fn parse(parser: &mut Parser) -> Result<Self> {
         let priority = parser.parse_token()?;
 
-        Ok(Priority(priority.into()))
+        Ok(priority.into())
     }
 }
 
 impl fmt::Display for Priority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", Priority::NAME, self.0)
+        write!(f, "{}: {}", Priority::NAME, self.as_str())
     }
 }
 
@@ -36,6 +87,34 @@ mod tests {
         let mut scanner = Parser::new(src);
         let priority = Priority::parse(&mut scanner).unwrap();
 
-        assert_eq!(priority.0, "emergency");
+        assert_eq!(priority, Priority::Emergency);
+    }
+
+    #[test]
+    fn test_parse_well_known_values() {
+        let values = [
+            ("emergency", Priority::Emergency),
+            ("URGENT", Priority::Urgent),
+            ("Normal", Priority::Normal),
+            ("non-urgent", Priority::NonUrgent),
+        ];
+
+        for (src, expected) in values {
+            let priority = Priority::from_bytes(src.as_bytes()).unwrap();
+
+            assert_eq!(priority, expected);
+            assert_eq!(
+                priority.to_string(),
+                format!("Priority: {}", src.to_ascii_lowercase())
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_extension_value() {
+        let priority = Priority::from_bytes(b"my-custom\r\n").unwrap();
+
+        assert_eq!(priority, Priority::Other("my-custom".into()));
+        assert_eq!(priority.to_string(), "Priority: my-custom");
     }
 }
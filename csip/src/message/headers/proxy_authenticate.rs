@@ -11,6 +11,18 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ProxyAuthenticate(Challenge);
 
+impl ProxyAuthenticate {
+    /// Creates a new `Proxy-Authenticate` header with the given challenge.
+    pub fn new(challenge: Challenge) -> Self {
+        Self(challenge)
+    }
+
+    /// Returns the challenge of the header.
+    pub fn challenge(&self) -> &Challenge {
+        &self.0
+    }
+}
+
 impl HeaderParser for ProxyAuthenticate {
     const NAME: &'static str = "Proxy-Authenticate";
 
@@ -42,12 +54,12 @@ mod tests {
         let proxy_auth = ProxyAuthenticate::parse(&mut scanner).unwrap();
 
         assert_matches!(proxy_auth.0, Challenge::Digest( DigestChallenge { realm, domain, nonce, opaque, stale, algorithm, qop, .. }) => {
-            assert_eq!(realm, Some("\"atlanta.com\"".into()));
+            assert_eq!(realm, Some("atlanta.com".into()));
             assert_eq!(algorithm, Some("MD5".into()));
-            assert_eq!(domain, Some("\"sip:ss1.carrier.com\"".into()));
-            assert_eq!(qop, Some("\"auth\"".into()));
-            assert_eq!(nonce, Some("\"f84f1cec41e6cbe5aea9c8e88d359\"".into()));
-            assert_eq!(opaque, Some("\"\"".into()));
+            assert_eq!(domain, Some("sip:ss1.carrier.com".into()));
+            assert_eq!(qop, Some("auth".into()));
+            assert_eq!(nonce, Some("f84f1cec41e6cbe5aea9c8e88d359".into()));
+            assert_eq!(opaque, Some("".into()));
             assert_eq!(stale, Some("FALSE".into()));
         });
     }
@@ -11,6 +11,20 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ProxyAuthorization(Credential);
 
+impl ProxyAuthorization {
+    /// Creates a new `Proxy-Authorization` header with the given
+    /// credential.
+    pub fn new(credential: Credential) -> Self {
+        Self(credential)
+    }
+
+    /// Get the `Credential` from the `Proxy-Authorization`
+    /// header.
+    pub fn credential(&self) -> &Credential {
+        &self.0
+    }
+}
+
 impl HeaderParser for ProxyAuthorization {
     const NAME: &'static str = "Proxy-Authorization";
 
@@ -41,12 +55,12 @@ mod tests {
         let proxy_auth = ProxyAuthorization::parse(&mut scanner).unwrap();
 
         assert_matches!(proxy_auth.0, Credential::Digest (DigestCredential { realm, username, nonce, response, .. }) => {
-            assert_eq!(username, Some("\"Alice\"".into()));
-            assert_eq!(realm, Some("\"atlanta.com\"".into()));
-            assert_eq!(nonce, Some("\"c60f3082ee1212b402a21831ae\"".into()));
+            assert_eq!(username, Some("Alice".into()));
+            assert_eq!(realm, Some("atlanta.com".into()));
+            assert_eq!(nonce, Some("c60f3082ee1212b402a21831ae".into()));
             assert_eq!(
                 response,
-                Some("\"245f23415f11432b3434341c022\"".into())
+                Some("245f23415f11432b3434341c022".into())
             );
         });
     }
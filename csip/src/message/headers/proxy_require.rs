@@ -3,21 +3,32 @@ use std::{fmt, str};
 use itertools::Itertools;
 
 use crate::error::Result;
-use crate::macros::comma_separated_header_value;
 use crate::parser::{HeaderParser, Parser};
 
 /// The `Proxy-Require` SIP header.
 ///
 /// Indicate `proxy-sensitive` features that must be
 /// supported by the proxy.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct ProxyRequire(Vec<String>);
 
+impl ProxyRequire {
+    /// Add a new tag to the list of required tags.
+    pub fn add_tag(&mut self, tag: &str) {
+        self.0.push(tag.into());
+    }
+
+    /// Returns an iterator over the option tags.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
 impl HeaderParser for ProxyRequire {
     const NAME: &'static str = "Proxy-Require";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let tags = comma_separated_header_value!(parser => parser.parse_token()?.into());
+        let tags = parser.parse_option_tags()?;
 
         Ok(ProxyRequire(tags))
     }
@@ -0,0 +1,81 @@
+use std::{fmt, str};
+
+use crate::error::Result;
+use crate::message::Method;
+use crate::parser::{HeaderParser, Parser};
+
+/// The `RAck` SIP header.
+///
+/// Sent in a `PRACK` request to acknowledge a reliable
+/// provisional response, as defined in `RFC3262`.
+///
+/// # Examples
+/// ```
+/// # use csip::message::{headers::RAck, Method};
+/// let rack = RAck::new(776656, 1, Method::Invite);
+///
+/// assert_eq!("RAck: 776656 1 INVITE", rack.to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RAck {
+    /// The `RSeq` of the acknowledged response.
+    pub rseq: u32,
+    /// The `CSeq` number of the acknowledged response.
+    pub cseq: u32,
+    /// The `CSeq` method of the acknowledged response.
+    pub method: Method,
+}
+
+impl RAck {
+    /// Creates a new `RAck` header value.
+    pub fn new(rseq: u32, cseq: u32, method: Method) -> Self {
+        Self { rseq, cseq, method }
+    }
+}
+
+impl HeaderParser for RAck {
+    const NAME: &'static str = "RAck";
+
+    /*
+     * RAck          =  "RAck" HCOLON response-num LWS CSeq-num LWS Method
+     * response-num  =  1*DIGIT
+     * CSeq-num      =  1*DIGIT
+     */
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let rseq = parser.read_u32()?;
+        parser.skip_ws();
+        let cseq = parser.read_u32()?;
+        parser.skip_ws();
+        let method = parser.parse_method()?;
+
+        Ok(RAck { rseq, cseq, method })
+    }
+}
+
+impl fmt::Display for RAck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} {}",
+            RAck::NAME,
+            self.rseq,
+            self.cseq,
+            self.method
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = b"776656 1 INVITE\r\n";
+        let mut scanner = Parser::new(src);
+        let rack = RAck::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(rack, RAck::new(776656, 1, Method::Invite));
+    }
+}
@@ -15,6 +15,30 @@ pub struct ReplyTo {
     param: Option<Params>,
 }
 
+impl ReplyTo {
+    /// Creates a new `ReplyTo` header with the given uri.
+    pub fn new(uri: SipUri) -> Self {
+        Self { uri, param: None }
+    }
+
+    /// Creates a new `ReplyTo` header with the given uri and
+    /// params.
+    pub fn from_parts(uri: SipUri, param: Option<Params>) -> Self {
+        Self { uri, param }
+    }
+
+    /// Returns the uri of this header, either a name-addr or
+    /// a bare uri.
+    pub fn uri(&self) -> &SipUri {
+        &self.uri
+    }
+
+    /// Returns the header params, if any.
+    pub fn params(&self) -> Option<&Params> {
+        self.param.as_ref()
+    }
+}
+
 impl HeaderParser for ReplyTo {
     const NAME: &'static str = "Reply-To";
 
@@ -30,7 +54,7 @@ impl fmt::Display for ReplyTo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {}", ReplyTo::NAME, self.uri)?;
         if let Some(param) = &self.param {
-            write!(f, ";{}", param)?;
+            write!(f, "{}", param)?;
         }
 
         Ok(())
@@ -64,4 +88,22 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_parse_with_params() {
+        let src = b"Bob <sip:bob@biloxi.com>;x-type=voicemail\r\n";
+        let mut scanner = Parser::new(src);
+        let reply_to = ReplyTo::parse(&mut scanner).unwrap();
+
+        assert!(reply_to.uri().is_name_addr());
+        assert_eq!(reply_to.uri().uri().host_port.to_string(), "biloxi.com");
+        assert_eq!(
+            reply_to.params().unwrap().get_named("x-type"),
+            Some("voicemail")
+        );
+        assert_eq!(
+            reply_to.to_string(),
+            "Reply-To: Bob <sip:bob@biloxi.com>;x-type=voicemail"
+        );
+    }
 }
@@ -3,7 +3,6 @@ use std::{fmt, str};
 use itertools::Itertools;
 
 use crate::error::Result;
-use crate::macros::comma_separated_header_value;
 use crate::parser::{HeaderParser, Parser};
 
 /// The `Require` SIP header.
@@ -11,14 +10,31 @@ use crate::parser::{HeaderParser, Parser};
 /// Is used by `UACs` to tell `UASs` about options that the
 /// `UAC` expects the `UAS` to support in order to process
 /// the request.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Require(Vec<String>);
 
+impl Require {
+    /// Add a new tag to the list of required tags.
+    pub fn add_tag(&mut self, tag: &str) {
+        self.0.push(tag.into());
+    }
+
+    /// Returns `true` if the given option `tag` is required.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|t| t == tag)
+    }
+
+    /// Returns an iterator over the option tags.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
 impl HeaderParser for Require {
     const NAME: &'static str = "Require";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let tags = comma_separated_header_value!(parser => parser.parse_token()?.into());
+        let tags = parser.parse_option_tags()?;
 
         Ok(Require(tags))
     }
@@ -43,4 +59,14 @@ mod tests {
 
         assert_eq!(require.0.get(0), Some(&"100rel".into()));
     }
+
+    #[test]
+    fn test_contains() {
+        let mut require = Require::default();
+        require.add_tag("100rel");
+
+        assert!(require.contains("100rel"));
+        assert!(!require.contains("timer"));
+        assert_eq!(require.to_string(), "Require: 100rel");
+    }
 }
@@ -1,23 +1,73 @@
+use std::time::Duration;
 use std::{fmt, str, u32};
 
-use crate::error::Result;
+use crate::error::{ParseErrorKind as ErrorKind, Result};
 use crate::macros::parse_header_param;
 use crate::message::Params;
 use crate::parser::{HeaderParser, Parser};
 
+const DURATION_PARAM: &str = "duration";
+
 /// The `Retry-After` SIP header.
 ///
 /// Indicate how long the service is expected to be
 /// unavailable to the requesting client.
 /// Or when the called party anticipates being available
 /// again.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use csip::message::headers::RetryAfter;
+/// let retry_after = RetryAfter::new(120);
+///
+/// assert_eq!(retry_after.delta(), Duration::from_secs(120));
+/// assert_eq!("Retry-After: 120", retry_after.to_string());
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct RetryAfter {
     seconds: u32,
+    duration: Option<u32>,
     param: Option<Params>,
     comment: Option<String>,
 }
 
+impl RetryAfter {
+    /// Creates a new `RetryAfter` header with the given delta seconds.
+    pub fn new(seconds: u32) -> Self {
+        Self {
+            seconds,
+            duration: None,
+            param: None,
+            comment: None,
+        }
+    }
+
+    /// Returns the time after which the request can be retried.
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs(self.seconds.into())
+    }
+
+    /// Returns the comment, if present.
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Returns the value of the `duration` parameter, if present.
+    ///
+    /// It indicates how long the called party will be reachable starting at
+    /// the initial time of availability.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration.map(|d| Duration::from_secs(d.into()))
+    }
+
+    /// Returns the other parameters, if any.
+    pub fn params(&self) -> Option<&Params> {
+        self.param.as_ref()
+    }
+}
+
 impl HeaderParser for RetryAfter {
     const NAME: &'static str = "Retry-After";
 
@@ -32,10 +82,16 @@ impl HeaderParser for RetryAfter {
             parser.next_byte()?;
             comment = Some(str::from_utf8(b)?);
         }
-        let param = parse_header_param!(parser);
+        let mut duration: Option<&str> = None;
+        let param = parse_header_param!(parser, DURATION_PARAM = duration);
+        let duration = duration
+            .map(|d| d.parse())
+            .transpose()
+            .or_else(|_| parser.parse_error(ErrorKind::Param))?;
 
         Ok(RetryAfter {
             seconds: digits,
+            duration,
             param,
             comment: comment.map(|c| c.into()),
         })
@@ -44,13 +100,16 @@ impl HeaderParser for RetryAfter {
 
 impl fmt::Display for RetryAfter {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.seconds)?;
+        write!(f, "{}: {}", RetryAfter::NAME, self.seconds)?;
 
-        if let Some(param) = &self.param {
-            write!(f, ";{}", param)?;
-        }
         if let Some(comment) = &self.comment {
-            write!(f, "{}", comment)?;
+            write!(f, " ({})", comment)?;
+        }
+        if let Some(duration) = &self.duration {
+            write!(f, ";{}={}", DURATION_PARAM, duration)?;
+        }
+        if let Some(param) = &self.param {
+            write!(f, "{}", param)?;
         }
 
         Ok(())
@@ -71,10 +130,7 @@ mod tests {
 
         assert_eq!(scanner.remaining(), b"\r\n");
         assert_eq!(retry_after.seconds, 18000);
-        assert_eq!(
-            retry_after.param.unwrap().get_named("duration"),
-            Some("3600")
-        );
+        assert_eq!(retry_after.duration(), Some(Duration::from_secs(3600)));
 
         let src = b"120 (I'm in a meeting)\r\n";
         let mut scanner = Parser::new(src);
@@ -85,4 +141,21 @@ mod tests {
         assert_eq!(retry_after.seconds, 120);
         assert_eq!(retry_after.comment, Some("I'm in a meeting".into()));
     }
+
+    #[test]
+    fn test_parse_with_comment_and_duration() {
+        let src = b"120 (I'm in a meeting);duration=3600\r\n";
+        let mut scanner = Parser::new(src);
+        let retry_after = RetryAfter::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(retry_after.delta(), Duration::from_secs(120));
+        assert_eq!(retry_after.comment(), Some("I'm in a meeting"));
+        assert_eq!(retry_after.duration(), Some(Duration::from_secs(3600)));
+        assert!(retry_after.params().is_none());
+        assert_eq!(
+            retry_after.to_string(),
+            "Retry-After: 120 (I'm in a meeting);duration=3600"
+        );
+    }
 }
@@ -0,0 +1,69 @@
+use std::{fmt, str};
+
+use crate::error::Result;
+use crate::parser::{HeaderParser, Parser};
+
+/// The `RSeq` SIP header.
+///
+/// Sequence number of a reliable provisional response, as
+/// defined in `RFC3262`.
+///
+/// # Examples
+/// ```
+/// # use csip::message::headers::RSeq;
+/// let rseq = RSeq::new(988789);
+///
+/// assert_eq!("RSeq: 988789", rseq.to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(transparent)]
+pub struct RSeq(u32);
+
+impl RSeq {
+    /// Creates a new `RSeq` header value.
+    #[inline]
+    pub const fn new(value: u32) -> Self {
+        Self(value)
+    }
+
+    /// Returns the `RSeq` value as a `u32`.
+    #[inline]
+    pub const fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl HeaderParser for RSeq {
+    const NAME: &'static str = "RSeq";
+
+    /*
+     * RSeq          =  "RSeq" HCOLON response-num
+     * response-num  =  1*DIGIT
+     */
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let rseq = parser.read_u32()?;
+
+        Ok(RSeq(rseq))
+    }
+}
+
+impl fmt::Display for RSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", RSeq::NAME, self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = b"988789\r\n";
+        let mut scanner = Parser::new(src);
+        let rseq = RSeq::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(rseq.as_u32(), 988789);
+    }
+}
@@ -1,6 +1,6 @@
 use std::{fmt, str};
 
-use crate::error::Result;
+use crate::error::{ParseErrorKind, Result};
 use crate::parser::{HeaderParser, Parser};
 
 /// The `Server` SIP header.
@@ -16,13 +16,39 @@ impl Server {
     pub fn new(s: &str) -> Self {
         Self(s.into())
     }
+
+    /// Returns the header value.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Parses the header value into its products and comments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::{Server, ServerVal};
+    /// let server = Server::new("HomeServer/2.1 (Linux)");
+    /// let vals = server.products().unwrap();
+    ///
+    /// assert_eq!(
+    ///     vals,
+    ///     [
+    ///         ServerVal::Product { name: "HomeServer", version: Some("2.1") },
+    ///         ServerVal::Comment("Linux"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn products(&self) -> Result<Vec<ServerVal<'_>>> {
+        parse_server_vals(&self.0)
+    }
 }
 
 impl HeaderParser for Server {
     const NAME: &'static str = "Server";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let server = parser.read_until_new_line_as_str()?;
+        let server = parser.read_folded_text()?;
 
         Ok(Server(server.into()))
     }
@@ -34,6 +60,67 @@ impl fmt::Display for Server {
     }
 }
 
+/// A `server-val` of the [`Server`] and
+/// [`UserAgent`](super::UserAgent) headers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ServerVal<'a> {
+    /// A product token with an optional version.
+    Product {
+        /// The product name.
+        name: &'a str,
+        /// The product version, if present.
+        version: Option<&'a str>,
+    },
+    /// A comment, without the enclosing parentheses.
+    Comment(&'a str),
+}
+
+/*
+ * server-val       =  product / comment
+ * product          =  token [SLASH product-version]
+ * product-version  =  token
+ */
+pub(crate) fn parse_server_vals(value: &str) -> Result<Vec<ServerVal<'_>>> {
+    let mut parser = Parser::new(value);
+    let mut vals = Vec::new();
+
+    loop {
+        parser.skip_ws();
+        match parser.peek_byte() {
+            None => break,
+            Some(b'(') => {
+                let start = parser.offset();
+                let mut depth = 0;
+                loop {
+                    match parser.next_byte()? {
+                        b'(' => depth += 1,
+                        b')' if depth == 1 => break,
+                        b')' => depth -= 1,
+                        _ => (),
+                    }
+                }
+                vals.push(ServerVal::Comment(&value[start + 1..parser.offset() - 1]));
+            }
+            Some(_) => {
+                let name = parser.read_token_str();
+                if name.is_empty() {
+                    return parser.parse_error(ParseErrorKind::Header);
+                }
+                let version = match parser.peek_byte() {
+                    Some(b'/') => {
+                        parser.next_byte()?;
+                        Some(parser.read_token_str())
+                    }
+                    _ => None,
+                };
+                vals.push(ServerVal::Product { name, version });
+            }
+        }
+    }
+
+    Ok(vals)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -48,4 +135,26 @@ mod tests {
         assert_eq!(scanner.remaining(), b"\r\n");
         assert_eq!(server.0, "HomeServer v2");
     }
+
+    #[test]
+    fn test_products() {
+        let server = Server::new("HomeServer/2.1 (Linux (x86_64)) Proxy");
+        let vals = server.products().unwrap();
+
+        assert_eq!(
+            vals,
+            [
+                ServerVal::Product {
+                    name: "HomeServer",
+                    version: Some("2.1")
+                },
+                ServerVal::Comment("Linux (x86_64)"),
+                ServerVal::Product {
+                    name: "Proxy",
+                    version: None
+                },
+            ]
+        );
+        assert!(Server::new("HomeServer (Linux").products().is_err());
+    }
 }
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::macros::parse_header_param;
+use crate::message::Params;
+use crate::parser::{HeaderParser, Parser};
+
+/// The refresher parameter of the `Session-Expires` header.
+const REFRESHER_PARAM: &str = "refresher";
+
+/// The `Session-Expires` SIP header.
+///
+/// Conveys the session interval of a session timer and which
+/// side is responsible for refreshing the session, as defined
+/// in `RFC4028`.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::message::headers::{Refresher, SessionExpires};
+/// let session_expires = SessionExpires::new(1800).with_refresher(Refresher::Uac);
+///
+/// assert_eq!("Session-Expires: 1800;refresher=uac", session_expires.to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SessionExpires {
+    delta: u32,
+    refresher: Option<Refresher>,
+    params: Option<Params>,
+}
+
+impl SessionExpires {
+    /// Creates a new `Session-Expires` header with the session
+    /// interval in seconds.
+    pub fn new(delta: u32) -> Self {
+        Self {
+            delta,
+            refresher: None,
+            params: None,
+        }
+    }
+
+    /// Sets the `refresher` parameter.
+    pub fn with_refresher(mut self, refresher: Refresher) -> Self {
+        self.refresher = Some(refresher);
+        self
+    }
+
+    /// Returns the session interval in seconds.
+    pub fn delta(&self) -> u32 {
+        self.delta
+    }
+
+    /// Returns the session interval.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.delta.into())
+    }
+
+    /// Returns the `refresher` parameter.
+    pub fn refresher(&self) -> Option<Refresher> {
+        self.refresher
+    }
+
+    /// Returns the other parameters of the header.
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+}
+
+impl HeaderParser for SessionExpires {
+    const NAME: &'static str = "Session-Expires";
+    const SHORT_NAME: &'static str = "x";
+
+    /*
+     * Session-Expires  =  ("Session-Expires" / "x") HCOLON delta-seconds
+     *                     *(SEMI se-params)
+     * se-params        = refresher-param / generic-param
+     * refresher-param  = "refresher" EQUAL  ("uas" / "uac")
+     */
+    fn parse(parser: &mut Parser) -> Result<Self> {
+        let delta = parser.read_u32()?;
+        let mut refresher: Option<String> = None;
+        let params = parse_header_param!(parser, REFRESHER_PARAM = refresher);
+
+        Ok(SessionExpires {
+            delta,
+            refresher: refresher.as_deref().and_then(Refresher::from_str),
+            params,
+        })
+    }
+}
+
+impl SessionExpires {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.delta)?;
+
+        if let Some(refresher) = &self.refresher {
+            write!(f, ";{}={}", REFRESHER_PARAM, refresher)?;
+        }
+        if let Some(params) = &self.params {
+            write!(f, "{}", params)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SessionExpires {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", SessionExpires::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
+/// The side of a session responsible for refreshing it.
+///
+/// The value is relative to the transaction that negotiated the
+/// session timer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Refresher {
+    /// The client of the transaction refreshes the session.
+    Uac,
+    /// The server of the transaction refreshes the session.
+    Uas,
+}
+
+impl Refresher {
+    /// Returns the refresher as str.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Uac => "uac",
+            Self::Uas => "uas",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            s if s.eq_ignore_ascii_case("uac") => Some(Self::Uac),
+            s if s.eq_ignore_ascii_case("uas") => Some(Self::Uas),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Refresher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = b"4000;refresher=UAS;foo=bar\r\n";
+        let mut scanner = Parser::new(src);
+        let session_expires = SessionExpires::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(session_expires.delta(), 4000);
+        assert_eq!(session_expires.refresher(), Some(Refresher::Uas));
+        assert_eq!(
+            session_expires.params().and_then(|p| p.get_named("foo")),
+            Some("bar")
+        );
+    }
+
+    #[test]
+    fn test_parse_without_refresher() {
+        let src = b"1800\r\n";
+        let mut scanner = Parser::new(src);
+        let session_expires = SessionExpires::parse(&mut scanner).unwrap();
+
+        assert_eq!(session_expires.interval(), Duration::from_secs(1800));
+        assert_eq!(session_expires.refresher(), None);
+        assert_eq!(session_expires.to_string(), "Session-Expires: 1800");
+    }
+}
@@ -9,20 +9,40 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Subject(String);
 
+impl Subject {
+    /// Creates a new `Subject` header with the given value.
+    pub fn new(s: &str) -> Self {
+        Self(s.into())
+    }
+
+    /// Returns the subject text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl HeaderParser for Subject {
     const NAME: &'static str = "Subject";
     const SHORT_NAME: &'static str = "s";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let subject = parser.read_until_new_line_as_str()?;
+        let subject = parser.read_folded_text()?;
 
         Ok(Subject(subject.into()))
     }
 }
 
+impl Subject {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl fmt::Display for Subject {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", Subject::NAME, self.0)
+        write!(f, "{}: ", Subject::NAME)?;
+        self.fmt_value(f)
     }
 }
 
@@ -48,4 +68,32 @@ mod tests {
         assert_eq!(scanner.remaining(), b"\r\n");
         assert_eq!(subject.0, "Tech Support");
     }
+
+    #[test]
+    fn test_parse_folded_utf8() {
+        let src = "Reunião de\r\n  equipe às 10h\r\n".as_bytes();
+        let mut scanner = Parser::new(src);
+        let subject = Subject::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(subject.as_str(), "Reunião de equipe às 10h");
+    }
+
+    #[test]
+    fn test_parse_trims_trailing_whitespace() {
+        let src = b"Lunch \t\r\n";
+        let mut scanner = Parser::new(src);
+        let subject = Subject::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(subject.as_str(), "Lunch");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_utf8() {
+        let src = b"Need more \xff boxes\r\n";
+        let mut scanner = Parser::new(src);
+
+        assert!(Subject::parse(&mut scanner).is_err());
+    }
 }
@@ -3,7 +3,6 @@ use std::{fmt, str};
 use itertools::Itertools;
 
 use crate::error::Result;
-use crate::macros::comma_separated_header_value;
 use crate::parser::{HeaderParser, Parser};
 
 /// The `Supported` SIP header.
@@ -18,6 +17,16 @@ impl Supported {
     pub fn add_tag(&mut self, tag: &str) {
         self.0.push(tag.into());
     }
+
+    /// Returns `true` if the given option `tag` is supported.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|t| t == tag)
+    }
+
+    /// Returns an iterator over the option tags.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
 }
 
 impl HeaderParser for Supported {
@@ -25,15 +34,23 @@ impl HeaderParser for Supported {
     const SHORT_NAME: &'static str = "k";
 
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let tags = comma_separated_header_value!(parser => parser.parse_token()?.into());
+        let tags = parser.parse_option_tags()?;
 
         Ok(Supported(tags))
     }
 }
 
+impl Supported {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().format(", "))
+    }
+}
+
 impl fmt::Display for Supported {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", Supported::NAME, self.0.iter().format(", "))
+        write!(f, "{}: ", Supported::NAME)?;
+        self.fmt_value(f)
     }
 }
 
@@ -52,4 +69,34 @@ mod tests {
         assert_eq!(supported.0.get(0), Some(&"100rel".into()));
         assert_eq!(supported.0.get(1), Some(&"other".into()));
     }
+
+    #[test]
+    fn test_parse_option_tag_list() {
+        let src = b"100rel , timer,path\r\n";
+        let mut scanner = Parser::new(src);
+        let supported = Supported::parse(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(
+            supported.iter().collect::<Vec<_>>(),
+            ["100rel", "timer", "path"]
+        );
+        assert!(supported.contains("timer"));
+        assert_eq!(supported.to_string(), "Supported: 100rel, timer, path");
+    }
+
+    #[test]
+    fn test_parse_empty_list() {
+        let supported = Supported::from_bytes(b"\r\n").unwrap();
+
+        assert_eq!(supported.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_contains() {
+        let supported = Supported::from_bytes(b"timer, 100rel\r\n").unwrap();
+
+        assert!(supported.contains("100rel"));
+        assert!(!supported.contains("path"));
+    }
 }
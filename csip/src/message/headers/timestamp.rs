@@ -1,3 +1,4 @@
+use std::time::SystemTime;
 use std::{fmt, str};
 
 use crate::error::Result;
@@ -6,12 +7,54 @@ use crate::parser::{HeaderParser, Parser};
 /// The `Timestamp` SIP header.
 ///
 /// Describes when the `UAC` sent the request to the `UAS`.
+///
+/// # Examples
+///
+/// ```
+/// # use csip::message::headers::Timestamp;
+/// let timestamp = Timestamp::new(54.21, Some(1.34));
+///
+/// assert_eq!("Timestamp: 54.21 1.34", timestamp.to_string());
+/// ```
 #[derive(Debug, PartialEq, Clone)]
 pub struct Timestamp {
     time: f32,
     delay: Option<f32>,
 }
 
+impl Timestamp {
+    /// Creates a new `Timestamp` header with the given `time` and optional
+    /// `delay`.
+    pub const fn new(time: f32, delay: Option<f32>) -> Self {
+        Self { time, delay }
+    }
+
+    /// Returns the timestamp value.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Returns the delay value, if present.
+    pub fn delay(&self) -> Option<f32> {
+        self.delay
+    }
+
+    /// Creates the `Timestamp` to be sent in a response to a request
+    /// received at `received`.
+    ///
+    /// As described in RFC 3261 section 8.2.6.1, the timestamp value is
+    /// copied from the request and the delay is the time elapsed since
+    /// the request was received.
+    pub fn response_timestamp(&self, received: SystemTime) -> Self {
+        let elapsed = received.elapsed().unwrap_or_default();
+
+        Self {
+            time: self.time,
+            delay: Some(elapsed.as_secs_f32()),
+        }
+    }
+}
+
 impl HeaderParser for Timestamp {
     const NAME: &'static str = "Timestamp";
 
@@ -34,7 +77,7 @@ impl fmt::Display for Timestamp {
         write!(f, "{}: {}", Timestamp::NAME, self.time)?;
 
         if let Some(delay) = &self.delay {
-            write!(f, "{}", delay)?;
+            write!(f, " {}", delay)?;
         }
 
         Ok(())
@@ -43,6 +86,8 @@ impl fmt::Display for Timestamp {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     #[test]
@@ -54,4 +99,26 @@ mod tests {
 
         assert_eq!(timestamp.time, 54.0);
     }
+
+    #[test]
+    fn test_parse_with_delay() {
+        let src = b"54.21 1.34\r\n";
+        let mut scanner = Parser::new(src);
+        let timestamp = Timestamp::parse(&mut scanner).unwrap();
+
+        assert_eq!(timestamp.time(), 54.21);
+        assert_eq!(timestamp.delay(), Some(1.34));
+        assert_eq!(timestamp.to_string(), "Timestamp: 54.21 1.34");
+    }
+
+    #[test]
+    fn test_response_timestamp_computes_delay() {
+        let timestamp = Timestamp::new(54.21, None);
+        let received = SystemTime::now() - Duration::from_millis(1500);
+
+        let response = timestamp.response_timestamp(received);
+
+        assert_eq!(response.time(), 54.21);
+        assert!(response.delay().is_some_and(|delay| delay >= 1.5));
+    }
 }
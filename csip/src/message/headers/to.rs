@@ -5,7 +5,7 @@ use std::str::{
 
 use crate::error::Result;
 use crate::macros::parse_header_param;
-use crate::message::headers::TAG_PARAM;
+use crate::message::headers::{From as FromHeader, TAG_PARAM};
 use crate::message::{Params, SipUri, Uri};
 use crate::parser::{HeaderParser, Parser};
 
@@ -35,9 +35,9 @@ use crate::parser::{HeaderParser, Parser};
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct To {
-    uri: SipUri,
-    tag: Option<String>,
-    params: Option<Params>,
+    pub(crate) uri: SipUri,
+    pub(crate) tag: Option<String>,
+    pub(crate) params: Option<Params>,
 }
 
 impl FromStr for To {
@@ -85,6 +85,18 @@ impl To {
     }
 }
 
+impl From<FromHeader> for To {
+    /// Converts a `From` header into a `To` header with the
+    /// same URI, tag and parameters.
+    fn from(from: FromHeader) -> Self {
+        Self {
+            uri: from.uri,
+            tag: from.tag,
+            params: from.params,
+        }
+    }
+}
+
 impl HeaderParser for To {
     const NAME: &'static str = "To";
     const SHORT_NAME: &'static str = "t";
@@ -98,9 +110,10 @@ impl HeaderParser for To {
     }
 }
 
-impl fmt::Display for To {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", To::NAME, self.uri)?;
+impl To {
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.uri)?;
         if let Some(tag) = &self.tag {
             write!(f, ";tag={}", tag)?;
         }
@@ -112,6 +125,13 @@ impl fmt::Display for To {
     }
 }
 
+impl fmt::Display for To {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", To::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // ToHeader inputs
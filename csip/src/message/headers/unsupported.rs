@@ -3,15 +3,37 @@ use std::{fmt, str};
 use itertools::Itertools;
 
 use crate::error::Result;
-use crate::macros::comma_separated_header_value;
 use crate::parser::{HeaderParser, Parser};
 
 /// The `Unsupported` SIP header.
 ///
 /// Lists the features not supported by the `UAS`.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Unsupported(Vec<String>);
 
+impl Unsupported {
+    /// Add a new tag to the list of unsupported tags.
+    pub fn add_tag(&mut self, tag: &str) {
+        self.0.push(tag.into());
+    }
+
+    /// Returns `true` if the given option `tag` is listed.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|t| t == tag)
+    }
+
+    /// Returns an iterator over the option tags.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl From<Vec<String>> for Unsupported {
+    fn from(tags: Vec<String>) -> Self {
+        Self(tags)
+    }
+}
+
 impl HeaderParser for Unsupported {
     const NAME: &'static str = "Unsupported";
 
@@ -20,7 +42,7 @@ impl HeaderParser for Unsupported {
      * *(COMMA option-tag)
      */
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let tags = comma_separated_header_value!(parser => parser.parse_token()?.into());
+        let tags = parser.parse_option_tags()?;
 
         Ok(Unsupported(tags))
     }
@@ -31,3 +53,17 @@ impl fmt::Display for Unsupported {
         write!(f, "{}: {}", Unsupported::NAME, self.0.iter().format(", "))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let unsupported = Unsupported::from_bytes(b"100rel, timer\r\n").unwrap();
+
+        assert!(unsupported.contains("timer"));
+        assert!(!unsupported.contains("path"));
+        assert_eq!(unsupported.to_string(), "Unsupported: 100rel, timer");
+    }
+}
@@ -1,5 +1,6 @@
 use std::{fmt, str};
 
+use super::server::{ServerVal, parse_server_vals};
 use crate::error::Result;
 use crate::parser::{HeaderParser, Parser};
 
@@ -10,6 +11,23 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct UserAgent(String);
 
+impl UserAgent {
+    /// Creates a new `UserAgent` header with the given value.
+    pub fn new(s: &str) -> Self {
+        Self(s.into())
+    }
+
+    /// Returns the header value.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Parses the header value into its products and comments.
+    pub fn products(&self) -> Result<Vec<ServerVal<'_>>> {
+        parse_server_vals(&self.0)
+    }
+}
+
 impl HeaderParser for UserAgent {
     const NAME: &'static str = "User-Agent";
 
@@ -18,7 +36,7 @@ impl HeaderParser for UserAgent {
      * server-val)
      */
     fn parse(parser: &mut Parser) -> Result<Self> {
-        let agent = parser.read_until_new_line_as_str()?;
+        let agent = parser.read_folded_text()?;
 
         Ok(UserAgent(agent.into()))
     }
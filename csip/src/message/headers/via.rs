@@ -3,7 +3,7 @@ use std::net::IpAddr;
 use std::str::{self, FromStr};
 
 use crate::error::{ParseErrorKind as ErrorKind, Result};
-use crate::macros::parse_param;
+use crate::macros::{comma_separated_header_value, parse_param};
 use crate::message::{DomainName, Host, HostPort, Params};
 use crate::parser::{
     HeaderParser, Parser, SIPV2, {self},
@@ -16,6 +16,9 @@ const TTL_PARAM: &str = "ttl";
 const RPORT_PARAM: &str = "rport";
 const RECEIVED_PARAM: &str = "received";
 
+/// The `ttl` used for multicast when the `Via` has none.
+pub const DEFAULT_VIA_TTL: u8 = 1;
+
 /// The `Via` SIP header.
 ///
 /// Indicates the path taken by the request so far and the
@@ -96,18 +99,118 @@ impl Via {
             params: None,
         }
     }
-}
 
-impl fmt::Display for Via {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}: {}/{} {}",
-            Via::NAME,
-            SIPV2,
-            self.transport,
-            self.sent_by
-        )?;
+    /// Returns the `ttl` parameter, or [`DEFAULT_VIA_TTL`]
+    /// if it is absent.
+    ///
+    /// This is the IP TTL for responses sent to the
+    /// multicast `maddr` (RFC 3261 section 18.2.2).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use csip::message::headers::Via;
+    /// let via = Via::from_str("SIP/2.0/UDP a.com;ttl=16;maddr=224.0.1.75").unwrap();
+    ///
+    /// assert_eq!(via.ttl(), 16);
+    /// ```
+    pub fn ttl(&self) -> u8 {
+        self.ttl.unwrap_or(DEFAULT_VIA_TTL)
+    }
+
+    /// Returns the transport and `sent-by` of this `Via`, with
+    /// the default port of the transport filled in when the
+    /// `sent-by` has none.
+    ///
+    /// This is where responses are sent when the `Via` has no
+    /// `received` or `maddr` parameter (RFC 3261 section
+    /// 18.2.2).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use csip::message::headers::Via;
+    /// # use csip::transport::TransportType;
+    /// let via = Via::from_str("SIP/2.0/TLS a.com;branch=z9hG4bK1").unwrap();
+    /// let (transport, sent_by) = via.sent_by_target();
+    ///
+    /// assert_eq!(transport, TransportType::Tls);
+    /// assert_eq!(sent_by.port, Some(5061));
+    /// ```
+    pub fn sent_by_target(&self) -> (TransportType, HostPort) {
+        let sent_by = HostPort {
+            host: self.sent_by.host.clone(),
+            port: Some(
+                self.sent_by
+                    .port
+                    .unwrap_or_else(|| self.transport.default_port()),
+            ),
+        };
+
+        (self.transport, sent_by)
+    }
+
+    /// Returns `true` if the request carrying this `Via` was
+    /// received from an address other than its `sent-by`, which
+    /// is the case for a client behind a NAT.
+    ///
+    /// This is the case when the `received` parameter differs
+    /// from the `sent-by` host, or the `rport` parameter from
+    /// the `sent-by` port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use csip::message::headers::Via;
+    /// let via = Via::from_str("SIP/2.0/UDP 192.168.1.2;received=203.0.113.7").unwrap();
+    ///
+    /// assert!(via.is_behind_nat());
+    /// ```
+    pub fn is_behind_nat(&self) -> bool {
+        let (_, sent_by) = self.sent_by_target();
+        let received = self
+            .received
+            .is_some_and(|ip| sent_by.host != Host::IpAddr(ip));
+        let rport = self.rport.is_some_and(|port| sent_by.port != Some(port));
+
+        received || rport
+    }
+
+    /// Parses one or more comma-separated `Via` values, as
+    /// found when several hops are folded onto a single
+    /// header line.
+    pub fn parse_list(parser: &mut Parser) -> Result<Vec<Self>> {
+        let vias = comma_separated_header_value!(parser => {
+            let via = Self::parse(parser)?;
+            parser.skip_ws();
+            via
+        });
+
+        Ok(vias)
+    }
+
+    /// Parses one or more comma-separated `Via` values from
+    /// a raw byte slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::headers::Via;
+    /// let vias = Via::list_from_bytes(b"SIP/2.0/UDP a.com;branch=x, SIP/2.0/TCP b.com;branch=y")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vias.len(), 2);
+    /// ```
+    pub fn list_from_bytes(src: &[u8]) -> Result<Vec<Self>> {
+        Self::parse_list(&mut Parser::new(src))
+    }
+
+    // Writes the header value, without the header name.
+    pub(crate) fn fmt_value(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} {}", SIPV2, self.transport, self.sent_by)?;
 
         if let Some(rport) = self.rport {
             write!(f, ";rport={}", rport)?;
@@ -135,6 +238,13 @@ impl fmt::Display for Via {
     }
 }
 
+impl fmt::Display for Via {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", Via::NAME)?;
+        self.fmt_value(f)
+    }
+}
+
 impl HeaderParser for Via {
     const NAME: &'static str = "Via";
     const SHORT_NAME: &'static str = "v";
@@ -166,9 +276,9 @@ impl HeaderParser for Via {
         parser.next_byte()?;
 
         let transport = parser.read_token_str();
-        let transport = transport
-            .parse()
-            .or_else(|_| parser.parse_error(ErrorKind::Transport))?;
+        let Some(transport) = TransportType::from_via_token(transport) else {
+            return parser.parse_error(ErrorKind::Transport);
+        };
 
         parser.skip_ws();
 
@@ -193,7 +303,9 @@ impl HeaderParser for Via {
             Ok(addr) => Host::IpAddr(addr),
             Err(_) => Host::DomainName(DomainName::new(a.to_string())),
         });
-        let ttl = ttl.map(|ttl: &str| ttl.parse().unwrap());
+        let Ok(ttl) = ttl.map(|ttl: &str| ttl.parse()).transpose() else {
+            return parser.parse_error(ErrorKind::Header);
+        };
         let branch = branch.map(|b: &str| b.into());
 
         let rport = if let Some(rport) = rport_p
@@ -284,4 +396,111 @@ mod tests {
         assert_eq!(via.received, Some("192.0.2.207".parse().unwrap()));
         assert_eq!(via.branch, Some("z9hG4bK77asjd".into()));
     }
+
+    #[test]
+    fn test_parse_transport() {
+        for (src, transport) in [
+            ("SIP/2.0/WSS a.com\r\n", TransportType::Wss),
+            ("SIP/2.0/ws a.com\r\n", TransportType::Ws),
+            ("SIP/2.0/Sctp a.com\r\n", TransportType::Sctp),
+            ("SIP/2.0/tls a.com\r\n", TransportType::Tls),
+        ] {
+            let via = Via::from_bytes(src.as_bytes()).unwrap();
+
+            assert_eq!(via.transport, transport);
+        }
+    }
+
+    #[test]
+    fn test_unknown_transport_keeps_token() {
+        let via = Via::from_bytes(b"SIP/2.0/QUIC a.com;branch=z9hG4bK1\r\n").unwrap();
+
+        assert!(matches!(via.transport, TransportType::Unknown(_)));
+        assert_eq!(via.to_string(), "Via: SIP/2.0/QUIC a.com;branch=z9hG4bK1");
+    }
+
+    #[test]
+    fn test_invalid_sent_by_is_an_error() {
+        for src in [
+            "SIP/2.0/UDP a.com:99999;branch=z9hG4bK1\r\n",
+            "SIP/2.0/UDP a.com:0;branch=z9hG4bK1\r\n",
+            "SIP/2.0/UDP ;branch=z9hG4bK1\r\n",
+        ] {
+            assert!(Via::from_bytes(src.as_bytes()).is_err(), "{src}");
+        }
+    }
+
+    #[test]
+    fn test_sent_by_target_keeps_explicit_port() {
+        let via = Via::from_bytes(b"SIP/2.0/TCP 10.0.0.1:5080;branch=z9hG4bK1\r\n").unwrap();
+        let (transport, sent_by) = via.sent_by_target();
+
+        assert_eq!(transport, TransportType::Tcp);
+        assert_eq!(sent_by.to_string(), "10.0.0.1:5080");
+    }
+
+    #[test]
+    fn test_is_behind_nat() {
+        for (src, expected) in [
+            ("SIP/2.0/UDP 192.168.1.2;received=203.0.113.7\r\n", true),
+            ("SIP/2.0/UDP 192.168.1.2;rport=40312\r\n", true),
+            (
+                "SIP/2.0/UDP 203.0.113.7;received=203.0.113.7;rport=5060\r\n",
+                false,
+            ),
+            ("SIP/2.0/UDP 192.168.1.2\r\n", false),
+        ] {
+            let via = Via::from_bytes(src.as_bytes()).unwrap();
+            assert_eq!(via.is_behind_nat(), expected, "{src}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_ttl_is_an_error() {
+        for src in [
+            "SIP/2.0/UDP a.com;ttl=\r\n",
+            "SIP/2.0/UDP a.com;ttl=256\r\n",
+        ] {
+            assert!(Via::from_bytes(src.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_comma_separated_list() {
+        let src = b"SIP/2.0/UDP a.com;branch=x, SIP/2.0/TCP b.com;branch=y\r\n";
+        let mut scanner = Parser::new(src);
+        let vias = Via::parse_list(&mut scanner).unwrap();
+
+        assert_eq!(scanner.remaining(), b"\r\n");
+        assert_eq!(vias.len(), 2);
+        assert_eq!(vias[0].transport, TransportType::Udp);
+        assert_eq!(
+            vias[0].sent_by.host,
+            Host::DomainName(DomainName::new("a.com"))
+        );
+        assert_eq!(vias[0].branch.as_deref(), Some("x"));
+        assert_eq!(vias[1].transport, TransportType::Tcp);
+        assert_eq!(
+            vias[1].sent_by.host,
+            Host::DomainName(DomainName::new("b.com"))
+        );
+        assert_eq!(vias[1].branch.as_deref(), Some("y"));
+    }
+
+    #[test]
+    fn test_ttl_defaults_to_one() {
+        let src = b"SIP/2.0/UDP 10.0.0.1;ttl=16;maddr=224.0.1.75\r\n";
+        let via = Via::parse(&mut Parser::new(src)).unwrap();
+
+        assert_eq!(via.ttl(), 16);
+        assert_eq!(
+            via.maddr,
+            Some(Host::IpAddr(IpAddr::V4(Ipv4Addr::new(224, 0, 1, 75))))
+        );
+
+        let src = b"SIP/2.0/UDP 10.0.0.1;maddr=224.0.1.75\r\n";
+        let via = Via::parse(&mut Parser::new(src)).unwrap();
+
+        assert_eq!(via.ttl(), DEFAULT_VIA_TTL);
+    }
 }
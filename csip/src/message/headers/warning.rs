@@ -1,6 +1,7 @@
-use std::{fmt, str};
+use std::fmt;
 
 use crate::error::{ParseErrorKind as ErrorKind, Result};
+use crate::message::uri::write_quoted_string;
 use crate::parser::{HeaderParser, Parser, is_host};
 
 /// The `Warning` SIP header.
@@ -29,34 +30,27 @@ impl HeaderParser for Warning {
     fn parse(parser: &mut Parser) -> Result<Self> {
         let code = parser.read_u32()?;
         parser.skip_ws();
+        // SAFETY: `is_host` only accepts ASCII bytes.
         let host = unsafe { parser.read_while_as_str_unchecked(is_host) };
         parser.skip_ws();
         let Some(b'"') = parser.peek_byte() else {
             return parser.parse_error(ErrorKind::Header);
         };
-        parser.next_byte()?;
-        let text = parser.read_until(b'"');
-        parser.next_byte()?;
-        let text = str::from_utf8(text)?;
+        let text = parser.read_quoted_string()?;
 
         Ok(Warning {
             code,
             host: host.into(),
-            text: text.into(),
+            text: text.into_owned(),
         })
     }
 }
 
 impl fmt::Display for Warning {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}: {} {} {}",
-            Warning::NAME,
-            self.code,
-            self.host,
-            self.text
-        )
+        write!(f, "{}: {} {} ", Warning::NAME, self.code, self.host)?;
+
+        write_quoted_string(f, &self.text)
     }
 }
 
@@ -75,4 +69,12 @@ mod tests {
         assert_eq!(warn.host, "isi.edu");
         assert_eq!(warn.text, "InvSession parameter 'foo' not understood");
     }
+
+    #[test]
+    fn test_parse_escaped_text() {
+        let src = br#"399 example.com "the \"quoted\" \\ text""#;
+        let warn = Warning::from_bytes(src).unwrap();
+
+        assert_eq!(warn.text, r#"the "quoted" \ text"#);
+    }
 }
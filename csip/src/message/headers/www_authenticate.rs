@@ -12,6 +12,18 @@ use crate::parser::{HeaderParser, Parser};
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct WWWAuthenticate(Challenge);
 
+impl WWWAuthenticate {
+    /// Creates a new `WWW-Authenticate` header with the given challenge.
+    pub fn new(challenge: Challenge) -> Self {
+        Self(challenge)
+    }
+
+    /// Returns the challenge of the header.
+    pub fn challenge(&self) -> &Challenge {
+        &self.0
+    }
+}
+
 impl HeaderParser for WWWAuthenticate {
     const NAME: &'static str = "WWW-Authenticate";
 
@@ -53,13 +65,58 @@ mod tests {
         let www_auth = www_auth.unwrap();
 
         assert_matches!(www_auth.0, Challenge::Digest (DigestChallenge { realm, domain, nonce, opaque, stale, algorithm, qop, .. }) => {
-            assert_eq!(realm, Some("\"atlanta.com\"".into()));
+            assert_eq!(realm, Some("atlanta.com".into()));
             assert_eq!(algorithm, Some("MD5".into()));
-            assert_eq!(domain, Some("\"sip:boxesbybob.com\"".into()));
-            assert_eq!(qop, Some("\"auth\"".into()));
-            assert_eq!(nonce, Some("\"f84f1cec41e6cbe5aea9c8e88d359\"".into()));
-            assert_eq!(opaque, Some("\"\"".into()));
+            assert_eq!(domain, Some("sip:boxesbybob.com".into()));
+            assert_eq!(qop, Some("auth".into()));
+            assert_eq!(nonce, Some("f84f1cec41e6cbe5aea9c8e88d359".into()));
+            assert_eq!(opaque, Some("".into()));
             assert_eq!(stale, Some("FALSE".into()));
         });
     }
+
+    #[test]
+    fn test_challenge_round_trip() {
+        let digest = DigestChallenge::new("atlanta.com")
+            .with_domain("sip:boxesbybob.com")
+            .with_nonce("f84f1cec41e6cbe5aea9c8e88d359")
+            .with_opaque("")
+            .with_stale(false)
+            .with_algorithm("MD5")
+            .with_qop("auth,auth-int");
+        let www_auth = WWWAuthenticate::new(Challenge::Digest(digest));
+        let encoded = www_auth.to_string();
+
+        assert_eq!(
+            encoded,
+            "WWW-Authenticate: Digest realm=\"atlanta.com\", \
+             domain=\"sip:boxesbybob.com\", nonce=\"f84f1cec41e6cbe5aea9c8e88d359\", \
+             opaque=\"\", stale=false, algorithm=MD5, qop=\"auth,auth-int\""
+        );
+
+        let value = encoded.strip_prefix("WWW-Authenticate: ").unwrap();
+        let mut scanner = Parser::new(value.as_bytes());
+        let parsed = WWWAuthenticate::parse(&mut scanner).unwrap();
+
+        assert_eq!(parsed, www_auth);
+    }
+
+    #[test]
+    fn test_other_challenge_round_trip() {
+        let src = b"Bearer realm=\"atlanta.com\", scope=\"sip\"";
+        let mut scanner = Parser::new(src);
+        let www_auth = WWWAuthenticate::parse(&mut scanner).unwrap();
+        let encoded = www_auth.to_string();
+
+        assert_eq!(
+            encoded,
+            "WWW-Authenticate: Bearer realm=\"atlanta.com\", scope=\"sip\""
+        );
+
+        let value = encoded.strip_prefix("WWW-Authenticate: ").unwrap();
+        let mut scanner = Parser::new(value.as_bytes());
+        let parsed = WWWAuthenticate::parse(&mut scanner).unwrap();
+
+        assert_eq!(parsed, www_auth);
+    }
 }
@@ -1,6 +1,7 @@
-use std::fmt;
+use std::sync::Arc;
+use std::{fmt, str};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 /// An SIP Method.
 ///
 /// This enum declares SIP methods as described by RFC3261 and Others.
@@ -33,13 +34,42 @@ pub enum Method {
     Message,
     /// SIP PUBLISH Method.
     Publish,
-    /// An unknown SIP method.
-    Unknown,
+    /// An unknown (extension) SIP method.
+    Unknown(ExtensionMethod),
+}
+
+/// The name of an extension SIP method.
+///
+/// The name is shared, so cloning a [`Method`] does not
+/// copy it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ExtensionMethod(Arc<str>);
+
+impl ExtensionMethod {
+    /// Creates a new extension method name.
+    ///
+    /// Returns `None` if `name` is not valid `UTF-8`.
+    pub fn new(name: &[u8]) -> Option<Self> {
+        let name = str::from_utf8(name).ok()?;
+
+        Some(Self(name.into()))
+    }
+
+    /// Returns the method name as str.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ExtensionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
 }
 
 impl Method {
     /// Returns the byte representation of a method.
-    pub fn as_bytes(&self) -> &'static [u8] {
+    pub fn as_bytes(&self) -> &[u8] {
         self.as_str().as_bytes()
     }
 
@@ -51,10 +81,58 @@ impl Method {
         matches!(self, Self::Ack)
     }
 
+    /// Returns `true` if a request with this method can create
+    /// a dialog: `INVITE`, `SUBSCRIBE` (RFC 6665) and `REFER`
+    /// (RFC 3515).
+    pub fn is_dialog_creating(&self) -> bool {
+        matches!(self, Self::Invite | Self::Subscribe | Self::Refer)
+    }
+
+    /// Returns `true` if a request with this method creates a
+    /// subscription: `SUBSCRIBE` (RFC 6665) and `REFER`, that
+    /// implicitly subscribes to the `refer` event (RFC 3515).
+    pub fn is_subscription_creating(&self) -> bool {
+        matches!(self, Self::Subscribe | Self::Refer)
+    }
+
+    /// Returns `true` if a request with this method is a target
+    /// refresh request, that updates the remote target of the
+    /// dialog: `INVITE` and `UPDATE` (RFC 3311).
+    pub fn is_target_refresh(&self) -> bool {
+        matches!(self, Self::Invite | Self::Update)
+    }
+
+    /// Returns `true` if a request with this method creates a
+    /// transaction, that is any method but `ACK`.
+    pub fn creates_transaction(&self) -> bool {
+        !self.is_ack()
+    }
+
+    /// Converts the method name into a [`Method`], replacing
+    /// invalid `UTF-8` in extension names.
+    #[deprecated(note = "use `Method::try_from`, which rejects names that are not valid UTF-8")]
+    pub fn from(value: &[u8]) -> Self {
+        if let Ok(method) = Method::try_from(value) {
+            return method;
+        }
+        let name = String::from_utf8_lossy(value);
+        Method::Unknown(ExtensionMethod(name.into()))
+    }
+
     /// Returns the string representation of a method.
     #[inline(always)]
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
+            Method::Unknown(method) => method.as_str(),
+            known => known.as_static_str().expect("not an extension method"),
+        }
+    }
+
+    /// Returns the string representation of a known method,
+    /// or `None` for a [`Method::Unknown`], whose name is not
+    /// `'static`.
+    pub fn as_static_str(&self) -> Option<&'static str> {
+        let name = match self {
             Method::Invite => "INVITE",
             Method::Ack => "ACK",
             Method::Bye => "BYE",
@@ -69,14 +147,22 @@ impl Method {
             Method::Prack => "PRACK",
             Method::Message => "MESSAGE",
             Method::Publish => "PUBLISH",
-            Method::Unknown => "UNKNOWN-Method",
-        }
+            Method::Unknown(_) => return None,
+        };
+
+        Some(name)
     }
 }
 
-impl From<&[u8]> for Method {
-    fn from(value: &[u8]) -> Self {
-        match value {
+impl TryFrom<&[u8]> for Method {
+    type Error = crate::Error;
+
+    /// Converts the method name into a [`Method`].
+    ///
+    /// Unknown names are kept in [`Method::Unknown`], names
+    /// that are not valid `UTF-8` are an error.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(match value {
             b"INVITE" => Method::Invite,
             b"CANCEL" => Method::Cancel,
             b"ACK" => Method::Ack,
@@ -91,8 +177,10 @@ impl From<&[u8]> for Method {
             b"PRACK" => Method::Prack,
             b"MESSAGE" => Method::Message,
             b"PUBLISH" => Method::Publish,
-            _ => Method::Unknown,
-        }
+            other => {
+                Method::Unknown(ExtensionMethod::new(other).ok_or(crate::Error::InvalidMethod)?)
+            }
+        })
     }
 }
 
@@ -101,3 +189,82 @@ impl fmt::Display for Method {
         write!(f, "{}", self.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_method_keeps_name() {
+        let method = Method::try_from(&b"PUSH"[..]).unwrap();
+
+        assert!(matches!(method, Method::Unknown(_)));
+        assert_eq!(method.to_string(), "PUSH");
+        assert_eq!(method, Method::try_from(&b"PUSH"[..]).unwrap());
+        assert_ne!(method, Method::try_from(&b"PULL"[..]).unwrap());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_from_replaces_invalid_utf8() {
+        assert_eq!(Method::from(b"INVITE"), Method::Invite);
+        assert_eq!(Method::from(b"PU\xffSH").as_str(), "PU\u{fffd}SH");
+    }
+
+    #[test]
+    fn test_known_methods_have_static_names() {
+        assert_eq!(Method::Invite.as_static_str(), Some("INVITE"));
+        assert_eq!(
+            Method::try_from(&b"PUSH"[..]).unwrap().as_static_str(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_method_classification() {
+        // (method, dialog creating, target refresh, creates transaction)
+        let methods = [
+            (Method::Invite, true, true, true),
+            (Method::Ack, false, false, false),
+            (Method::Bye, false, false, true),
+            (Method::Cancel, false, false, true),
+            (Method::Register, false, false, true),
+            (Method::Options, false, false, true),
+            (Method::Info, false, false, true),
+            (Method::Notify, false, false, true),
+            (Method::Subscribe, true, false, true),
+            (Method::Update, false, true, true),
+            (Method::Refer, true, false, true),
+            (Method::Prack, false, false, true),
+            (Method::Message, false, false, true),
+            (Method::Publish, false, false, true),
+            (Method::try_from(&b"PUSH"[..]).unwrap(), false, false, true),
+        ];
+
+        for (method, dialog_creating, target_refresh, creates_transaction) in methods {
+            assert_eq!(method.is_dialog_creating(), dialog_creating, "{method}");
+            assert_eq!(method.is_target_refresh(), target_refresh, "{method}");
+            assert_eq!(
+                method.creates_transaction(),
+                creates_transaction,
+                "{method}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extension_method_has_no_length_limit() {
+        let name = [b'A'; 256];
+
+        assert_eq!(Method::try_from(&name[..]).unwrap().as_bytes(), &name[..]);
+    }
+
+    #[test]
+    fn test_invalid_utf8_method_is_an_error() {
+        assert!(ExtensionMethod::new(b"PU\xffSH").is_none());
+        assert!(matches!(
+            Method::try_from(&b"PU\xffSH"[..]),
+            Err(crate::Error::InvalidMethod)
+        ));
+    }
+}
@@ -9,26 +9,35 @@
 
 use std::borrow::Cow;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Write;
 use std::ops::Deref;
 use std::result::Result as StdResult;
 
-use bytes::Bytes;
+use bytes::{BufMut, Bytes, BytesMut};
 
 pub mod headers;
 
-use headers::{CSeq, CallId, From as FromHeader, Header, Headers, To, Via};
+use headers::{
+    CSeq, CallId, Contact, ContentEncoding, ContentLength, ContentType, Expires,
+    From as FromHeader, Header, Headers, To, Via,
+};
 
+use crate::MediaType;
 use crate::error::{Error, Result};
 use crate::parser::HeaderParser;
 
 mod auth;
 mod code;
+mod codec;
+mod encoding;
 mod method;
 mod param;
 pub(crate) mod uri;
 
 pub use auth::*;
 pub use code::*;
+pub use codec::*;
+pub use encoding::*;
 pub use method::*;
 pub use param::*;
 pub use uri::*;
@@ -37,6 +46,10 @@ pub use uri::*;
 ///
 /// This enum can contain either an [`Request`] or an [`Response`], see their
 /// respective documentation for more details.
+// Requests are at least as common as responses, boxing them would
+// add an allocation per parsed message to save stack space that is
+// only held until the message is matched on.
+#[allow(clippy::large_enum_variant)]
 pub enum SipMessage {
     /// An SIP Request.
     Request(Request),
@@ -105,6 +118,24 @@ impl SipMessage {
         }
     }
 
+    /// Appends the encoded message to `buf`, reusing its
+    /// allocation.
+    pub fn write_to(&self, buf: &mut BytesMut) -> Result<()> {
+        match self {
+            SipMessage::Request(req) => req.write_to(buf),
+            SipMessage::Response(res) => res.write_to(buf),
+        }
+    }
+
+    /// Like [`SipMessage::write_to`], but uses the compact form
+    /// of the header names.
+    pub fn write_compact_to(&self, buf: &mut BytesMut) -> Result<()> {
+        match self {
+            SipMessage::Request(req) => req.write_compact_to(buf),
+            SipMessage::Response(res) => res.write_compact_to(buf),
+        }
+    }
+
     /// If this message is an request, returns `true` otherwise returns `false`.
     pub fn is_request(&self) -> bool {
         matches!(self, SipMessage::Request(_))
@@ -175,10 +206,10 @@ impl TryFrom<&Headers> for MandatoryHeaders {
         for header in headers.iter() {
             match header {
                 Header::Via(v) if via.is_none() => via = Some(v.clone()),
-                Header::From(f) => from = Some(f.clone()),
-                Header::To(t) => to = Some(t.clone()),
-                Header::CallId(c) => call_id = Some(c.clone()),
-                Header::CSeq(c) => cseq = Some(*c),
+                Header::From(f) if from.is_none() => from = Some(f.clone()),
+                Header::To(t) if to.is_none() => to = Some(t.clone()),
+                Header::CallId(c) if call_id.is_none() => call_id = Some(c.clone()),
+                Header::CSeq(c) if cseq.is_none() => cseq = Some(c.clone()),
                 _ => (),
             }
         }
@@ -231,7 +262,109 @@ impl Request {
 
     /// Returns the SIP method of the request.
     pub fn method(&self) -> Method {
-        self.req_line.method
+        self.req_line.method.clone()
+    }
+
+    /// Returns the body with its `Content-Encoding` removed, or
+    /// `None` if the request has no body.
+    ///
+    /// The codings of all `Content-Encoding` headers are
+    /// removed, as if they were listed in a single header. See
+    /// [`decode_body`] for the supported codings.
+    pub fn decoded_body(&self) -> Result<Option<Cow<'_, [u8]>>> {
+        let Some(body) = &self.body else {
+            return Ok(None);
+        };
+        let encoding: ContentEncoding = self
+            .headers
+            .iter()
+            .filter_map(Header::as_content_encoding)
+            .flat_map(ContentEncoding::iter)
+            .collect();
+
+        decode_body(Some(&encoding), body).map(Some)
+    }
+
+    /// Returns the `Expires` header, if present.
+    pub fn expires(&self) -> Option<Expires> {
+        self.headers
+            .iter()
+            .find_map(|header| header.as_expires().copied())
+    }
+
+    /// Returns the `Contact` headers of a `REGISTER` that remove
+    /// their binding.
+    ///
+    /// A contact is removed when its `expires` param is `0`, or
+    /// when it has no `expires` param and the `Expires` header
+    /// is `0`.
+    pub fn removed_contacts(&self) -> impl Iterator<Item = &Contact> {
+        let expires = self.expires();
+
+        self.headers
+            .iter()
+            .filter_map(|header| header.as_contact())
+            .filter(move |contact| contact.is_removal(expires))
+    }
+
+    /// Returns `true` if this is a `SUBSCRIBE` with `Expires: 0`,
+    /// which terminates the subscription.
+    pub fn is_unsubscribe(&self) -> bool {
+        self.req_line.method == Method::Subscribe
+            && self.expires().is_some_and(|expires| expires.is_zero())
+    }
+
+    /// Sets the body of the request. It can be `None` to remove the body.
+    ///
+    /// The `Content-Type` header is left untouched, see
+    /// [`Request::set_body_with_type`].
+    pub fn set_body(&mut self, body: Option<impl Into<SipBody>>) {
+        self.body = body.map(|b| b.into());
+    }
+
+    /// Sets the body of the request and its `Content-Type` header,
+    /// replacing any existing one.
+    pub fn set_body_with_type(&mut self, body: impl Into<SipBody>, media_type: MediaType) {
+        self.body = Some(body.into());
+        set_content_type(&mut self.headers, media_type);
+    }
+
+    /// Appends the encoded request to `buf`, reusing its
+    /// allocation.
+    ///
+    /// The `Content-Length` is computed from the body, any
+    /// `Content-Length` header in the request is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bytes::BytesMut;
+    /// # use csip::message::{Method, Request};
+    /// let request = Request::new(Method::Options, "sip:alice@localhost".parse().unwrap());
+    /// let mut buf = BytesMut::new();
+    /// request.write_to(&mut buf).unwrap();
+    ///
+    /// assert!(buf.starts_with(b"OPTIONS sip:alice@localhost SIP/2.0\r\n"));
+    /// ```
+    pub fn write_to(&self, buf: &mut BytesMut) -> Result<()> {
+        let mut writer = buf.writer();
+
+        write!(writer, "{}", self.req_line)?;
+        write!(writer, "{}", MessageHeaders::new(&self.headers, false))?;
+        write_body(&mut writer, ContentLength::NAME, self.body.as_ref())
+    }
+
+    /// Like [`Request::write_to`], but uses the compact form of
+    /// the header names, as `v:` for `Via` and `l:` for
+    /// `Content-Length`.
+    ///
+    /// Keeps messages smaller, for example over UDP.
+    pub fn write_compact_to(&self, buf: &mut BytesMut) -> Result<()> {
+        let mut writer = buf.writer();
+
+        write!(writer, "{}", self.req_line)?;
+        write!(writer, "{}", MessageHeaders::new(&self.headers, true))?;
+        write_body(&mut writer, ContentLength::SHORT_NAME, self.body.as_ref())
     }
 }
 
@@ -330,6 +463,101 @@ impl Response {
     pub fn set_headers(&mut self, headers: Headers) {
         self.headers = headers;
     }
+
+    /// Sets the body of the response. It can be `None` to remove the body.
+    ///
+    /// The `Content-Type` header is left untouched, see
+    /// [`Response::set_body_with_type`].
+    pub fn set_body(&mut self, body: Option<impl Into<SipBody>>) {
+        self.body = body.map(|b| b.into());
+    }
+
+    /// Sets the body of the response and its `Content-Type` header,
+    /// replacing any existing one.
+    pub fn set_body_with_type(&mut self, body: impl Into<SipBody>, media_type: MediaType) {
+        self.body = Some(body.into());
+        set_content_type(&mut self.headers, media_type);
+    }
+
+    /// Appends the encoded response to `buf`, reusing its
+    /// allocation.
+    pub fn write_to(&self, buf: &mut BytesMut) -> Result<()> {
+        let mut writer = buf.writer();
+
+        write!(
+            writer,
+            "SIP/2.0 {} {}\r\n",
+            self.status().as_u16(),
+            self.reason().as_str()
+        )?;
+        write!(writer, "{}", MessageHeaders::new(&self.headers, false))?;
+        write_body(&mut writer, ContentLength::NAME, self.body.as_ref())
+    }
+
+    /// Like [`Response::write_to`], but uses the compact form of
+    /// the header names.
+    pub fn write_compact_to(&self, buf: &mut BytesMut) -> Result<()> {
+        let mut writer = buf.writer();
+
+        write!(
+            writer,
+            "SIP/2.0 {} {}\r\n",
+            self.status().as_u16(),
+            self.reason().as_str()
+        )?;
+        write!(writer, "{}", MessageHeaders::new(&self.headers, true))?;
+        write_body(&mut writer, ContentLength::SHORT_NAME, self.body.as_ref())
+    }
+}
+
+// Displays the headers of a message, without `Content-Length`
+// which `write_body` computes from the body.
+struct MessageHeaders<'a> {
+    headers: &'a Headers,
+    compact: bool,
+}
+
+impl<'a> MessageHeaders<'a> {
+    fn new(headers: &'a Headers, compact: bool) -> Self {
+        Self { headers, compact }
+    }
+}
+
+impl Display for MessageHeaders<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for hdr in self.headers.iter() {
+            match hdr {
+                Header::ContentLength(_) => continue,
+                hdr if self.compact => hdr.fmt_compact(f)?,
+                hdr => write!(f, "{hdr}")?,
+            }
+            write!(f, "\r\n")?;
+        }
+        Ok(())
+    }
+}
+
+// `content_length` is the name used for the `Content-Length` header.
+fn write_body<W: Write>(
+    writer: &mut W,
+    content_length: &str,
+    body: Option<&SipBody>,
+) -> Result<()> {
+    if let Some(body) = body {
+        write!(writer, "{content_length}: {}\r\n", body.len())?;
+        write!(writer, "\r\n")?;
+        writer.write_all(body)?;
+    } else {
+        write!(writer, "{content_length}: 0\r\n")?;
+        write!(writer, "\r\n")?;
+    }
+    Ok(())
+}
+
+// Leaves exactly one `Content-Type` header, carrying `media_type`.
+fn set_content_type(headers: &mut Headers, media_type: MediaType) {
+    headers.remove_all(|h| matches!(h, Header::ContentType(_)));
+    headers.push(Header::ContentType(ContentType::new(media_type)));
 }
 
 /// Represents a `reason-phrase` in Status-Line.
@@ -374,6 +602,14 @@ impl SipBody {
     pub fn new(data: Bytes) -> Self {
         Self { data }
     }
+
+    /// Returns the underlying buffer of the body.
+    ///
+    /// Cloning it is cheap, it shares the same allocation.
+    #[inline]
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.data
+    }
 }
 
 impl From<&str> for SipBody {
@@ -421,3 +657,255 @@ impl StatusLine {
         StatusLine { code, reason }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::transport::outgoing::Encode;
+
+    fn parse_request(src: &str) -> Request {
+        match Parser::parse(src.as_bytes()).unwrap() {
+            SipMessage::Request(request) => request,
+            SipMessage::Response(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_write_compact_uses_short_names() {
+        let mut request = parse_request(
+            "OPTIONS sip:carol@chicago.com SIP/2.0\r\n\
+             Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bKhjhs8ass877\r\n\
+             Max-Forwards: 70\r\n\
+             To: <sip:carol@chicago.com>\r\n\
+             From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+             Call-ID: a84b4c76e66710\r\n\
+             CSeq: 63104 OPTIONS\r\n\
+             Content-Length: 0\r\n\r\n",
+        );
+        request.set_body(Some("test"));
+        let mut buf = BytesMut::new();
+        request.write_compact_to(&mut buf).unwrap();
+        let compact = std::str::from_utf8(&buf).unwrap();
+
+        assert!(
+            compact.contains("\r\nv: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bKhjhs8ass877\r\n")
+        );
+        assert!(compact.contains("\r\nl: 4\r\n\r\ntest"));
+        assert_eq!(compact.matches("\r\nl: ").count(), 1);
+        assert!(compact.contains("\r\ni: a84b4c76e66710\r\n"));
+        assert!(compact.contains("\r\nMax-Forwards: 70\r\n"));
+        assert!(!compact.contains("Via:"));
+
+        let parsed = match Parser::parse(&buf[..]).unwrap() {
+            SipMessage::Request(request) => request,
+            SipMessage::Response(_) => unreachable!(),
+        };
+        let not_length = |h: &&Header| !matches!(h, Header::ContentLength(_));
+        assert!(
+            parsed
+                .headers
+                .iter()
+                .filter(not_length)
+                .eq(request.headers.iter().filter(not_length))
+        );
+    }
+
+    #[test]
+    fn test_encode_round_trips_parsed_invite() {
+        let sdp = "v=0\r\n\
+                   o=alice 2890844526 2890844526 IN IP4 pc33.atlanta.com\r\n\
+                   s=-\r\n\
+                   c=IN IP4 pc33.atlanta.com\r\n\
+                   t=0 0\r\n\
+                   m=audio 49172 RTP/AVP 0\r\n\
+                   a=rtpmap:0 PCMU/8000\r\n";
+        let src = format!(
+            "INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+             Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+             Max-Forwards: 70\r\n\
+             To: Bob <sip:bob@biloxi.com>\r\n\
+             From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+             Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+             CSeq: 314159 INVITE\r\n\
+             Contact: <sip:alice@pc33.atlanta.com>\r\n\
+             Content-Type: application/sdp\r\n\
+             Content-Length: {}\r\n\r\n{sdp}",
+            sdp.len()
+        );
+        let request = parse_request(&src);
+
+        let encoded = request.encode().unwrap();
+        let parsed = parse_request(std::str::from_utf8(&encoded).unwrap());
+
+        assert_eq!(parsed.req_line.method, Method::Invite);
+        assert_eq!(parsed.req_line.uri, request.req_line.uri);
+        assert!(parsed.headers.iter().eq(request.headers.iter()));
+        assert_eq!(parsed.body.as_deref(), request.body.as_deref());
+        assert_eq!(parsed.encode().unwrap(), encoded);
+    }
+
+    #[test]
+    fn test_write_to_round_trips_bearer_auth_headers() {
+        let src = "INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+                   Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+                   To: <sip:bob@biloxi.com>\r\n\
+                   From: <sip:alice@atlanta.com>;tag=1928301774\r\n\
+                   Call-ID: a84b4c76e66710\r\n\
+                   CSeq: 1 INVITE\r\n\
+                   Authorization: Bearer abc\r\n\
+                   Proxy-Authorization: Bearer abc\r\n\
+                   WWW-Authenticate: Bearer realm=\"x\"\r\n\
+                   Content-Length: 0\r\n\r\n";
+        let message = SipMessage::Request(parse_request(src));
+
+        let mut buf = BytesMut::new();
+        message.write_to(&mut buf).unwrap();
+        let parsed = parse_request(std::str::from_utf8(&buf).unwrap());
+
+        assert!(parsed.headers.iter().eq(message.headers().iter()));
+        assert_eq!(&buf[..], src.as_bytes());
+    }
+
+    #[test]
+    fn test_encode_recomputes_content_length() {
+        let mut request = parse_request(
+            "MESSAGE sip:bob@biloxi.com SIP/2.0\r\n\
+             Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+             To: <sip:bob@biloxi.com>\r\n\
+             From: <sip:alice@atlanta.com>;tag=1928301774\r\n\
+             Call-ID: a84b4c76e66710\r\n\
+             CSeq: 1 MESSAGE\r\n\
+             Content-Length: 5\r\n\r\nhello",
+        );
+        request.set_body(Some("hello, bob"));
+
+        let encoded = SipMessage::Request(request).encode().unwrap();
+        let encoded = std::str::from_utf8(&encoded).unwrap();
+
+        assert_eq!(encoded.matches("Content-Length").count(), 1);
+        assert!(encoded.ends_with("Content-Length: 10\r\n\r\nhello, bob"));
+    }
+
+    #[test]
+    fn test_register_expires_zero_removes_only_unparameterized_contact() {
+        let request = parse_request(
+            "REGISTER sip:registrar.biloxi.com SIP/2.0\r\n\
+             Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+             To: Bob <sip:bob@biloxi.com>\r\n\
+             From: Bob <sip:bob@biloxi.com>;tag=456248\r\n\
+             Call-ID: 843817637684230@998sdasdh09\r\n\
+             CSeq: 1826 REGISTER\r\n\
+             Contact: <sip:bob@192.0.2.4>\r\n\
+             Contact: <sip:bob@192.0.2.5>;expires=3600\r\n\
+             Expires: 0\r\n\
+             Content-Length: 0\r\n\r\n",
+        );
+
+        let removed: Vec<_> = request
+            .removed_contacts()
+            .map(|contact| contact.uri.to_string())
+            .collect();
+
+        assert!(request.expires().unwrap().is_zero());
+        assert_eq!(removed, ["<sip:bob@192.0.2.4>"]);
+    }
+
+    #[test]
+    fn test_contact_expires_param_overrides_header() {
+        let request = parse_request(
+            "REGISTER sip:registrar.biloxi.com SIP/2.0\r\n\
+             Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+             To: Bob <sip:bob@biloxi.com>\r\n\
+             From: Bob <sip:bob@biloxi.com>;tag=456248\r\n\
+             Call-ID: 843817637684230@998sdasdh09\r\n\
+             CSeq: 1826 REGISTER\r\n\
+             Contact: <sip:bob@192.0.2.4>\r\n\
+             Contact: <sip:bob@192.0.2.5>;expires=0\r\n\
+             Expires: 7200\r\n\
+             Content-Length: 0\r\n\r\n",
+        );
+
+        let removed: Vec<_> = request
+            .removed_contacts()
+            .map(|contact| contact.uri.to_string())
+            .collect();
+
+        assert_eq!(removed, ["<sip:bob@192.0.2.5>"]);
+        assert!(!request.is_unsubscribe());
+    }
+
+    #[test]
+    fn test_subscribe_with_expires_zero_is_unsubscribe() {
+        let request = parse_request(
+            "SUBSCRIBE sip:bob@biloxi.com SIP/2.0\r\n\
+             Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+             To: Bob <sip:bob@biloxi.com>;tag=8321234356\r\n\
+             From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+             Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+             CSeq: 2 SUBSCRIBE\r\n\
+             Event: presence\r\n\
+             Expires: 0\r\n\
+             Content-Length: 0\r\n\r\n",
+        );
+
+        assert!(request.is_unsubscribe());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decoded_body_removes_gzip_encoding() {
+        let xml = b"<?xml version=\"1.0\"?><presence entity=\"sip:alice@atlanta.com\"/>";
+        let mut request = parse_request(
+            "NOTIFY sip:alice@atlanta.com SIP/2.0\r\n\
+             Call-ID: a84b4c76e66710\r\n\r\n",
+        );
+        request
+            .headers
+            .push(Header::ContentEncoding(ContentEncoding::from([GZIP])));
+        request.set_body(Some(encode_body(GZIP, xml).unwrap()));
+
+        assert_eq!(request.decoded_body().unwrap().unwrap(), &xml[..]);
+    }
+
+    #[test]
+    fn test_decoded_body_applies_all_content_encoding_headers() {
+        let mut request = parse_request(
+            "NOTIFY sip:alice@atlanta.com SIP/2.0\r\n\
+             Call-ID: a84b4c76e66710\r\n\r\n",
+        );
+        request
+            .headers
+            .push(Header::ContentEncoding(ContentEncoding::from([IDENTITY])));
+        request
+            .headers
+            .push(Header::ContentEncoding(ContentEncoding::from(["compress"])));
+        request.set_body(Some(SipBody::from("hello")));
+
+        assert!(matches!(
+            request.decoded_body(),
+            Err(Error::UnsupportedContentEncoding(c)) if c == "compress"
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decoded_body_removes_codings_of_two_headers() {
+        let xml = b"<?xml version=\"1.0\"?><presence entity=\"sip:alice@atlanta.com\"/>";
+        let once = encode_body(GZIP, xml).unwrap();
+        let twice = encode_body(GZIP, &once).unwrap();
+        let mut request = parse_request(
+            "NOTIFY sip:alice@atlanta.com SIP/2.0\r\n\
+             Call-ID: a84b4c76e66710\r\n\r\n",
+        );
+        request
+            .headers
+            .push(Header::ContentEncoding(ContentEncoding::from([GZIP])));
+        request
+            .headers
+            .push(Header::ContentEncoding(ContentEncoding::from([GZIP])));
+        request.set_body(Some(twice));
+
+        assert_eq!(request.decoded_body().unwrap().unwrap(), &xml[..]);
+    }
+}
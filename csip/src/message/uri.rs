@@ -3,11 +3,13 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
 use std::{fmt, ops};
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use itertools::Itertools;
 
 use super::{Method, Params};
 use crate::error::{Error, Result};
-use crate::parser::Parser;
+use crate::parser::{self, Parser};
 use crate::transport::TransportType;
 
 /// A SIP uri.
@@ -119,8 +121,8 @@ impl SipUri {
     /// Returns the method parameter of the uri.
     pub fn method_param(&self) -> Option<Method> {
         match self {
-            SipUri::Uri(uri) => uri.method_param,
-            SipUri::NameAddr(addr) => addr.uri.method_param,
+            SipUri::Uri(uri) => uri.method_param.clone(),
+            SipUri::NameAddr(addr) => addr.uri.method_param.clone(),
         }
     }
 
@@ -257,6 +259,127 @@ impl Uri {
             headers: None,
         }
     }
+
+    /// Returns `true` if both uris are equivalent according to the
+    /// comparison rules of RFC 3261 section 19.1.4.
+    ///
+    /// Unlike `==`, this compares the host and most parameters
+    /// case-insensitively, decodes escaped characters, ignores the order
+    /// of parameters and headers, and ignores uri parameters that appear
+    /// in only one of the uris.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use csip::message::Uri;
+    ///
+    /// let a: Uri = "sip:%61lice@atlanta.com;transport=TCP".parse().unwrap();
+    /// let b: Uri = "sip:alice@AtLanTa.CoM;Transport=tcp".parse().unwrap();
+    ///
+    /// assert!(a.equivalent(&b));
+    /// ```
+    pub fn equivalent(&self, other: &Uri) -> bool {
+        if self.scheme != other.scheme {
+            return false;
+        }
+
+        let user_eq = match (&self.user, &other.user) {
+            (Some(a), Some(b)) => {
+                percent_decode(&a.user) == percent_decode(&b.user)
+                    && a.pass.as_deref().map(percent_decode)
+                        == b.pass.as_deref().map(percent_decode)
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if !user_eq {
+            return false;
+        }
+
+        // A port is never equal to an absent port, even if it is the default.
+        if self.host_port.host.normalized() != other.host_port.host.normalized()
+            || self.host_port.port != other.host_port.port
+        {
+            return false;
+        }
+
+        // The user, ttl, method, transport and maddr parameters must match
+        // when present in either uri.
+        if !eq_ignore_case_opt(self.user_param.as_deref(), other.user_param.as_deref())
+            || self.ttl_param != other.ttl_param
+            || self.method_param != other.method_param
+            || self.transport_param != other.transport_param
+            || self.maddr_param.as_ref().map(Host::normalized)
+                != other.maddr_param.as_ref().map(Host::normalized)
+        {
+            return false;
+        }
+
+        // Any other parameter is only compared when present in both uris.
+        if let (Some(params), Some(other_params)) = (&self.parameters, &other.parameters) {
+            for param in params.iter() {
+                let found = other_params
+                    .iter()
+                    .find(|p| p.name().eq_ignore_ascii_case(param.name()));
+                if let Some(other_param) = found
+                    && !eq_ignore_case_opt(param.value(), other_param.value())
+                {
+                    return false;
+                }
+            }
+        }
+
+        // Headers are compared as a whole, regardless of their order.
+        let headers = self.headers.as_deref().map_or(0, Params::len);
+        let other_headers = other.headers.as_deref().map_or(0, Params::len);
+        if headers != other_headers {
+            return false;
+        }
+        if let (Some(hdrs), Some(other_hdrs)) = (&self.headers, &other.headers) {
+            return hdrs.iter().all(|hdr| {
+                other_hdrs.iter().any(|other_hdr| {
+                    hdr.name().eq_ignore_ascii_case(other_hdr.name())
+                        && eq_ignore_case_opt(
+                            hdr.value().map(percent_decode).as_deref(),
+                            other_hdr.value().map(percent_decode).as_deref(),
+                        )
+                })
+            });
+        }
+
+        true
+    }
+}
+
+fn eq_ignore_case_opt(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Decodes `%HH` escapes, leaving malformed escapes untouched.
+fn percent_decode(s: &str) -> Cow<'_, str> {
+    if !s.contains('%') {
+        return Cow::Borrowed(s);
+    }
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    Cow::Owned(String::from_utf8_lossy(&out).into_owned())
 }
 
 impl FromStr for Uri {
@@ -451,6 +574,133 @@ impl DisplayName {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Returns the display name with any RFC 2047
+    /// encoded-words (`=?charset?B?text?=` or
+    /// `=?charset?Q?text?=`) decoded.
+    ///
+    /// Only the `UTF-8`, `US-ASCII` and `ISO-8859-1`
+    /// charsets are decoded, other words are kept as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::DisplayName;
+    /// let name = DisplayName::new("=?UTF-8?B?Sm/Do28=?=");
+    ///
+    /// assert_eq!(name.decode(), "João");
+    /// ```
+    pub fn decode(&self) -> Cow<'_, str> {
+        if !self.0.contains("=?") {
+            return Cow::Borrowed(&self.0);
+        }
+        let mut decoded = String::with_capacity(self.0.len());
+        let mut rest = self.0.as_str();
+        let mut after_word = false;
+
+        while let Some(start) = rest.find("=?") {
+            let (before, candidate) = rest.split_at(start);
+            let word = encoded_word_len(candidate)
+                .and_then(|len| decode_encoded_word(&candidate[..len]).map(|text| (len, text)));
+
+            match word {
+                Some((len, text)) => {
+                    // Whitespace between two encoded-words is ignored.
+                    if !(after_word && before.trim().is_empty()) {
+                        decoded.push_str(before);
+                    }
+                    decoded.push_str(&text);
+                    rest = &candidate[len..];
+                    after_word = true;
+                }
+                None => {
+                    decoded.push_str(before);
+                    decoded.push_str("=?");
+                    rest = &candidate[2..];
+                    after_word = false;
+                }
+            }
+        }
+        decoded.push_str(rest);
+
+        Cow::Owned(decoded)
+    }
+}
+
+// Returns the length of the encoded-word at the start of `s`.
+fn encoded_word_len(s: &str) -> Option<usize> {
+    let inner = s.strip_prefix("=?")?;
+    let charset = inner.find('?')?;
+    let encoding = inner[charset + 1..].find('?')? + charset + 1;
+    let end = inner[encoding + 1..].find("?=")? + encoding + 1;
+
+    Some(end + 4)
+}
+
+// Decodes a single `=?charset?encoding?text?=` encoded-word.
+fn decode_encoded_word(word: &str) -> Option<String> {
+    let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut parts = inner.splitn(3, '?');
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let text = parts.next()?;
+
+    let bytes = match encoding {
+        "B" | "b" => BASE64.decode(text).ok()?,
+        "Q" | "q" => decode_q(text)?,
+        _ => return None,
+    };
+    // RFC 2231 allows a language after the charset: `UTF-8*en`.
+    let charset = charset.split('*').next()?;
+
+    if charset.eq_ignore_ascii_case("utf-8") || charset.eq_ignore_ascii_case("us-ascii") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+// Decodes the "Q" encoding of RFC 2047 section 4.2.
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b => bytes.push(b),
+        }
+    }
+
+    Some(bytes)
+}
+
+impl fmt::Display for DisplayName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.0.is_empty() && self.0.bytes().all(parser::is_token) {
+            return f.write_str(&self.0);
+        }
+        write_quoted_string(f, &self.0)
+    }
+}
+
+// Writes `s` as a quoted-string, escaping `"` and `\`.
+pub(crate) fn write_quoted_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        if matches!(c, '"' | '\\') {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
 }
 
 /// Represents an SIP `name-addr`.
@@ -488,7 +738,7 @@ impl FromStr for NameAddr {
 impl fmt::Display for NameAddr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(display) = &self.display {
-            write!(f, "{} ", display.0)?;
+            write!(f, "{} ", display)?;
         }
         write!(f, "<{}>", self.uri)?;
 
@@ -601,6 +851,33 @@ impl Host {
             Host::IpAddr(host) => Cow::Owned(host.to_string()),
         }
     }
+
+    /// Returns the host in a canonical form for comparison.
+    ///
+    /// Domain names are case-insensitive and may be fully qualified
+    /// with a trailing dot, so they are lowercased and a single
+    /// trailing dot is removed. IP addresses are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::Host;
+    /// let fqdn: Host = "Example.COM.".parse().unwrap();
+    /// let name: Host = "example.com".parse().unwrap();
+    ///
+    /// assert_ne!(fqdn, name);
+    /// assert_eq!(fqdn.normalized(), name.normalized());
+    /// ```
+    pub fn normalized(&self) -> Host {
+        match self {
+            Host::DomainName(domain) => {
+                let name = domain.as_str();
+                let name = name.strip_suffix('.').unwrap_or(name);
+                Host::DomainName(DomainName::new(name.to_ascii_lowercase()))
+            }
+            Host::IpAddr(_) => self.clone(),
+        }
+    }
 }
 
 impl FromStr for Host {
@@ -638,6 +915,29 @@ impl HostPort {
     pub fn is_ip_addr(&self) -> bool {
         self.ip_addr().is_some()
     }
+
+    /// Returns `true` if `self` and `other` have the same host
+    /// and port, a missing port being `default_port`.
+    ///
+    /// Unlike `==`, `sip.example.com` matches
+    /// `sip.example.com:5060` when the default port is `5060`, and
+    /// hosts are compared in their [`Host::normalized`] form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::message::HostPort;
+    /// # use csip::transport::TransportType;
+    /// let explicit: HostPort = "sip.example.com:5060".parse().unwrap();
+    /// let implicit: HostPort = "sip.example.com".parse().unwrap();
+    ///
+    /// assert!(explicit.matches(&implicit, TransportType::Udp.default_port()));
+    /// assert!(!explicit.matches(&implicit, TransportType::Tls.default_port()));
+    /// ```
+    pub fn matches(&self, other: &HostPort, default_port: u16) -> bool {
+        self.host.normalized() == other.host.normalized()
+            && self.port.unwrap_or(default_port) == other.port.unwrap_or(default_port)
+    }
 }
 
 impl FromStr for HostPort {
@@ -694,3 +994,113 @@ impl Default for HostPort {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_domain_name_ignores_case_and_trailing_dot() {
+        let fqdn: Host = "Example.COM.".parse().unwrap();
+        let name: Host = "example.com".parse().unwrap();
+
+        assert_eq!(fqdn.normalized(), name.normalized());
+        assert_eq!(fqdn.normalized().as_str(), "example.com");
+        assert_eq!(fqdn.to_string(), "Example.COM.");
+    }
+
+    #[test]
+    fn test_normalized_ip_addr_is_unchanged() {
+        let v4: Host = "192.0.2.1".parse().unwrap();
+        let v6: Host = "2001:DB8::1".parse().unwrap();
+
+        assert_eq!(v4.normalized(), v4);
+        assert_eq!(v6.normalized(), v6);
+    }
+
+    #[test]
+    fn test_host_port_matches_normalized_hosts() {
+        let fqdn: HostPort = "Example.COM.:5060".parse().unwrap();
+        let name: HostPort = "example.com".parse().unwrap();
+
+        assert!(fqdn.matches(&name, 5060));
+        assert!(!fqdn.matches(&name, 5061));
+    }
+
+    fn assert_equivalent(a: &str, b: &str) {
+        let a: Uri = a.parse().unwrap();
+        let b: Uri = b.parse().unwrap();
+
+        assert!(a.equivalent(&b), "{a} should be equivalent to {b}");
+        assert!(b.equivalent(&a), "{b} should be equivalent to {a}");
+    }
+
+    fn assert_not_equivalent(a: &str, b: &str) {
+        let a: Uri = a.parse().unwrap();
+        let b: Uri = b.parse().unwrap();
+
+        assert!(!a.equivalent(&b), "{a} should not be equivalent to {b}");
+        assert!(!b.equivalent(&a), "{b} should not be equivalent to {a}");
+    }
+
+    #[test]
+    fn test_equivalent_uris() {
+        assert_equivalent(
+            "sip:%61lice@atlanta.com;transport=TCP",
+            "sip:alice@AtLanTa.CoM;Transport=tcp",
+        );
+        assert_equivalent("sip:carol@chicago.com", "sip:carol@chicago.com;newparam=5");
+        assert_equivalent("sip:carol@chicago.com", "sip:carol@chicago.com;security=on");
+        assert_equivalent(
+            "sip:carol@chicago.com;newparam=5",
+            "sip:carol@chicago.com;security=on",
+        );
+        assert_equivalent(
+            "sip:biloxi.com;transport=tcp;method=REGISTER?to=sip:bob%40biloxi.com",
+            "sip:biloxi.com;method=REGISTER;transport=tcp?to=sip:bob%40biloxi.com",
+        );
+        assert_equivalent(
+            "sip:alice@atlanta.com?subject=project%20x&priority=urgent",
+            "sip:alice@atlanta.com?priority=urgent&subject=project%20x",
+        );
+    }
+
+    #[test]
+    fn test_not_equivalent_uris() {
+        assert_not_equivalent(
+            "sip:ALICE@AtLanTa.CoM;Transport=udp",
+            "sip:alice@AtLanTa.CoM;Transport=UDP",
+        );
+        assert_not_equivalent("sip:bob@biloxi.com", "sip:bob@biloxi.com:5060");
+        assert_not_equivalent("sip:bob@biloxi.com", "sip:bob@biloxi.com;transport=udp");
+        assert_not_equivalent(
+            "sip:bob@biloxi.com",
+            "sip:bob@biloxi.com:6000;transport=tcp",
+        );
+        assert_not_equivalent(
+            "sip:carol@chicago.com",
+            "sip:carol@chicago.com?Subject=next%20meeting",
+        );
+        assert_not_equivalent("sip:bob@phone21.boxesbybob.com", "sip:bob@192.0.2.4");
+        assert_not_equivalent(
+            "sip:carol@chicago.com;newparam=5",
+            "sip:carol@chicago.com;newparam=6",
+        );
+    }
+
+    #[test]
+    fn test_maddr_is_significant_for_equivalence() {
+        assert_equivalent(
+            "sip:bob@biloxi.com;maddr=239.255.255.1",
+            "sip:bob@BILOXI.com;maddr=239.255.255.1",
+        );
+        assert_not_equivalent(
+            "sip:bob@biloxi.com",
+            "sip:bob@biloxi.com;maddr=239.255.255.1",
+        );
+        assert_not_equivalent(
+            "sip:bob@biloxi.com;maddr=239.255.255.1",
+            "sip:bob@biloxi.com;maddr=239.255.255.2",
+        );
+    }
+}
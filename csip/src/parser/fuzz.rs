@@ -0,0 +1,170 @@
+//! Randomized robustness harness for the message parser.
+//!
+//! Feeds random bytes and mutated versions of valid messages to
+//! [`Parser::parse`] and fails if any input makes it panic. The run is
+//! seeded, so a failure can be replayed; set `CSIP_FUZZ_SEED` and
+//! `CSIP_FUZZ_ITERATIONS` to explore beyond the default run.
+
+use std::panic;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::Parser;
+
+const DEFAULT_SEED: u64 = 0x5195_2026;
+const DEFAULT_ITERATIONS: usize = 20_000;
+
+/// Bytes that are significant to the SIP grammar, inserted to steer
+/// mutations towards delimiter handling.
+const DELIMITERS: &[u8] = b" \t:;,<>\"\r\n@=/\\()[]?&.0123456789";
+
+const CORPUS: &[&[u8]] = &[
+    b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds;rport;received=1.2.3.4\r\n\
+Max-Forwards: 70\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: \"Alice\" <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+CSeq: 314159 INVITE\r\n\
+Contact: <sip:alice@pc33.atlanta.com>;q=0.7;expires=30, <sips:a@[::1]:5061>\r\n\
+Content-Type: application/sdp\r\n\
+Content-Length: 4\r\n\r\n\
+v=0\r\n",
+    b"SIP/2.0 401 Unauthorized\r\n\
+Via: SIP/2.0/TCP a.com;branch=x, SIP/2.0/TLS b.com:5061;branch=y;ttl=3;maddr=10.0.0.1\r\n\
+WWW-Authenticate: Digest realm=\"atlanta.com\", nonce=\"abc\", qop=\"auth\", stale=FALSE, algorithm=MD5\r\n\
+Proxy-Authenticate: Digest realm=\"x\"\r\n\
+Authorization: Digest username=\"bob\", realm=\"r\", nonce=\"n\", uri=\"sip:a\", response=\"x\", nc=00000001\r\n\
+Date: Sat, 13 Nov 2010 23:29:00 GMT\r\n\
+Retry-After: 18000 (in a meeting);duration=3600\r\n\
+Warning: 307 isi.edu \"Session parameter 'foo' not understood\"\r\n\
+Accept: application/sdp;level=1, application/x-private\r\n\
+Accept-Encoding: gzip;q=0.5\r\n\
+Accept-Language: da, en-gb;q=0.8\r\n\
+Timestamp: 54.2 1.5\r\n\
+To: <sip:+1234@a;user=phone>\r\n\
+From: sip:a@b;tag=1\r\n\
+Call-ID: x\r\n\
+CSeq: 1 REGISTER\r\n\
+MIME-Version: 1.0\r\n\
+Subject: hi\r\n there\r\n\
+Server: A/1.0 (c)\r\n\
+Alert-Info: <http://a>;p=1, <http://b>\r\n\
+Error-Info: <sip:x@y>\r\n\
+In-Reply-To: a@b, c@d\r\n\
+Reply-To: Bob <sip:bob@b>;x=y\r\n\
+Priority: urgent\r\n\
+Require: 100rel\r\n\
+Min-Expires: 60\r\n\
+Expires: 30\r\n\
+Content-Disposition: session;handling=optional\r\n\
+Route: <sip:p1@a;lr>, <sip:p2@b>\r\n\
+Record-Route: <sip:p1@a;lr>\r\n\
+Authentication-Info: nextnonce=\"47364c23432d2e131a5fb210812c\"\r\n\
+Content-Encoding: gzip\r\n\
+Content-Language: fr\r\n\
+Organization: Boxes\r\n\
+User-Agent: Softphone Beta1.5\r\n\
+Unsupported: foo\r\n\
+Supported: timer\r\n\
+Allow: INVITE, ACK\r\n\
+Call-Info: <http://wwww.example.com/alice/photo.jpg> ;purpose=icon\r\n\
+l: 0\r\n\r\n",
+    b"REGISTER sips:[2001:db8::1]:5061;transport=tcp;ttl=5;maddr=239.255.255.1;lr;method=INVITE;user=phone;x?h=v&a=b SIP/2.0\r\n\
+v: SIP/2.0/WS [::1]:80;rport=5060;received=::1;branch=z9hG4bKa (c)\r\n\
+f: <sip:+1;ext=2@a.b:70;u=p?x=y>;tag=a\r\n\
+t: \"A b\" <sip:+1-201-555-0123@b.c;user=phone>\r\n\
+i: c\r\n\
+m: <sip:x@y>;expires=0\r\n\
+k: 100rel\r\n\
+e: gzip\r\n\
+c: multipart/mixed; boundary=\"x\"\r\n\
+s: x\r\n\
+Proxy-Require: foo\r\n\
+Proxy-Authorization: Digest username=\"a\", qop=auth, nc=1, cnonce=\"x\", opaque=\"o\"\r\n\
+X-Custom: foo\r\n\
+Content-Length: 9999\r\n\r\n\
+body",
+    b"ACK sip:a SIP/2.0\r\nMax-Forwards: 1\r\n\r\n",
+    b"SIP/2.0 199 \r\nCSeq: 1 A\r\n\r\n",
+];
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn random_bytes(rng: &mut StdRng) -> Vec<u8> {
+    let len = rng.random_range(0..128);
+    (0..len).map(|_| rng.random()).collect()
+}
+
+fn mutate(rng: &mut StdRng, src: &[u8]) -> Vec<u8> {
+    let mut buf = src.to_vec();
+
+    for _ in 0..rng.random_range(1..8) {
+        if buf.is_empty() {
+            buf.push(rng.random());
+            continue;
+        }
+        let i = rng.random_range(0..buf.len());
+        match rng.random_range(0..5) {
+            0 => buf[i] = rng.random(),
+            1 => {
+                buf.remove(i);
+            }
+            2 => {
+                let delim = DELIMITERS[rng.random_range(0..DELIMITERS.len())];
+                buf.insert(i, delim);
+            }
+            3 => buf.truncate(i),
+            _ => {
+                let end = rng.random_range(i..buf.len());
+                let chunk = buf[i..end].to_vec();
+                let at = rng.random_range(0..buf.len());
+                buf.splice(at..at, chunk);
+            }
+        }
+    }
+
+    buf
+}
+
+#[test]
+fn corpus_is_well_formed() {
+    for src in CORPUS {
+        assert!(
+            Parser::parse(*src).is_ok(),
+            "{:?}",
+            String::from_utf8_lossy(src)
+        );
+    }
+}
+
+#[test]
+fn parser_never_panics() {
+    let seed = env_or("CSIP_FUZZ_SEED", DEFAULT_SEED);
+    let iterations = env_or("CSIP_FUZZ_ITERATIONS", DEFAULT_ITERATIONS);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for n in 0..iterations {
+        let input = if n % 10 == 0 {
+            random_bytes(&mut rng)
+        } else {
+            mutate(&mut rng, CORPUS[n % CORPUS.len()])
+        };
+
+        let result = panic::catch_unwind(|| {
+            let _ = Parser::parse(&input[..]);
+        });
+
+        assert!(
+            result.is_ok(),
+            "parser panicked (seed {seed}, iteration {n}) on input {:?}",
+            String::from_utf8_lossy(&input)
+        );
+    }
+}
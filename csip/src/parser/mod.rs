@@ -4,17 +4,22 @@
 //! requests and responses, as well as various components such as URIs and
 //! headers.
 
+use std::borrow::Cow;
 use std::str::{self, FromStr};
 
+use bytes::Bytes;
 use utils::{Position, Scanner, ScannerError};
 
 use crate::Result;
-use crate::error::{Error, ParseError, ParseErrorKind as Kind};
+use crate::error::{Error, MessageSection, ParseError, ParseErrorKind as Kind};
 use crate::macros::{comma_separated, lookup_table, parse_param, try_parse_hdr};
 use crate::message::headers::*;
 use crate::message::*;
 use crate::transport::TransportType;
 
+#[cfg(test)]
+mod fuzz;
+
 // ---------------------------------------------------------------------
 // Parser constants
 // ---------------------------------------------------------------------
@@ -59,6 +64,10 @@ const PASS: &[u8] = b"&=+$,";
 /// Valid characters in SIP URIs host part.
 const HOST: &[u8] = b"_-.";
 
+/// Upper bound of the number of headers preallocated when
+/// parsing a message.
+const MAX_HEADER_CAPACITY_HINT: usize = 32;
+
 /// The "sip" schema used in SIP URIs.
 const SIP: &[u8] = b"sip";
 
@@ -97,6 +106,55 @@ lookup_table!(VIA_PARAM_TAB => b"[:]", ALPHANUMERIC, TOKEN);
 
 type ParamRef<'a> = (&'a str, Option<&'a str>);
 
+/// Indexes of the mandatory headers in the parsed header list.
+#[derive(Default)]
+struct MandatoryPositions {
+    via: Option<usize>,
+    from: Option<usize>,
+    to: Option<usize>,
+    call_id: Option<usize>,
+    cseq: Option<usize>,
+}
+
+impl MandatoryPositions {
+    fn extract(&self, headers: &Headers) -> Result<MandatoryHeaders> {
+        let get = |index: Option<usize>| index.map(|i| &headers[i]);
+
+        let via = get(self.via).and_then(Header::as_via).cloned();
+        let from = get(self.from).and_then(Header::as_from).cloned();
+        let to = get(self.to).and_then(Header::as_to).cloned();
+        let call_id = get(self.call_id).and_then(Header::as_call_id).cloned();
+        let cseq = get(self.cseq).and_then(Header::as_c_seq).cloned();
+
+        Ok(MandatoryHeaders {
+            via: MandatoryHeaders::required(via, Via::NAME)?,
+            from: MandatoryHeaders::required(from, From::NAME)?,
+            to: MandatoryHeaders::required(to, To::NAME)?,
+            call_id: MandatoryHeaders::required(call_id, CallId::NAME)?,
+            cseq: MandatoryHeaders::required(cseq, CSeq::NAME)?,
+        })
+    }
+}
+
+/// The part of the message being parsed, borrowed from the
+/// input until an error needs a [`MessageSection`].
+#[derive(Clone, Copy)]
+enum Section<'buf> {
+    StartLine,
+    Header(&'buf str),
+    Body,
+}
+
+impl std::convert::From<Section<'_>> for MessageSection {
+    fn from(section: Section<'_>) -> Self {
+        match section {
+            Section::StartLine => MessageSection::StartLine,
+            Section::Header(name) => MessageSection::Header(name.into()),
+            Section::Body => MessageSection::Body,
+        }
+    }
+}
+
 /// Trait to parse SIP headers.
 ///
 /// This trait defines how a specific SIP header type can be
@@ -132,6 +190,46 @@ pub trait HeaderParser: Sized {
     }
 }
 
+/// The body of a message parsed with
+/// [`Parser::parse_sip_msg_headers`].
+///
+/// Empty if the message has no `Content-Type`, as the body is
+/// then ignored.
+pub struct PendingBody<'buf> {
+    bytes: &'buf [u8],
+    /// The shared buffer the body comes from, if any.
+    payload: Option<&'buf Bytes>,
+}
+
+impl<'buf> PendingBody<'buf> {
+    /// Returns the bytes of the body.
+    pub fn as_bytes(&self) -> &'buf [u8] {
+        self.bytes
+    }
+
+    /// Returns `true` if the body is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Converts into a [`SipBody`], or `None` if the body is
+    /// empty.
+    ///
+    /// A body parsed from a shared buffer, see
+    /// [`Parser::from_bytes`], shares its allocation.
+    pub fn into_body(self) -> Option<SipBody> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let body = match self.payload {
+            Some(payload) => SipBody::new(payload.slice_ref(self.bytes)),
+            None => SipBody::from(self.bytes),
+        };
+
+        Some(body)
+    }
+}
+
 /// A SIP message parser.
 ///
 /// This struct provides methods for parsing various components of SIP messages,
@@ -139,6 +237,8 @@ pub trait HeaderParser: Sized {
 pub struct Parser<'buf> {
     /// The scanner used to read the input buffer.
     scanner: Scanner<'buf>,
+    /// The shared buffer the input comes from, if any.
+    payload: Option<&'buf Bytes>,
 }
 
 impl<'buf> Parser<'buf> {
@@ -163,6 +263,36 @@ impl<'buf> Parser<'buf> {
     {
         Self {
             scanner: Scanner::new(buf.as_ref()),
+            payload: None,
+        }
+    }
+
+    /// Creates a new `Parser` that reads from a shared `Bytes`
+    /// buffer, such as the payload of a received packet.
+    ///
+    /// The body of a message parsed this way is a slice of
+    /// `payload` and shares its allocation, instead of being
+    /// copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use csip::parser::Parser;
+    /// let payload = Bytes::from_static(
+    ///     b"MESSAGE sip:bob@biloxi.com SIP/2.0\r\n\
+    ///     Content-Type: text/plain\r\n\
+    ///     Content-Length: 5\r\n\r\nhello",
+    /// );
+    /// let msg = Parser::from_bytes(&payload).parse_sip_msg().unwrap();
+    ///
+    /// assert_eq!(&msg.body().unwrap()[..], b"hello");
+    /// ```
+    #[inline]
+    pub fn from_bytes(payload: &'buf Bytes) -> Self {
+        Self {
+            scanner: Scanner::new(payload),
+            payload: Some(payload),
         }
     }
 
@@ -202,8 +332,102 @@ impl<'buf> Parser<'buf> {
     /// assert_eq!(res.headers.len(), 1);
     /// ```
     pub fn parse_sip_msg(&mut self) -> Result<SipMessage> {
-        // Might be enough for most messages.
-        let minimal_header_size = 7;
+        self.parse_sip_msg_with_positions().map(|(msg, _)| msg)
+    }
+
+    /// Parses the internal buffer into a [`SipMessage`] together with its
+    /// [`MandatoryHeaders`].
+    ///
+    /// The position of each mandatory header is recorded while the headers
+    /// are parsed, so they are not searched for again after parsing.
+    ///
+    /// Returns an error if any of the mandatory headers is missing.
+    pub fn parse_sip_msg_with_mandatory_headers(
+        &mut self,
+    ) -> Result<(SipMessage, MandatoryHeaders)> {
+        let (msg, positions) = self.parse_sip_msg_with_positions()?;
+        let mandatory_headers = positions.extract(msg.headers())?;
+
+        Ok((msg, mandatory_headers))
+    }
+
+    /// Parses the start line and headers of the internal buffer
+    /// into a [`SipMessage`] without a body.
+    ///
+    /// The body is returned as a [`PendingBody`], which can be
+    /// attached to the message later, or ignored, for example by
+    /// a proxy that only inspects the headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::parser::Parser;
+    /// let buf = b"MESSAGE sip:bob@biloxi.com SIP/2.0\r\n\
+    ///     Content-Type: text/plain\r\n\
+    ///     Content-Length: 5\r\n\r\nhello";
+    /// let (mut msg, body) = Parser::new(buf).parse_sip_msg_headers().unwrap();
+    ///
+    /// assert!(msg.body().is_none());
+    /// assert_eq!(body.as_bytes(), b"hello");
+    ///
+    /// msg.set_body(body.into_body());
+    /// assert_eq!(&msg.body().unwrap()[..], b"hello");
+    /// ```
+    pub fn parse_sip_msg_headers(&mut self) -> Result<(SipMessage, PendingBody<'buf>)> {
+        self.parse_head().map(|(msg, _, body)| (msg, body))
+    }
+
+    fn parse_sip_msg_with_positions(&mut self) -> Result<(SipMessage, MandatoryPositions)> {
+        let (mut msg, positions, body) = self.parse_head()?;
+        if let Some(body) = body.into_body() {
+            msg.set_body(Some(body));
+        }
+
+        Ok((msg, positions))
+    }
+
+    fn parse_head(&mut self) -> Result<(SipMessage, MandatoryPositions, PendingBody<'buf>)> {
+        let mut section = Section::StartLine;
+
+        self.parse_message(&mut section).map_err(|err| match err {
+            Error::ParseError(err) => {
+                let input = self.scanner.buffer();
+                Error::ParseError(err.with_section(section.into()).with_source_line(input))
+            }
+            err => err,
+        })
+    }
+
+    // Estimates the number of headers by counting the lines up to the
+    // empty line that ends the message head, so the header vector does not
+    // grow while parsing. Folded lines make this an overestimate.
+    //
+    // The hint is capped at `MAX_HEADER_CAPACITY_HINT`, so a message with
+    // many lines does not make the parser preallocate a huge vector.
+    fn header_capacity_hint(&self) -> usize {
+        let mut lines = 0usize;
+
+        for line in self.scanner.remaining().split(|&b| b == b'\n') {
+            if lines > MAX_HEADER_CAPACITY_HINT || (lines > 0 && matches!(line, b"" | b"\r")) {
+                break;
+            }
+            lines += 1;
+        }
+
+        // The first line is the start line.
+        lines.saturating_sub(1).min(MAX_HEADER_CAPACITY_HINT)
+    }
+
+    // `section` is updated as parsing moves through the message, so the
+    // caller can tell which part of the message an error comes from.
+    fn parse_message(
+        &mut self,
+        section: &mut Section<'buf>,
+    ) -> Result<(SipMessage, MandatoryPositions, PendingBody<'buf>)> {
+        // Servers should ignore any empty line preceding the start line
+        // (RFC 3261 section 7.5).
+        self.skip_new_line();
+        let minimal_header_size = self.header_capacity_hint();
         let mut sip_message = if matches!(self.scanner.peek_bytes(B_SIPV2.len()), Some(B_SIPV2)) {
             // Is an status line, e.g, "SIP/2.0 200 OK".
             // TODO: Add "match" here.
@@ -224,194 +448,42 @@ impl<'buf> Parser<'buf> {
         };
 
         let mut found_content_type = false;
+        let mut positions = MandatoryPositions::default();
 
         // Parse headers loop.
         let headers = sip_message.headers_mut();
         'headers: loop {
             // Get name.
-            let header_name = self.parse_token()?;
+            let name = self.parse_token()?;
+            *section = Section::Header(name);
 
             self.skip_ws();
             self.must_read(b':')?;
             self.skip_ws();
 
-            match header_name {
-                ErrorInfo::NAME => {
-                    let header = try_parse_hdr!(ErrorInfo, self);
-                    headers.push(Header::ErrorInfo(header));
-                }
-                Route::NAME => comma_separated!(self => {
-                    let header = try_parse_hdr!(Route, self);
-                    headers.push(Header::Route(header));
-                }),
-                Via::NAME | Via::SHORT_NAME => comma_separated!(self => {
-                    let header = try_parse_hdr!(Via, self);
-                    headers.push(Header::Via(header));
-                }),
-                MaxForwards::NAME => {
-                    let header = try_parse_hdr!(MaxForwards, self);
-                    headers.push(Header::MaxForwards(header));
+            let name = expand_compact_form(name);
+            let index = headers.len();
+            self.parse_header_value(name, headers)?;
+
+            match name {
+                Via::NAME => {
+                    positions.via.get_or_insert(index);
                 }
-                From::NAME | From::SHORT_NAME => {
-                    let header = try_parse_hdr!(From, self);
-                    headers.push(Header::From(header));
+                From::NAME => {
+                    positions.from.get_or_insert(index);
                 }
-                To::NAME | To::SHORT_NAME => {
-                    let header = try_parse_hdr!(To, self);
-                    headers.push(Header::To(header));
+                To::NAME => {
+                    positions.to.get_or_insert(index);
                 }
-                CallId::NAME | CallId::SHORT_NAME => {
-                    let header = try_parse_hdr!(CallId, self);
-                    headers.push(Header::CallId(header));
+                CallId::NAME => {
+                    positions.call_id.get_or_insert(index);
                 }
                 CSeq::NAME => {
-                    let header = try_parse_hdr!(CSeq, self);
-                    headers.push(Header::CSeq(header));
-                }
-                Authorization::NAME => {
-                    let header = try_parse_hdr!(Authorization, self);
-                    headers.push(Header::Authorization(header));
-                }
-                Contact::NAME | Contact::SHORT_NAME => comma_separated!(self => {
-                    let header = try_parse_hdr!(Contact, self);
-                    headers.push(Header::Contact(header));
-                }),
-                Expires::NAME => {
-                    let header = try_parse_hdr!(Expires, self);
-                    headers.push(Header::Expires(header));
-                }
-                InReplyTo::NAME => {
-                    let header = try_parse_hdr!(InReplyTo, self);
-                    headers.push(Header::InReplyTo(header));
-                }
-                MimeVersion::NAME => {
-                    let header = try_parse_hdr!(MimeVersion, self);
-                    headers.push(Header::MimeVersion(header));
-                }
-                MinExpires::NAME => {
-                    let header = try_parse_hdr!(MinExpires, self);
-                    headers.push(Header::MinExpires(header));
-                }
-                UserAgent::NAME => {
-                    let header = try_parse_hdr!(UserAgent, self);
-                    headers.push(Header::UserAgent(header));
-                }
-                Date::NAME => {
-                    let header = try_parse_hdr!(Date, self);
-                    headers.push(Header::Date(header));
-                }
-                Server::NAME => {
-                    let header = try_parse_hdr!(Server, self);
-                    headers.push(Header::Server(header));
-                }
-                Subject::NAME | Subject::SHORT_NAME => {
-                    let header = try_parse_hdr!(Subject, self);
-                    headers.push(Header::Subject(header));
-                }
-                Priority::NAME => {
-                    let header = try_parse_hdr!(Priority, self);
-                    headers.push(Header::Priority(header));
-                }
-                ProxyAuthenticate::NAME => {
-                    let header = try_parse_hdr!(ProxyAuthenticate, self);
-                    headers.push(Header::ProxyAuthenticate(header));
-                }
-                ProxyAuthorization::NAME => {
-                    let header = try_parse_hdr!(ProxyAuthorization, self);
-                    headers.push(Header::ProxyAuthorization(header));
-                }
-                ProxyRequire::NAME => {
-                    let header = try_parse_hdr!(ProxyRequire, self);
-                    headers.push(Header::ProxyRequire(header));
-                }
-                ReplyTo::NAME => {
-                    let header = try_parse_hdr!(ReplyTo, self);
-                    headers.push(Header::ReplyTo(header));
-                }
-                ContentLength::NAME | ContentLength::SHORT_NAME => {
-                    let header = try_parse_hdr!(ContentLength, self);
-                    headers.push(Header::ContentLength(header));
-                }
-                ContentEncoding::NAME | ContentEncoding::SHORT_NAME => {
-                    let header = try_parse_hdr!(ContentEncoding, self);
-                    headers.push(Header::ContentEncoding(header));
-                }
-                ContentType::NAME | ContentType::SHORT_NAME => {
-                    let header = try_parse_hdr!(ContentType, self);
-                    headers.push(Header::ContentType(header));
-                    found_content_type = true;
-                }
-                ContentDisposition::NAME => {
-                    let header = try_parse_hdr!(ContentDisposition, self);
-                    headers.push(Header::ContentDisposition(header));
-                }
-                RecordRoute::NAME => comma_separated!(self => {
-                    let header = try_parse_hdr!(RecordRoute, self);
-                    headers.push(Header::RecordRoute(header));
-                }),
-                Require::NAME => {
-                    let header = try_parse_hdr!(Require, self);
-                    headers.push(Header::Require(header));
-                }
-                RetryAfter::NAME => {
-                    let header = try_parse_hdr!(RetryAfter, self);
-                    headers.push(Header::RetryAfter(header));
-                }
-                Organization::NAME => {
-                    let header = try_parse_hdr!(Organization, self);
-                    headers.push(Header::Organization(header));
-                }
-                AcceptEncoding::NAME => {
-                    let header = try_parse_hdr!(AcceptEncoding, self);
-                    headers.push(Header::AcceptEncoding(header));
-                }
-                Accept::NAME => {
-                    let header = try_parse_hdr!(Accept, self);
-                    headers.push(Header::Accept(header));
-                }
-                AcceptLanguage::NAME => {
-                    let header = try_parse_hdr!(AcceptLanguage, self);
-                    headers.push(Header::AcceptLanguage(header));
-                }
-                AlertInfo::NAME => {
-                    let header = try_parse_hdr!(AlertInfo, self);
-                    headers.push(Header::AlertInfo(header));
-                }
-                Allow::NAME => {
-                    let header = try_parse_hdr!(Allow, self);
-                    headers.push(Header::Allow(header));
-                }
-                AuthenticationInfo::NAME => {
-                    let header = try_parse_hdr!(AuthenticationInfo, self);
-                    headers.push(Header::AuthenticationInfo(header));
-                }
-                Supported::NAME | Supported::SHORT_NAME => {
-                    let header = try_parse_hdr!(Supported, self);
-                    headers.push(Header::Supported(header));
-                }
-                Timestamp::NAME => {
-                    let header = try_parse_hdr!(Timestamp, self);
-                    headers.push(Header::Timestamp(header));
-                }
-                Unsupported::NAME => {
-                    let header = try_parse_hdr!(Unsupported, self);
-                    headers.push(Header::Unsupported(header));
-                }
-                WWWAuthenticate::NAME => {
-                    let header = try_parse_hdr!(WWWAuthenticate, self);
-                    headers.push(Header::WWWAuthenticate(header));
-                }
-                Warning::NAME => {
-                    let header = try_parse_hdr!(Warning, self);
-                    headers.push(Header::Warning(header));
-                }
-                name => {
-                    // Found a header that is not defined in RFC 3261.
-                    let data = self.read_until_new_line_as_str()?;
-                    let header = RawHeader::new(name, data);
-                    headers.push(Header::RawHeader(header));
+                    positions.cseq.get_or_insert(index);
                 }
-            };
+                ContentType::NAME => found_content_type = true,
+                _ => (),
+            }
 
             if !self.parse_header_end() {
                 return self.parse_error(Kind::Header);
@@ -421,14 +493,256 @@ impl<'buf> Parser<'buf> {
                 break 'headers;
             }
         }
+        *section = Section::Body;
 
-        if found_content_type {
-            self.skip_new_line();
-            let body = self.remaining();
-            sip_message.set_body(body.into());
+        // Only the empty line, the body may start with line breaks.
+        if self.scanner.peek_byte().is_some() && !self.scanner.advance_if_crlf_or_lf() {
+            return self.parse_error(Kind::Header);
         }
+        let bytes = if found_content_type {
+            &self.scanner.buffer()[self.offset()..]
+        } else {
+            &[]
+        };
+        let body = PendingBody {
+            bytes,
+            payload: self.payload,
+        };
 
-        Ok(sip_message)
+        Ok((sip_message, positions, body))
+    }
+
+    // Parses a single `name: value` header line, optionally ended
+    // by a line break. A list header with more than one value or
+    // anything left after the line is an error.
+    pub(crate) fn parse_header(&mut self) -> Result<Header> {
+        let name = self.parse_token()?;
+
+        self.skip_ws();
+        self.must_read(b':')?;
+        self.skip_ws();
+
+        let mut headers = Headers::with_capacity(1);
+        self.parse_header_value(expand_compact_form(name), &mut headers)?;
+        self.skip_ws();
+        self.skip_new_line();
+
+        if headers.len() != 1 || !self.remaining().is_empty() {
+            return self.parse_error(Kind::Header);
+        }
+
+        Ok(headers.remove(0))
+    }
+
+    // Parses the value of the header `name`, in its full form,
+    // into `headers`. List headers push one header per value.
+    fn parse_header_value(&mut self, name: &'buf str, headers: &mut Headers) -> Result<()> {
+        match name {
+            ErrorInfo::NAME => {
+                let header = try_parse_hdr!(ErrorInfo, self);
+                headers.push(Header::ErrorInfo(header));
+            }
+            Route::NAME => comma_separated!(self => {
+                let header = try_parse_hdr!(Route, self);
+                headers.push(Header::Route(header));
+            }),
+            Via::NAME => {
+                let vias = try_parse_hdr!(Via::parse_list, self);
+                headers.extend(vias.into_iter().map(Header::Via));
+            }
+            Event::NAME => {
+                let header = try_parse_hdr!(Event, self);
+                headers.push(Header::Event(header));
+            }
+            MaxForwards::NAME => {
+                let header = try_parse_hdr!(MaxForwards, self);
+                headers.push(Header::MaxForwards(header));
+            }
+            MaxBreadth::NAME => {
+                let header = try_parse_hdr!(MaxBreadth, self);
+                headers.push(Header::MaxBreadth(header));
+            }
+            From::NAME => {
+                let header = try_parse_hdr!(From, self);
+                headers.push(Header::From(header));
+            }
+            To::NAME => {
+                let header = try_parse_hdr!(To, self);
+                headers.push(Header::To(header));
+            }
+            CallId::NAME => {
+                let header = try_parse_hdr!(CallId, self);
+                headers.push(Header::CallId(header));
+            }
+            CSeq::NAME => {
+                let header = try_parse_hdr!(CSeq, self);
+                headers.push(Header::CSeq(header));
+            }
+            Authorization::NAME => {
+                let header = try_parse_hdr!(Authorization, self);
+                headers.push(Header::Authorization(header));
+            }
+            Contact::NAME => comma_separated!(self => {
+                let header = try_parse_hdr!(Contact, self);
+                headers.push(Header::Contact(header));
+            }),
+            Expires::NAME => {
+                let header = try_parse_hdr!(Expires, self);
+                headers.push(Header::Expires(header));
+            }
+            InReplyTo::NAME => {
+                let header = try_parse_hdr!(InReplyTo, self);
+                headers.push(Header::InReplyTo(header));
+            }
+            MimeVersion::NAME => {
+                let header = try_parse_hdr!(MimeVersion, self);
+                headers.push(Header::MimeVersion(header));
+            }
+            MinExpires::NAME => {
+                let header = try_parse_hdr!(MinExpires, self);
+                headers.push(Header::MinExpires(header));
+            }
+            MinSe::NAME => {
+                let header = try_parse_hdr!(MinSe, self);
+                headers.push(Header::MinSe(header));
+            }
+            UserAgent::NAME => {
+                let header = try_parse_hdr!(UserAgent, self);
+                headers.push(Header::UserAgent(header));
+            }
+            Date::NAME => {
+                let header = try_parse_hdr!(Date, self);
+                headers.push(Header::Date(header));
+            }
+            Server::NAME => {
+                let header = try_parse_hdr!(Server, self);
+                headers.push(Header::Server(header));
+            }
+            SessionExpires::NAME => {
+                let header = try_parse_hdr!(SessionExpires, self);
+                headers.push(Header::SessionExpires(header));
+            }
+            Subject::NAME => {
+                let header = try_parse_hdr!(Subject, self);
+                headers.push(Header::Subject(header));
+            }
+            Priority::NAME => {
+                let header = try_parse_hdr!(Priority, self);
+                headers.push(Header::Priority(header));
+            }
+            ProxyAuthenticate::NAME => {
+                let header = try_parse_hdr!(ProxyAuthenticate, self);
+                headers.push(Header::ProxyAuthenticate(header));
+            }
+            ProxyAuthorization::NAME => {
+                let header = try_parse_hdr!(ProxyAuthorization, self);
+                headers.push(Header::ProxyAuthorization(header));
+            }
+            ProxyRequire::NAME => {
+                let header = try_parse_hdr!(ProxyRequire, self);
+                headers.push(Header::ProxyRequire(header));
+            }
+            ReplyTo::NAME => {
+                let header = try_parse_hdr!(ReplyTo, self);
+                headers.push(Header::ReplyTo(header));
+            }
+            ContentLength::NAME => {
+                let header = try_parse_hdr!(ContentLength, self);
+                headers.push(Header::ContentLength(header));
+            }
+            ContentEncoding::NAME => {
+                let header = try_parse_hdr!(ContentEncoding, self);
+                headers.push(Header::ContentEncoding(header));
+            }
+            ContentType::NAME => {
+                let header = try_parse_hdr!(ContentType, self);
+                headers.push(Header::ContentType(header));
+            }
+            ContentDisposition::NAME => {
+                let header = try_parse_hdr!(ContentDisposition, self);
+                headers.push(Header::ContentDisposition(header));
+            }
+            RecordRoute::NAME => comma_separated!(self => {
+                let header = try_parse_hdr!(RecordRoute, self);
+                headers.push(Header::RecordRoute(header));
+            }),
+            Require::NAME => {
+                let header = try_parse_hdr!(Require, self);
+                headers.push(Header::Require(header));
+            }
+            RSeq::NAME => {
+                let header = try_parse_hdr!(RSeq, self);
+                headers.push(Header::RSeq(header));
+            }
+            RAck::NAME => {
+                let header = try_parse_hdr!(RAck, self);
+                headers.push(Header::RAck(header));
+            }
+            RetryAfter::NAME => {
+                let header = try_parse_hdr!(RetryAfter, self);
+                headers.push(Header::RetryAfter(header));
+            }
+            Organization::NAME => {
+                let header = try_parse_hdr!(Organization, self);
+                headers.push(Header::Organization(header));
+            }
+            AcceptEncoding::NAME => {
+                let header = try_parse_hdr!(AcceptEncoding, self);
+                headers.push(Header::AcceptEncoding(header));
+            }
+            Accept::NAME => {
+                let header = try_parse_hdr!(Accept, self);
+                headers.push(Header::Accept(header));
+            }
+            AcceptLanguage::NAME => {
+                let header = try_parse_hdr!(AcceptLanguage, self);
+                headers.push(Header::AcceptLanguage(header));
+            }
+            AlertInfo::NAME => {
+                let header = try_parse_hdr!(AlertInfo, self);
+                headers.push(Header::AlertInfo(header));
+            }
+            Allow::NAME => {
+                let header = try_parse_hdr!(Allow, self);
+                headers.push(Header::Allow(header));
+            }
+            AuthenticationInfo::NAME => {
+                let header = try_parse_hdr!(AuthenticationInfo, self);
+                headers.push(Header::AuthenticationInfo(header));
+            }
+            CallInfo::NAME => {
+                let header = try_parse_hdr!(CallInfo, self);
+                headers.push(Header::CallInfo(header));
+            }
+            Supported::NAME => {
+                let header = try_parse_hdr!(Supported, self);
+                headers.push(Header::Supported(header));
+            }
+            Timestamp::NAME => {
+                let header = try_parse_hdr!(Timestamp, self);
+                headers.push(Header::Timestamp(header));
+            }
+            Unsupported::NAME => {
+                let header = try_parse_hdr!(Unsupported, self);
+                headers.push(Header::Unsupported(header));
+            }
+            WWWAuthenticate::NAME => {
+                let header = try_parse_hdr!(WWWAuthenticate, self);
+                headers.push(Header::WWWAuthenticate(header));
+            }
+            Warning::NAME => {
+                let header = try_parse_hdr!(Warning, self);
+                headers.push(Header::Warning(header));
+            }
+            name => {
+                // Found a header that is not defined in RFC 3261.
+                let data = self.read_until_new_line_as_str()?;
+                let header = RawHeader::new(name, data);
+                headers.push(Header::RawHeader(header));
+            }
+        }
+
+        Ok(())
     }
 
     pub fn parse_status_line(&mut self) -> Result<StatusLine> {
@@ -443,8 +757,18 @@ impl<'buf> Parser<'buf> {
     }
 
     pub fn parse_request_line(&mut self) -> Result<RequestLine> {
-        let token = self.scanner.read_while(is_token);
-        let method = token.into();
+        let method = self.parse_method()?;
+        // Methods are case-sensitive, so a known method in
+        // any other case (`invite`) is malformed rather than
+        // an extension method.
+        if let Method::Unknown(_) = method
+            && !matches!(
+                Method::try_from(method.as_str().to_ascii_uppercase().as_bytes()),
+                Ok(Method::Unknown(_))
+            )
+        {
+            return self.parse_error(Kind::Method);
+        }
         let uri = self.parse_uri(true)?;
 
         self.parse_sip_version()?;
@@ -510,9 +834,15 @@ impl<'buf> Parser<'buf> {
             .map(TransportType::from_str)
             .transpose()
             .or_else(|_| self.parse_error(Kind::Transport))?;
-        let ttl_param = ttl_param.map(|ttl: &str| ttl.parse().unwrap());
+        let ttl_param = ttl_param
+            .map(|ttl: &str| ttl.parse())
+            .transpose()
+            .or_else(|_| self.parse_error(Kind::Param))?;
         let lr_param = lr_param.is_some();
-        let method_param = method_param.map(|p: &str| p.as_bytes().into());
+        let method_param = method_param
+            .map(|p: &str| Method::try_from(p.as_bytes()))
+            .transpose()
+            .or_else(|_| self.parse_error(Kind::Method))?;
         let user_param = user_param.map(|u: &str| u.into());
         let maddr_param = maddr_param.and_then(|m: &str| m.parse::<Host>().ok());
 
@@ -672,10 +1002,8 @@ impl<'buf> Parser<'buf> {
     fn parse_display_name(&mut self) -> Result<Option<DisplayName>> {
         match self.scanner.peek_byte() {
             Some(b'"') => {
-                self.next_byte()?; // consume '"'
-                let name = self.scanner.read_while(|b| b != b'"');
-                self.next_byte()?; // consume closing '"'
-                Ok(Some(DisplayName::new(str::from_utf8(name)?.into())))
+                let name = self.read_quoted_string()?;
+                Ok(Some(DisplayName::new(&name)))
             }
             Some(b'<') => Ok(None), // no display name
             None => {
@@ -696,10 +1024,27 @@ impl<'buf> Parser<'buf> {
             .any(|&b| b == b'@')
     }
 
+    // Peers may end lines with a bare `\n`, which is tolerated
+    // on receipt (RFC 3261 section 7.5).
     #[inline]
     fn parse_header_end(&mut self) -> bool {
-        !(self.scanner.advance_if_eq(b'\r').is_none()
-            || self.scanner.advance_if_eq(b'\n').is_none())
+        self.scanner.advance_if_crlf_or_lf()
+    }
+
+    /// Parses a possibly empty comma-separated list of option
+    /// tags, as used by `Require`, `Proxy-Require`, `Supported`
+    /// and `Unsupported`.
+    pub(crate) fn parse_option_tags(&mut self) -> Result<Vec<String>> {
+        let mut tags = Vec::new();
+        comma_separated!(self => {
+            let tag = self.read_token_str();
+            if !tag.is_empty() {
+                tags.push(tag.into());
+            }
+            self.skip_ws();
+        });
+
+        Ok(tags)
     }
 
     #[inline]
@@ -723,16 +1068,78 @@ impl<'buf> Parser<'buf> {
         })
     }
 
+    /// Parses a method name, a token as in the request line
+    /// and the `CSeq` header.
+    pub(crate) fn parse_method(&mut self) -> Result<Method> {
+        let name = self.scanner.read_while_in(&TOKEN_TAB);
+        if name.is_empty() {
+            return self.parse_error(Kind::Method);
+        }
+
+        Method::try_from(name).or_else(|_| self.parse_error(Kind::Method))
+    }
+
     /// Shortcut for yielding a parse error wrapped in a result type.
     pub(crate) fn parse_error<T>(&self, kind: Kind) -> Result<T> {
-        Err(Error::ParseError(ParseError::new(kind, *self.position())))
+        let error = ParseError::new(kind, *self.position(), self.offset());
+        Err(Error::ParseError(error))
     }
 
-    /// Read until a new line (`\r` or `\n`) is found.
+    /// Read until a new line (`\r\n` or `\n`) is found.
     pub(crate) fn read_until_new_line_as_str(&mut self) -> Result<&'buf str> {
-        let bytes = self.scanner.read_while(is_not_newline);
+        let bytes = self.scanner.read_until_crlf_or_lf();
 
-        Ok(str::from_utf8(bytes)?)
+        self.utf8(bytes)
+    }
+
+    /// Reads an UTF-8 header value until the end of the header.
+    ///
+    /// Lines folded with a line break followed by whitespace
+    /// (RFC 3261 section 7.3.1) are unfolded, each fold being
+    /// replaced by a single space.
+    pub(crate) fn read_folded_text(&mut self) -> Result<Cow<'buf, str>> {
+        let mut value = Cow::Borrowed(self.read_until_new_line_as_str()?);
+
+        while let Some(len) = self.fold_len() {
+            self.scanner.advance_by(len);
+            self.skip_ws();
+            let line = self.read_until_new_line_as_str()?;
+
+            let value = value.to_mut();
+            value.truncate(trim_ws_end(value).len());
+            value.push(' ');
+            value.push_str(line);
+        }
+        // Trailing whitespace is not part of the value.
+        let value = match value {
+            Cow::Borrowed(value) => Cow::Borrowed(trim_ws_end(value)),
+            Cow::Owned(mut value) => {
+                value.truncate(trim_ws_end(&value).len());
+                Cow::Owned(value)
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Returns the length of the line break if the header value
+    /// continues on the next line.
+    fn fold_len(&self) -> Option<usize> {
+        let line_break = match self.remaining() {
+            [b'\r', b'\n', ..] => 2,
+            [b'\n', ..] => 1,
+            _ => return None,
+        };
+
+        match self.remaining().get(line_break) {
+            Some(b' ' | b'\t') => Some(line_break),
+            _ => None,
+        }
+    }
+
+    fn utf8(&self, bytes: &'buf [u8]) -> Result<&'buf str> {
+        str::from_utf8(bytes)
+            .or_else(|_| self.parse_error(Kind::Scanner(ScannerError::InvalidUtf8)))
     }
 
     pub(crate) fn parse_auth_challenge(&mut self) -> Result<Challenge> {
@@ -760,14 +1167,16 @@ impl<'buf> Parser<'buf> {
         comma_separated!(self => {
             let (name, value) = self.parse_ref_param()?;
 
+            let value = value.map(unquote);
+
             match name {
-                REALM => digest.realm = value.map(String::from),
-                NONCE => digest.nonce = value.map(String::from),
-                DOMAIN => digest.domain = value.map(String::from),
-                ALGORITHM => digest.algorithm = value.map(String::from),
-                OPAQUE => digest.opaque = value.map(String::from),
-                QOP => digest.qop = value.map(String::from),
-                STALE => digest.stale = value.map(String::from),
+                REALM => digest.realm = value,
+                NONCE => digest.nonce = value,
+                DOMAIN => digest.domain = value,
+                ALGORITHM => digest.algorithm = value,
+                OPAQUE => digest.opaque = value,
+                QOP => digest.qop = value,
+                STALE => digest.stale = value,
                 _other => {
                     // return err?
                 }
@@ -783,17 +1192,19 @@ impl<'buf> Parser<'buf> {
         comma_separated!(self => {
             let (name, value) = self.parse_ref_param()?;
 
+            let value = value.map(unquote);
+
             match name {
-                REALM => digest.realm = value.map(String::from),
-                USERNAME => digest.username = value.map(String::from),
-                NONCE => digest.nonce = value.map(String::from),
-                URI => digest.uri = value.map(String::from),
-                RESPONSE => digest.response = value.map(String::from),
-                ALGORITHM => digest.algorithm = value.map(String::from),
-                CNONCE => digest.cnonce = value.map(String::from),
-                OPAQUE => digest.opaque = value.map(String::from),
-                QOP => digest.qop = value.map(String::from),
-                NC => digest.nc = value.map(String::from),
+                REALM => digest.realm = value,
+                USERNAME => digest.username = value,
+                NONCE => digest.nonce = value,
+                URI => digest.uri = value,
+                RESPONSE => digest.response = value,
+                ALGORITHM => digest.algorithm = value,
+                CNONCE => digest.cnonce = value,
+                OPAQUE => digest.opaque = value,
+                QOP => digest.qop = value,
+                NC => digest.nc = value,
                 _ => {}, // Ignore unknown parameters
             }
         });
@@ -845,6 +1256,11 @@ impl<'buf> Parser<'buf> {
         self.scanner.position()
     }
 
+    #[inline]
+    pub(crate) fn offset(&self) -> usize {
+        self.scanner.offset()
+    }
+
     #[inline]
     pub(crate) fn remaining(&self) -> &[u8] {
         self.scanner.remaining()
@@ -868,6 +1284,13 @@ impl<'buf> Parser<'buf> {
             .or_else(|err| self.parse_error(Kind::Scanner(err)))?)
     }
 
+    /// Reads a `quoted-string`, returning its unescaped content.
+    pub(crate) fn read_quoted_string(&mut self) -> Result<Cow<'buf, str>> {
+        self.scanner
+            .read_quoted_string()
+            .or_else(|err| self.parse_error(Kind::Scanner(err)))
+    }
+
     #[inline]
     pub(crate) fn must_read(&mut self, byte: u8) -> Result<()> {
         Ok(self
@@ -886,37 +1309,51 @@ impl<'buf> Parser<'buf> {
 
     #[inline]
     fn read_user_str(&mut self) -> &'buf str {
+        // SAFETY: `is_user` only accepts ASCII bytes.
         unsafe { self.scanner.read_while_as_str_unchecked(is_user) }
     }
 
     #[inline]
     fn read_pass_as_str(&mut self) -> &'buf str {
+        // SAFETY: `is_pass` only accepts ASCII bytes.
         unsafe { self.scanner.read_while_as_str_unchecked(is_pass) }
     }
 
     #[inline]
     fn read_host_str(&mut self) -> &'buf str {
-        unsafe { self.scanner.read_while_as_str_unchecked(is_host) }
+        let bytes = self.scanner.read_while_in(&HOST_TAB);
+        // SAFETY: `HOST_TAB` only accepts ASCII bytes.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
     }
 
     #[inline]
     pub(crate) fn read_token_str(&mut self) -> &'buf str {
-        unsafe { self.scanner.read_while_as_str_unchecked(is_token) }
+        let bytes = self.scanner.read_while_in(&TOKEN_TAB);
+        // SAFETY: `TOKEN_TAB` only accepts ASCII bytes.
+        unsafe { std::str::from_utf8_unchecked(bytes) }
     }
 
+    /// # Safety
+    ///
+    /// `func` must only accept bytes that form valid UTF-8.
     #[inline]
     pub(crate) unsafe fn read_while_as_str_unchecked(
         &mut self,
         func: impl Fn(u8) -> bool,
     ) -> &'buf str {
+        // SAFETY: guaranteed by the caller.
         unsafe { self.scanner.read_while_as_str_unchecked(func) }
     }
 
+    /// # Safety
+    ///
+    /// `func` must only accept bytes that form valid UTF-8.
     pub(crate) unsafe fn parse_param_unchecked(
         &mut self,
         func: impl Fn(u8) -> bool,
     ) -> Result<(&'buf str, Option<&'buf str>)> {
         self.skip_ws();
+        // SAFETY: guaranteed by the caller.
         let name = unsafe { self.scanner.read_while_as_str_unchecked(&func) };
         let Some(b'=') = self.scanner.peek_byte() else {
             return Ok((name, None));
@@ -929,6 +1366,7 @@ impl<'buf> Parser<'buf> {
             };
             str::from_utf8(value)?
         } else {
+            // SAFETY: guaranteed by the caller.
             unsafe { self.scanner.read_while_as_str_unchecked(func) }
         };
 
@@ -936,6 +1374,7 @@ impl<'buf> Parser<'buf> {
     }
 
     pub(crate) fn parse_ref_param(&mut self) -> Result<ParamRef<'buf>> {
+        // SAFETY: `is_token` only accepts ASCII bytes.
         unsafe { self.parse_param_unchecked(is_token) }
     }
 
@@ -955,6 +1394,58 @@ impl<'buf> Parser<'buf> {
     }
 }
 
+/// Returns the content of a `quoted-string` auth-param value, with
+/// the quotes removed and quoted-pairs unescaped.
+pub(crate) fn unquote(value: &str) -> String {
+    let Some(value) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.into();
+    };
+    let mut unquoted = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+
+    unquoted
+}
+
+/// Removes the trailing linear whitespace (SP and HTAB) of `value`,
+/// other characters such as a form feed are part of the value.
+fn trim_ws_end(value: &str) -> &str {
+    value.trim_end_matches([' ', '\t'])
+}
+
+/// Returns the full name of the header if `name` is a compact form
+/// (RFC 3261 section 7.3.3), otherwise returns `name`.
+fn expand_compact_form(name: &str) -> &str {
+    const COMPACT_FORMS: [(&str, &str); 12] = [
+        (CallId::SHORT_NAME, CallId::NAME),
+        (Contact::SHORT_NAME, Contact::NAME),
+        (ContentEncoding::SHORT_NAME, ContentEncoding::NAME),
+        (ContentLength::SHORT_NAME, ContentLength::NAME),
+        (ContentType::SHORT_NAME, ContentType::NAME),
+        (Event::SHORT_NAME, Event::NAME),
+        (From::SHORT_NAME, From::NAME),
+        (SessionExpires::SHORT_NAME, SessionExpires::NAME),
+        (Subject::SHORT_NAME, Subject::NAME),
+        (Supported::SHORT_NAME, Supported::NAME),
+        (To::SHORT_NAME, To::NAME),
+        (Via::SHORT_NAME, Via::NAME),
+    ];
+
+    if name.len() != 1 {
+        return name;
+    }
+
+    COMPACT_FORMS
+        .iter()
+        .find(|(short, _)| name.eq_ignore_ascii_case(short))
+        .map_or(name, |(_, full)| full)
+}
+
 fn parse_uri_param<'a>(parser: &mut Parser<'a>) -> Result<ParamRef<'a>> {
     // SAFETY: `is_param` only accepts ASCII bytes, which are
     // always valid UTF-8.
@@ -984,11 +1475,6 @@ fn is_newline(c: u8) -> bool {
     matches!(c, b'\r' | b'\n')
 }
 
-#[inline(always)]
-fn is_not_newline(c: u8) -> bool {
-    !is_newline(c)
-}
-
 #[inline(always)]
 fn not_comma_or_newline(c: u8) -> bool {
     !is_newline(c) && c != b','
@@ -1041,7 +1527,11 @@ fn is_hdr_uri(b: u8) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::message::{Scheme, Uri, UserInfo};
+    use bytes::BytesMut;
+
+    use crate::error::{Error, MessageSection, ParseErrorKind};
+    use crate::message::headers::Header;
+    use crate::message::{MandatoryHeaders, Method, Scheme, Uri, UserInfo};
     use crate::{Result, uri_test_ok};
 
     uri_test_ok! {
@@ -1279,4 +1769,414 @@ mod tests {
             .with_header("foo", Some("bar"))
             .build()
     }
+
+    #[test]
+    fn test_captured_mandatory_headers_match_second_pass() {
+        let src = b"REGISTER sip:registrar.biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+Via: SIP/2.0/UDP proxy.biloxi.com;branch=z9hG4bK776asdhds\r\n\
+Max-Forwards: 70\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: Bob <sip:bob@biloxi.com>;tag=456248\r\n\
+Call-ID: 843817637684230@998sdasdh09\r\n\
+CSeq: 1826 REGISTER\r\n\
+Content-Length: 0\r\n\r\n";
+
+        let (msg, captured) = super::Parser::new(src)
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+        let second_pass = MandatoryHeaders::from_headers(msg.headers()).unwrap();
+
+        assert_eq!(captured.via, second_pass.via);
+        assert_eq!(captured.via.branch.as_deref(), Some("z9hG4bKnashds7"));
+        assert_eq!(captured.from, second_pass.from);
+        assert_eq!(captured.to, second_pass.to);
+        assert_eq!(captured.call_id, second_pass.call_id);
+        assert_eq!(captured.cseq, second_pass.cseq);
+    }
+
+    #[test]
+    fn test_duplicated_mandatory_header_captures_first_occurrence() {
+        let src = b"REGISTER sip:registrar.biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: Bob <sip:bob@biloxi.com>;tag=456248\r\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: 843817637684230@998sdasdh09\r\n\
+CSeq: 1826 REGISTER\r\n\
+CSeq: 1827 REGISTER\r\n\
+Content-Length: 0\r\n\r\n";
+
+        let (msg, captured) = super::Parser::new(src)
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+        let second_pass = MandatoryHeaders::from_headers(msg.headers()).unwrap();
+
+        assert_eq!(captured.from, second_pass.from);
+        assert_eq!(captured.from.tag().as_deref(), Some("456248"));
+        assert_eq!(captured.cseq, second_pass.cseq);
+        assert_eq!(captured.cseq.cseq, 1826);
+    }
+
+    #[test]
+    fn test_missing_mandatory_header_is_an_error() {
+        let src = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+Content-Length: 0\r\n\r\n";
+
+        let result = super::Parser::new(src).parse_sip_msg_with_mandatory_headers();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_unknown_method_round_trips() {
+        let src = b"PUSH sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+CSeq: 1 PUSH\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let request = msg.request().unwrap();
+        let mut buf = BytesMut::new();
+        msg.write_to(&mut buf).unwrap();
+
+        assert_eq!(request.req_line.method.as_str(), "PUSH");
+        assert!(buf.starts_with(b"PUSH sip:bob@biloxi.com SIP/2.0\r\n"));
+        assert!(buf.windows(14).any(|w| w == b"CSeq: 1 PUSH\r\n"));
+    }
+
+    #[test]
+    fn test_parse_call_info_entries() {
+        let src = b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+Call-Info: <http://wwww.example.com/alice/photo.jpg> ;purpose=icon, \
+<http://www.example.com/alice/> ;purpose=info\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let request = msg.request().unwrap();
+        let call_info = request
+            .headers
+            .iter()
+            .find_map(Header::as_call_info)
+            .unwrap();
+        let uris: Vec<_> = call_info.iter().map(|info| info.uri()).collect();
+
+        assert_eq!(
+            uris,
+            [
+                "http://wwww.example.com/alice/photo.jpg",
+                "http://www.example.com/alice/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hyphenated_method_round_trips() {
+        let src = b"X-PUSH sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+CSeq: 1 X-PUSH\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let mut buf = BytesMut::new();
+        msg.write_to(&mut buf).unwrap();
+        let reparsed = super::Parser::parse(&buf).unwrap();
+        let request = reparsed.request().unwrap();
+        let cseq = request.headers.iter().find_map(Header::as_c_seq).unwrap();
+
+        assert_eq!(request.req_line.method.as_str(), "X-PUSH");
+        assert_eq!(*cseq.method(), request.req_line.method);
+    }
+
+    #[test]
+    fn test_parse_mixed_line_terminators() {
+        let src = b"MESSAGE sip:bob@biloxi.com SIP/2.0\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+To: Bob <sip:bob@biloxi.com>\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710\n\
+CSeq: 1 MESSAGE\r\n\
+X-Custom: raw value\n\
+Subject: folded\n subject\r\n\
+Content-Type: text/plain\n\
+Content-Length: 7\n\
+\n\
+\nhello\n";
+
+        let (msg, mandatory) = super::Parser::new(&src[..])
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+
+        assert_eq!(mandatory.call_id.id(), "a84b4c76e66710");
+        assert_eq!(mandatory.cseq.cseq(), 1);
+        assert_eq!(mandatory.to.to_string(), "To: Bob <sip:bob@biloxi.com>");
+        let raw = msg
+            .headers()
+            .iter()
+            .find_map(Header::as_raw_header)
+            .unwrap();
+        assert_eq!(raw.to_string(), "X-Custom: raw value");
+        let subject = msg.headers().iter().find_map(Header::as_subject).unwrap();
+        assert_eq!(subject.to_string(), "Subject: folded subject");
+        assert_eq!(msg.body().map(|body| &body[..]), Some(&b"\nhello\n"[..]));
+    }
+
+    #[test]
+    fn test_parse_skips_leading_empty_lines() {
+        let src = b"\r\n\r\nINVITE sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710\r\n\
+CSeq: 314159 INVITE\r\n\
+Content-Length: 0\r\n\r\n";
+
+        let (msg, mandatory) = super::Parser::new(&src[..])
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+        let request = msg.request().unwrap();
+
+        assert_eq!(request.req_line.method, Method::Invite);
+        assert_eq!(request.req_line.uri.to_string(), "sip:bob@biloxi.com");
+        assert_eq!(mandatory.cseq.cseq(), 314159);
+    }
+
+    const MESSAGE_WITH_BODY: &[u8] = b"MESSAGE sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+To: Bob <sip:bob@biloxi.com>\r\n\
+From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+Call-ID: a84b4c76e66710\r\n\
+CSeq: 1 MESSAGE\r\n\
+Content-Type: text/plain\r\n\
+Content-Length: 12\r\n\
+\r\n\
+Hello Bob!\r\n";
+
+    #[test]
+    fn test_parse_headers_leaves_body_pending() {
+        let payload = bytes::Bytes::from_static(MESSAGE_WITH_BODY);
+        let (mut msg, body) = super::Parser::from_bytes(&payload)
+            .parse_sip_msg_headers()
+            .unwrap();
+
+        assert!(msg.body().is_none());
+        assert_eq!(msg.headers().len(), 7);
+        assert_eq!(body.as_bytes(), b"Hello Bob!\r\n");
+
+        msg.set_body(body.into_body());
+        assert_eq!(
+            msg.body().map(|body| &body[..]),
+            Some(&b"Hello Bob!\r\n"[..])
+        );
+    }
+
+    #[test]
+    fn test_parse_headers_without_content_type_has_empty_body() {
+        let src = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n\
+Call-ID: a84b4c76e66710\r\n\
+\r\n\
+ignored";
+        let (_, body) = super::Parser::new(src).parse_sip_msg_headers().unwrap();
+
+        assert!(body.is_empty());
+        assert!(body.into_body().is_none());
+    }
+
+    #[test]
+    fn test_full_parse_still_attaches_body() {
+        let msg = super::Parser::parse(MESSAGE_WITH_BODY).unwrap();
+
+        assert_eq!(
+            msg.body().map(|body| &body[..]),
+            Some(&b"Hello Bob!\r\n"[..])
+        );
+    }
+
+    #[test]
+    fn test_malformed_cseq_reports_header_and_offset() {
+        let src = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+CSeq: abc OPTIONS\r\n\r\n";
+
+        let Err(Error::ParseError(err)) = super::Parser::parse(src) else {
+            panic!("expected a parse error");
+        };
+        let cseq_start = src.windows(5).position(|w| w == b"CSeq:").unwrap();
+
+        assert_eq!(err.kind, ParseErrorKind::Header);
+        assert_eq!(err.header_name(), Some("CSeq"));
+        assert!(err.offset > cseq_start);
+        assert_eq!(err.position.line, 3);
+    }
+
+    #[test]
+    fn test_header_error_exposes_line_and_column() {
+        let src = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP bobspc.biloxi.com:5060;branch=z9hG4bKnashds7\r\n\
+Max-Forwards: 70\r\n\
+CSeq: abc OPTIONS\r\n\r\n";
+
+        let Err(Error::ParseError(err)) = super::Parser::parse(src) else {
+            panic!("expected a parse error");
+        };
+
+        assert_eq!(err.line(), 4);
+        assert_eq!(err.column(), 7);
+        assert_eq!(err.source_line.as_deref(), Some("CSeq: abc OPTIONS"));
+    }
+
+    #[test]
+    fn test_malformed_request_line_reports_start_line() {
+        let src = b"OPTIONS sip:bob@biloxi.com SIP/3.0\r\n\r\n";
+
+        let Err(Error::ParseError(err)) = super::Parser::parse(src) else {
+            panic!("expected a parse error");
+        };
+
+        assert_eq!(err.section, Some(MessageSection::StartLine));
+    }
+
+    #[test]
+    fn test_malformed_empty_line_reports_body() {
+        let src = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n\
+Max-Forwards: 70\r\n\rv=0\r\n";
+
+        let Err(Error::ParseError(err)) = super::Parser::parse(src) else {
+            panic!("expected a parse error");
+        };
+
+        assert_eq!(err.section, Some(MessageSection::Body));
+        assert_eq!(err.header_name(), None);
+    }
+
+    #[test]
+    fn test_parse_compact_header_forms() {
+        let src = b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+v: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+f: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+t: Bob <sip:bob@biloxi.com>\r\n\
+i: a84b4c76e66710@pc33.atlanta.com\r\n\
+CSeq: 314159 INVITE\r\n\
+m: <sip:alice@pc33.atlanta.com>\r\n\
+s: Lunch\r\n\
+k: 100rel\r\n\
+e: gzip\r\n\
+l: 0\r\n\
+c: application/sdp\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let headers = msg.headers();
+
+        assert_eq!(headers.len(), 11);
+        assert!(headers.iter().all(|h| h.as_raw_header().is_none()));
+        assert!(MandatoryHeaders::from_headers(headers).is_ok());
+    }
+
+    #[test]
+    fn test_parse_max_breadth_header() {
+        let src = b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+Max-Breadth: 60\r\n\
+Max-Forwards: 70\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let max_breadth = msg
+            .headers()
+            .iter()
+            .find_map(Header::as_max_breadth)
+            .unwrap();
+
+        assert_eq!(max_breadth.as_u32(), 60);
+    }
+
+    #[test]
+    fn test_compact_header_forms_are_case_insensitive() {
+        let src = b"SIP/2.0 200 OK\r\n\
+V: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+I: a84b4c76e66710@pc33.atlanta.com\r\n\
+L: 0\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let headers = msg.headers();
+
+        assert!(headers[0].as_via().is_some());
+        assert!(headers[1].as_call_id().is_some());
+        assert!(headers[2].as_content_length().is_some());
+    }
+
+    #[test]
+    fn test_parse_folded_header_value() {
+        let src = b"SIP/2.0 200 OK\r\n\
+Subject: Need more\r\n\tboxes\r\n\
+Content-Length: 0\r\n\r\n";
+
+        let msg = super::Parser::parse(src).unwrap();
+        let headers = msg.headers();
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].as_subject().unwrap().as_str(), "Need more boxes");
+    }
+
+    #[test]
+    fn test_invalid_uri_param_values_are_errors() {
+        let invalid_ttl = b"OPTIONS sip:bob@biloxi.com;ttl=abc SIP/2.0\r\n\
+CSeq: 1 OPTIONS\r\n\r\n";
+        let invalid_q = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n\
+Contact: <sip:bob@biloxi.com>;q=.7\r\n\r\n";
+
+        assert!(super::Parser::parse(invalid_ttl).is_err());
+        assert!(super::Parser::parse(invalid_q).is_err());
+    }
+
+    #[test]
+    fn test_request_line_method_is_case_sensitive() {
+        let parse = |src: &[u8]| super::Parser::new(src).parse_request_line();
+
+        assert!(parse(b"invite sip:bob@biloxi.com SIP/2.0\r\n").is_err());
+        assert!(parse(b"Invite sip:bob@biloxi.com SIP/2.0\r\n").is_err());
+        assert!(parse(b" sip:bob@biloxi.com SIP/2.0\r\n").is_err());
+
+        let req_line = parse(b"PUBLISH sip:bob@biloxi.com SIP/2.0\r\n").unwrap();
+        assert_eq!(req_line.method, Method::Publish);
+
+        let req_line = parse(b"X-FOO.BAR sip:bob@biloxi.com SIP/2.0\r\n").unwrap();
+        assert_eq!(req_line.method.as_str(), "X-FOO.BAR");
+    }
+
+    #[test]
+    fn test_long_method_is_kept() {
+        let method = "A".repeat(64);
+        let src = format!("{method} sip:bob@biloxi.com SIP/2.0\r\n");
+
+        let req_line = super::Parser::new(src.as_bytes())
+            .parse_request_line()
+            .unwrap();
+
+        assert_eq!(req_line.method.as_str(), method);
+    }
+
+    #[test]
+    fn test_header_capacity_hint_counts_header_lines() {
+        assert_eq!(
+            super::Parser::new(MESSAGE_WITH_BODY).header_capacity_hint(),
+            7
+        );
+        assert_eq!(
+            super::Parser::new(
+                b"OPTIONS sip:bob@biloxi.com SIP/2.0\nCall-ID: a\nCSeq: 1 OPTIONS\n\nbody\n"
+            )
+            .header_capacity_hint(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_header_capacity_hint_is_capped() {
+        let mut message = b"OPTIONS sip:bob@biloxi.com SIP/2.0\r\n".to_vec();
+        message.extend(b"X-Header: a\r\n".repeat(1000));
+        message.extend(b"\r\n");
+
+        assert_eq!(
+            super::Parser::new(&message).header_capacity_hint(),
+            super::MAX_HEADER_CAPACITY_HINT
+        );
+    }
 }
@@ -0,0 +1,559 @@
+//! Proxy functionalities.
+//!
+//! The module provides the [`StatelessProxy`] handler, that forwards requests
+//! to the next hop without keeping any transaction state (RFC 3261 §16.11).
+
+use std::net::SocketAddr;
+
+use crate::endpoint::{Endpoint, EndpointHandler};
+use crate::message::headers::{Header, Headers, MaxForwards, Via};
+use crate::message::{MandatoryHeaders, Request, StatusCode};
+use crate::transport::Transport;
+use crate::transport::incoming::{IncomingInfo, IncomingRequest, IncomingResponse};
+use crate::transport::outgoing::{OutgoingResponse, TargetTransportInfo};
+use crate::{Method, RFC3261_BRANCH_ID, Result, find_map_mut_header};
+
+/// A stateless proxy.
+///
+/// Every request received is forwarded to the next hop resolved from the
+/// `Route` set or `Request-URI`, with a new `Via` on top. Responses have the
+/// topmost `Via` removed and are sent back to the address in the next `Via`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use csip::Endpoint;
+/// # use csip::proxy::StatelessProxy;
+/// let endpoint = Endpoint::builder()
+///     .with_handler(StatelessProxy::new())
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct StatelessProxy;
+
+impl StatelessProxy {
+    /// Creates a new `StatelessProxy`.
+    pub fn new() -> Self {
+        Self
+    }
+
+    async fn forward_request(&self, request: IncomingRequest, endpoint: &Endpoint) -> Result<()> {
+        let IncomingRequest {
+            mut request,
+            incoming_info,
+            ..
+        } = request;
+        let loop_hash = loop_detection_hash(&request, &incoming_info.mandatory_headers);
+        let headers = &mut request.headers;
+
+        // 16.6 Request Forwarding - 3. Max-Forwards
+        match find_map_mut_header!(headers, MaxForwards) {
+            Some(max_forwards) if max_forwards.max_fowards() == 0 => {
+                return reject(request, incoming_info, endpoint, |incoming| {
+                    endpoint.create_outgoing_response(incoming, StatusCode::TooManyHops, None)
+                })
+                .await;
+            }
+            Some(max_forwards) => {
+                *max_forwards = MaxForwards::new(max_forwards.max_fowards() - 1);
+            }
+            None => headers.push(Header::MaxForwards(MaxForwards::new(70))),
+        }
+
+        // 16.3 Request Validation - 4. Optional Loop Detection
+        if is_looped(endpoint, headers, &loop_hash) {
+            return reject(request, incoming_info, endpoint, |incoming| {
+                endpoint.create_outgoing_response(incoming, StatusCode::LoopDetected, None)
+            })
+            .await;
+        }
+
+        // 16.3 Request Validation - 5. Proxy-Require
+        let required = headers
+            .iter()
+            .filter_map(|h| h.as_proxy_require())
+            .flat_map(|r| r.iter());
+        let unsupported = endpoint.unsupported_option_tags(required);
+        if !unsupported.is_empty() {
+            return reject(request, incoming_info, endpoint, |incoming| {
+                endpoint.create_bad_extension_response(incoming, unsupported)
+            })
+            .await;
+        }
+
+        // RFC 5393 5.3.2 Max-Breadth
+        // Forwarding is a single branch, which a breadth of 0 leaves no
+        // room for.
+        let max_breadth = headers.iter().find_map(|h| h.as_max_breadth());
+        if max_breadth.is_some_and(|max_breadth| max_breadth.split(1).is_none()) {
+            return reject(request, incoming_info, endpoint, |incoming| {
+                endpoint.create_outgoing_response(incoming, StatusCode::MaxBreadthExceeded, None)
+            })
+            .await;
+        }
+
+        // 18.2.1 Receiving Requests
+        // The received parameter is needed to route the response back.
+        if let Some(via) = find_map_mut_header!(headers, Via) {
+            via.received = incoming_info.mandatory_headers.via.received;
+        }
+
+        let mut outgoing = endpoint.create_outgoing_request(request, None).await?;
+        let TargetTransportInfo { transport, .. } = &outgoing.target_info;
+
+        // 16.11 Stateless Proxy
+        // The branch must be the same for retransmissions of a request, so it
+        // is computed from the topmost Via of the received request.
+        let branch = compute_branch(&incoming_info.mandatory_headers.via, &loop_hash);
+        let via = Via::new_with_transport(
+            transport.transport_type(),
+            endpoint.local_name(transport),
+            Some(branch),
+        );
+        outgoing.request.headers.push_front(Header::Via(via));
+
+        endpoint.send_outgoing_request(&mut outgoing).await
+    }
+
+    async fn forward_response(
+        &self,
+        response: IncomingResponse,
+        endpoint: &Endpoint,
+    ) -> Result<()> {
+        let IncomingResponse {
+            mut response,
+            incoming_info,
+        } = response;
+        let headers = response.headers_mut();
+
+        // 16.11 Stateless Proxy
+        // The proxy removes the topmost Via and forwards the response using
+        // the next Via value. A response whose topmost Via was not placed by
+        // us is dropped (16.7 step 3).
+        let Some(index) = headers.iter().position(|h| matches!(h, Header::Via(_))) else {
+            return Ok(());
+        };
+        let Ok(transports) = endpoint.transports().all() else {
+            return Ok(());
+        };
+        if !matches!(&headers[index], Header::Via(via) if is_local_via(endpoint, &transports, via))
+        {
+            log::debug!("Dropping response whose topmost Via is not ours");
+            return Ok(());
+        }
+        headers.remove(index);
+
+        let Some(via) = headers.iter().find_map(|h| h.as_via()) else {
+            log::debug!("Dropping response with no Via left, it was addressed to us");
+            return Ok(());
+        };
+
+        let transport = &incoming_info.transport.transport;
+        let (target, transport) = self.response_target(via, transport, endpoint).await?;
+
+        let mut outgoing = OutgoingResponse {
+            response,
+            target_info: TargetTransportInfo { target, transport },
+            encoded: Default::default(),
+        };
+
+        endpoint.send_outgoing_response(&mut outgoing).await
+    }
+
+    async fn response_target(
+        &self,
+        via: &Via,
+        transport: &Transport,
+        endpoint: &Endpoint,
+    ) -> Result<(SocketAddr, Transport)> {
+        if !via.transport.is_reliable() && !transport.is_reliable() {
+            return endpoint.get_outbound_addr(via, transport).await;
+        }
+        let ip = match via.received {
+            Some(ip) => ip,
+            None => endpoint.lookup_address(&via.sent_by.host).await?,
+        };
+        let port = via
+            .rport
+            .or(via.sent_by.port)
+            .unwrap_or(via.transport.default_port());
+        let addr = SocketAddr::new(ip, port);
+        let transport = endpoint
+            .transports()
+            .get_or_create_transport(via.transport, addr, endpoint)
+            .await?;
+
+        Ok((addr, transport))
+    }
+}
+
+#[async_trait::async_trait]
+impl EndpointHandler for StatelessProxy {
+    async fn handle(&self, request: IncomingRequest, endpoint: &Endpoint) {
+        if let Err(err) = self.forward_request(request, endpoint).await {
+            log::warn!("Failed to forward request: {}", err);
+        }
+    }
+
+    async fn handle_response(&self, response: IncomingResponse, endpoint: &Endpoint) {
+        if let Err(err) = self.forward_response(response, endpoint).await {
+            log::warn!("Failed to forward response: {}", err);
+        }
+    }
+}
+
+// Responds to a request that can not be forwarded with the response built
+// by `create`. An ACK is dropped, it is never responded to.
+async fn reject<F>(
+    request: Request,
+    incoming_info: Box<IncomingInfo>,
+    endpoint: &Endpoint,
+    create: F,
+) -> Result<()>
+where
+    F: FnOnce(&IncomingRequest) -> OutgoingResponse,
+{
+    if request.req_line.method == Method::Ack {
+        return Ok(());
+    }
+    let incoming = IncomingRequest {
+        request,
+        incoming_info,
+        endpoint: Some(endpoint.clone()),
+    };
+    let mut response = create(&incoming);
+
+    endpoint.send_outgoing_response(&mut response).await
+}
+
+// The first component identifies the transaction and the second one, after
+// the '.', the values checked for loops (RFC 3261 16.11).
+fn compute_branch(via: &Via, loop_hash: &str) -> String {
+    let hash = Fnv1a::new()
+        .field(via.branch.as_deref().unwrap_or_default())
+        .field(via.sent_by.to_string())
+        .finish();
+
+    format!("{}{:x}.{}", RFC3261_BRANCH_ID, hash, loop_hash)
+}
+
+// Hashes the fields that stay the same when a request loops back to this
+// proxy (RFC 3261 16.6 step 8). A spiral, where the Request-URI changed,
+// gets a different hash.
+fn loop_detection_hash(request: &Request, mandatory_headers: &MandatoryHeaders) -> String {
+    let mut hasher = Fnv1a::new();
+
+    hasher
+        .field(request.req_line.uri.to_string())
+        .field(mandatory_headers.from.tag().as_deref().unwrap_or_default())
+        .field(mandatory_headers.to.tag().as_deref().unwrap_or_default())
+        .field(mandatory_headers.call_id.id())
+        .field(mandatory_headers.cseq.cseq().to_be_bytes());
+    request
+        .headers
+        .iter()
+        .filter(|h| {
+            matches!(
+                h,
+                Header::Route(_) | Header::ProxyRequire(_) | Header::ProxyAuthorization(_)
+            )
+        })
+        .for_each(|h| {
+            hasher.field(h.to_string());
+        });
+
+    format!("{:x}", hasher.finish())
+}
+
+// The 64-bit FNV-1a hash. Unlike `DefaultHasher`, its output is fixed by
+// its definition, so the branches and loop hashes computed by different
+// builds of the proxy are the same.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> &mut Self {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+        self
+    }
+
+    // Hashes `bytes` followed by a separator, so that adjacent fields can
+    // not run into each other.
+    fn field(&mut self, bytes: impl AsRef<[u8]>) -> &mut Self {
+        self.write(bytes.as_ref()).write(&[0xff])
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Returns `true` if the sent-by of `via` is the address advertised for
+/// one of `transports`, which means the `Via` was placed by this proxy.
+fn is_local_via(endpoint: &Endpoint, transports: &[Transport], via: &Via) -> bool {
+    transports.iter().any(|transport| {
+        via.sent_by.matches(
+            &endpoint.local_name(transport),
+            via.transport.default_port(),
+        )
+    })
+}
+
+/// Returns `true` if a `Via` placed by this proxy carries `loop_hash`,
+/// which means the request was forwarded by us before, unchanged.
+fn is_looped(endpoint: &Endpoint, headers: &Headers, loop_hash: &str) -> bool {
+    let Ok(transports) = endpoint.transports().all() else {
+        return false;
+    };
+
+    headers
+        .iter()
+        .filter_map(|h| h.as_via())
+        .filter(|via| is_local_via(endpoint, &transports, via))
+        .filter_map(|via| via.branch.as_deref())
+        .any(|branch| {
+            branch
+                .rsplit_once('.')
+                .is_some_and(|(_, hash)| hash == loop_hash)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::endpoint::EndpointBuilder;
+    use crate::message::headers::{MaxBreadth, ProxyRequire};
+    use crate::message::{MandatoryHeaders, Response, SipMessage, StatusLine, Uri};
+    use crate::test_utils::create_test_request;
+    use crate::test_utils::transport::MockTransport;
+    use crate::transport::incoming::IncomingInfo;
+    use crate::transport::{Packet, TransportMessage};
+
+    fn setup() -> (Endpoint, MockTransport) {
+        let mock = MockTransport::new_udp();
+        let endpoint = EndpointBuilder::new()
+            .with_handler(StatelessProxy::new())
+            .build();
+
+        endpoint
+            .transports()
+            .register_transport(Transport::new(mock.clone()))
+            .unwrap();
+
+        (endpoint, mock)
+    }
+
+    fn count_vias(headers: &Headers) -> usize {
+        headers
+            .iter()
+            .filter(|h| matches!(h, Header::Via(_)))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_forward_request_and_response() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let mut incoming = create_test_request(Method::Options, transport.clone());
+        incoming.request.req_line.uri = Uri::from_str("sip:bob@127.0.0.1:5070").unwrap();
+        incoming.incoming_info.mandatory_headers.via.received = Some([127, 0, 0, 1].into());
+
+        endpoint.process_request(incoming).await.unwrap();
+
+        let forwarded = mock.get_last_sent_request().expect("a forwarded request");
+        assert_eq!(count_vias(&forwarded.headers), 2);
+        let max_forwards = forwarded
+            .headers
+            .iter()
+            .find_map(|h| h.as_max_forwards())
+            .unwrap();
+        assert_eq!(max_forwards.max_fowards(), 69);
+
+        let status_line = StatusLine::new(StatusCode::Ok, StatusCode::Ok.reason());
+        let response = Response::with_headers(status_line, forwarded.headers.clone());
+        let mandatory_headers = MandatoryHeaders::from_headers(response.headers()).unwrap();
+        let packet = Packet::new(Default::default(), "127.0.0.1:5070".parse().unwrap());
+        let incoming = IncomingResponse {
+            response,
+            incoming_info: Box::new(IncomingInfo {
+                mandatory_headers,
+                transport: TransportMessage { transport, packet },
+            }),
+        };
+
+        endpoint.process_response(incoming).await.unwrap();
+
+        let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+            panic!("expected a forwarded response");
+        };
+        assert_eq!(count_vias(response.headers()), 1);
+        let via = response.headers().iter().find_map(|h| h.as_via()).unwrap();
+        assert_ne!(via.branch, forwarded.headers[0].as_via().unwrap().branch);
+    }
+
+    #[tokio::test]
+    async fn test_drops_response_with_foreign_topmost_via() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let request = create_test_request(Method::Options, transport.clone());
+        let mut headers = request.request.headers;
+        // Only the topmost Via is foreign, the next one names a client.
+        let client = find_map_mut_header!(headers, Via).unwrap();
+        *client = Via::from_str("SIP/2.0/UDP 127.0.0.1:5070;branch=z9hG4bK1").unwrap();
+        let foreign =
+            Via::from_str("SIP/2.0/UDP evil.example.com:5060;branch=z9hG4bKevil").unwrap();
+        headers.push_front(Header::Via(foreign));
+
+        let status_line = StatusLine::new(StatusCode::Ok, StatusCode::Ok.reason());
+        let response = Response::with_headers(status_line, headers);
+        let mandatory_headers = MandatoryHeaders::from_headers(response.headers()).unwrap();
+        let packet = Packet::new(Default::default(), "127.0.0.1:5070".parse().unwrap());
+        let incoming = IncomingResponse {
+            response,
+            incoming_info: Box::new(IncomingInfo {
+                mandatory_headers,
+                transport: TransportMessage { transport, packet },
+            }),
+        };
+
+        endpoint.process_response(incoming).await.unwrap();
+
+        assert_eq!(mock.sent_count(), 0);
+    }
+
+    #[test]
+    fn test_fnv1a_matches_reference_values() {
+        assert_eq!(Fnv1a::new().finish(), 0xcbf29ce484222325);
+        assert_eq!(Fnv1a::new().write(b"a").finish(), 0xaf63dc4c8601ec8c);
+        assert_eq!(Fnv1a::new().write(b"foobar").finish(), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_branch_is_stable() {
+        let via = Via::from_str("SIP/2.0/UDP client.atlanta.com:5060;branch=z9hG4bK74bf9").unwrap();
+
+        assert_eq!(compute_branch(&via, "abc"), "z9hG4bK13b89460b6eee7a4.abc");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_request_with_max_forwards_zero() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let mut incoming = create_test_request(Method::Options, transport);
+        incoming.incoming_info.mandatory_headers.via.received = Some([127, 0, 0, 1].into());
+        let max_forwards = find_map_mut_header!(incoming.request.headers, MaxForwards).unwrap();
+        *max_forwards = MaxForwards::new(0);
+
+        endpoint.process_request(incoming).await.unwrap();
+
+        let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.status(), StatusCode::TooManyHops);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_request_with_max_breadth_exceeded() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let mut incoming = create_test_request(Method::Options, transport);
+        incoming.incoming_info.mandatory_headers.via.received = Some([127, 0, 0, 1].into());
+        incoming
+            .request
+            .headers
+            .push(Header::MaxBreadth(MaxBreadth::new(0)));
+
+        endpoint.process_request(incoming).await.unwrap();
+
+        let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.status(), StatusCode::MaxBreadthExceeded);
+    }
+
+    fn forwarded_options(mock: &MockTransport) -> Vec<u8> {
+        mock.last_buffer().expect("a forwarded request")
+    }
+
+    #[tokio::test]
+    async fn test_rejects_looped_request() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let mut incoming = create_test_request(Method::Options, transport.clone());
+        incoming.request.req_line.uri = Uri::from_str("sip:bob@127.0.0.1:5070").unwrap();
+        incoming.incoming_info.mandatory_headers.via.received = Some([127, 0, 0, 1].into());
+        endpoint.process_request(incoming).await.unwrap();
+
+        // The next hop routes the request back to us.
+        let forwarded = forwarded_options(&mock);
+        let source = "127.0.0.1:5070".parse().unwrap();
+        crate::testing::receive(&endpoint, &transport, source, forwarded)
+            .await
+            .unwrap();
+
+        let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.status(), StatusCode::LoopDetected);
+    }
+
+    #[tokio::test]
+    async fn test_forwards_spiraled_request() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let mut incoming = create_test_request(Method::Options, transport.clone());
+        incoming.request.req_line.uri = Uri::from_str("sip:bob@127.0.0.1:5070").unwrap();
+        incoming.incoming_info.mandatory_headers.via.received = Some([127, 0, 0, 1].into());
+        endpoint.process_request(incoming).await.unwrap();
+
+        // The next hop retargets the request and sends it back to us.
+        let forwarded = String::from_utf8(forwarded_options(&mock)).unwrap();
+        let spiraled = forwarded.replacen("sip:bob@", "sip:carol@", 1);
+        let source = "127.0.0.1:5070".parse().unwrap();
+        crate::testing::receive(&endpoint, &transport, source, spiraled)
+            .await
+            .unwrap();
+
+        let request = mock.get_last_sent_request().expect("a forwarded request");
+        assert_eq!(request.req_line.uri.to_string(), "sip:carol@127.0.0.1:5070");
+        assert_eq!(count_vias(&request.headers), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_request_with_unsupported_proxy_require() {
+        let (endpoint, mock) = setup();
+        let transport = Transport::new(mock.clone());
+        let mut incoming = create_test_request(Method::Options, transport);
+        incoming.incoming_info.mandatory_headers.via.received = Some([127, 0, 0, 1].into());
+        let mut proxy_require = ProxyRequire::default();
+        proxy_require.add_tag("foo");
+        incoming
+            .request
+            .headers
+            .push(Header::ProxyRequire(proxy_require));
+
+        endpoint.process_request(incoming).await.unwrap();
+
+        let Some(SipMessage::Response(response)) = mock.get_last_sent_message() else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.status(), StatusCode::BadExtension);
+        let unsupported = response
+            .headers()
+            .iter()
+            .find_map(|h| h.as_unsupported())
+            .unwrap();
+        assert_eq!(unsupported.iter().collect::<Vec<_>>(), ["foo"]);
+    }
+}
@@ -37,7 +37,7 @@ fn create_test_headers(method: Method) -> Headers {
 }
 
 pub fn create_test_request(method: Method, transport: Transport) -> IncomingRequest {
-    let headers = create_test_headers(method);
+    let headers = create_test_headers(method.clone());
     let target = format!("sip:{}", transport.local_addr());
     let uri = Uri::from_str(&target).unwrap();
 
@@ -56,6 +56,7 @@ pub fn create_test_request(method: Method, transport: Transport) -> IncomingRequ
     IncomingRequest {
         request,
         incoming_info: Box::new(incoming_info),
+        endpoint: None,
     }
 }
 
@@ -121,11 +122,12 @@ pub mod transaction {
     use super::transport::MockTransport;
     use super::{create_test_endpoint, create_test_request};
     use crate::endpoint::Endpoint;
-    use crate::message::{Method, Request, StatusCode};
+    use crate::message::{MandatoryHeaders, Method, Request, StatusCode};
     use crate::transaction::client::ClientTransaction;
     use crate::transaction::fsm::{self};
     use crate::transaction::{ServerTransaction, T1, T2, T4, TransactionMessage};
     use crate::transport::incoming::{IncomingInfo, IncomingRequest, IncomingResponse};
+    use crate::transport::outgoing::OutgoingResponse;
     use crate::transport::{Packet, Transport, TransportMessage};
 
     pub const CODE_100_TRYING: StatusCode = StatusCode::Trying;
@@ -162,10 +164,16 @@ pub mod transaction {
 
     impl FakeUAS {
         pub async fn respond(&self, code: StatusCode) {
-            let mandatory_headers = self.request.incoming_info.mandatory_headers.clone();
             let outgoing = self
                 .endpoint
                 .create_outgoing_response(&self.request, code, None);
+
+            self.respond_with(outgoing).await;
+        }
+
+        pub async fn respond_with(&self, outgoing: OutgoingResponse) {
+            let mandatory_headers =
+                MandatoryHeaders::from_headers(outgoing.response.headers()).unwrap();
             let packet = Packet::new(outgoing.encoded, outgoing.target_info.target);
 
             let transport = TransportMessage {
@@ -315,7 +323,7 @@ pub mod transaction {
             let timer = TestTimer::new();
 
             let endpoint = create_test_endpoint();
-            let request = create_test_request(method, transport_impl.clone());
+            let request = create_test_request(method.clone(), transport_impl.clone());
 
             let destination = request.incoming_info.transport.packet.source;
 
@@ -385,6 +393,12 @@ pub mod transaction {
             let transport_impl = Transport::new(transport.clone());
 
             let endpoint = create_test_endpoint();
+            // Responses go back over the connection of the request,
+            // so it must be registered like an accepted one.
+            endpoint
+                .transports()
+                .register_transport(transport_impl.clone())
+                .unwrap();
             let request = create_test_request(method, transport_impl);
 
             let mut server = ServerTransaction::new(request.clone(), endpoint.clone());
@@ -412,110 +426,5 @@ pub mod transaction {
 }
 
 pub mod transport {
-    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-    use std::sync::{Arc, Mutex};
-
-    use crate::message::{Request, SipMessage};
-    use crate::parser::Parser;
-    use crate::transport::{SipTransport, TransportType};
-
-    /// A mock transport, for testing purposes
-    #[derive(Clone)]
-    pub struct MockTransport {
-        sent: Arc<Mutex<Vec<(Vec<u8>, SocketAddr)>>>,
-        addr: SocketAddr,
-        tp_type: TransportType,
-        fail_at: Option<usize>,
-    }
-
-    impl MockTransport {
-        pub fn with_transport_type(tp_type: TransportType) -> Self {
-            let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
-            let port = tp_type.default_port();
-            let mock = Self {
-                sent: Default::default(),
-                addr: SocketAddr::new(ip, port),
-                tp_type,
-                fail_at: None,
-            };
-
-            mock
-        }
-
-        pub fn new_udp() -> Self {
-            Self::with_transport_type(TransportType::Udp)
-        }
-
-        pub fn new_tcp() -> Self {
-            Self::with_transport_type(TransportType::Tcp)
-        }
-
-        pub fn new_tls() -> Self {
-            Self::with_transport_type(TransportType::Tls)
-        }
-
-        pub fn sent_count(&self) -> usize {
-            self.sent.lock().unwrap().len()
-        }
-
-        pub fn get_last_sent_request(&self) -> Option<Request> {
-            self.get_last_sent_message().map(|msg| {
-                if let SipMessage::Request(req) = msg {
-                    Some(req)
-                } else {
-                    None
-                }
-            })?
-        }
-
-        pub fn last_buffer(&self) -> Option<Vec<u8>> {
-            let guard = self.sent.lock().unwrap();
-            guard.last().map(|(buff, _)| buff).cloned()
-        }
-
-        pub fn get_last_sent_message(&self) -> Option<SipMessage> {
-            self.last_buffer().map(|b| Parser::parse(&b).unwrap())
-        }
-
-        fn push_msg(&self, (buf_vec, address): (Vec<u8>, SocketAddr)) -> usize {
-            let mut guard = self.sent.lock().unwrap();
-            guard.push((buf_vec, address));
-            guard.len()
-        }
-    }
-
-    #[async_trait::async_trait]
-    impl SipTransport for MockTransport {
-        async fn send_msg(&self, buf: &[u8], address: &SocketAddr) -> crate::Result<usize> {
-            let current_count = self.push_msg((buf.to_vec(), *address));
-
-            if let Some(fail_at) = self.fail_at
-                && fail_at == current_count
-            {
-                return Err(crate::Error::TransportError("Simulated failure".into()));
-            }
-
-            Ok(buf.len())
-        }
-
-        fn remote_addr(&self) -> Option<SocketAddr> {
-            None
-        }
-
-        fn transport_type(&self) -> TransportType {
-            self.tp_type
-        }
-
-        fn local_addr(&self) -> SocketAddr {
-            self.addr
-        }
-
-        fn is_reliable(&self) -> bool {
-            self.tp_type.is_reliable()
-        }
-
-        fn is_secure(&self) -> bool {
-            self.tp_type.is_secure()
-        }
-    }
+    pub use crate::testing::MockTransport;
 }
@@ -0,0 +1,390 @@
+//! Utilities to test code built on top of the [`Endpoint`],
+//! such as an [`EndpointHandler`](crate::EndpointHandler),
+//! without opening sockets.
+//!
+//! Requires the `testing` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use csip::Method;
+//! use csip::testing::{self, MockTransport};
+//! use csip::transport::Transport;
+//!
+//! let transport = Transport::new(MockTransport::new_udp());
+//! let source = "127.0.0.1:5070".parse().unwrap();
+//! let request = testing::incoming_request(
+//!     &transport,
+//!     source,
+//!     "OPTIONS sip:bob@127.0.0.1 SIP/2.0\r\n\
+//!      Via: SIP/2.0/UDP 127.0.0.1:5070;branch=z9hG4bK776asdhds\r\n\
+//!      From: <sip:alice@127.0.0.1>;tag=1928301774\r\n\
+//!      To: <sip:bob@127.0.0.1>\r\n\
+//!      Call-ID: a84b4c76e66710\r\n\
+//!      CSeq: 1 OPTIONS\r\n\r\n",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(request.request.req_line.method, Method::Options);
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::endpoint::Endpoint;
+use crate::error::{Error, Result};
+use crate::message::{Request, Response, SipMessage};
+use crate::parser::Parser;
+use crate::transaction::{Sleep, Timer};
+use crate::transport::incoming::{IncomingInfo, IncomingRequest, IncomingResponse};
+use crate::transport::{Packet, SipTransport, Transport, TransportMessage, TransportType};
+
+/// The buffers sent through a [`MockTransport`], with their
+/// destinations.
+type SentBuffers = Arc<Mutex<Vec<(Vec<u8>, SocketAddr)>>>;
+
+/// A mock transport that records the messages sent through it
+/// instead of sending them.
+///
+/// Clones share the recorded messages, so a clone can be kept
+/// to inspect what was sent through a [`Transport`] created
+/// from the mock.
+#[derive(Clone)]
+pub struct MockTransport {
+    sent: SentBuffers,
+    addr: SocketAddr,
+    remote: Option<SocketAddr>,
+    tp_type: TransportType,
+    fail_at: Option<usize>,
+    transient_failures: Arc<AtomicUsize>,
+    multicast_ttl: Arc<Mutex<Option<u32>>>,
+}
+
+impl MockTransport {
+    /// Creates a mock transport of the given type, bound to the
+    /// default port of the transport on localhost.
+    pub fn with_transport_type(tp_type: TransportType) -> Self {
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let port = tp_type.default_port();
+
+        Self {
+            sent: Default::default(),
+            addr: SocketAddr::new(ip, port),
+            remote: None,
+            tp_type,
+            fail_at: None,
+            transient_failures: Default::default(),
+            multicast_ttl: Default::default(),
+        }
+    }
+
+    /// Creates an UDP mock transport.
+    pub fn new_udp() -> Self {
+        Self::with_transport_type(TransportType::Udp)
+    }
+
+    /// Creates a TCP mock transport.
+    pub fn new_tcp() -> Self {
+        Self::with_transport_type(TransportType::Tcp)
+    }
+
+    /// Creates a TLS mock transport.
+    pub fn new_tls() -> Self {
+        Self::with_transport_type(TransportType::Tls)
+    }
+
+    /// Sets the address the mock is bound to.
+    pub fn with_local_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// Sets the remote address, making the mock behave like a
+    /// connection.
+    pub fn with_remote_addr(mut self, remote: SocketAddr) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    /// Makes the `nth` send fail with a transport error, after
+    /// the message is recorded.
+    pub fn with_failure_at(mut self, nth: usize) -> Self {
+        self.fail_at = Some(nth);
+        self
+    }
+
+    /// Makes the next `count` sends fail with `WouldBlock`,
+    /// without sending anything.
+    pub fn with_transient_failures(self, count: usize) -> Self {
+        self.transient_failures.store(count, Ordering::SeqCst);
+        self
+    }
+
+    /// Returns the number of messages sent.
+    pub fn sent_count(&self) -> usize {
+        self.sent.lock().unwrap().len()
+    }
+
+    /// Returns the SIP messages sent, in order.
+    ///
+    /// Buffers that are not SIP messages, such as keep-alive
+    /// `CRLF`s, are skipped.
+    pub fn sent_messages(&self) -> Vec<SipMessage> {
+        let guard = self.sent.lock().unwrap();
+        guard
+            .iter()
+            .filter_map(|(buf, _)| Parser::parse(buf).ok())
+            .collect()
+    }
+
+    /// Returns the last request sent, if the last SIP message
+    /// sent is a request.
+    pub fn get_last_sent_request(&self) -> Option<Request> {
+        match self.get_last_sent_message()? {
+            SipMessage::Request(req) => Some(req),
+            SipMessage::Response(_) => None,
+        }
+    }
+
+    /// Returns the last response sent, if the last SIP message
+    /// sent is a response.
+    pub fn get_last_sent_response(&self) -> Option<Response> {
+        match self.get_last_sent_message()? {
+            SipMessage::Response(res) => Some(res),
+            SipMessage::Request(_) => None,
+        }
+    }
+
+    /// The ttl of the last multicast send, if any.
+    pub fn last_multicast_ttl(&self) -> Option<u32> {
+        *self.multicast_ttl.lock().unwrap()
+    }
+
+    /// Returns the raw bytes of the last message sent.
+    pub fn last_buffer(&self) -> Option<Vec<u8>> {
+        let guard = self.sent.lock().unwrap();
+        guard.last().map(|(buff, _)| buff).cloned()
+    }
+
+    /// Returns the last SIP message sent, skipping buffers that
+    /// are not SIP messages, such as keep-alive `CRLF`s.
+    pub fn get_last_sent_message(&self) -> Option<SipMessage> {
+        let guard = self.sent.lock().unwrap();
+        guard
+            .iter()
+            .rev()
+            .find_map(|(buf, _)| Parser::parse(buf).ok())
+    }
+
+    fn push_msg(&self, (buf_vec, address): (Vec<u8>, SocketAddr)) -> usize {
+        let mut guard = self.sent.lock().unwrap();
+        guard.push((buf_vec, address));
+        guard.len()
+    }
+}
+
+#[async_trait::async_trait]
+impl SipTransport for MockTransport {
+    async fn send_msg(&self, buf: &[u8], address: &SocketAddr) -> Result<usize> {
+        let failures = &self.transient_failures;
+        if failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            return Err(std::io::Error::from(std::io::ErrorKind::WouldBlock).into());
+        }
+        let current_count = self.push_msg((buf.to_vec(), *address));
+
+        if let Some(fail_at) = self.fail_at
+            && fail_at == current_count
+        {
+            return Err(Error::TransportError("Simulated failure".into()));
+        }
+
+        Ok(buf.len())
+    }
+
+    async fn send_multicast(&self, buf: &[u8], address: &SocketAddr, ttl: u32) -> Result<usize> {
+        *self.multicast_ttl.lock().unwrap() = Some(ttl);
+        self.send_msg(buf, address).await
+    }
+
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote
+    }
+
+    fn transport_type(&self) -> TransportType {
+        self.tp_type
+    }
+
+    fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    fn is_reliable(&self) -> bool {
+        self.tp_type.is_reliable()
+    }
+
+    fn is_secure(&self) -> bool {
+        self.tp_type.is_secure()
+    }
+}
+
+/// A [`Timer`] whose clock only moves when
+/// [`advance`](MockTimer::advance) is called.
+///
+/// Clones share the clock, so a clone can be kept to drive the
+/// timers of an [`Endpoint`] built
+/// [`with_timer`](crate::endpoint::EndpointBuilder::with_timer).
+#[derive(Clone)]
+pub struct MockTimer {
+    clock: Arc<Mutex<MockClock>>,
+}
+
+struct MockClock {
+    now: Instant,
+    sleepers: Vec<(Instant, oneshot::Sender<()>)>,
+}
+
+impl MockTimer {
+    /// Creates a mock timer starting at the current instant.
+    pub fn new() -> Self {
+        Self {
+            clock: Arc::new(Mutex::new(MockClock {
+                now: Instant::now(),
+                sleepers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Moves the clock forward by `duration`, firing the timers
+    /// whose deadline was reached.
+    pub fn advance(&self, duration: Duration) {
+        let mut clock = self.clock.lock().unwrap();
+        clock.now += duration;
+
+        let now = clock.now;
+        let (due, pending) = std::mem::take(&mut clock.sleepers)
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        clock.sleepers = pending;
+
+        for (_, sleeper) in due {
+            let _ = sleeper.send(());
+        }
+    }
+}
+
+impl Default for MockTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timer for MockTimer {
+    fn now(&self) -> Instant {
+        self.clock.lock().unwrap().now
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        let mut clock = self.clock.lock().unwrap();
+        if deadline <= clock.now {
+            return Box::pin(std::future::ready(()));
+        }
+        let (sender, receiver) = oneshot::channel();
+        clock.sleepers.push((deadline, sender));
+
+        Box::pin(async move {
+            if receiver.await.is_err() {
+                // The clock was dropped, it will never move again.
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+}
+
+/// Delivers `data` to the `endpoint` as if it was received by
+/// `transport` from `source`.
+///
+/// Unlike packets received from the network, this waits until
+/// the endpoint has processed the message, so its effects can
+/// be asserted right after.
+pub async fn receive(
+    endpoint: &Endpoint,
+    transport: &Transport,
+    source: SocketAddr,
+    data: impl Into<Bytes>,
+) -> Result<()> {
+    let message = TransportMessage {
+        transport: transport.clone(),
+        packet: Packet::new(data.into(), source),
+    };
+    transport.record_received(message.packet.data.len());
+
+    endpoint.clone().process_transport_message(message).await
+}
+
+/// Parses `data` into an [`IncomingRequest`] received by
+/// `transport` from `source`.
+pub fn incoming_request(
+    transport: &Transport,
+    source: SocketAddr,
+    data: impl Into<Bytes>,
+) -> Result<IncomingRequest> {
+    let (msg, incoming_info) = incoming(transport, source, data.into())?;
+    let SipMessage::Request(request) = msg else {
+        return Err(Error::Other("expected a request".into()));
+    };
+
+    Ok(IncomingRequest {
+        request,
+        incoming_info,
+        endpoint: None,
+    })
+}
+
+/// Parses `data` into an [`IncomingResponse`] received by
+/// `transport` from `source`.
+pub fn incoming_response(
+    transport: &Transport,
+    source: SocketAddr,
+    data: impl Into<Bytes>,
+) -> Result<IncomingResponse> {
+    let (msg, incoming_info) = incoming(transport, source, data.into())?;
+    let SipMessage::Response(response) = msg else {
+        return Err(Error::Other("expected a response".into()));
+    };
+
+    Ok(IncomingResponse {
+        response,
+        incoming_info,
+    })
+}
+
+fn incoming(
+    transport: &Transport,
+    source: SocketAddr,
+    data: Bytes,
+) -> Result<(SipMessage, Box<IncomingInfo>)> {
+    let message = TransportMessage {
+        transport: transport.clone(),
+        packet: Packet::new(data, source),
+    };
+    let (msg, mut mandatory_headers) = message.parse_with_mandatory_headers()?;
+    // As done by the endpoint for received messages.
+    mandatory_headers.via.received = source.ip().into();
+
+    Ok((
+        msg,
+        Box::new(IncomingInfo {
+            mandatory_headers,
+            transport: message,
+        }),
+    ))
+}
@@ -1,18 +1,20 @@
 use std::net::SocketAddr;
 
 use tokio::sync::mpsc::{self};
-use tokio::time::{Instant, timeout, timeout_at};
+use tokio::time::Instant;
 use utils::PeekableReceiver;
 
+use crate::dialog::{Dialog, DialogId};
 use crate::error::TransactionError;
-use crate::message::Request;
 use crate::message::headers::{Header, Via};
+use crate::message::{CodeClass, Request};
 use crate::transaction::fsm::{State, StateMachine};
 use crate::transaction::manager::TransactionKey;
-use crate::transaction::{Role, T1, T4, TransactionMessage};
+use crate::transaction::{Role, TransactionMessage};
 use crate::transport::Transport;
 use crate::transport::incoming::IncomingResponse;
 use crate::transport::outgoing::OutgoingRequest;
+use crate::ua::UserAgent;
 use crate::{Endpoint, Method, Result, find_map_mut_header};
 
 // ACK para 2xx é responsabilidade do TU.
@@ -47,24 +49,31 @@ impl ClientTransaction {
         target: Option<(Transport, SocketAddr)>,
         endpoint: Endpoint,
     ) -> Result<Self> {
-        let method = request.req_line.method;
-        assert_ne!(
-            method,
-            Method::Ack,
+        let method = request.req_line.method.clone();
+        assert!(
+            method.creates_transaction(),
             "ACK requests do not create transactions"
         );
         let mut outgoing = endpoint.create_outgoing_request(request, target).await?;
         let headers = &mut outgoing.request.headers;
 
+        // 8.1.1.3 From
+        // The From field MUST contain a new "tag" parameter.
+        if let Some(from) = find_map_mut_header!(headers, From)
+            && from.tag().is_none()
+        {
+            from.set_tag(Some(crate::generate_tag_n(16)));
+        }
+
         let via = match find_map_mut_header!(headers, Via) {
             Some(via) => via,
             None => {
-                let sent_by = outgoing.target_info.transport.local_addr().into();
+                let sent_by = endpoint.local_name(&outgoing.target_info.transport);
                 let transport = outgoing.target_info.transport.transport_type();
                 let branch = crate::generate_branch();
                 let via = Via::new_with_transport(transport, sent_by, Some(branch));
 
-                headers.prepend_header(Header::Via(via));
+                headers.push_front(Header::Via(via));
 
                 match headers.first_mut().unwrap() {
                     Header::Via(v) => v,
@@ -80,7 +89,7 @@ impl ClientTransaction {
                 branch
             }
         };
-        let key = TransactionKey::new_key_3261(Role::UAC, method, branch);
+        let key = TransactionKey::new_key_3261(Role::UAC, method.clone(), branch);
 
         endpoint.send_outgoing_request(&mut outgoing).await?;
 
@@ -90,6 +99,7 @@ impl ClientTransaction {
             State::Trying
         };
         let (sender, channel) = mpsc::channel(10);
+        let timeout = endpoint.timer().now() + endpoint.timers().t1 * 64;
 
         endpoint.register_transaction(key.clone(), sender);
 
@@ -99,10 +109,14 @@ impl ClientTransaction {
             state_machine: StateMachine::new(state),
             channel: channel.into(),
             request: outgoing,
-            timeout: Instant::now() + T1 * 64,
+            timeout,
         };
 
-        log::trace!("Transaction Created [{:#?}] ({:p})", Role::UAC, &uac);
+        log::trace!(
+            "Transaction Created [{}] to {}",
+            uac.key,
+            uac.request.target_info.target
+        );
 
         Ok(uac)
     }
@@ -138,21 +152,32 @@ impl ClientTransaction {
             State::Initial | State::Calling | State::Trying
                 if !self.request.target_info.transport.is_reliable() =>
             {
-                let mut retrans_interval = T1;
+                let timers = *self.endpoint.timers();
+                let timer = self.endpoint.timer().clone();
+                let timer_b = self.timeout;
+                let mut retrans_interval = timers.t1;
+                let mut retrans_count = 0;
                 loop {
-                    let timer = self.timeout.into();
-                    let msg = timeout(retrans_interval, self.recv_provisional_msg());
+                    let msg = timer.timeout(
+                        timers.jittered(retrans_interval),
+                        self.recv_provisional_msg(),
+                    );
 
-                    match timeout_at(timer, msg).await {
+                    match timer.timeout_at(timer_b, msg).await {
                         Ok(Ok(Some(msg))) => {
                             self.state_machine.set_state(State::Proceeding);
                             return Ok(Some(msg));
                         }
+                        Ok(Err(_)) if timers.retransmissions_exhausted(retrans_count) => {
+                            self.state_machine.set_state(State::Terminated);
+                            return Err(TransactionError::Timeout.into());
+                        }
                         Ok(Err(_)) => {
                             // retransmit
                             self.endpoint
                                 .send_outgoing_request(&mut self.request)
                                 .await?;
+                            retrans_count += 1;
                             retrans_interval *= 2;
                             continue;
                         }
@@ -165,7 +190,8 @@ impl ClientTransaction {
                 }
             }
             State::Initial | State::Calling | State::Trying => {
-                match timeout_at(self.timeout.into(), self.recv_provisional_msg()).await {
+                let (timer, timer_b) = (self.endpoint.timer().clone(), self.timeout);
+                match timer.timeout_at(timer_b, self.recv_provisional_msg()).await {
                     Ok(Some(msg)) => {
                         self.state_machine.set_state(State::Proceeding);
                         return Ok(Some(msg));
@@ -220,9 +246,10 @@ impl ClientTransaction {
                 .await?;
 
             // timer d fires
-            let timer_d = Instant::now() + 64 * T1;
+            let timer = self.endpoint.timer().clone();
+            let timer_d = timer.now() + 64 * self.endpoint.timers().t1;
             tokio::spawn(async move {
-                while let Ok(Some(_)) = timeout_at(timer_d, self.channel.recv()).await {
+                while let Ok(Some(_)) = timer.timeout_at(timer_d, self.channel.recv()).await {
                     if let Err(err) = self.endpoint.send_outgoing_request(&mut ack_request).await {
                         log::error!("Failed to retransmit: {}", err);
                     }
@@ -231,9 +258,10 @@ impl ClientTransaction {
             });
         } else {
             // timer k fires
-            let timer_k = Instant::now() + T4;
+            let timer = self.endpoint.timer().clone();
+            let timer_k = timer.now() + self.endpoint.timers().t4;
             tokio::spawn(async move {
-                while let Ok(Some(_)) = timeout_at(timer_k, self.channel.recv()).await {
+                while let Ok(Some(_)) = timer.timeout_at(timer_k, self.channel.recv()).await {
                     // buffer any additional response retransmissions that may be received
                 }
                 self.state_machine.set_state(State::Terminated);
@@ -243,6 +271,52 @@ impl ClientTransaction {
         Ok(response)
     }
 
+    /// Like [`ClientTransaction::receive_final_response`], but a
+    /// `2xx` to an `INVITE` also creates the confirmed UAC dialog
+    /// in `ua`, as described in RFC 3261 section 12.1.2.
+    ///
+    /// `early` are the early dialogs created from the provisional
+    /// responses to the request. If the `2xx` belongs to one of
+    /// them, that dialog is confirmed and returned, instead of
+    /// creating a new one. The other early dialogs are dropped.
+    ///
+    /// The response is returned even if the dialog can not be
+    /// created, for example when the `2xx` has no `Contact`, the
+    /// dialog is then `None` and the failure is logged.
+    pub async fn receive_final_response_with_dialog(
+        self,
+        ua: &UserAgent,
+        early: Vec<Dialog>,
+    ) -> Result<(IncomingResponse, Option<Dialog>)> {
+        let request = self.request.request.clone();
+        let response = self.receive_final_response().await?;
+
+        if request.req_line.method != Method::Invite
+            || response.status().class() != CodeClass::Success
+        {
+            return Ok((response, None));
+        }
+
+        let dialog_id = DialogId::from_response(&response);
+        let dialog = match early.into_iter().find(|dialog| dialog.id() == &dialog_id) {
+            Some(mut dialog) => dialog.confirm(ua, &response).map(|()| dialog),
+            None => Dialog::create_uac(ua, &request, &response),
+        };
+        let dialog = match dialog {
+            Ok(dialog) => Some(dialog),
+            Err(err) => {
+                log::warn!(
+                    "Failed to create dialog from {} response: {}",
+                    response.status().as_u16(),
+                    err
+                );
+                None
+            }
+        };
+
+        Ok((response, dialog))
+    }
+
     pub fn transaction_key(&self) -> &TransactionKey {
         &self.key
     }
@@ -255,19 +329,36 @@ impl ClientTransaction {
 impl Drop for ClientTransaction {
     fn drop(&mut self) {
         self.endpoint.transactions().remove(&self.key);
-        log::trace!("Transaction Destroyed [{:#?}] ({:p})", Role::UAC, &self);
+        log::trace!(
+            "Transaction Destroyed [{}] to {}",
+            self.key,
+            self.request.target_info.target
+        );
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use tokio::time;
+
     use super::*;
+    use crate::endpoint::EndpointBuilder;
     use crate::error::{Error, TransactionError};
+    use crate::message::headers::{CSeq, Contact};
+    use crate::message::uri::Uri;
+    use crate::message::{MandatoryHeaders, StatusCode};
     use crate::test_utils::transaction::{
         CODE_100_TRYING, CODE_180_RINGING, CODE_202_ACCEPTED, CODE_301_MOVED_PERMANENTLY,
-        CODE_404_NOT_FOUND, CODE_504_SERVER_TIMEOUT, CODE_603_DECLINE, ClientTestContext,
+        CODE_404_NOT_FOUND, CODE_504_SERVER_TIMEOUT, CODE_603_DECLINE, ClientTestContext, FakeUAS,
         SendRequestContext,
     };
+    use crate::test_utils::transport::MockTransport;
+    use crate::test_utils::{create_test_endpoint, create_test_request};
+    use crate::testing::MockTimer;
+    use crate::transaction::{T1, TimerConfig};
     use crate::{Method, assert_eq_state};
 
     // INVITE Client tests
@@ -291,6 +382,44 @@ mod tests {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn invite_terminates_when_retransmissions_are_exhausted() {
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timers(TimerConfig {
+                max_retransmissions: Some(2),
+                ..Default::default()
+            })
+            .build();
+        let transport = MockTransport::new_udp();
+        let request = create_test_request(Method::Invite, Transport::new(transport.clone()));
+        let destination = request.incoming_info.transport.packet.source;
+        let mut uac = ClientTransaction::send_request_with_target(
+            request.request,
+            (Transport::new(transport.clone()), destination),
+            endpoint,
+        )
+        .await
+        .expect("error sending request");
+        let start = Instant::now();
+
+        let opt_err = uac.receive_provisional_response().await.err();
+
+        assert_matches!(
+            opt_err,
+            Some(Error::TransactionError(TransactionError::Timeout)),
+            "Expected TransactionError::Timeout, got {opt_err:?}"
+        );
+        assert_eq!(
+            transport.sent_count(),
+            3,
+            "one request and two retransmissions"
+        );
+        // Timer A fired at T1, 2*T1 and 4*T1, well before timer B.
+        assert_eq!(start.elapsed(), 7 * T1);
+        assert_eq!(uac.state(), State::Terminated);
+    }
+
     #[tokio::test(start_paused = true)]
     async fn invite_should_not_start_timer_a_when_transport_is_reliable() {
         let mut ctx = ClientTestContext::setup_reliable(Method::Invite).await;
@@ -312,6 +441,122 @@ mod tests {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn invite_retransmits_request_when_timer_a_fires() {
+        let mut ctx = ClientTestContext::setup(Method::Invite).await;
+        let expected_requests = 1;
+        let expected_retrans = 3;
+
+        // Timer A fires at T1, 2*T1 and 4*T1.
+        let elapsed = T1 * 7 + Duration::from_millis(1);
+        let result = time::timeout(elapsed, ctx.client.receive_provisional_response()).await;
+
+        assert!(result.is_err(), "no provisional response was sent");
+        assert_eq!(
+            ctx.transport.sent_count(),
+            expected_requests + expected_retrans,
+            "sent count should match {expected_requests} requests and {expected_retrans} retransmissions"
+        );
+    }
+
+    #[tokio::test]
+    async fn invite_retransmits_request_when_mock_timer_advances() {
+        let timer = MockTimer::new();
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timer(timer.clone())
+            .build();
+        let transport = MockTransport::new_udp();
+        let request = create_test_request(Method::Invite, Transport::new(transport.clone()));
+        let destination = request.incoming_info.transport.packet.source;
+        let mut uac = ClientTransaction::send_request_with_target(
+            request.request,
+            (Transport::new(transport.clone()), destination),
+            endpoint,
+        )
+        .await
+        .expect("error sending request");
+        let receive = tokio::spawn(async move { uac.receive_provisional_response().await });
+        // Lets the transaction run until it waits on the timer again.
+        let settle = || async {
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+        };
+
+        settle().await;
+        timer.advance(T1 - Duration::from_millis(1));
+        settle().await;
+        assert_eq!(transport.sent_count(), 1, "timer A has not fired yet");
+
+        // Timer A fires at T1, 2*T1 and 4*T1.
+        timer.advance(Duration::from_millis(1));
+        settle().await;
+        assert_eq!(transport.sent_count(), 2);
+        timer.advance(2 * T1);
+        settle().await;
+        assert_eq!(transport.sent_count(), 3);
+        timer.advance(4 * T1);
+        settle().await;
+        assert_eq!(transport.sent_count(), 4);
+
+        // Timer B fires at 64*T1.
+        timer.advance(57 * T1);
+        let opt_err = receive.await.unwrap().err();
+        assert_matches!(
+            opt_err,
+            Some(Error::TransactionError(TransactionError::Timeout)),
+            "Expected TransactionError::Timeout, got {opt_err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn request_without_from_tag_gets_one() {
+        let endpoint = create_test_endpoint();
+        let transport = MockTransport::new_udp();
+        let tp = Transport::new(transport.clone());
+        let uri = Uri::from_str("sip:bob@127.0.0.1:5070").unwrap();
+
+        let target = (tp.clone(), tp.local_addr());
+        let request = Request::new(Method::Options, uri);
+        let _uac = ClientTransaction::send_request_with_target(request, target, endpoint.clone())
+            .await
+            .unwrap();
+        let sent = transport.get_last_sent_request().unwrap();
+        let from = sent.headers.iter().find_map(|h| h.as_from());
+        assert!(from.unwrap().tag().is_some());
+
+        let mut request = create_test_request(Method::Options, tp.clone()).request;
+        find_map_mut_header!(request.headers, From)
+            .unwrap()
+            .set_tag(None);
+        let target = (tp.clone(), tp.local_addr());
+        let _uac = ClientTransaction::send_request_with_target(request, target, endpoint)
+            .await
+            .unwrap();
+        let sent = transport.get_last_sent_request().unwrap();
+        let from = sent.headers.iter().find_map(|h| h.as_from());
+        assert!(from.unwrap().tag().is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invite_retransmission_preserves_from_tag() {
+        let mut ctx = ClientTestContext::setup(Method::Invite).await;
+        let from_tag = |ctx: &ClientTestContext| {
+            let request = ctx.transport.get_last_sent_request().unwrap();
+            let from = request.headers.iter().find_map(|h| h.as_from()).unwrap();
+            from.tag().clone()
+        };
+        let original = from_tag(&ctx);
+
+        let elapsed = T1 + Duration::from_millis(1);
+        let _ = time::timeout(elapsed, ctx.client.receive_provisional_response()).await;
+
+        assert_eq!(ctx.transport.sent_count(), 2);
+        assert!(original.is_some());
+        assert_eq!(from_tag(&ctx), original);
+    }
+
     #[tokio::test]
     async fn invite_transitions_from_calling_to_proceeding_when_receiving_1xx_response() {
         let mut ctx = ClientTestContext::setup(Method::Invite).await;
@@ -516,6 +761,40 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn invite_ack_for_non_2xx_response_reuses_invite_headers() {
+        let ctx = ClientTestContext::setup(Method::Invite).await;
+
+        ctx.server.respond(StatusCode::BusyHere).await;
+
+        let response = ctx
+            .client
+            .receive_final_response()
+            .await
+            .expect("Error receiving final response");
+
+        let invite = &ctx.server.request;
+        let ack = ctx.transport.get_last_sent_request().expect("A request");
+        let mandatory = MandatoryHeaders::from_headers(&ack.headers).unwrap();
+        let invite_mandatory = &invite.incoming_info.mandatory_headers;
+
+        assert_eq!(ack.method(), Method::Ack);
+        assert_eq!(ack.req_line.uri, invite.request.req_line.uri);
+        assert_eq!(
+            ack.headers.iter().filter(|h| h.as_via().is_some()).count(),
+            1
+        );
+        assert_eq!(mandatory.via.branch, invite_mandatory.via.branch);
+        assert_eq!(mandatory.call_id, invite_mandatory.call_id);
+        assert_eq!(mandatory.from, invite_mandatory.from);
+        assert_eq!(
+            mandatory.cseq,
+            CSeq::new(invite_mandatory.cseq.cseq, Method::Ack)
+        );
+        assert_eq!(mandatory.to, response.incoming_info.mandatory_headers.to);
+        assert!(mandatory.to.tag().is_some());
+    }
+
     #[tokio::test]
     async fn invite_transitions_from_proceeding_to_completed_when_receiving_3xx_response() {
         let mut ctx = ClientTestContext::setup(Method::Invite).await;
@@ -1168,4 +1447,129 @@ mod tests {
             "should transition to Terminated after timer d fires"
         );
     }
+
+    async fn send_invite_with_contact(endpoint: &Endpoint) -> (ClientTransaction, FakeUAS) {
+        let transport = Transport::new(MockTransport::new_udp());
+        let mut request = create_test_request(Method::Invite, transport.clone());
+        let contact = Contact::from_str("<sip:alice@pc33.atlanta.com>").unwrap();
+        request.request.headers.push(Header::Contact(contact));
+        let destination = request.incoming_info.transport.packet.source;
+        let uac = ClientTransaction::send_request_with_target(
+            request.request.clone(),
+            (transport, destination),
+            endpoint.clone(),
+        )
+        .await
+        .expect("error sending request");
+        let server = FakeUAS {
+            sender: endpoint
+                .transactions()
+                .get_entry(uac.transaction_key())
+                .unwrap(),
+            request,
+            endpoint: endpoint.clone(),
+        };
+
+        (uac, server)
+    }
+
+    #[tokio::test]
+    async fn invite_2xx_creates_confirmed_dialog() {
+        let endpoint = create_test_endpoint();
+        let (uac, server) = send_invite_with_contact(&endpoint).await;
+        let ua = UserAgent::new(endpoint.clone());
+
+        let mut ok = endpoint.create_outgoing_response(&server.request, StatusCode::Ok, None);
+        let contact = Contact::from_str("<sip:bob@192.0.2.4>").unwrap();
+        ok.headers_mut().push(Header::Contact(contact));
+        server.respond_with(ok).await;
+        let (response, dialog) = uac
+            .receive_final_response_with_dialog(&ua, Vec::new())
+            .await
+            .unwrap();
+
+        let dialog = dialog.expect("2xx to INVITE must create a dialog");
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert!(!dialog.is_early());
+        assert_eq!(dialog.remote_target().to_string(), "sip:bob@192.0.2.4");
+    }
+
+    #[tokio::test]
+    async fn invite_2xx_confirms_early_dialog() {
+        let endpoint = create_test_endpoint();
+        endpoint
+            .transports()
+            .register_transport(Transport::new(MockTransport::new_udp()))
+            .unwrap();
+        let (mut uac, server) = send_invite_with_contact(&endpoint).await;
+        let ua = UserAgent::new(endpoint.clone());
+        let contact = Contact::from_str("<sip:bob@192.0.2.4>").unwrap();
+        let cseq = |request: &OutgoingRequest| {
+            request
+                .request
+                .headers
+                .iter()
+                .find_map(Header::as_c_seq)
+                .unwrap()
+                .cseq
+        };
+
+        let mut ringing =
+            endpoint.create_outgoing_response(&server.request, StatusCode::Ringing, None);
+        ringing.headers_mut().push(Header::Contact(contact.clone()));
+        server.respond_with(ringing).await;
+        let provisional = uac.receive_provisional_response().await.unwrap().unwrap();
+        let mut early = ua
+            .new_uac_dialog(&server.request.request, &provisional)
+            .unwrap();
+        // A request in the early dialog advances its local CSeq.
+        let update = early.create_request(Method::Update).await.unwrap();
+
+        let mut ok = endpoint.create_outgoing_response(&server.request, StatusCode::Ok, None);
+        ok.headers_mut().push(Header::Contact(contact));
+        server.respond_with(ok).await;
+        let (_, dialog) = uac
+            .receive_final_response_with_dialog(&ua, vec![early])
+            .await
+            .unwrap();
+
+        let mut dialog = dialog.expect("2xx to INVITE must confirm the early dialog");
+        assert!(!dialog.is_early());
+        assert!(ua.early_dialogs().is_empty());
+        let bye = dialog.create_request(Method::Bye).await.unwrap();
+        assert_eq!(cseq(&bye), cseq(&update) + 1);
+    }
+
+    #[tokio::test]
+    async fn invite_2xx_without_contact_is_still_returned() {
+        let endpoint = create_test_endpoint();
+        let (uac, server) = send_invite_with_contact(&endpoint).await;
+        let ua = UserAgent::new(endpoint.clone());
+
+        let ok = endpoint.create_outgoing_response(&server.request, StatusCode::Ok, None);
+        server.respond_with(ok).await;
+        let (response, dialog) = uac
+            .receive_final_response_with_dialog(&ua, Vec::new())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert!(dialog.is_none());
+    }
+
+    #[tokio::test]
+    async fn non_2xx_final_response_creates_no_dialog() {
+        let ctx = ClientTestContext::setup_reliable(Method::Invite).await;
+        let ua = UserAgent::new(create_test_endpoint());
+
+        ctx.server.respond(CODE_404_NOT_FOUND).await;
+        let (response, dialog) = ctx
+            .client
+            .receive_final_response_with_dialog(&ua, Vec::new())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), CODE_404_NOT_FOUND);
+        assert!(dialog.is_none());
+    }
 }
@@ -1,19 +1,70 @@
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt;
 use std::sync::Mutex;
 
+use tokio::sync::Notify;
 use tokio::sync::mpsc::{self};
 
+use super::server::ServerTransaction;
 use super::{Role, TransactionMessage};
+use crate::endpoint::Endpoint;
 use crate::message::HostPort;
 use crate::transport::incoming::{IncomingInfo, IncomingRequest, IncomingResponse};
 use crate::{Method, RFC3261_BRANCH_ID};
 
 type TransactionChannel = mpsc::Sender<TransactionMessage>;
 
+/// How many bytes of a branch are shown when a key is
+/// displayed, enough to tell transactions apart in the logs.
+const BRANCH_DISPLAY_LEN: usize = 16;
+
+struct TransactionEntry {
+    channel: TransactionChannel,
+    /// The `To` tag of the non-2xx final response, set while an
+    /// INVITE server transaction waits for its `ACK`.
+    ack_to_tag: Option<Option<String>>,
+}
+
+impl TransactionEntry {
+    fn new(channel: TransactionChannel) -> Self {
+        Self {
+            channel,
+            ack_to_tag: None,
+        }
+    }
+
+    /// Returns `true` if `ack` acknowledges the non-2xx final
+    /// response of this transaction.
+    ///
+    /// The `ACK` for a 2xx response is a separate transaction
+    /// that belongs to the TU.
+    fn absorbs_ack(&self, ack: &IncomingRequest) -> bool {
+        self.ack_to_tag
+            .as_ref()
+            .is_some_and(|tag| tag == ack.incoming_info.mandatory_headers.to.tag())
+    }
+}
+
+/// The result of [`TransactionManager::find_or_create_server`].
+pub enum ServerLookup {
+    /// The request was passed to its existing server
+    /// transaction as a retransmission.
+    Found,
+    /// A new server transaction was created for the request.
+    Created(Box<ServerTransaction>),
+    /// The request matches no transaction and creates none, it
+    /// must be handled by the TU. This is an `ACK` for a 2xx
+    /// response.
+    Unmatched(Box<IncomingRequest>),
+}
+
 /// This type holds all server and client Transactions created by the TU (Transaction User).
 #[derive(Default)]
 pub struct TransactionManager {
-    transactions: Mutex<HashMap<TransactionKey, TransactionChannel>>,
+    transactions: Mutex<HashMap<TransactionKey, TransactionEntry>>,
+    /// Notified when the last transaction is removed.
+    drained: Notify,
 }
 
 impl TransactionManager {
@@ -25,7 +76,17 @@ impl TransactionManager {
     pub(crate) fn add_transaction(&self, key: TransactionKey, entry: TransactionChannel) {
         let mut map = self.transactions.lock().expect("Lock failed");
 
-        map.insert(key, entry);
+        map.insert(key, TransactionEntry::new(entry));
+    }
+
+    /// Marks the server transaction `key` as waiting for the
+    /// `ACK` of a non-2xx final response sent with `to_tag`.
+    pub(crate) fn expect_ack(&self, key: &TransactionKey, to_tag: Option<String>) {
+        let mut map = self.transactions.lock().expect("Lock failed");
+
+        if let Some(entry) = map.get_mut(key) {
+            entry.ack_to_tag = Some(to_tag);
+        }
     }
 
     #[inline]
@@ -33,13 +94,47 @@ impl TransactionManager {
         let mut map = self.transactions.lock().expect("Lock failed");
 
         map.remove(key);
+        if map.is_empty() {
+            self.drained.notify_waiters();
+        }
+    }
+
+    /// Returns the number of active transactions.
+    pub fn len(&self) -> usize {
+        self.transactions.lock().expect("Lock failed").len()
+    }
+
+    /// Returns `true` if there are no active transactions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Waits until all active transactions have been removed.
+    pub(crate) async fn wait_until_empty(&self) {
+        loop {
+            // Created before checking so a concurrent removal is not missed.
+            let drained = self.drained.notified();
+            if self.is_empty() {
+                return;
+            }
+            drained.await;
+        }
     }
 
     #[inline]
     pub(crate) fn get_entry(&self, key: &TransactionKey) -> Option<TransactionChannel> {
         let map = self.transactions.lock().expect("Lock failed");
 
-        map.get(key).cloned()
+        map.get(key).map(|entry| entry.channel.clone())
+    }
+
+    /// Returns the key of the INVITE server transaction cancelled
+    /// by `cancel`, if it is still active.
+    pub(crate) fn find_cancelled_invite(&self, cancel: &IncomingRequest) -> Option<TransactionKey> {
+        let key = TransactionKey::from_cancel(cancel)?;
+        let map = self.transactions.lock().expect("Lock failed");
+
+        map.contains_key(&key).then_some(key)
     }
 
     pub(crate) async fn handle_response(
@@ -66,28 +161,119 @@ impl TransactionManager {
         None
     }
 
+    /// Finds the server transaction matching `request` or
+    /// creates a new one.
+    ///
+    /// The lookup and the registration happen under the same
+    /// lock, so concurrent copies of a request never create two
+    /// transactions. When a transaction already exists the
+    /// request is passed to it as a retransmission. Otherwise an
+    /// INVITE or non-INVITE transaction, based on the method, is
+    /// created.
+    ///
+    /// An `ACK` never creates a transaction, and is only passed
+    /// to the INVITE server transaction when it acknowledges a
+    /// non-2xx final response. Any other `ACK` is returned in
+    /// [`ServerLookup::Unmatched`].
+    pub async fn find_or_create_server(
+        &self,
+        request: IncomingRequest,
+        endpoint: &Endpoint,
+    ) -> ServerLookup {
+        let key = TransactionKey::from_request(&request);
+        let is_ack = request.req_line.method.is_ack();
+
+        let channel = {
+            let mut map = self.transactions.lock().expect("Lock failed");
+
+            match map.entry(key) {
+                Entry::Occupied(entry) if !is_ack || entry.get().absorbs_ack(&request) => {
+                    entry.get().channel.clone()
+                }
+                Entry::Occupied(_) => return ServerLookup::Unmatched(Box::new(request)),
+                Entry::Vacant(_) if !request.req_line.method.creates_transaction() => {
+                    return ServerLookup::Unmatched(Box::new(request));
+                }
+                Entry::Vacant(entry) => {
+                    let key = entry.key().clone();
+                    let (sender, receiver) = mpsc::channel(10);
+                    entry.insert(TransactionEntry::new(sender));
+
+                    return ServerLookup::Created(Box::new(ServerTransaction::with_receiver(
+                        request,
+                        endpoint.clone(),
+                        key,
+                        receiver,
+                    )));
+                }
+            }
+        };
+        let _res = channel.send(TransactionMessage::Request(request)).await;
+
+        ServerLookup::Found
+    }
+
+    /// Passes `request` to its server transaction, returns it
+    /// back if it must be handled by the TU.
+    ///
+    /// An `ACK` is only absorbed by the INVITE server transaction
+    /// when it acknowledges a non-2xx final response, the `ACK`
+    /// for a 2xx response is always returned.
     pub(crate) async fn receive(&self, request: IncomingRequest) -> Option<IncomingRequest> {
         let key = TransactionKey::from_request(&request);
 
-        let Some(channel) = self.get_entry(&key) else {
-            return Some(request);
+        let channel = {
+            let map = self.transactions.lock().expect("Lock failed");
+
+            match map.get(&key) {
+                Some(entry) if !request.req_line.method.is_ack() || entry.absorbs_ack(&request) => {
+                    entry.channel.clone()
+                }
+                _ => return Some(request),
+            }
         };
         let _res = channel.send(TransactionMessage::Request(request)).await;
         None
     }
 }
 
+/// The key that identifies a transaction.
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum TransactionKey {
+    /// Key for messages from RFC 2543 implementations, without a magic
+    /// cookie in the branch.
     Rfc2543(Rfc2543),
+    /// Key for RFC 3261 compliant messages.
     Rfc3261(Rfc3261),
 }
 
 impl TransactionKey {
+    /// Creates the server transaction key for the `request`.
     pub fn from_request(request: &IncomingRequest) -> Self {
         Self::from_incoming_info(&request.incoming_info, Role::UAS)
     }
 
+    /// Creates the key of the INVITE server transaction cancelled
+    /// by the `CANCEL` `request`, or `None` if `request` is not a
+    /// `CANCEL`.
+    ///
+    /// The `CANCEL` has its own transaction, but carries the same
+    /// branch as the INVITE it cancels (RFC 3261 section 9.2).
+    pub fn from_cancel(request: &IncomingRequest) -> Option<Self> {
+        if request.req_line.method != Method::Cancel {
+            return None;
+        }
+
+        Some(match Self::from_request(request) {
+            Self::Rfc3261(key) => Self::new_key_3261(key.role, Method::Invite, key.branch),
+            Self::Rfc2543(key) => Self::Rfc2543(Rfc2543 {
+                method: None,
+                ..key
+            }),
+        })
+    }
+
+    /// Creates the client transaction key for the `response`.
     pub fn from_response(response: &IncomingResponse) -> Self {
         Self::from_incoming_info(&response.incoming_info, Role::UAC)
     }
@@ -96,7 +282,7 @@ impl TransactionKey {
         match info.mandatory_headers.via.branch {
             Some(ref branch) if branch.starts_with(RFC3261_BRANCH_ID) => {
                 let branch = branch.clone();
-                let method = info.mandatory_headers.cseq.method;
+                let method = info.mandatory_headers.cseq.method.clone();
 
                 Self::new_key_3261(role, method, branch)
             }
@@ -106,6 +292,9 @@ impl TransactionKey {
         }
     }
 
+    /// Creates an RFC 3261 key from the `branch` and `method`.
+    ///
+    /// `ACK` shares the key of the `INVITE` it acknowledges.
     pub fn new_key_3261(role: Role, method: Method, branch: String) -> Self {
         let method = if matches!(method, Method::Invite | Method::Ack) {
             None
@@ -121,6 +310,32 @@ impl TransactionKey {
     }
 }
 
+impl fmt::Display for TransactionKey {
+    /// Formats the key for logging, as
+    /// `UAS z9hG4bK776asdhds… INVITE`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rfc3261(key) => {
+                let (branch, ellipsis) = match key.branch.get(..BRANCH_DISPLAY_LEN) {
+                    Some(prefix) if prefix.len() < key.branch.len() => (prefix, "…"),
+                    _ => (key.branch.as_str(), ""),
+                };
+                // INVITE and ACK share the key without a method.
+                let method = key.method.as_ref().unwrap_or(&Method::Invite);
+
+                write!(f, "{:?} {branch}{ellipsis} {method}", key.role)
+            }
+            Self::Rfc2543(key) => {
+                write!(f, "{} {} {}", key.call_id, key.cseq, key.via_host_port)?;
+                if let Some(method) = &key.method {
+                    write!(f, " {method}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct Rfc2543 {
     pub cseq: u32,
@@ -143,6 +358,194 @@ mod tests {
     use super::*;
     use crate::endpoint;
     use crate::message::Method;
+    use crate::message::StatusCode;
+    use crate::test_utils::transport::MockTransport;
+    use crate::test_utils::{create_test_endpoint, create_test_request};
+    use crate::transaction::fsm::State;
+    use crate::transport::Transport;
+
+    #[test]
+    fn test_key_display_truncates_branch() {
+        let invite = TransactionKey::new_key_3261(
+            Role::UAS,
+            Method::Invite,
+            "z9hG4bK776asdhds9a8b7c6d".into(),
+        );
+        let bye = TransactionKey::new_key_3261(Role::UAC, Method::Bye, "z9hG4bK1".into());
+
+        assert_eq!(invite.to_string(), "UAS z9hG4bK776asdhds… INVITE");
+        assert_eq!(bye.to_string(), "UAC z9hG4bK1 BYE");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_find_or_create_server_is_atomic() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Invite, transport);
+
+        let spawn = |request: IncomingRequest| {
+            let endpoint = endpoint.clone();
+            tokio::spawn(async move {
+                endpoint
+                    .transactions()
+                    .find_or_create_server(request, &endpoint)
+                    .await
+            })
+        };
+        let first = spawn(request.clone());
+        let second = spawn(request);
+
+        let mut created: Vec<_> = [first.await.unwrap(), second.await.unwrap()]
+            .into_iter()
+            .filter_map(|lookup| match lookup {
+                ServerLookup::Created(server) => Some(server),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(created.len(), 1);
+        assert_eq!(endpoint.transactions().len(), 1);
+        assert_eq!(created[0].state_machine_mut().state(), State::Proceeding);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_finds_invite_server_transaction() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let invite = create_test_request(Method::Invite, transport);
+        let mut cancel = invite.clone();
+        cancel.request.req_line.method = Method::Cancel;
+        cancel.incoming_info.mandatory_headers.cseq.method = Method::Cancel;
+
+        assert_eq!(endpoint.transactions().find_cancelled_invite(&cancel), None);
+
+        assert_eq!(TransactionKey::from_cancel(&invite), None);
+        let ServerLookup::Created(server) = endpoint
+            .transactions()
+            .find_or_create_server(invite, &endpoint)
+            .await
+        else {
+            panic!("the INVITE must create a transaction");
+        };
+
+        assert_ne!(
+            &TransactionKey::from_request(&cancel),
+            server.transaction_key()
+        );
+        assert_eq!(
+            endpoint
+                .transactions()
+                .find_cancelled_invite(&cancel)
+                .as_ref(),
+            Some(server.transaction_key())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_ack_creates_no_transaction() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let invite = create_test_request(Method::Invite, transport);
+        let ack = create_ack(&invite, None);
+
+        let lookup = endpoint
+            .transactions()
+            .find_or_create_server(ack, &endpoint)
+            .await;
+
+        assert!(matches!(lookup, ServerLookup::Unmatched(_)));
+        assert_eq!(endpoint.transactions().len(), 0);
+    }
+
+    fn create_ack(invite: &IncomingRequest, to_tag: Option<String>) -> IncomingRequest {
+        let mut ack = invite.clone();
+        ack.request.req_line.method = Method::Ack;
+        ack.incoming_info.mandatory_headers.cseq.method = Method::Ack;
+        ack.incoming_info.mandatory_headers.to.set_tag(to_tag);
+
+        ack
+    }
+
+    #[tokio::test]
+    async fn test_ack_for_non_2xx_is_absorbed_by_server_transaction() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let invite = create_test_request(Method::Invite, transport);
+        let mut server = ServerTransaction::new(invite.clone(), endpoint.clone());
+        let mut state = server.state_machine_mut().subscribe_state();
+
+        server
+            .send_final_status(StatusCode::BusyHere)
+            .await
+            .unwrap();
+        crate::assert_eq_state!(
+            state,
+            State::Completed,
+            "a non-2xx response must complete the transaction"
+        );
+
+        let to_tag = invite.incoming_info.mandatory_headers.via.branch.clone();
+        let ack = create_ack(&invite, to_tag);
+
+        assert!(endpoint.transactions().receive(ack).await.is_none());
+        crate::assert_eq_state!(
+            state,
+            State::Confirmed,
+            "the ACK must confirm the transaction"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ack_for_2xx_is_passed_to_tu() {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let invite = create_test_request(Method::Invite, Transport::new(mock.clone()));
+        let mut server = ServerTransaction::new(invite.clone(), endpoint.clone());
+
+        server
+            .send_provisional_status(StatusCode::Ringing)
+            .await
+            .unwrap();
+        let ack = create_ack(&invite, Some("a6c85cf".into()));
+
+        // The ACK must not trigger a retransmission of the 180.
+        assert!(endpoint.transactions().receive(ack).await.is_some());
+        tokio::task::yield_now().await;
+        assert_eq!(mock.sent_count(), 1);
+
+        server.send_final_status(StatusCode::Ok).await.unwrap();
+        let ack = create_ack(&invite, Some("a6c85cf".into()));
+
+        assert!(endpoint.transactions().receive(ack).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_find_or_create_server_routes_acks() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let invite = create_test_request(Method::Invite, transport);
+        let mut server = ServerTransaction::new(invite.clone(), endpoint.clone());
+        let find = |ack| {
+            endpoint
+                .transactions()
+                .find_or_create_server(ack, &endpoint)
+        };
+
+        server
+            .send_provisional_status(StatusCode::Ringing)
+            .await
+            .unwrap();
+        let ack = create_ack(&invite, Some("a6c85cf".into()));
+        assert!(matches!(find(ack).await, ServerLookup::Unmatched(_)));
+
+        server
+            .send_final_status(StatusCode::BusyHere)
+            .await
+            .unwrap();
+        let to_tag = invite.incoming_info.mandatory_headers.via.branch.clone();
+        let ack = create_ack(&invite, to_tag);
+        assert!(matches!(find(ack).await, ServerLookup::Found));
+    }
 
     #[tokio::test]
     async fn test_non_invite_server_tsx() {
@@ -4,8 +4,9 @@
 use std::time::Duration;
 
 pub use client::ClientTransaction;
-pub use manager::TransactionManager;
+pub use manager::{ServerLookup, TransactionKey, TransactionManager};
 pub use server::ServerTransaction;
+pub use timer::{Sleep, Timer, TokioTimer};
 
 use crate::transport::incoming::{IncomingRequest, IncomingResponse};
 
@@ -13,6 +14,7 @@ pub(crate) mod client;
 pub(crate) mod fsm;
 pub(crate) mod manager;
 pub(crate) mod server;
+pub(crate) mod timer;
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug, Copy)]
 pub enum Role {
@@ -29,7 +31,61 @@ pub(crate) const T2: Duration = Duration::from_secs(4);
 /// Maximum duration that a message may remain in the network before being discarded.
 pub(crate) const T4: Duration = Duration::from_secs(5);
 
+/// The transaction timer values.
+///
+/// Defaults to the values recommended by `RFC3261`, lower
+/// values can be used on networks with a known short RTT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerConfig {
+    /// Estimated round‑trip time (RTT).
+    pub t1: Duration,
+    /// Maximum retransmission interval for non‑INVITE requests and INVITE responses.
+    pub t2: Duration,
+    /// Maximum duration that a message may remain in the network.
+    pub t4: Duration,
+    /// Upper bound of the random delay added to each
+    /// retransmission interval, so that transactions started
+    /// together do not retransmit together.
+    pub jitter: Duration,
+    /// Number of retransmissions of a message after which the
+    /// transaction gives up and terminates, `None` to
+    /// retransmit until the transaction times out.
+    pub max_retransmissions: Option<u32>,
+}
+
+impl TimerConfig {
+    /// Returns `interval` plus a random delay of at most
+    /// [`jitter`](Self::jitter).
+    pub(crate) fn jittered(&self, interval: Duration) -> Duration {
+        if self.jitter.is_zero() {
+            return interval;
+        }
+        interval + rand::random_range(Duration::ZERO..=self.jitter)
+    }
+
+    /// Returns `true` if `count` retransmissions reached
+    /// [`max_retransmissions`](Self::max_retransmissions).
+    pub(crate) fn retransmissions_exhausted(&self, count: u32) -> bool {
+        self.max_retransmissions.is_some_and(|max| count >= max)
+    }
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self {
+            t1: T1,
+            t2: T2,
+            t4: T4,
+            jitter: Duration::ZERO,
+            max_retransmissions: None,
+        }
+    }
+}
+
+// Like `SipMessage`, boxing the request would add an allocation per
+// message routed to a transaction.
 #[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum TransactionMessage {
     Request(IncomingRequest),
     Response(IncomingResponse),
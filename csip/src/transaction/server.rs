@@ -1,18 +1,23 @@
 use std::future;
 
 use tokio::sync::mpsc::{self};
-use tokio::time::{Instant, sleep, timeout_at};
+use tokio::sync::oneshot;
 use tokio_util::either::Either;
 
 use crate::Method;
 use crate::endpoint::Endpoint;
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, TransactionError};
+use crate::message::headers::{Header, RAck, RSeq, Require};
 use crate::message::{CodeClass, ReasonPhrase, StatusCode};
+use crate::transaction::TransactionMessage;
 use crate::transaction::fsm::{State, StateMachine};
 use crate::transaction::manager::TransactionKey;
-use crate::transaction::{T1, T2, T4, TransactionMessage};
 use crate::transport::incoming::IncomingRequest;
-use crate::transport::outgoing::OutgoingResponse;
+use crate::transport::outgoing::{Encode, OutgoingResponse};
+
+/// The option tag for reliable provisional responses
+/// (`RFC3262`).
+pub(crate) const TAG_100REL: &str = "100rel";
 
 /// A Server Transaction.
 ///
@@ -24,6 +29,8 @@ pub struct ServerTransaction {
     request: IncomingRequest,
     receiver: Option<mpsc::Receiver<TransactionMessage>>,
     provisonal_retrans_handle: Option<ProvisionalRetransHandle>,
+    reliable_provisional: Option<ReliableProvisional>,
+    next_rseq: u32,
 }
 
 struct ProvisionalRetransHandle {
@@ -31,6 +38,15 @@ struct ProvisionalRetransHandle {
     provisional_tx: mpsc::UnboundedSender<OutgoingResponse>,
 }
 
+/// A reliable provisional response waiting for its `PRACK`.
+struct ReliableProvisional {
+    rack: RAck,
+    // Dropping the sender stops the retransmissions.
+    _stop_tx: oneshot::Sender<()>,
+    // Fires when `64*T1` expires without a `PRACK`.
+    timeout_rx: oneshot::Receiver<()>,
+}
+
 impl ServerTransaction {
     /// Create a new [`ServerTransaction`] from the given request.
     ///
@@ -38,23 +54,38 @@ impl ServerTransaction {
     ///
     /// Panics if request method is `ACK`.
     pub(crate) fn new(request: IncomingRequest, endpoint: Endpoint) -> Self {
-        assert_ne!(
-            request.req_line.method,
-            Method::Ack,
+        assert!(
+            request.req_line.method.creates_transaction(),
             "ACK requests do not create transactions"
         );
 
+        let (sender, receiver) = mpsc::channel(10);
+        let transaction_key = TransactionKey::from_request(&request);
+
+        endpoint.register_transaction(transaction_key.clone(), sender);
+
+        Self::with_receiver(request, endpoint, transaction_key, receiver)
+    }
+
+    /// Creates the transaction for a channel already registered
+    /// under `transaction_key`.
+    pub(crate) fn with_receiver(
+        request: IncomingRequest,
+        endpoint: Endpoint,
+        transaction_key: TransactionKey,
+        receiver: mpsc::Receiver<TransactionMessage>,
+    ) -> Self {
         let initial_state = if request.req_line.method == Method::Invite {
             State::Proceeding
         } else {
             State::Trying
         };
         let state_machine = StateMachine::new(initial_state);
-
-        let (sender, receiver) = mpsc::channel(10);
-        let transaction_key = TransactionKey::from_request(&request);
-
-        endpoint.register_transaction(transaction_key.clone(), sender);
+        log::trace!(
+            "Transaction Created [{}] from {}",
+            transaction_key,
+            request.incoming_info.transport.packet.source
+        );
 
         Self {
             endpoint,
@@ -63,6 +94,9 @@ impl ServerTransaction {
             state_machine,
             receiver: Some(receiver),
             provisonal_retrans_handle: None,
+            reliable_provisional: None,
+            // The initial RSeq value is chosen uniformly between 1 and 2**31 - 1.
+            next_rseq: rand::random_range(1..(1 << 31)),
         }
     }
 
@@ -102,20 +136,153 @@ impl ServerTransaction {
         );
 
         self.send_response(&mut response).await?;
+        self.set_last_provisional(response)?;
 
-        if let Some(ref mut handle) = self.provisonal_retrans_handle {
-            handle
-                .provisional_tx
-                .send(response)
-                .map_err(|_| Error::ChannelClosed)?
-        } else {
-            let handle = self.spawn_retransmit_provisional_task(response);
-            self.provisonal_retrans_handle = Some(handle);
+        Ok(())
+    }
+
+    /// Returns `true` if the request indicates support for
+    /// reliable provisional responses, with `100rel` in
+    /// either the `Supported` or the `Require` header.
+    pub fn supports_100rel(&self) -> bool {
+        self.request
+            .request
+            .headers
+            .iter()
+            .any(|header| match header {
+                Header::Supported(supported) => supported.contains(TAG_100REL),
+                Header::Require(require) => require.contains(TAG_100REL),
+                _ => false,
+            })
+    }
+
+    /// Sends a provisional response reliably (`RFC3262`).
+    ///
+    /// Adds `Require: 100rel` and a new `RSeq` to the
+    /// `response` and retransmits it until a matching `PRACK`
+    /// is passed to [`process_prack`](Self::process_prack),
+    /// a final response is sent or `64*T1` expires.
+    ///
+    /// Returns an error if the request does not
+    /// [`supports_100rel`](Self::supports_100rel), or if the
+    /// previous reliable provisional response is not yet
+    /// acknowledged by a `PRACK` (`RFC3262` section 3). If the
+    /// previous one timed out without a `PRACK`, the error is
+    /// [`ReliableProvisionalTimeout`](TransactionError::ReliableProvisionalTimeout)
+    /// and the request should be rejected with a `5xx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `response` is not provisional (`101-199`).
+    pub async fn send_reliable_provisional(
+        &mut self,
+        mut response: OutgoingResponse,
+    ) -> Result<()> {
+        let code = response.status();
+
+        assert!(
+            code.class() == CodeClass::Provisional && code != StatusCode::Trying,
+            "Invalid reliable provisional response (expected 101-199) got {:?}",
+            code
+        );
+
+        if !self.supports_100rel() {
+            return Err(TransactionError::ReliableProvisionalNotSupported.into());
         }
+        if self.clear_timed_out_reliable_provisional() {
+            return Err(TransactionError::ReliableProvisionalTimeout.into());
+        }
+        if self.reliable_provisional.is_some() {
+            return Err(TransactionError::ReliableProvisionalPending.into());
+        }
+
+        let rseq = self.next_rseq;
+        self.next_rseq += 1;
+
+        let mut require = Require::default();
+        require.add_tag(TAG_100REL);
+        response.headers_mut().push(Header::Require(require));
+        response.headers_mut().push(Header::RSeq(RSeq::new(rseq)));
+        response.encoded = response.encode()?;
+
+        self.send_response(&mut response).await?;
+
+        let cseq = &self.request.incoming_info.mandatory_headers.cseq;
+        let rack = RAck::new(rseq, cseq.cseq, cseq.method.clone());
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let (timeout_tx, timeout_rx) = oneshot::channel();
+
+        self.spawn_retransmit_reliable_provisional_task(&response, stop_rx, timeout_tx);
+        self.reliable_provisional = Some(ReliableProvisional {
+            rack,
+            _stop_tx: stop_tx,
+            timeout_rx,
+        });
+        self.set_last_provisional(response)?;
 
         Ok(())
     }
 
+    /// Waits until the pending reliable provisional response is
+    /// retransmitted for `64*T1` without a `PRACK`, and clears it.
+    ///
+    /// The request should then be rejected with a `5xx`
+    /// response (`RFC3262` section 3). Never completes if no
+    /// reliable provisional response is pending.
+    pub async fn reliable_provisional_timeout(&mut self) {
+        let Some(pending) = &mut self.reliable_provisional else {
+            return future::pending().await;
+        };
+        if (&mut pending.timeout_rx).await.is_err() {
+            // Stopped by a PRACK or a final response.
+            return future::pending().await;
+        }
+        self.reliable_provisional = None;
+    }
+
+    /// Clears the pending reliable provisional response if it
+    /// timed out without a `PRACK`, returning `true` if it did.
+    fn clear_timed_out_reliable_provisional(&mut self) -> bool {
+        let timed_out = self
+            .reliable_provisional
+            .as_mut()
+            .is_some_and(|pending| pending.timeout_rx.try_recv().is_ok());
+        if timed_out {
+            self.reliable_provisional = None;
+        }
+        timed_out
+    }
+
+    /// Processes a `PRACK` request received for this transaction.
+    ///
+    /// Returns `true` if the `RAck` header matches the pending
+    /// reliable provisional response, in which case its
+    /// retransmissions are stopped.
+    pub fn process_prack(&mut self, prack: &IncomingRequest) -> bool {
+        self.clear_timed_out_reliable_provisional();
+        let Some(pending) = &self.reliable_provisional else {
+            return false;
+        };
+        if prack.request.req_line.method != Method::Prack
+            || prack.incoming_info.mandatory_headers.call_id
+                != self.request.incoming_info.mandatory_headers.call_id
+        {
+            return false;
+        }
+        let matches = prack
+            .request
+            .headers
+            .iter()
+            .filter_map(Header::as_r_ack)
+            .any(|rack| *rack == pending.rack);
+
+        if matches {
+            self.reliable_provisional = None;
+        }
+
+        matches
+    }
+
     /// Sends a final response with the given `status`.
     ///
     /// This is a shortcut for:
@@ -149,6 +316,7 @@ impl ServerTransaction {
         );
 
         self.send_response(&mut response).await?;
+        self.reliable_provisional = None;
 
         if self.request.request.req_line.method == Method::Invite {
             if let 200..299 = response.status().as_u16() {
@@ -157,6 +325,14 @@ impl ServerTransaction {
             }
             // 300-699 from TU send response --> Completed
             self.state_machine.set_state(State::Completed);
+            let to_tag = response
+                .headers()
+                .iter()
+                .find_map(|header| header.as_to())
+                .and_then(|to| to.tag().clone());
+            self.endpoint
+                .transactions()
+                .expect_ack(&self.transaction_key, to_tag);
 
             let mut channel = if let Some(task) = self.provisonal_retrans_handle.take() {
                 task.join_handle.await.unwrap()
@@ -164,14 +340,16 @@ impl ServerTransaction {
                 self.receiver.take().unwrap()
             };
 
+            let timers = *self.endpoint.timers();
+            let timer = self.endpoint.timer().clone();
             // For unreliable transports.
             let timer_g = if !self.is_reliable() {
-                Either::Left(sleep(T1))
+                Either::Left(timer.sleep(timers.jittered(timers.t1)))
             } else {
                 Either::Right(future::pending::<()>())
             };
             // For all transports.
-            let timer_h = sleep(64 * T1);
+            let timer_h = timer.sleep(64 * timers.t1);
             let mut retrans_count = 0;
             tokio::spawn(async move {
                 tokio::pin!(timer_g);
@@ -179,13 +357,17 @@ impl ServerTransaction {
                 loop {
                     tokio::select! {
                         _ = timer_g.as_mut() => {
+                        if timers.retransmissions_exhausted(retrans_count) {
+                            self.state_machine.set_state(State::Terminated);
+                            return;
+                        }
                            let _res =  self.endpoint
                             .send_outgoing_response(&mut response)
                             .await;
                         retrans_count += 1;
 
-                        let new_timer = T1 * (1 << retrans_count);
-                        let sleep = sleep(std::cmp::min(new_timer, T2));
+                        let new_timer = timers.t1 * (1 << retrans_count);
+                        let sleep = timer.sleep(timers.jittered(std::cmp::min(new_timer, timers.t2)));
 
                         timer_g.set(Either::Left(sleep));
 
@@ -200,7 +382,7 @@ impl ServerTransaction {
                          Some(TransactionMessage::Request(req)) = channel.recv() => {
                             if req.request.req_line.method.is_ack() {
                                 self.state_machine.set_state(State::Confirmed);
-                                sleep(T4).await;
+                                timer.sleep(timers.t4).await;
                                 self.state_machine.set_state(State::Terminated);
                                 return;
                             }
@@ -226,10 +408,11 @@ impl ServerTransaction {
                 self.receiver.take().unwrap()
             };
 
-            let timer_j = Instant::now() + 64 * T1;
+            let timer = self.endpoint.timer().clone();
+            let timer_j = timer.now() + 64 * self.endpoint.timers().t1;
 
             tokio::spawn(async move {
-                while let Ok(Some(_)) = timeout_at(timer_j, channel.recv()).await {
+                while let Ok(Some(_)) = timer.timeout_at(timer_j, channel.recv()).await {
                     let _result = self.endpoint.send_outgoing_response(&mut response).await;
                 }
                 self.state_machine.set_state(State::Terminated);
@@ -265,6 +448,64 @@ impl ServerTransaction {
         self.request.incoming_info.transport.transport.is_reliable()
     }
 
+    fn set_last_provisional(&mut self, response: OutgoingResponse) -> Result<()> {
+        if let Some(ref mut handle) = self.provisonal_retrans_handle {
+            handle
+                .provisional_tx
+                .send(response)
+                .map_err(|_| Error::ChannelClosed)?
+        } else {
+            let handle = self.spawn_retransmit_provisional_task(response);
+            self.provisonal_retrans_handle = Some(handle);
+        }
+
+        Ok(())
+    }
+
+    fn spawn_retransmit_reliable_provisional_task(
+        &self,
+        response: &OutgoingResponse,
+        mut stop_rx: oneshot::Receiver<()>,
+        timeout_tx: oneshot::Sender<()>,
+    ) {
+        let encoded = response.encoded.clone();
+        let target_info = response.target_info.clone();
+        let timers = *self.endpoint.timers();
+        let timer = self.endpoint.timer().clone();
+        let send_retry = *self.endpoint.send_retry();
+
+        tokio::spawn(async move {
+            let mut interval = timers.t1;
+            let mut retrans_count = 0;
+            let timeout = timer.sleep(64 * timers.t1);
+            tokio::pin!(timeout);
+            loop {
+                tokio::select! {
+                    biased;
+
+                    _ = &mut stop_rx => return,
+                    _ = timeout.as_mut() => break,
+                    _ = timer.sleep(timers.jittered(interval)) => {
+                        if timers.retransmissions_exhausted(retrans_count) {
+                            break;
+                        }
+                        if let Err(err) = send_retry
+                            .send(&target_info.transport, || {
+                                target_info.transport.send_msg(&encoded, &target_info.target)
+                            })
+                            .await {
+                            log::error!("Failed to retransmit: {}", err);
+                        }
+                        retrans_count += 1;
+                        interval *= 2;
+                    }
+                }
+            }
+            log::warn!("No PRACK received for reliable provisional response");
+            let _ = timeout_tx.send(());
+        });
+    }
+
     fn spawn_retransmit_provisional_task(
         &mut self,
         mut response: OutgoingResponse,
@@ -278,6 +519,7 @@ impl ServerTransaction {
 
         let mut state_rx = self.state_machine.subscribe_state();
         let (provisional_tx, mut tu_provisional_rx) = mpsc::unbounded_channel();
+        let send_retry = *self.endpoint.send_retry();
 
         let join_handle = tokio::spawn(async move {
             loop {
@@ -292,10 +534,11 @@ impl ServerTransaction {
                         response = new_tu_provisional;
                     }
                     Some(_msg) = receiver.recv() => {
-                           if let Err(err) = response
-                           .target_info
-                           .transport
-                           .send_msg(&response.encoded, &response.target_info.target)
+                           let OutgoingResponse { encoded, target_info, .. } = &response;
+                           if let Err(err) = send_retry
+                           .send(&target_info.transport, || {
+                               target_info.transport.send_msg(encoded, &target_info.target)
+                           })
                            .await {
                             log::error!("Failed to retransmit: {}", err);
                            }
@@ -314,18 +557,34 @@ impl ServerTransaction {
 impl Drop for ServerTransaction {
     fn drop(&mut self) {
         self.endpoint.transactions().remove(&self.transaction_key);
+        log::trace!(
+            "Transaction Destroyed [{}] from {}",
+            self.transaction_key,
+            self.request.incoming_info.transport.packet.source
+        );
     }
 }
 
 /// Unit tests
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use futures_util::FutureExt;
+
     use super::*;
     use crate::assert_eq_state;
+    use crate::endpoint::EndpointBuilder;
+    use crate::message::headers::Supported;
     use crate::test_utils::transaction::{
-        CODE_100_TRYING, CODE_202_ACCEPTED, CODE_301_MOVED_PERMANENTLY, CODE_504_SERVER_TIMEOUT,
-        ServerTestContext,
+        CODE_100_TRYING, CODE_180_RINGING, CODE_202_ACCEPTED, CODE_301_MOVED_PERMANENTLY,
+        CODE_504_SERVER_TIMEOUT, ServerTestContext,
     };
+    use crate::test_utils::transport::MockTransport;
+    use crate::test_utils::{create_test_endpoint, create_test_request};
+    use crate::testing::MockTimer;
+    use crate::transaction::{T1, TimerConfig};
+    use crate::transport::Transport;
 
     // INVITE Server tests
 
@@ -695,4 +954,321 @@ mod tests {
             "server non-INVITE must transition to the Terminated state when timer J fires"
         );
     }
+
+    #[tokio::test]
+    async fn computed_tsx_key_matches_created_transaction_key() {
+        let endpoint = create_test_endpoint();
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Invite, transport);
+
+        let key = request.compute_tsx_key();
+        let transaction = ServerTransaction::new(request, endpoint);
+
+        assert_eq!(&key, transaction.transaction_key());
+    }
+
+    fn create_100rel_server(transport: &MockTransport, supported: bool) -> ServerTransaction {
+        create_100rel_server_with_endpoint(transport, supported, create_test_endpoint())
+    }
+
+    fn create_100rel_server_with_endpoint(
+        transport: &MockTransport,
+        supported: bool,
+        endpoint: Endpoint,
+    ) -> ServerTransaction {
+        let mut request = create_test_request(Method::Invite, Transport::new(transport.clone()));
+        if supported {
+            let mut supported = Supported::default();
+            supported.add_tag(TAG_100REL);
+            request.request.headers.push(Header::Supported(supported));
+        }
+
+        ServerTransaction::new(request, endpoint)
+    }
+
+    #[tokio::test]
+    async fn reliable_provisional_requires_100rel_support() {
+        let transport = MockTransport::new_udp();
+        let mut server = create_100rel_server(&transport, false);
+        let response = server.create_response(CODE_180_RINGING, None);
+
+        assert!(!server.supports_100rel());
+        assert!(server.send_reliable_provisional(response).await.is_err());
+        assert_eq!(transport.sent_count(), 0);
+
+        let mut server = create_100rel_server(&transport, true);
+        let response = server.create_response(CODE_180_RINGING, None);
+
+        assert!(server.supports_100rel());
+        server.send_reliable_provisional(response).await.unwrap();
+
+        let sent = transport.get_last_sent_message().unwrap();
+        let headers = sent.headers();
+        assert!(
+            headers
+                .iter()
+                .any(|h| matches!(h, Header::Require(r) if r.contains(TAG_100REL)))
+        );
+        assert!(headers.iter().any(|h| matches!(h, Header::RSeq(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn prack_stops_reliable_provisional_retransmissions() {
+        let transport = MockTransport::new_udp();
+        let mut server = create_100rel_server(&transport, true);
+        let response = server.create_response(CODE_180_RINGING, None);
+
+        server.send_reliable_provisional(response).await.unwrap();
+        let rseq = transport
+            .get_last_sent_message()
+            .unwrap()
+            .headers()
+            .iter()
+            .find_map(|h| h.as_r_seq().copied())
+            .unwrap();
+
+        tokio::time::sleep(T1 + T1 * 2).await;
+        tokio::task::yield_now().await;
+        assert_eq!(transport.sent_count(), 3);
+
+        let mut prack = server.request.clone();
+        prack.request.req_line.method = Method::Prack;
+        prack.request.headers.push(Header::RAck(RAck::new(
+            rseq.as_u32() + 1,
+            1,
+            Method::Invite,
+        )));
+        assert!(
+            !server.process_prack(&prack),
+            "RAck with wrong RSeq must not match"
+        );
+
+        prack.request.headers.pop();
+        prack
+            .request
+            .headers
+            .push(Header::RAck(RAck::new(rseq.as_u32(), 1, Method::Invite)));
+        assert!(server.process_prack(&prack));
+        tokio::task::yield_now().await;
+
+        tokio::time::sleep(64 * T1).await;
+        assert_eq!(transport.sent_count(), 3, "no retransmissions after PRACK");
+    }
+
+    #[tokio::test]
+    async fn reliable_provisional_waits_for_prack_of_previous_one() {
+        let transport = MockTransport::new_udp();
+        let mut server = create_100rel_server(&transport, true);
+
+        let response = server.create_response(CODE_180_RINGING, None);
+        server.send_reliable_provisional(response).await.unwrap();
+        let rseq = transport
+            .get_last_sent_message()
+            .unwrap()
+            .headers()
+            .iter()
+            .find_map(|h| h.as_r_seq().copied())
+            .unwrap();
+
+        let response = server.create_response(StatusCode::SessionProgress, None);
+        assert!(matches!(
+            server.send_reliable_provisional(response).await,
+            Err(Error::TransactionError(
+                TransactionError::ReliableProvisionalPending
+            ))
+        ));
+        assert_eq!(transport.sent_count(), 1);
+
+        let mut prack = server.request.clone();
+        prack.request.req_line.method = Method::Prack;
+        prack
+            .request
+            .headers
+            .push(Header::RAck(RAck::new(rseq.as_u32(), 1, Method::Invite)));
+        assert!(server.process_prack(&prack));
+
+        let response = server.create_response(StatusCode::SessionProgress, None);
+        server.send_reliable_provisional(response).await.unwrap();
+        assert_eq!(transport.sent_count(), 2);
+    }
+
+    fn create_100rel_server_with_timer(
+        transport: &MockTransport,
+        timer: &MockTimer,
+    ) -> ServerTransaction {
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timer(timer.clone())
+            .build();
+
+        create_100rel_server_with_endpoint(transport, true, endpoint)
+    }
+
+    // Lets the retransmission task run until it waits on the timer again.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn reliable_provisional_timeout_is_reported_and_cleared() {
+        let transport = MockTransport::new_udp();
+        let timer = MockTimer::new();
+        let mut server = create_100rel_server_with_timer(&transport, &timer);
+
+        let response = server.create_response(CODE_180_RINGING, None);
+        server.send_reliable_provisional(response).await.unwrap();
+        settle().await;
+        timer.advance(64 * T1);
+        settle().await;
+
+        let response = server.create_response(StatusCode::SessionProgress, None);
+        assert_matches!(
+            server.send_reliable_provisional(response).await,
+            Err(Error::TransactionError(
+                TransactionError::ReliableProvisionalTimeout
+            ))
+        );
+
+        let response = server.create_response(StatusCode::SessionProgress, None);
+        server.send_reliable_provisional(response).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reliable_provisional_timeout_completes_after_64_t1() {
+        let transport = MockTransport::new_udp();
+        let timer = MockTimer::new();
+        let mut server = create_100rel_server_with_timer(&transport, &timer);
+
+        let response = server.create_response(CODE_180_RINGING, None);
+        server.send_reliable_provisional(response).await.unwrap();
+        settle().await;
+        timer.advance(64 * T1 - Duration::from_millis(1));
+        settle().await;
+        assert!(
+            server
+                .reliable_provisional_timeout()
+                .now_or_never()
+                .is_none(),
+            "64*T1 has not expired yet"
+        );
+
+        timer.advance(Duration::from_millis(1));
+        settle().await;
+        assert!(
+            server
+                .reliable_provisional_timeout()
+                .now_or_never()
+                .is_some()
+        );
+        assert!(server.reliable_provisional.is_none());
+
+        let response = server.create_response(StatusCode::SessionProgress, None);
+        server.send_reliable_provisional(response).await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invite_final_response_retransmission_falls_within_jitter() {
+        let t1 = Duration::from_millis(100);
+        let jitter = Duration::from_millis(20);
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timers(TimerConfig {
+                t1,
+                jitter,
+                ..Default::default()
+            })
+            .build();
+        let transport = MockTransport::new_udp();
+        let request = create_test_request(Method::Invite, Transport::new(transport.clone()));
+        let server = ServerTransaction::new(request, endpoint);
+
+        server
+            .send_final_status(CODE_301_MOVED_PERMANENTLY)
+            .await
+            .expect("Error sending final response");
+
+        tokio::time::sleep(t1 - Duration::from_millis(1)).await;
+        assert_eq!(transport.sent_count(), 1, "timer G fired before T1");
+        tokio::time::sleep(jitter + Duration::from_millis(2)).await;
+        assert_eq!(transport.sent_count(), 2, "timer G fired after T1 + jitter");
+    }
+
+    #[test]
+    fn jittered_interval_stays_within_band() {
+        let interval = Duration::from_millis(500);
+        let timers = TimerConfig {
+            jitter: Duration::from_millis(50),
+            ..Default::default()
+        };
+
+        for _ in 0..1000 {
+            let jittered = timers.jittered(interval);
+            assert!(jittered >= interval && jittered <= interval + timers.jitter);
+        }
+        assert_eq!(TimerConfig::default().jittered(interval), interval);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invite_terminates_when_retransmissions_are_exhausted() {
+        let t1 = Duration::from_millis(10);
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timers(TimerConfig {
+                t1,
+                max_retransmissions: Some(2),
+                ..Default::default()
+            })
+            .build();
+        let transport = MockTransport::new_udp();
+        let request = create_test_request(Method::Invite, Transport::new(transport.clone()));
+        let mut server = ServerTransaction::new(request, endpoint.clone());
+        let mut state = server.state_machine_mut().subscribe_state();
+
+        server
+            .send_final_status(CODE_301_MOVED_PERMANENTLY)
+            .await
+            .expect("Error sending final response");
+
+        // Timer H would only fire after 64 * T1.
+        tokio::time::timeout(16 * t1, state.wait_for(|s| *s == State::Terminated))
+            .await
+            .expect("transaction must terminate once retransmissions are exhausted")
+            .unwrap();
+        assert_eq!(
+            transport.sent_count(),
+            3,
+            "one response and two retransmissions"
+        );
+        assert!(endpoint.transactions().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invite_retransmits_final_response_using_configured_t1() {
+        let t1 = Duration::from_millis(10);
+        let endpoint = EndpointBuilder::new()
+            .with_transaction(Default::default())
+            .with_timers(TimerConfig {
+                t1,
+                ..Default::default()
+            })
+            .build();
+        let transport = MockTransport::new_udp();
+        let request = create_test_request(Method::Invite, Transport::new(transport.clone()));
+        let server = ServerTransaction::new(request, endpoint);
+
+        server
+            .send_final_status(CODE_301_MOVED_PERMANENTLY)
+            .await
+            .expect("Error sending final response");
+        assert_eq!(transport.sent_count(), 1);
+
+        tokio::time::sleep(t1 + Duration::from_millis(1)).await;
+        assert_eq!(
+            transport.sent_count(),
+            2,
+            "timer G must fire after the configured T1"
+        );
+    }
 }
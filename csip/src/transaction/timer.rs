@@ -0,0 +1,79 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// A future that completes when a [`Timer`] deadline is reached.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// The clock the transaction timers are scheduled on.
+///
+/// Retransmission timers (A, E, G) and timeout timers (B, D, F,
+/// H, I, J, K) all go through the endpoint [`Timer`], so tests
+/// can replace it with a clock they drive themselves.
+///
+/// The endpoint uses [`TokioTimer`] unless another one is set
+/// with
+/// [`EndpointBuilder::with_timer`](crate::endpoint::EndpointBuilder::with_timer).
+pub trait Timer: Send + Sync + 'static {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+
+    /// Returns a future that completes once `deadline` is reached.
+    fn sleep_until(&self, deadline: Instant) -> Sleep;
+}
+
+/// A [`Timer`] backed by [`tokio::time`].
+///
+/// Its clock can be paused and advanced with
+/// [`tokio::time::pause`] and [`tokio::time::advance`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioTimer;
+
+impl Timer for TokioTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Sleep {
+        Box::pin(tokio::time::sleep_until(deadline))
+    }
+}
+
+/// The error returned when a timeout elapses before the future
+/// completes.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Elapsed;
+
+impl dyn Timer {
+    /// Returns a future that completes after `duration`.
+    pub(crate) fn sleep(&self, duration: Duration) -> Sleep {
+        self.sleep_until(self.now() + duration)
+    }
+
+    /// Awaits `future`, failing if `duration` elapses first.
+    pub(crate) async fn timeout<F: Future>(
+        &self,
+        duration: Duration,
+        future: F,
+    ) -> Result<F::Output, Elapsed> {
+        self.timeout_at(self.now() + duration, future).await
+    }
+
+    /// Awaits `future`, failing if `deadline` is reached first.
+    pub(crate) async fn timeout_at<F: Future>(
+        &self,
+        deadline: Instant,
+        future: F,
+    ) -> Result<F::Output, Elapsed> {
+        let sleep = self.sleep_until(deadline);
+
+        tokio::select! {
+            biased;
+
+            output = future => Ok(output),
+            _ = sleep => Err(Elapsed),
+        }
+    }
+}
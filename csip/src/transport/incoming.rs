@@ -1,6 +1,13 @@
+use std::net::SocketAddr;
 use std::ops;
 
-use crate::message::{MandatoryHeaders, Request, Response};
+use crate::endpoint::Endpoint;
+use crate::error::{Error, Result};
+use crate::message::headers::{CSeq, CallId, From, To};
+use crate::message::{
+    HostPort, MandatoryHeaders, Method, ReasonPhrase, Request, Response, StatusCode,
+};
+use crate::transaction::{ServerLookup, TransactionKey};
 
 /// This type represents an received SIP request.
 #[derive(Clone)]
@@ -9,6 +16,89 @@ pub struct IncomingRequest {
     pub request: Request,
     /// Incoming message info.
     pub incoming_info: Box<IncomingInfo>,
+    /// The endpoint that received the request, used to respond
+    /// to it.
+    pub endpoint: Option<Endpoint>,
+}
+
+impl IncomingRequest {
+    fn endpoint(&self) -> Result<&Endpoint> {
+        self.endpoint
+            .as_ref()
+            .ok_or_else(|| Error::Other("request is not bound to an endpoint".into()))
+    }
+
+    /// Responds statelessly to this request, see
+    /// [`Endpoint::respond`].
+    ///
+    /// Returns an error if the request was not received by an
+    /// endpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a server transaction exists for the request,
+    /// its responses must be sent through the transaction.
+    pub async fn respond(&self, code: StatusCode, reason: Option<ReasonPhrase>) -> Result<()> {
+        let endpoint = self.endpoint()?;
+
+        assert!(
+            !endpoint.has_transaction(&self.compute_tsx_key()),
+            "a server transaction exists for the request"
+        );
+
+        endpoint.respond(self, code, reason).await
+    }
+
+    /// Responds to this request with a final response sent
+    /// through its server transaction, created if it does not
+    /// exist yet.
+    ///
+    /// A retransmission of a request that already has a
+    /// transaction is passed to it and no response is sent.
+    ///
+    /// An `ACK` is never responded to. The `ACK` for a 2xx
+    /// response, that belongs to no transaction, is returned to
+    /// be handled by the caller.
+    ///
+    /// Returns an error if the request was not received by an
+    /// endpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `code` is provisional (`1xx`).
+    pub async fn respond_stateful(
+        self,
+        code: StatusCode,
+        reason: Option<ReasonPhrase>,
+    ) -> Result<Option<IncomingRequest>> {
+        let endpoint = self.endpoint()?.clone();
+
+        let transaction = match endpoint.find_or_create_server_transaction(self).await {
+            ServerLookup::Created(transaction) => *transaction,
+            ServerLookup::Found => return Ok(None),
+            ServerLookup::Unmatched(request) => return Ok(Some(*request)),
+        };
+        let response = transaction.create_response(code, reason);
+        transaction.send_final_response(response).await?;
+
+        Ok(None)
+    }
+
+    /// Computes the server transaction key of this request.
+    ///
+    /// The key is derived from the topmost `Via` branch and the `CSeq`
+    /// method, so it can be computed before any transaction exists. It is
+    /// the same key used by the transaction created for this request.
+    pub fn compute_tsx_key(&self) -> TransactionKey {
+        TransactionKey::from_request(self)
+    }
+
+    /// Returns the key used to detect retransmissions of this request.
+    ///
+    /// See [`RetransmissionKey`].
+    pub fn retransmission_key(&self) -> RetransmissionKey {
+        RetransmissionKey::new(&self.incoming_info.mandatory_headers, None)
+    }
 }
 
 impl ops::Deref for IncomingRequest {
@@ -27,6 +117,48 @@ pub struct IncomingResponse {
     pub incoming_info: Box<IncomingInfo>,
 }
 
+impl IncomingResponse {
+    /// Returns the status code of the response.
+    pub fn code(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    /// Returns the `CSeq` header of the response.
+    pub fn cseq(&self) -> &CSeq {
+        &self.incoming_info.mandatory_headers.cseq
+    }
+
+    /// Returns the `From` header of the response.
+    pub fn from(&self) -> &From {
+        &self.incoming_info.mandatory_headers.from
+    }
+
+    /// Returns the `To` header of the response.
+    pub fn to(&self) -> &To {
+        &self.incoming_info.mandatory_headers.to
+    }
+
+    /// Returns the `Call-ID` header of the response.
+    pub fn call_id(&self) -> &CallId {
+        &self.incoming_info.mandatory_headers.call_id
+    }
+
+    /// Returns the address the response was received from.
+    pub fn addr(&self) -> SocketAddr {
+        self.incoming_info.transport.packet.source
+    }
+
+    /// Returns the key used to detect retransmissions of this response.
+    ///
+    /// See [`RetransmissionKey`].
+    pub fn retransmission_key(&self) -> RetransmissionKey {
+        RetransmissionKey::new(
+            &self.incoming_info.mandatory_headers,
+            Some(self.response.status()),
+        )
+    }
+}
+
 impl ops::Deref for IncomingResponse {
     type Target = Response;
     fn deref(&self) -> &Self::Target {
@@ -42,3 +174,227 @@ pub struct IncomingInfo {
     /// The received transport packet.
     pub transport: super::TransportMessage,
 }
+
+/// A key that identifies a received message up to retransmission.
+///
+/// Two datagrams carrying the same message produce equal keys, so a
+/// duplicate can be recognized without comparing the raw bytes. The key is
+/// built from the topmost `Via` branch and sent-by, the `Call-ID`, the
+/// `CSeq`, the `From` and `To` tags and, for responses, the status code.
+///
+/// The `received` and `rport` parameters of the `Via` are not part of the
+/// key, since they are added by the receiver and not by the sender.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RetransmissionKey {
+    branch: Option<String>,
+    sent_by: HostPort,
+    call_id: CallId,
+    cseq: u32,
+    method: Method,
+    from_tag: Option<String>,
+    to_tag: Option<String>,
+    status: Option<u16>,
+}
+
+impl RetransmissionKey {
+    /// Creates the key from the mandatory headers of a message and,
+    /// for responses, its status code.
+    pub fn new(headers: &MandatoryHeaders, status: Option<StatusCode>) -> Self {
+        Self {
+            branch: headers.via.branch.clone(),
+            sent_by: headers.via.sent_by.clone(),
+            call_id: headers.call_id.clone(),
+            cseq: headers.cseq.cseq(),
+            method: headers.cseq.method().clone(),
+            from_tag: headers.from.tag().clone(),
+            to_tag: headers.to.tag().clone(),
+            status: status.map(StatusCode::as_u16),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::SipMessage;
+    use crate::parser::Parser;
+    use crate::test_utils::transport::MockTransport;
+    use crate::test_utils::{create_test_endpoint, create_test_request};
+    use crate::transport::{Packet, Transport, TransportMessage};
+
+    const REQUEST: &[u8] = b"INVITE sip:bob@biloxi.com SIP/2.0\r\n\
+        Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+        Max-Forwards: 70\r\n\
+        To: Bob <sip:bob@biloxi.com>\r\n\
+        From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+        Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+        CSeq: 314159 INVITE\r\n\
+        Content-Length: 0\r\n\r\n";
+
+    fn key(src: &[u8]) -> RetransmissionKey {
+        let (_, headers) = Parser::new(src)
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+
+        RetransmissionKey::new(&headers, None)
+    }
+
+    fn replace(src: &[u8], from: &str, to: &str) -> Vec<u8> {
+        std::str::from_utf8(src)
+            .unwrap()
+            .replacen(from, to, 1)
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_identical_datagrams_have_same_key() {
+        let datagram = REQUEST.to_vec();
+
+        assert_eq!(key(REQUEST), key(&datagram));
+    }
+
+    #[test]
+    fn test_key_ignores_received_and_rport() {
+        let src = replace(
+            REQUEST,
+            "branch=z9hG4bK776asdhds",
+            "branch=z9hG4bK776asdhds;received=192.0.2.1;rport=5060",
+        );
+
+        assert_eq!(key(REQUEST), key(&src));
+    }
+
+    #[test]
+    fn test_modified_datagram_has_different_key() {
+        let cseq = replace(REQUEST, "CSeq: 314159", "CSeq: 314160");
+        let branch = replace(REQUEST, "z9hG4bK776asdhds", "z9hG4bK776asdhdt");
+        let to_tag = replace(
+            REQUEST,
+            "<sip:bob@biloxi.com>\r\n",
+            "<sip:bob@biloxi.com>;tag=a6c85cf\r\n",
+        );
+
+        assert_ne!(key(REQUEST), key(&cseq));
+        assert_ne!(key(REQUEST), key(&branch));
+        assert_ne!(key(REQUEST), key(&to_tag));
+    }
+
+    #[test]
+    fn test_response_key_includes_status() {
+        let (_, headers) = Parser::new(REQUEST)
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+
+        assert_ne!(
+            RetransmissionKey::new(&headers, Some(StatusCode::Trying)),
+            RetransmissionKey::new(&headers, Some(StatusCode::Ok))
+        );
+    }
+
+    fn bound_request(method: Method) -> (IncomingRequest, Endpoint, MockTransport) {
+        let endpoint = create_test_endpoint();
+        let mock = MockTransport::new_udp();
+        let mut request = create_test_request(method, Transport::new(mock.clone()));
+        request.endpoint = Some(endpoint.clone());
+
+        (request, endpoint, mock)
+    }
+
+    #[tokio::test]
+    async fn test_respond_sends_stateless_response() {
+        let (request, endpoint, mock) = bound_request(Method::Options);
+
+        request.respond(StatusCode::Ok, None).await.unwrap();
+
+        assert_eq!(mock.sent_count(), 1);
+        assert!(endpoint.transactions().is_empty());
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "a server transaction exists for the request")]
+    async fn test_respond_panics_if_transaction_exists() {
+        let (request, endpoint, _mock) = bound_request(Method::Options);
+        let _transaction = endpoint.new_server_transaction(request.clone());
+
+        let _ = request.respond(StatusCode::Ok, None).await;
+    }
+
+    #[tokio::test]
+    async fn test_respond_stateful_uses_server_transaction() {
+        let (request, endpoint, mock) = bound_request(Method::Options);
+
+        request
+            .clone()
+            .respond_stateful(StatusCode::Ok, None)
+            .await
+            .unwrap();
+
+        assert_eq!(mock.sent_count(), 1);
+        // The transaction waits for retransmissions in Completed.
+        assert_eq!(endpoint.transactions().len(), 1);
+
+        request
+            .respond_stateful(StatusCode::Ok, None)
+            .await
+            .unwrap();
+        assert_eq!(endpoint.transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_respond_stateful_returns_unmatched_ack() {
+        let (request, endpoint, mock) = bound_request(Method::Ack);
+
+        let ack = request
+            .respond_stateful(StatusCode::Ok, None)
+            .await
+            .unwrap();
+
+        assert!(ack.is_some_and(|ack| ack.method() == Method::Ack));
+        assert_eq!(mock.sent_count(), 0);
+        assert!(endpoint.transactions().is_empty());
+    }
+
+    #[test]
+    fn test_response_accessors_return_parsed_values() {
+        let src = b"SIP/2.0 180 Ringing\r\n\
+            Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+            To: Bob <sip:bob@biloxi.com>;tag=a6c85cf\r\n\
+            From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+            Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+            CSeq: 314159 INVITE\r\n\
+            Content-Length: 0\r\n\r\n";
+        let (message, mandatory_headers) = Parser::new(src)
+            .parse_sip_msg_with_mandatory_headers()
+            .unwrap();
+        let SipMessage::Response(response) = message else {
+            panic!("expected a response");
+        };
+        let source = "192.0.2.4:5060".parse().unwrap();
+        let response = IncomingResponse {
+            response,
+            incoming_info: Box::new(IncomingInfo {
+                mandatory_headers,
+                transport: TransportMessage {
+                    transport: Transport::new(MockTransport::new_udp()),
+                    packet: Packet::new(Default::default(), source),
+                },
+            }),
+        };
+
+        assert_eq!(response.code(), StatusCode::Ringing);
+        assert_eq!(response.cseq().cseq(), 314159);
+        assert_eq!(response.cseq().method(), &Method::Invite);
+        assert_eq!(response.from().tag().as_deref(), Some("1928301774"));
+        assert_eq!(response.to().tag().as_deref(), Some("a6c85cf"));
+        assert_eq!(response.call_id().id(), "a84b4c76e66710@pc33.atlanta.com");
+        assert_eq!(response.addr(), source);
+    }
+
+    #[tokio::test]
+    async fn test_respond_without_endpoint_is_an_error() {
+        let transport = Transport::new(MockTransport::new_udp());
+        let request = create_test_request(Method::Options, transport);
+
+        assert!(request.respond(StatusCode::Ok, None).await.is_err());
+    }
+}
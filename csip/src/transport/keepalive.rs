@@ -0,0 +1,174 @@
+//! Keep-alive for connection-oriented transports.
+//!
+//! Implements the client side of the CRLF keep-alive
+//! technique described in [RFC 5626 section 4.4.1]: a
+//! double-CRLF "ping" is periodically sent over the flow and
+//! a single CRLF "pong" is expected back.
+//!
+//! [RFC 5626 section 4.4.1]: https://www.rfc-editor.org/rfc/rfc5626#section-4.4.1
+
+use std::time::Duration;
+
+use super::{KEEPALIVE_REQUEST, Transport};
+use crate::Endpoint;
+
+/// Default interval between two pings.
+pub const DEFAULT_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Default time to wait for a pong.
+pub const DEFAULT_KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Keep-alive settings for reliable transports.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use csip::*;
+/// # use csip::transport::keepalive::KeepAliveConfig;
+/// let endpoint = endpoint::EndpointBuilder::new()
+///     .with_keepalive(KeepAliveConfig {
+///         interval: Duration::from_secs(30),
+///         ..Default::default()
+///     })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepAliveConfig {
+    /// Time between two pings.
+    pub interval: Duration,
+    /// Time to wait for the pong before the flow is
+    /// considered failed.
+    pub timeout: Duration,
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_KEEPALIVE_INTERVAL,
+            timeout: DEFAULT_KEEPALIVE_TIMEOUT,
+        }
+    }
+}
+
+/// Sends pings over `transport` until a pong is missed or
+/// the ping can not be sent, then closes the transport and
+/// stops its read loop.
+pub(crate) async fn run(transport: Transport, endpoint: Endpoint, config: KeepAliveConfig) {
+    let Some(remote) = transport.remote_addr() else {
+        return;
+    };
+
+    loop {
+        tokio::time::sleep(config.interval).await;
+
+        // Registered before the ping is sent, so a pong received
+        // right after it is not missed.
+        let pong = transport.pong.notified();
+        tokio::pin!(pong);
+        pong.as_mut().enable();
+
+        if let Err(err) = transport.send_msg(KEEPALIVE_REQUEST, &remote).await {
+            log::warn!("Failed to send keep-alive to {}: {}", remote, err);
+            break;
+        }
+        if tokio::time::timeout(config.timeout, pong).await.is_err() {
+            log::info!("No keep-alive response from {}, closing flow", remote);
+            break;
+        }
+    }
+
+    if let Err(err) = endpoint.close_transport(&transport).await {
+        log::warn!("Failed to close transport {}: {}", remote, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::endpoint::EndpointHandler;
+    use crate::test_utils::transport::MockTransport;
+    use crate::transport::incoming::IncomingRequest;
+
+    const CONFIG: KeepAliveConfig = KeepAliveConfig {
+        interval: Duration::from_secs(30),
+        timeout: Duration::from_secs(10),
+    };
+
+    struct ClosedHandler(Arc<Notify>);
+
+    #[async_trait::async_trait]
+    impl EndpointHandler for ClosedHandler {
+        async fn handle(&self, _request: IncomingRequest, _endpoint: &Endpoint) {}
+
+        async fn on_transport_closed(&self, _transport: &Transport, _endpoint: &Endpoint) {
+            self.0.notify_one();
+        }
+    }
+
+    fn setup() -> (MockTransport, Transport, Endpoint, Arc<Notify>) {
+        let mock = MockTransport::new_tcp().with_remote_addr("127.0.0.1:5070".parse().unwrap());
+        let transport = Transport::new(mock.clone());
+        let closed = Arc::new(Notify::new());
+        let endpoint = Endpoint::builder()
+            .with_handler(ClosedHandler(closed.clone()))
+            .build();
+        endpoint
+            .transports()
+            .register_transport(transport.clone())
+            .unwrap();
+
+        (mock, transport, endpoint, closed)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ping_pong_keeps_flow_open() {
+        let (mock, transport, endpoint, _) = setup();
+        let task = tokio::spawn(run(transport.clone(), endpoint.clone(), CONFIG));
+
+        tokio::time::sleep(CONFIG.interval + Duration::from_millis(1)).await;
+        assert_eq!(mock.last_buffer().as_deref(), Some(KEEPALIVE_REQUEST));
+        assert_eq!(mock.sent_count(), 1);
+
+        transport.record_pong();
+        tokio::time::sleep(CONFIG.interval + Duration::from_millis(1)).await;
+
+        assert_eq!(mock.sent_count(), 2);
+        assert!(!task.is_finished());
+        assert_eq!(endpoint.transports().transport_count().unwrap(), 1);
+        task.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_missing_pong_closes_flow() {
+        let (mock, transport, endpoint, closed) = setup();
+        let task = tokio::spawn(run(transport.clone(), endpoint.clone(), CONFIG));
+
+        tokio::time::timeout(CONFIG.interval + CONFIG.timeout * 2, closed.notified())
+            .await
+            .expect("on_transport_closed was not called");
+
+        tokio::time::timeout(Duration::from_millis(1), transport.closed())
+            .await
+            .expect("transport was not shut down");
+        assert_eq!(mock.sent_count(), 1);
+        assert_eq!(endpoint.transports().transport_count().unwrap(), 0);
+        assert!(task.await.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_closing_twice_notifies_once() {
+        let (_, transport, endpoint, closed) = setup();
+
+        endpoint.close_transport(&transport).await.unwrap();
+        endpoint.close_transport(&transport).await.unwrap();
+
+        closed.notified().await;
+        let second = tokio::time::timeout(Duration::from_millis(1), closed.notified()).await;
+        assert!(second.is_err());
+    }
+}
@@ -21,31 +21,39 @@ use std::io::{self};
 use std::net::{IpAddr, SocketAddr};
 use std::ops;
 use std::result::Result as StdResult;
-use std::str::FromStr;
+use std::str::{self, FromStr};
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
 use utils::{NAPTR, Name, RData, SRV};
 
 use crate::Endpoint;
 use crate::error::{Error, Result};
-use crate::message::SipMessage;
-use crate::message::uri::{DomainName, Host, Scheme, Uri};
+use crate::message::uri::{DomainName, Host, HostPort, Scheme, Uri};
+use crate::message::{MandatoryHeaders, SipMessage};
 use crate::parser::Parser;
 use crate::transport::tcp::TcpTransport;
 use crate::transport::ws::WebSocketTransport;
 
 // Core Transport modules
 mod decode;
+mod stats;
 
 pub mod incoming;
+pub mod keepalive;
 pub mod outgoing;
+pub mod retry;
 pub mod tcp;
 pub mod udp;
 pub mod ws;
 
+pub use stats::TransportStats;
+use stats::{Counters, TransportCounters};
+
 /// Keep-alive Request.
 pub const KEEPALIVE_REQUEST: &[u8] = b"\r\n\r\n";
 
@@ -63,6 +71,13 @@ pub(crate) type TransportsMap = HashMap<TransportKey, Transport>;
 pub struct Transport {
     /// Shared transport instance.
     shared: Arc<dyn SipTransport>,
+    /// Counters shared by all clones of this transport.
+    counters: Arc<TransportCounters>,
+    /// Notified when a keep-alive response is received.
+    pong: Arc<Notify>,
+    /// Cancelled when the transport is closed, to stop its
+    /// read loop.
+    closed: CancellationToken,
 }
 
 impl Transport {
@@ -70,8 +85,71 @@ impl Transport {
     pub fn new(transport: impl SipTransport) -> Self {
         Transport {
             shared: Arc::new(transport),
+            counters: Default::default(),
+            pong: Default::default(),
+            closed: CancellationToken::new(),
         }
     }
+
+    /// Sends data on the socket to the given address, updating the
+    /// transport statistics. On success, returns the number of bytes
+    /// written.
+    pub async fn send_msg(&self, buf: &[u8], address: &SocketAddr) -> Result<usize> {
+        let sent = self.shared.send_msg(buf, address).await?;
+        self.counters.record_sent(sent);
+
+        Ok(sent)
+    }
+
+    /// Sends data to the multicast group `address` with the
+    /// given IP `ttl`, updating the transport statistics.
+    pub async fn send_multicast(
+        &self,
+        buf: &[u8],
+        address: &SocketAddr,
+        ttl: u32,
+    ) -> Result<usize> {
+        let sent = self.shared.send_multicast(buf, address, ttl).await?;
+        self.counters.record_sent(sent);
+
+        Ok(sent)
+    }
+
+    /// Returns the address this transport is bound to, as
+    /// a `HostPort` suitable for `Via` sent-by and `Contact`.
+    ///
+    /// An advertised address configured in the endpoint takes
+    /// precedence over this, see [`Endpoint::local_name`].
+    pub fn local_name(&self) -> HostPort {
+        self.local_addr().into()
+    }
+
+    /// Returns a snapshot of the statistics of this transport.
+    pub fn stats(&self) -> TransportStats {
+        self.counters.snapshot()
+    }
+
+    pub(crate) fn record_received(&self, len: usize) {
+        self.counters.record_received(len);
+    }
+
+    pub(crate) fn record_parse_error(&self) {
+        self.counters.record_parse_error();
+    }
+
+    pub(crate) fn record_pong(&self) {
+        self.pong.notify_waiters();
+    }
+
+    // Stops the read loop of the transport, if any.
+    pub(crate) fn shutdown(&self) {
+        self.closed.cancel();
+    }
+
+    // Completes once the transport has been shut down.
+    pub(crate) async fn closed(&self) {
+        self.closed.cancelled().await
+    }
 }
 
 impl ops::Deref for Transport {
@@ -86,12 +164,22 @@ impl ops::Deref for Transport {
 pub struct TransportManager {
     /// All transports indexed by their unique keys.
     transports: Mutex<TransportsMap>,
+    /// Factories used to create connection-oriented transports on demand.
+    factories: Mutex<HashMap<TransportType, Arc<dyn TransportFactory>>>,
+    /// Counters of all transports registered.
+    counters: Arc<Counters>,
 }
 
 impl From<TransportsMap> for TransportManager {
     fn from(value: TransportsMap) -> Self {
+        let counters = Arc::new(Counters::default());
+        for transport in value.values() {
+            transport.counters.link(counters.clone());
+        }
         Self {
             transports: Mutex::new(value),
+            factories: Default::default(),
+            counters,
         }
     }
 }
@@ -101,11 +189,37 @@ impl TransportManager {
     pub fn new() -> Self {
         TransportManager {
             transports: Mutex::new(HashMap::new()),
+            factories: Default::default(),
+            counters: Default::default(),
         }
     }
 
+    /// Registers a factory used to create transports of its
+    /// [`TransportType`] when no connection to the destination exists.
+    ///
+    /// Replaces any factory previously registered for the same type.
+    pub fn register_factory(&self, factory: impl TransportFactory) -> Result<()> {
+        let mut map = self.factories.lock().map_err(|_| Error::PoisonedLock)?;
+
+        map.insert(factory.transport_type(), Arc::new(factory));
+
+        Ok(())
+    }
+
+    fn get_factory(&self, protocol: TransportType) -> Result<Option<Arc<dyn TransportFactory>>> {
+        let map = self.factories.lock().map_err(|_| Error::PoisonedLock)?;
+
+        Ok(map.get(&protocol).cloned())
+    }
+
+    /// Returns a snapshot of the statistics of all transports registered.
+    pub fn stats(&self) -> TransportStats {
+        self.counters.snapshot()
+    }
+
     /// Add a new transport to the manager.
     pub fn register_transport(&self, transport: Transport) -> Result<()> {
+        transport.counters.link(self.counters.clone());
         let key = transport.key();
         let mut map = self.transports.lock().map_err(|_| Error::PoisonedLock)?;
 
@@ -114,11 +228,25 @@ impl TransportManager {
         Ok(())
     }
 
-    /// Remove a transport by its key.
-    pub fn remove_transport(&self, key: &TransportKey) -> Result<()> {
+    /// Remove a transport by its key, returning it if it was
+    /// registered.
+    pub fn remove_transport(&self, key: &TransportKey) -> Result<Option<Transport>> {
+        let mut map = self.transports.lock().map_err(|_| Error::PoisonedLock)?;
+
+        Ok(map.remove(key))
+    }
+
+    /// Shut down and remove all transports.
+    ///
+    /// Shutting down a transport stops its read loop, closing the
+    /// connection of connection oriented transports.
+    pub fn clear(&self) -> Result<()> {
         let mut map = self.transports.lock().map_err(|_| Error::PoisonedLock)?;
 
-        map.remove(key);
+        for transport in map.values() {
+            transport.shutdown();
+        }
+        map.clear();
 
         Ok(())
     }
@@ -319,6 +447,20 @@ impl TransportManager {
         Ok(map.get(key).cloned())
     }
 
+    /// Returns all the registered transports.
+    pub(crate) fn all(&self) -> Result<Vec<Transport>> {
+        let map = self.transports.lock().map_err(|_| Error::PoisonedLock)?;
+
+        Ok(map.values().cloned().collect())
+    }
+
+    /// Returns `true` if a transport with `key` is registered.
+    pub(crate) fn contains(&self, key: &TransportKey) -> Result<bool> {
+        let map = self.transports.lock().map_err(|_| Error::PoisonedLock)?;
+
+        Ok(map.contains_key(key))
+    }
+
     fn get_by_transport_type_and_ip_family(
         &self,
         protocol: TransportType,
@@ -336,7 +478,7 @@ impl TransportManager {
         }
     }
 
-    async fn get_or_create_transport(
+    pub(crate) async fn get_or_create_transport(
         &self,
         protocol: TransportType,
         addr: SocketAddr,
@@ -346,6 +488,14 @@ impl TransportManager {
         if let Some(transport) = self.get_by_key(&key)? {
             return Ok(transport.clone());
         }
+        if protocol.is_reliable()
+            && let Some(factory) = self.get_factory(protocol)?
+        {
+            let transport = factory.create(addr, endpoint).await?;
+            self.register_transport(transport.clone())?;
+
+            return Ok(transport);
+        }
         let transport = match protocol {
             TransportType::Tcp => TcpTransport::connect(addr, endpoint).await?,
             TransportType::Ws | TransportType::Wss => {
@@ -391,6 +541,54 @@ pub enum TransportType {
     Tls,
     /// Sctp.
     Sctp,
+    /// An unknown transport, as found in a `Via`.
+    Unknown(ExtensionTransport),
+}
+
+/// The maximum length of an [`ExtensionTransport`] token.
+///
+/// Keeps [`TransportType`] small and `Copy`.
+pub const MAX_EXTENSION_TRANSPORT_LEN: usize = 14;
+
+/// The token of an unknown transport.
+///
+/// The token is stored inline, so [`TransportType`] can remain
+/// `Copy`. Tokens are at most [`MAX_EXTENSION_TRANSPORT_LEN`]
+/// bytes long.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtensionTransport {
+    len: u8,
+    buf: [u8; MAX_EXTENSION_TRANSPORT_LEN],
+}
+
+impl ExtensionTransport {
+    /// Creates a new extension transport token.
+    ///
+    /// Returns `None` if `token` is empty or longer than
+    /// [`MAX_EXTENSION_TRANSPORT_LEN`] bytes.
+    pub fn new(token: &str) -> Option<Self> {
+        if token.is_empty() || token.len() > MAX_EXTENSION_TRANSPORT_LEN {
+            return None;
+        }
+        let mut buf = [0; MAX_EXTENSION_TRANSPORT_LEN];
+        buf[..token.len()].copy_from_slice(token.as_bytes());
+
+        Some(Self {
+            len: token.len() as u8,
+            buf,
+        })
+    }
+
+    /// Returns the transport token as str.
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.buf[..self.len as usize]).expect("copied from a `&str`")
+    }
+}
+
+impl fmt::Debug for ExtensionTransport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
 }
 
 impl TransportType {
@@ -402,6 +600,27 @@ impl TransportType {
         )
     }
 
+    /// Parses the transport token of a `Via` sent-protocol,
+    /// ignoring case.
+    ///
+    /// Unknown tokens map to [`TransportType::Unknown`], keeping
+    /// the token for display. Returns `None` if the token is
+    /// empty or longer than [`MAX_EXTENSION_TRANSPORT_LEN`] bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use csip::transport::TransportType;
+    /// assert_eq!(TransportType::from_via_token("wss"), Some(TransportType::Wss));
+    /// assert_eq!(TransportType::from_via_token("QUIC").unwrap().to_string(), "QUIC");
+    /// ```
+    pub fn from_via_token(token: &str) -> Option<Self> {
+        token
+            .parse()
+            .ok()
+            .or_else(|| ExtensionTransport::new(token).map(Self::Unknown))
+    }
+
     pub(crate) fn from_naptr_service(service: &[u8]) -> Option<Self> {
         match service {
             b"SIP+D2U" => Some(Self::Udp),
@@ -426,7 +645,7 @@ impl TransportType {
     #[inline]
     pub const fn default_port(&self) -> u16 {
         match self {
-            Self::Udp | Self::Tcp | Self::Sctp => 5060,
+            Self::Udp | Self::Tcp | Self::Sctp | Self::Unknown(_) => 5060,
             Self::Tls => 5061,
             Self::Ws | Self::Wss => 80,
         }
@@ -476,6 +695,7 @@ impl fmt::Display for TransportType {
             Sctp => "SCTP",
             Ws => "WS",
             Wss => "WSS",
+            Unknown(token) => token.as_str(),
         })
     }
 }
@@ -487,6 +707,16 @@ pub trait SipTransport: Send + Sync + 'static {
     /// number of bytes written.
     async fn send_msg(&self, buf: &[u8], address: &SocketAddr) -> Result<usize>;
 
+    /// Sends data to the multicast group `address` with the
+    /// given IP `ttl`.
+    ///
+    /// Transports without multicast support send it as a
+    /// regular message.
+    async fn send_multicast(&self, buf: &[u8], address: &SocketAddr, ttl: u32) -> Result<usize> {
+        let _ = ttl;
+        self.send_msg(buf, address).await
+    }
+
     /// Get transport type.
     fn transport_type(&self) -> TransportType;
 
@@ -514,6 +744,20 @@ pub trait SipTransport: Send + Sync + 'static {
     }
 }
 
+/// A factory that creates connection-oriented transports on demand.
+///
+/// Factories are registered with [`TransportManager::register_factory`] and
+/// consulted when a message must be sent to a destination for which no
+/// connection exists yet.
+#[async_trait]
+pub trait TransportFactory: Send + Sync + 'static {
+    /// The type of the transports created by this factory.
+    fn transport_type(&self) -> TransportType;
+
+    /// Creates a new transport connected to `addr`.
+    async fn create(&self, addr: SocketAddr, endpoint: &Endpoint) -> Result<Transport>;
+}
+
 /// Unique key for a transport instance.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct TransportKey {
@@ -535,7 +779,10 @@ where
     T: SipTransport + ?Sized,
 {
     fn from(transport: &T) -> Self {
-        let address = transport.local_addr();
+        // Connection-oriented transports are identified by their peer.
+        let address = transport
+            .remote_addr()
+            .unwrap_or_else(|| transport.local_addr());
         let tp_type = transport.transport_type();
 
         Self { address, tp_type }
@@ -576,24 +823,33 @@ pub struct TransportMessage {
 impl TransportMessage {
     /// Parse the packet into an sip message.
     pub fn parse(&self) -> Result<SipMessage> {
+        let parsed = Parser::from_bytes(&self.packet.data).parse_sip_msg();
+
+        self.log_parse_error(parsed)
+    }
+
+    /// Parse the packet into an sip message, extracting its
+    /// [`MandatoryHeaders`] in the same pass.
+    pub fn parse_with_mandatory_headers(&self) -> Result<(SipMessage, MandatoryHeaders)> {
+        let parsed = Parser::from_bytes(&self.packet.data).parse_sip_msg_with_mandatory_headers();
+
+        self.log_parse_error(parsed)
+    }
+
+    fn log_parse_error<T>(&self, parsed: Result<T>) -> Result<T> {
         let Self { transport, packet } = self;
-        let sip_message = match Parser::parse(&packet.data) {
-            Ok(parsed) => parsed,
-            Err(err) => {
-                log::warn!(
-                    "Ignoring {} bytes packet from {} {} : {}\n{}-- end of packet.",
-                    packet.data.len(),
-                    transport.transport_type(),
-                    packet.source,
-                    err,
-                    String::from_utf8_lossy(&packet.data)
-                );
-
-                return Err(err);
-            }
-        };
 
-        Ok(sip_message)
+        parsed.inspect_err(|err| {
+            transport.record_parse_error();
+            log::warn!(
+                "Ignoring {} bytes packet from {} {} : {}\n{}-- end of packet.",
+                packet.data.len(),
+                transport.transport_type(),
+                packet.source,
+                err,
+                String::from_utf8_lossy(&packet.data)
+            );
+        })
     }
 }
 
@@ -607,6 +863,8 @@ fn is_same_ip_family(first: &IpAddr, second: &IpAddr) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use super::*;
     use crate::test_utils::transport::MockTransport;
 
@@ -701,4 +959,72 @@ mod tests {
         assert_eq!(key.address, transport.local_addr());
         assert_eq!(key.tp_type, transport.transport_type());
     }
+
+    struct CountingFactory(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl TransportFactory for CountingFactory {
+        fn transport_type(&self) -> TransportType {
+            TransportType::Tcp
+        }
+
+        async fn create(&self, addr: SocketAddr, _endpoint: &Endpoint) -> Result<Transport> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(Transport::new(
+                MockTransport::new_tcp().with_remote_addr(addr),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_or_create_transport_invokes_factory_once() {
+        let endpoint = crate::test_utils::create_test_endpoint();
+        let created = Arc::new(AtomicUsize::new(0));
+        let manager = TransportManager::new();
+        manager
+            .register_factory(CountingFactory(created.clone()))
+            .unwrap();
+        let addr: SocketAddr = "192.0.2.10:5060".parse().unwrap();
+
+        let first = manager
+            .get_or_create_transport(TransportType::Tcp, addr, &endpoint)
+            .await
+            .unwrap();
+        let second = manager
+            .get_or_create_transport(TransportType::Tcp, addr, &endpoint)
+            .await
+            .unwrap();
+
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+        assert_eq!(first.key(), second.key());
+        assert_eq!(first.remote_addr(), Some(addr));
+        assert_eq!(manager.transport_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_parsed_body_shares_packet_payload() {
+        let data = Bytes::from_static(
+            b"MESSAGE sip:bob@biloxi.com SIP/2.0\r\n\
+            Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK776asdhds\r\n\
+            To: Bob <sip:bob@biloxi.com>\r\n\
+            From: Alice <sip:alice@atlanta.com>;tag=1928301774\r\n\
+            Call-ID: a84b4c76e66710@pc33.atlanta.com\r\n\
+            CSeq: 1 MESSAGE\r\n\
+            Content-Type: text/plain\r\n\
+            Content-Length: 5\r\n\r\n\
+            hello",
+        );
+        let range = data.as_ptr_range();
+        let message = TransportMessage {
+            transport: Transport::new(MockTransport::new_udp()),
+            packet: Packet::new(data.clone(), "127.0.0.1:5060".parse().unwrap()),
+        };
+
+        let (msg, _) = message.parse_with_mandatory_headers().unwrap();
+        let body = msg.body().unwrap().as_bytes();
+
+        assert_eq!(&body[..], b"hello");
+        assert!(range.contains(&body.as_ptr()));
+        assert_eq!(body.as_ptr_range().end, range.end);
+    }
 }
@@ -1,13 +1,11 @@
-use std::io::Write;
 use std::net::SocketAddr;
 use std::ops;
 
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{Bytes, BytesMut};
 
+use crate::MediaType;
 use crate::error::Result;
-use crate::message::headers::{ContentLength, Headers};
-use crate::message::{ReasonPhrase, Request, Response, SipBody, StatusCode};
-use crate::parser::HeaderParser;
+use crate::message::{Request, Response, SipBody, SipMessage};
 
 /// This type represents an outbound SIP request.
 pub struct OutgoingRequest {
@@ -72,24 +70,64 @@ pub trait Encode {
     fn encode(&self) -> Result<Self::Buffer>;
 }
 
+impl OutgoingRequest {
+    /// Appends the encoded request to `buf`, reusing its
+    /// allocation.
+    pub fn write_to(&self, buf: &mut BytesMut) -> Result<()> {
+        self.request.write_to(buf)
+    }
+
+    /// Sets the body of the request, see [`Request::set_body`].
+    ///
+    /// Any previously encoded representation is discarded.
+    pub fn set_body(&mut self, body: Option<impl Into<SipBody>>) {
+        self.request.set_body(body);
+        self.encoded = Bytes::new();
+    }
+
+    /// Sets the body of the request and its `Content-Type` header,
+    /// see [`Request::set_body_with_type`].
+    ///
+    /// Any previously encoded representation is discarded.
+    pub fn set_body_with_type(&mut self, body: impl Into<SipBody>, media_type: MediaType) {
+        self.request.set_body_with_type(body, media_type);
+        self.encoded = Bytes::new();
+    }
+}
+
+impl OutgoingResponse {
+    /// Appends the encoded response to `buf`, reusing its
+    /// allocation.
+    pub fn write_to(&self, buf: &mut BytesMut) -> Result<()> {
+        self.response.write_to(buf)
+    }
+
+    /// Sets the body of the response, see [`Response::set_body`].
+    ///
+    /// Any previously encoded representation is discarded.
+    pub fn set_body(&mut self, body: Option<impl Into<SipBody>>) {
+        self.response.set_body(body);
+        self.encoded = Bytes::new();
+    }
+
+    /// Sets the body of the response and its `Content-Type` header,
+    /// see [`Response::set_body_with_type`].
+    ///
+    /// Any previously encoded representation is discarded.
+    pub fn set_body_with_type(&mut self, body: impl Into<SipBody>, media_type: MediaType) {
+        self.response.set_body_with_type(body, media_type);
+        self.encoded = Bytes::new();
+    }
+}
+
 impl Encode for OutgoingResponse {
     type Buffer = Bytes;
 
     fn encode(&self) -> Result<Self::Buffer> {
-        let response = &self.response;
-        let buf = BytesMut::new();
-        let mut writer = buf.writer();
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf)?;
 
-        write!(
-            writer,
-            "SIP/2.0 {} {}\r\n",
-            response.status().as_u16(),
-            response.reason().as_str()
-        )?;
-        write!(writer, "{}", response.headers())?;
-        write_body(&mut writer, response.body())?;
-
-        Ok(writer.into_inner().freeze())
+        Ok(buf.freeze())
     }
 }
 
@@ -97,27 +135,121 @@ impl Encode for OutgoingRequest {
     type Buffer = Bytes;
 
     fn encode(&self) -> Result<Self::Buffer> {
-        let request = &self.request;
-        let buf = BytesMut::new();
-        let mut writer = buf.writer();
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf)?;
+
+        Ok(buf.freeze())
+    }
+}
 
-        write!(writer, "{}", request.req_line)?;
-        write!(writer, "{}", request.headers)?;
-        write_body(&mut writer, request.body.as_ref())?;
+impl Encode for Request {
+    type Buffer = Bytes;
+
+    fn encode(&self) -> Result<Self::Buffer> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf)?;
 
-        Ok(writer.into_inner().freeze())
+        Ok(buf.freeze())
     }
 }
 
-fn write_body<W: Write>(writer: &mut W, body: Option<&SipBody>) -> Result<()> {
-    const CONTENT_LENGTH: &str = ContentLength::NAME;
-    if let Some(body) = body {
-        write!(writer, "{CONTENT_LENGTH}: {}\r\n", body.len())?;
-        write!(writer, "\r\n")?;
-        writer.write_all(body)?;
-    } else {
-        write!(writer, "{CONTENT_LENGTH}: 0\r\n")?;
-        write!(writer, "\r\n")?;
+impl Encode for Response {
+    type Buffer = Bytes;
+
+    fn encode(&self) -> Result<Self::Buffer> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf)?;
+
+        Ok(buf.freeze())
+    }
+}
+
+impl Encode for SipMessage {
+    type Buffer = Bytes;
+
+    fn encode(&self) -> Result<Self::Buffer> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf)?;
+
+        Ok(buf.freeze())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::headers::{ContentType, Header};
+    use crate::message::{Method, StatusCode, StatusLine};
+    use crate::test_utils::transport::MockTransport;
+    use crate::transport::Transport;
+
+    #[test]
+    fn test_write_to_reuses_buffer() {
+        let status_line = StatusLine::new(StatusCode::Ok, StatusCode::Ok.reason());
+        let response = OutgoingResponse {
+            response: Response::with_body(status_line, b"hello"),
+            target_info: TargetTransportInfo {
+                target: "127.0.0.1:5060".parse().unwrap(),
+                transport: Transport::new(MockTransport::new_udp()),
+            },
+            encoded: Bytes::new(),
+        };
+        let encoded = response.encode().unwrap();
+        let mut buf = BytesMut::new();
+
+        response.write_to(&mut buf).unwrap();
+        response.write_to(&mut buf).unwrap();
+
+        assert_eq!(&buf[..], [&encoded[..], &encoded[..]].concat());
+        assert!(encoded.ends_with(b"Content-Length: 5\r\n\r\nhello"));
+    }
+
+    #[test]
+    fn test_set_body_with_type_sets_one_content_type() {
+        let status_line = StatusLine::new(StatusCode::Ok, StatusCode::Ok.reason());
+        let mut response = OutgoingResponse {
+            response: Response::new(status_line),
+            target_info: TargetTransportInfo {
+                target: "127.0.0.1:5060".parse().unwrap(),
+                transport: Transport::new(MockTransport::new_udp()),
+            },
+            encoded: Bytes::from_static(b"stale"),
+        };
+        let sdp = b"v=0\r\n";
+        response
+            .headers_mut()
+            .push(Header::ContentType(ContentType::new(MediaType::new(
+                "text", "plain",
+            ))));
+
+        response.set_body_with_type(&sdp[..], MediaType::new("application", "sdp"));
+
+        let content_types: Vec<_> = response
+            .headers()
+            .iter()
+            .filter_map(Header::as_content_type)
+            .collect();
+        assert_eq!(content_types.len(), 1);
+        assert_eq!(
+            content_types[0].media_type(),
+            &MediaType::new("application", "sdp")
+        );
+        assert_eq!(response.body().map(|b| &b[..]), Some(&sdp[..]));
+        assert!(response.encoded.is_empty());
+    }
+
+    #[test]
+    fn test_set_body_keeps_content_type() {
+        let mut request = Request::new(Method::Options, "sip:alice@localhost".parse().unwrap());
+
+        request.set_body(Some(&b"hello"[..]));
+
+        assert!(
+            request
+                .headers
+                .iter()
+                .all(|h| h.as_content_type().is_none())
+        );
+        assert!(request.body.is_some());
     }
-    Ok(())
 }
@@ -0,0 +1,175 @@
+//! Retry of sends that fail with a transient I/O error.
+//!
+//! A full socket buffer (`WouldBlock`, `ENOBUFS`) or an
+//! interrupted write is retried a few times with a growing
+//! delay instead of failing the whole transaction.
+//!
+//! Only sends over unreliable transports are retried: a write
+//! to a stream may have been partially done, resending the
+//! message would corrupt the framing.
+
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use super::Transport;
+use crate::error::{Error, Result};
+
+/// Default number of retries of a failed send.
+pub const DEFAULT_SEND_RETRIES: u32 = 2;
+
+/// Default delay before the first retry.
+pub const DEFAULT_SEND_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Retry settings for sends that fail with a transient I/O
+/// error.
+///
+/// The delay doubles after each retry.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use csip::*;
+/// # use csip::transport::retry::SendRetryConfig;
+/// let endpoint = endpoint::EndpointBuilder::new()
+///     .with_send_retry(SendRetryConfig {
+///         retries: 3,
+///         base_delay: Duration::from_millis(5),
+///     })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendRetryConfig {
+    /// Number of retries after the first attempt, `0` to
+    /// disable retrying.
+    pub retries: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+}
+
+impl SendRetryConfig {
+    /// Runs `send`, running it again while it fails with a
+    /// transient error and retries are left.
+    ///
+    /// Sends over a reliable `transport` are never retried.
+    pub(crate) async fn send<F, Fut>(&self, transport: &Transport, mut send: F) -> Result<usize>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<usize>>,
+    {
+        let max_retries = if transport.is_reliable() {
+            0
+        } else {
+            self.retries
+        };
+        let mut delay = self.base_delay;
+        let mut retries = 0;
+
+        loop {
+            match send().await {
+                Err(err) if retries < max_retries && is_transient(&err) => {
+                    log::debug!("Retrying send in {delay:?} after transient error: {err}");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for SendRetryConfig {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_SEND_RETRIES,
+            base_delay: DEFAULT_SEND_RETRY_DELAY,
+        }
+    }
+}
+
+fn is_transient(err: &Error) -> bool {
+    let Error::Io(err) = err else {
+        return false;
+    };
+
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut
+    ) || is_no_buffer_space(err)
+}
+
+// `ENOBUFS`, returned by UDP sends when the interface queue
+// is full.
+#[cfg(unix)]
+fn is_no_buffer_space(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENOBUFS)
+}
+
+#[cfg(not(unix))]
+fn is_no_buffer_space(_err: &io::Error) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::test_utils::transport::MockTransport;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_configured_retries() {
+        let config = SendRetryConfig {
+            retries: 2,
+            base_delay: Duration::from_millis(10),
+        };
+        let attempts = AtomicU32::new(0);
+        let start = tokio::time::Instant::now();
+        let transport = Transport::new(MockTransport::new_udp());
+
+        let result = config
+            .send(&transport, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(io::Error::from(io::ErrorKind::WouldBlock).into())
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(start.elapsed(), Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_permanent_errors() {
+        let attempts = AtomicU32::new(0);
+        let transport = Transport::new(MockTransport::new_udp());
+
+        let result = SendRetryConfig::default()
+            .send(&transport, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(io::Error::from(io::ErrorKind::ConnectionRefused).into())
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_reliable_transports() {
+        let attempts = AtomicU32::new(0);
+        let transport = Transport::new(MockTransport::new_tcp());
+
+        let result = SendRetryConfig::default()
+            .send(&transport, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(io::Error::from(io::ErrorKind::WouldBlock).into())
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
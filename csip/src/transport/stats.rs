@@ -0,0 +1,99 @@
+//! Transport statistics.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// A snapshot of the transport counters.
+///
+/// Returned by [`Transport::stats`] for a single transport and by
+/// [`TransportManager::stats`] for the whole transport layer.
+///
+/// [`Transport::stats`]: super::Transport::stats
+/// [`TransportManager::stats`]: super::TransportManager::stats
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Number of messages sent.
+    pub messages_sent: u64,
+    /// Number of messages received.
+    pub messages_received: u64,
+    /// Number of bytes sent.
+    pub bytes_sent: u64,
+    /// Number of bytes received.
+    pub bytes_received: u64,
+    /// Number of received packets that failed to parse.
+    pub parse_errors: u64,
+}
+
+/// Atomic counters backing [`TransportStats`].
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    parse_errors: AtomicU64,
+}
+
+impl Counters {
+    fn record_sent(&self, len: usize) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    fn record_received(&self, len: usize) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    fn record_parse_error(&self) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> TransportStats {
+        TransportStats {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            parse_errors: self.parse_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters of a single transport, also accounted to the transport layer
+/// once the transport is registered.
+#[derive(Debug, Default)]
+pub(crate) struct TransportCounters {
+    own: Counters,
+    layer: OnceLock<Arc<Counters>>,
+}
+
+impl TransportCounters {
+    /// Links these counters to the transport layer counters.
+    pub(crate) fn link(&self, layer: Arc<Counters>) {
+        let _ = self.layer.set(layer);
+    }
+
+    pub(crate) fn record_sent(&self, len: usize) {
+        self.for_each(|c| c.record_sent(len));
+    }
+
+    pub(crate) fn record_received(&self, len: usize) {
+        self.for_each(|c| c.record_received(len));
+    }
+
+    pub(crate) fn record_parse_error(&self) {
+        self.for_each(Counters::record_parse_error);
+    }
+
+    pub(crate) fn snapshot(&self) -> TransportStats {
+        self.own.snapshot()
+    }
+
+    fn for_each(&self, f: impl Fn(&Counters)) {
+        f(&self.own);
+        if let Some(layer) = self.layer.get() {
+            f(layer);
+        }
+    }
+}
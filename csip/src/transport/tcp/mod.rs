@@ -1,6 +1,7 @@
 //! TCP transport implementation for SIP.
 
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use tokio::io::{AsyncWriteExt, ReadHalf, WriteHalf, split};
@@ -10,11 +11,14 @@ use tokio_stream::StreamExt;
 use tokio_util::codec::FramedRead;
 
 use super::decode::{FramedMessage, StreamingDecoder};
-use super::{KEEPALIVE_RESPONSE, Packet, SipTransport, Transport, TransportMessage, TransportType};
+use super::{
+    KEEPALIVE_RESPONSE, Packet, SipTransport, Transport, TransportMessage, TransportType, keepalive,
+};
 use crate::Endpoint;
 use crate::error::{Error, Result};
 
 type TcpFrameRead = FramedRead<ReadHalf<TcpStream>, StreamingDecoder>;
+type TcpWriteHalf = Arc<Mutex<WriteHalf<TcpStream>>>;
 type TcpAccept = (TcpStream, SocketAddr);
 
 /// TCP transport implementation.
@@ -26,8 +30,9 @@ pub struct TcpTransport {
     bind_addr: SocketAddr,
     /// Connected remote address.
     remote_addr: SocketAddr,
-    /// The tcp writer.
-    write_half: Mutex<WriteHalf<TcpStream>>,
+    /// The tcp writer, shared with the read loop to shut the
+    /// connection down when it ends.
+    write_half: TcpWriteHalf,
 }
 
 impl TcpTransport {
@@ -44,25 +49,31 @@ impl TcpTransport {
         let decoder = StreamingDecoder::new();
 
         let read_half = FramedRead::new(read, decoder);
-        let write_half = Mutex::new(write);
+        let write_half = Arc::new(Mutex::new(write));
 
         let transport = Transport::new(TcpTransport {
             bind_addr,
             remote_addr,
-            write_half,
+            write_half: write_half.clone(),
         });
 
-        // TODO: Start keep-alive timer.
-        endpoint
-            .transports()
-            .register_transport(transport.clone())?;
+        endpoint.add_transport(transport.clone()).await?;
+
+        // RFC 5626 section 4.4.1: the side that opened the flow
+        // sends the keep-alive pings.
+        let keepalive = endpoint.keepalive().map(|config| {
+            tokio::spawn(keepalive::run(transport.clone(), endpoint.clone(), *config))
+        });
 
         let endpoint = endpoint.clone();
         let tcp = transport.clone();
         tokio::spawn(async move {
-            if let Err(err) = tcp_read(read_half, remote_addr, tcp, endpoint).await {
+            if let Err(err) = tcp_read(read_half, write_half, remote_addr, tcp, endpoint).await {
                 log::warn!("An error occured; error = {:#}", err);
             }
+            if let Some(keepalive) = keepalive {
+                keepalive.abort();
+            }
         });
 
         Ok(transport)
@@ -146,18 +157,16 @@ impl TcpListener {
         let decoder = StreamingDecoder::new();
 
         let read_half = FramedRead::new(read, decoder);
-        let write_half = Mutex::new(write);
+        let write_half = Arc::new(Mutex::new(write));
 
         let transport = Transport::new(TcpTransport {
             bind_addr,
             remote_addr,
-            write_half,
+            write_half: write_half.clone(),
         });
-        endpoint
-            .transports()
-            .register_transport(transport.clone())?;
+        endpoint.add_transport(transport.clone()).await?;
 
-        if let Err(err) = tcp_read(read_half, addr, transport, endpoint).await {
+        if let Err(err) = tcp_read(read_half, write_half, addr, transport, endpoint).await {
             log::warn!("An error occured; error = {:#}", err);
         }
 
@@ -166,13 +175,40 @@ impl TcpListener {
 }
 
 async fn tcp_read(
-    mut framed: TcpFrameRead,
+    framed: TcpFrameRead,
+    write_half: TcpWriteHalf,
     peer: SocketAddr,
     transport: Transport,
     endpoint: Endpoint,
+) -> Result<()> {
+    let result = tcp_read_loop(framed, peer, &transport, &endpoint).await;
+
+    // The read half is dropped here, shutting down the write
+    // half closes the connection even if clones of the
+    // transport are still alive.
+    if let Err(err) = write_half.lock().await.shutdown().await {
+        log::debug!("Failed to shut down TCP connection {}: {}", peer, err);
+    }
+
+    result
+}
+
+async fn tcp_read_loop(
+    mut framed: TcpFrameRead,
+    peer: SocketAddr,
+    transport: &Transport,
+    endpoint: &Endpoint,
 ) -> Result<()> {
     loop {
-        match framed.next().await {
+        let next = tokio::select! {
+            next = framed.next() => next,
+            _ = transport.closed() => {
+                log::info!("TCP connection closed: {}", peer);
+                break;
+            }
+        };
+
+        match next {
             Some(Ok(FramedMessage::Complete(data))) => {
                 let packet = Packet::new(data, peer);
                 let transport = transport.clone();
@@ -183,13 +219,16 @@ async fn tcp_read(
             Some(Ok(FramedMessage::KeepaliveRequest)) => {
                 transport.send_msg(KEEPALIVE_RESPONSE, &peer).await?;
             }
-            Some(Ok(FramedMessage::KeepaliveResponse)) => {}
+            Some(Ok(FramedMessage::KeepaliveResponse)) => {
+                transport.record_pong();
+            }
             Some(Err(err)) => {
+                endpoint.close_transport(transport).await?;
                 return Err(Error::Io(err));
             }
             None => {
                 log::info!("TCP connection disconnected: {}", peer);
-                endpoint.transports().remove_transport(&transport.key())?;
+                endpoint.close_transport(transport).await?;
                 break;
             }
         };
@@ -3,7 +3,9 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use socket2::SockRef;
 use tokio::net::{ToSocketAddrs, UdpSocket};
+use tokio::sync::Mutex;
 
 use super::{Packet, SipTransport, Transport, TransportType};
 use crate::Endpoint;
@@ -14,6 +16,9 @@ use crate::transport::TransportMessage;
 struct UdpInner {
     sock: UdpSocket,
     addr: SocketAddr,
+    // The multicast ttl is set on the whole socket, multicast
+    // sends hold this lock so each one goes out with its own ttl.
+    multicast: Mutex<()>,
 }
 
 /// UDP transport implementation.
@@ -39,18 +44,28 @@ impl UdpTransport {
         let sock = UdpSocket::bind(addr).await?;
         let addr = sock.local_addr()?;
         Ok(Self {
-            inner: Arc::new(UdpInner { sock, addr }),
+            inner: Arc::new(UdpInner {
+                sock,
+                addr,
+                multicast: Mutex::new(()),
+            }),
         })
     }
 
     /// Receive UDP datagrams on this transport.
-    pub(crate) async fn receive_datagram(self, endpoint: Endpoint) -> Result<()> {
-        let udp_tp = Transport::new(self.clone());
+    pub(crate) async fn receive_datagram(
+        self,
+        udp_tp: Transport,
+        endpoint: Endpoint,
+    ) -> Result<()> {
         // Buffer to recv packet.
         let mut buf = vec![0u8; 4000];
         loop {
             // Read data into buf.
-            let (len, source) = self.inner.sock.recv_from(&mut buf).await?;
+            let (len, source) = tokio::select! {
+                received = self.inner.sock.recv_from(&mut buf) => received?,
+                _ = udp_tp.closed() => return Ok(()),
+            };
 
             if len == 0 {
                 log::error!("[{}] Got an empty message from the peer.", source);
@@ -77,6 +92,15 @@ impl SipTransport for UdpTransport {
         Ok(self.inner.sock.send_to(buf, addr).await?)
     }
 
+    async fn send_multicast(&self, buf: &[u8], addr: &SocketAddr, ttl: u32) -> Result<usize> {
+        let _guard = self.inner.multicast.lock().await;
+        match addr {
+            SocketAddr::V4(_) => self.inner.sock.set_multicast_ttl_v4(ttl)?,
+            SocketAddr::V6(_) => SockRef::from(&self.inner.sock).set_multicast_hops_v6(ttl)?,
+        }
+        Ok(self.inner.sock.send_to(buf, addr).await?)
+    }
+
     fn remote_addr(&self) -> Option<SocketAddr> {
         None
     }
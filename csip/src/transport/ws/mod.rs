@@ -319,9 +319,7 @@ where
 
     let (mut send, mut recv) = stream.split();
 
-    endpoint
-        .transports()
-        .register_transport(transport.clone())?;
+    endpoint.add_transport(transport.clone()).await?;
 
     let send_task = tokio::spawn(async move {
         while let Some(msg) = rx.recv().await {
@@ -333,7 +331,12 @@ where
         log::debug!("WebSocket send handler finished for {}", addr);
     });
 
-    while let Some(ws_msg) = recv.next().await {
+    loop {
+        let ws_msg = tokio::select! {
+            Some(ws_msg) = recv.next() => ws_msg,
+            _ = transport.closed() => break,
+            else => break,
+        };
         let data = match ws_msg {
             Ok(WsMessage::Text(text)) => text.into(),
             Ok(WsMessage::Binary(bin)) => bin,
@@ -357,7 +360,7 @@ where
     }
 
     log::info!("WebSocket connection disconnected: {}", addr);
-    endpoint.transports().remove_transport(&transport.key())?;
+    endpoint.close_transport(&transport).await?;
     send_task.abort();
 
     Ok(())
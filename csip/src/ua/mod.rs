@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 
 pub(crate) mod inv;
@@ -7,12 +7,14 @@ use tokio::sync::mpsc;
 
 use crate::dialog::{Dialog, DialogId, DialogMessage};
 
+use crate::message::Request;
 use crate::message::headers::Contact;
-use crate::transport::incoming::IncomingRequest;
+use crate::transport::incoming::{IncomingRequest, IncomingResponse};
 use crate::{Endpoint, Method, Result};
 
 pub struct UserAgent {
     dialogs: Mutex<HashMap<DialogId, mpsc::Sender<DialogMessage>>>,
+    early_dialogs: Mutex<HashSet<DialogId>>,
     endpoint: Endpoint,
 }
 
@@ -21,6 +23,7 @@ impl UserAgent {
         Self {
             endpoint,
             dialogs: Default::default(),
+            early_dialogs: Default::default(),
         }
     }
 
@@ -41,12 +44,54 @@ impl UserAgent {
         Ok(dialog)
     }
 
+    /// Creates a UAC dialog from the sent `request` and its
+    /// `101-199` or `2xx` `response`.
+    pub fn new_uac_dialog(&self, request: &Request, response: &IncomingResponse) -> Result<Dialog> {
+        Dialog::create_uac(self, request, response)
+    }
+
     pub(crate) fn add_dialog(&self, dialog_id: DialogId, dialog: mpsc::Sender<DialogMessage>) {
         let mut dialogs = self.dialogs.lock().expect("Lock failed");
 
         dialogs.insert(dialog_id, dialog);
     }
 
+    pub(crate) fn add_early_dialog(
+        &self,
+        dialog_id: DialogId,
+        dialog: mpsc::Sender<DialogMessage>,
+    ) {
+        let mut early_dialogs = self.early_dialogs.lock().expect("Lock failed");
+
+        self.add_dialog(dialog_id.clone(), dialog);
+        early_dialogs.insert(dialog_id);
+    }
+
+    /// Marks the dialog as confirmed, discarding the other early
+    /// dialogs created by the same request.
+    pub(crate) fn confirm_dialog(&self, dialog_id: &DialogId) {
+        let mut early_dialogs = self.early_dialogs.lock().expect("Lock failed");
+        let mut dialogs = self.dialogs.lock().expect("Lock failed");
+
+        early_dialogs.retain(|early| {
+            if early == dialog_id {
+                return false;
+            }
+            if early.is_same_request(dialog_id) {
+                dialogs.remove(early);
+                return false;
+            }
+            true
+        });
+    }
+
+    /// Returns the ids of the early dialogs.
+    pub fn early_dialogs(&self) -> Vec<DialogId> {
+        let early_dialogs = self.early_dialogs.lock().expect("Lock failed");
+
+        early_dialogs.iter().cloned().collect()
+    }
+
     fn find_dialog_from_incoming(&self, request: &IncomingRequest) -> Option<mpsc::Sender<DialogMessage>> {
         let Some(dialog_id) = DialogId::from_incoming_request(request) else {
             return None;
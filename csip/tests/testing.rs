@@ -0,0 +1,116 @@
+//! Drives an [`EndpointHandler`] through the mock transport of
+//! the `testing` module, as a downstream service would.
+
+use std::net::SocketAddr;
+
+use csip::endpoint::{Endpoint, EndpointBuilder};
+use csip::message::StatusCode;
+use csip::message::headers::Header;
+use csip::testing::{self, MockTransport};
+use csip::transport::incoming::IncomingRequest;
+use csip::transport::{SipTransport, Transport, TransportManager};
+use csip::{EndpointHandler, Method};
+
+const OPTIONS: &str = "OPTIONS sip:bob@127.0.0.1 SIP/2.0\r\n\
+Via: SIP/2.0/UDP 127.0.0.1:5070;branch=z9hG4bK776asdhds\r\n\
+From: <sip:alice@127.0.0.1>;tag=1928301774\r\n\
+To: <sip:bob@127.0.0.1>\r\n\
+Call-ID: a84b4c76e66710\r\n\
+CSeq: 1 OPTIONS\r\n\
+Content-Length: 0\r\n\r\n";
+
+/// Accepts `OPTIONS` and rejects every other method.
+struct OptionsService;
+
+#[async_trait::async_trait]
+impl EndpointHandler for OptionsService {
+    async fn handle(&self, request: IncomingRequest, endpoint: &Endpoint) {
+        let code = match request.request.method() {
+            Method::Options => StatusCode::Ok,
+            _ => StatusCode::MethodNotAllowed,
+        };
+        endpoint.respond(&request, code, None).await.unwrap();
+    }
+}
+
+fn create_endpoint(mock: &MockTransport) -> (Endpoint, Transport) {
+    let transport = Transport::new(mock.clone());
+    let transports = TransportManager::new();
+    transports.register_transport(transport.clone()).unwrap();
+
+    let endpoint = EndpointBuilder::new()
+        .with_handler(OptionsService)
+        .with_transport(transports)
+        .build();
+
+    (endpoint, transport)
+}
+
+fn source() -> SocketAddr {
+    "127.0.0.1:5070".parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_service_responds_through_mock_transport() {
+    let mock = MockTransport::new_udp();
+    let (endpoint, transport) = create_endpoint(&mock);
+
+    testing::receive(&endpoint, &transport, source(), OPTIONS)
+        .await
+        .unwrap();
+
+    let response = mock.get_last_sent_response().unwrap();
+    let cseq = response
+        .headers()
+        .iter()
+        .find_map(Header::as_c_seq)
+        .unwrap();
+    assert_eq!(mock.sent_count(), 1);
+    assert_eq!(response.status(), StatusCode::Ok);
+    assert_eq!(*cseq.method(), Method::Options);
+}
+
+#[tokio::test]
+async fn test_service_rejects_other_methods() {
+    let mock = MockTransport::new_udp();
+    let (endpoint, transport) = create_endpoint(&mock);
+    let message = OPTIONS.replace("OPTIONS", "MESSAGE");
+
+    testing::receive(&endpoint, &transport, source(), message)
+        .await
+        .unwrap();
+
+    let response = mock.get_last_sent_response().unwrap();
+    assert_eq!(response.status(), StatusCode::MethodNotAllowed);
+}
+
+#[tokio::test]
+async fn test_incoming_request_can_be_handled_directly() {
+    let mock = MockTransport::new_udp();
+    let (endpoint, transport) = create_endpoint(&mock);
+    let request = testing::incoming_request(&transport, source(), OPTIONS).unwrap();
+
+    OptionsService.handle(request, &endpoint).await;
+
+    let sent = mock.sent_messages();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].response().unwrap().status(), StatusCode::Ok);
+}
+
+#[tokio::test]
+async fn test_keep_alives_are_not_sent_messages() {
+    let mock = MockTransport::new_udp();
+    let (endpoint, transport) = create_endpoint(&mock);
+
+    testing::receive(&endpoint, &transport, source(), OPTIONS)
+        .await
+        .unwrap();
+    mock.send_msg(b"\r\n\r\n", &source()).await.unwrap();
+
+    assert_eq!(mock.sent_count(), 2);
+    assert_eq!(mock.sent_messages().len(), 1);
+    assert_eq!(
+        mock.get_last_sent_response().unwrap().status(),
+        StatusCode::Ok
+    );
+}
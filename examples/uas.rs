@@ -60,5 +60,6 @@ async fn main() -> std::result::Result<(), Box<dyn Error>> {
         }
         }
     }
+    endpoint.shutdown(Duration::from_secs(5)).await?;
     Ok(())
 }
@@ -0,0 +1,63 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use utils::Scanner;
+
+const HEADER: &[u8] = b"Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bKnashds8\r\n";
+
+fn header_block() -> Vec<u8> {
+    HEADER.iter().copied().cycle().take(4096).collect()
+}
+
+fn bench_read_until(c: &mut Criterion) {
+    let block = header_block();
+    let mut group = c.benchmark_group("4KB header block");
+
+    group.bench_function("scalar read_while", |b| {
+        b.iter(|| {
+            let mut scanner = Scanner::new(black_box(&block));
+            while !scanner.remaining().is_empty() {
+                black_box(scanner.read_while(|b| b != b'\r' && b != b'\n'));
+                scanner.read_while(|b| b == b'\r' || b == b'\n');
+            }
+        })
+    });
+    group.bench_function("swar read_until_either", |b| {
+        b.iter(|| {
+            let mut scanner = Scanner::new(black_box(&block));
+            while !scanner.remaining().is_empty() {
+                black_box(scanner.read_until_either(b'\r', b'\n'));
+                scanner.read_while(|b| b == b'\r' || b == b'\n');
+            }
+        })
+    });
+
+    let mut token = [false; 256];
+    for b in (b'a'..=b'z').chain(b'A'..=b'Z').chain(b'0'..=b'9') {
+        token[b as usize] = true;
+    }
+    for &b in b"-.!%*_+`'~" {
+        token[b as usize] = true;
+    }
+    group.bench_function("scalar read_while token", |b| {
+        b.iter(|| {
+            let mut scanner = Scanner::new(black_box(&block));
+            while !scanner.remaining().is_empty() {
+                black_box(scanner.read_while(|b| token[b as usize]));
+                scanner.next_byte();
+            }
+        })
+    });
+    group.bench_function("table read_while_in token", |b| {
+        b.iter(|| {
+            let mut scanner = Scanner::new(black_box(&block));
+            while !scanner.remaining().is_empty() {
+                black_box(scanner.read_while_in(&token));
+                scanner.next_byte();
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_until);
+criterion_main!(benches);
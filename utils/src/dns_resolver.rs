@@ -1,7 +1,10 @@
 //! DNS resolve with the `DnsResolver` type.
 
+use std::collections::HashMap;
 use std::io;
 use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
 
 pub use hickory_resolver::Name;
 use hickory_resolver::lookup::Lookup;
@@ -11,9 +14,17 @@ use hickory_resolver::proto::rr::RecordType;
 pub use hickory_resolver::proto::rr::rdata::{NAPTR, SRV};
 use hickory_resolver::{IntoName, ResolveError};
 
+/// Default number of hosts kept by the [`DnsResolver`] cache.
+pub const DEFAULT_DNS_CACHE_CAPACITY: usize = 256;
+
 /// A DNS resolver backed by [hickory-dns](https://github.com/hickory-dns/hickory-dns).
+///
+/// The addresses returned by [`DnsResolver::resolve`] and
+/// [`DnsResolver::resolve_all`] are cached until the TTL of the
+/// DNS records expires.
 pub struct DnsResolver {
     dns_resolver: hickory_resolver::TokioResolver,
+    cache: Mutex<DnsCache>,
 }
 
 impl DnsResolver {
@@ -42,32 +53,164 @@ impl DnsResolver {
 
     /// Resolve a single.
     pub async fn resolve(&self, host: &str) -> Result<IpAddr, io::Error> {
-        Ok(self
-            .lookup_ip(host)
-            .await
-            .map_err(|err| io::Error::other(format!("Failed to lookup DNS: {}", err)))?
-            .iter()
-            .next()
-            .unwrap())
+        Ok(self.resolve_all(host).await?[0])
     }
 
     /// Resolve a all.
     pub async fn resolve_all(&self, host: &str) -> Result<Vec<IpAddr>, io::Error> {
+        if let Some(addresses) = self.cache.lock().unwrap().get(host, Instant::now()) {
+            return Ok(addresses);
+        }
         let result = self
             .lookup_ip(host)
             .await
             .map_err(|err| io::Error::other(format!("Failed to lookup dns: {}", err)))?;
 
-        let addresses = result.iter().collect();
+        let addresses: Vec<IpAddr> = result.iter().collect();
+        if addresses.is_empty() {
+            return Err(io::Error::other(format!("No address found for {host}")));
+        }
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(host, addresses.clone(), result.valid_until());
 
         Ok(addresses)
     }
 }
 
+/// A LRU cache of resolved addresses, each entry expires with
+/// the TTL of its DNS records.
+struct DnsCache {
+    entries: HashMap<String, CacheEntry>,
+    capacity: usize,
+    // Incremented on every access, orders the entries by use.
+    clock: u64,
+}
+
+struct CacheEntry {
+    addresses: Vec<IpAddr>,
+    valid_until: Instant,
+    last_used: u64,
+}
+
+impl DnsCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, host: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        let key = cache_key(host);
+        let entry = self.entries.get_mut(&key)?;
+
+        if entry.valid_until <= now {
+            self.entries.remove(&key);
+            return None;
+        }
+        self.clock += 1;
+        entry.last_used = self.clock;
+
+        Some(entry.addresses.clone())
+    }
+
+    fn insert(&mut self, host: &str, addresses: Vec<IpAddr>, valid_until: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = cache_key(host);
+
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                self.entries.remove(&lru);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                addresses,
+                valid_until,
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+// Host names are case insensitive and may be fully qualified.
+fn cache_key(host: &str) -> String {
+    let host = host.strip_suffix('.').unwrap_or(host);
+
+    host.to_ascii_lowercase()
+}
+
 impl Default for DnsResolver {
     fn default() -> Self {
         Self {
             dns_resolver: hickory_resolver::Resolver::builder_tokio().unwrap().build(),
+            cache: Mutex::new(DnsCache::new(DEFAULT_DNS_CACHE_CAPACITY)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use super::*;
+
+    const ADDR: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    #[tokio::test]
+    async fn test_resolve_within_ttl_hits_cache() {
+        let resolver = DnsResolver::default();
+        let valid_until = Instant::now() + Duration::from_secs(60);
+        // `.invalid` never resolves (RFC 6761), only the cache can answer.
+        resolver
+            .cache
+            .lock()
+            .unwrap()
+            .insert("sip.example.invalid", vec![ADDR], valid_until);
+
+        assert_eq!(resolver.resolve("sip.example.invalid").await.unwrap(), ADDR);
+        assert_eq!(
+            resolver.resolve("SIP.example.invalid.").await.unwrap(),
+            ADDR
+        );
+    }
+
+    #[test]
+    fn test_expired_entry_is_evicted() {
+        let mut cache = DnsCache::new(4);
+        let now = Instant::now();
+        cache.insert("example.com", vec![ADDR], now + Duration::from_secs(1));
+
+        assert_eq!(cache.get("example.com", now), Some(vec![ADDR]));
+        assert_eq!(cache.get("example.com", now + Duration::from_secs(1)), None);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_entry_is_evicted() {
+        let mut cache = DnsCache::new(2);
+        let valid_until = Instant::now() + Duration::from_secs(60);
+        cache.insert("a.example.com", vec![ADDR], valid_until);
+        cache.insert("b.example.com", vec![ADDR], valid_until);
+        cache.get("a.example.com", Instant::now());
+
+        cache.insert("c.example.com", vec![ADDR], valid_until);
+
+        assert!(cache.entries.contains_key("a.example.com"));
+        assert!(!cache.entries.contains_key("b.example.com"));
+        assert!(cache.entries.contains_key("c.example.com"));
+    }
+}
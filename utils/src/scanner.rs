@@ -1,11 +1,20 @@
 //! Text scanning with the `Scanner` type.
 
+use std::borrow::Cow;
+
 type Result<T> = std::result::Result<T, ScannerError>;
 
 /// A text scanner for sequentially reading bytes from an input slice.
 ///
 /// The `Scanner` provides methods to iterate over the input while
 /// tracking the current position in terms of line and column numbers.
+///
+/// # Invariant
+///
+/// `index <= len == buffer.len()` holds at all times: `index` only moves
+/// forward one byte at a time after a successful [`Scanner::peek_byte`], or
+/// by at most `remaining().len()` bytes. The unchecked slicing inside the
+/// scanner relies on it.
 pub struct Scanner<'buf> {
     /// The input byte slice being scanned.
     buffer: &'buf [u8],
@@ -70,6 +79,19 @@ impl<'buf> Scanner<'buf> {
         &self.position
     }
 
+    /// Returns the number of bytes consumed so far.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the whole input buffer, including the bytes
+    /// already consumed.
+    #[inline]
+    pub fn buffer(&self) -> &'buf [u8] {
+        self.buffer
+    }
+
     /// Returns `true` if the upcoming bytes match the given `prefix`.
     #[inline]
     pub fn matches_prefix(&self, prefix: &[u8]) -> bool {
@@ -86,6 +108,7 @@ impl<'buf> Scanner<'buf> {
 
     fn read_number_str(&mut self) -> &'buf str {
         let bytes = self.read_while(|b| b.is_ascii_digit() || b == b'.');
+        debug_assert!(bytes.is_ascii());
         // SAFETY: `bytes` contains only ASCII digits (0–9) and optionally '.',
         // all of which are valid single-byte UTF-8 characters.
         unsafe { std::str::from_utf8_unchecked(bytes) }
@@ -133,10 +156,18 @@ impl<'buf> Scanner<'buf> {
                 break;
             }
         }
-        let end = self.index;
-        // SAFETY: We ensure `start..end` is valid because we only next_byted indexes
-        // within the buffer bounds.
-        unsafe { self.buffer.get_unchecked(start..end) }
+        self.slice(start, self.index)
+    }
+
+    /// Reads bytes while `table[byte]` is `true`.
+    ///
+    /// Same as `read_while(|b| table[b as usize])`, but long runs are
+    /// checked 8 bytes at a time and the position is updated once.
+    pub fn read_while_in(&mut self, table: &[bool; 256]) -> &'buf [u8] {
+        let remaining = self.remaining_buf();
+        let n = span_in(remaining, table);
+
+        self.consume(n)
     }
 
     /// Reads a slice between two occurrences of byte `c`.
@@ -147,10 +178,7 @@ impl<'buf> Scanner<'buf> {
         self.read_while(|b| b != c);
         self.must_read(c).ok()?;
 
-        let end = self.index;
-        // SAFETY: We ensure `start..end` is valid because we only next_byted indexes
-        // within the buffer bounds.
-        Some(unsafe { self.buffer.get_unchecked(start..end) })
+        Some(self.slice(start, self.index))
     }
 
     /// peek_byte bytes in the buffer while the `predicate` returns true.
@@ -164,9 +192,7 @@ impl<'buf> Scanner<'buf> {
             .position(|&b| !predicate(b))
             .unwrap_or(buffer.len());
 
-        // SAFETY: `n` is guaranteed to be ≤ `buffer.len()`, so the range is always
-        // in-bounds.
-        unsafe { buffer.get_unchecked(..n) }
+        &buffer[..n]
     }
 
     /// Peek next byte if `condition` returns `true`.
@@ -175,8 +201,13 @@ impl<'buf> Scanner<'buf> {
     }
 
     /// Read next bytes if equals to `expected`
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` on the first mismatching byte, or
+    /// `ScannerError::Eof` if the buffer ends before `expected` does.
     pub fn must_read_bytes(&mut self, expected: &[u8]) -> Result<()> {
-        let remaining = &self.buffer[self.index..];
+        let remaining = self.remaining_buf();
         let iter = remaining.iter().zip(expected);
         for (&found, &expected) in iter {
             if found != expected {
@@ -184,6 +215,9 @@ impl<'buf> Scanner<'buf> {
             }
             self.bump(found);
         }
+        if remaining.len() < expected.len() {
+            return Err(ScannerError::Eof);
+        }
         Ok(())
     }
 
@@ -207,9 +241,107 @@ impl<'buf> Scanner<'buf> {
     /// Reads bytes until the next byte equals `byte`.
     ///
     /// The matching byte is not consumed.
+    ///
+    /// Long inputs are searched a word at a time instead of byte by byte.
     #[inline]
     pub fn read_until(&mut self, byte: u8) -> &'buf [u8] {
-        self.read_while(|b| b != byte)
+        self.read_until_either(byte, byte)
+    }
+
+    /// Reads bytes until the next byte equals `a` or `b`.
+    ///
+    /// The matching byte is not consumed.
+    pub fn read_until_either(&mut self, a: u8, b: u8) -> &'buf [u8] {
+        let remaining = self.remaining_buf();
+        let n = find_either(remaining, a, b).unwrap_or(remaining.len());
+
+        self.consume(n)
+    }
+
+    /// Reads bytes until the end of the line, a `\r\n` or a
+    /// bare `\n`.
+    ///
+    /// The line terminator is not consumed, see
+    /// [`Scanner::advance_if_crlf_or_lf`]. A `\r` not followed
+    /// by `\n` is part of the line.
+    pub fn read_until_crlf_or_lf(&mut self) -> &'buf [u8] {
+        let remaining = self.remaining_buf();
+        let n = match find_either(remaining, b'\n', b'\n') {
+            Some(lf) if lf > 0 && remaining[lf - 1] == b'\r' => lf - 1,
+            Some(lf) => lf,
+            None => remaining.len(),
+        };
+        let bytes = &remaining[..n];
+
+        self.index += n;
+        self.position.column += n;
+
+        bytes
+    }
+
+    /// Consumes a line terminator, a `\r\n` or a bare `\n`.
+    ///
+    /// Returns `true` if a line terminator was consumed.
+    pub fn advance_if_crlf_or_lf(&mut self) -> bool {
+        match self.remaining_buf() {
+            [b'\r', b'\n', ..] => {
+                self.bump(b'\r');
+                self.bump(b'\n');
+                true
+            }
+            [b'\n', ..] => {
+                self.bump(b'\n');
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads a `quoted-string` as defined in RFC 3261.
+    ///
+    /// Consumes the opening and closing `"` and unescapes any `quoted-pair`
+    /// (`\` followed by a byte). The content is borrowed from the buffer
+    /// unless it contains escapes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the next byte is not `"`, the closing quote is
+    /// missing, or the content is not valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use utils::Scanner;
+    /// let mut scanner = Scanner::new(br#""say \"hi\"" rest"#);
+    ///
+    /// assert_eq!(scanner.read_quoted_string().unwrap(), r#"say "hi""#);
+    /// assert_eq!(scanner.remaining(), b" rest");
+    /// ```
+    pub fn read_quoted_string(&mut self) -> Result<Cow<'buf, str>> {
+        self.must_read(b'"')?;
+
+        let mut unescaped: Option<Vec<u8>> = None;
+        loop {
+            let bytes = self.read_until_either(b'"', b'\\');
+            match self.next_byte() {
+                Some(b'"') => {
+                    let Some(mut owned) = unescaped else {
+                        let text = std::str::from_utf8(bytes);
+                        return text.map(Cow::Borrowed).or(Err(ScannerError::InvalidUtf8));
+                    };
+                    owned.extend_from_slice(bytes);
+                    let text = String::from_utf8(owned);
+                    return text.map(Cow::Owned).or(Err(ScannerError::InvalidUtf8));
+                }
+                Some(_) => {
+                    let escaped = self.next_byte().ok_or(ScannerError::Eof)?;
+                    let owned = unescaped.get_or_insert_with(Vec::new);
+                    owned.extend_from_slice(bytes);
+                    owned.push(escaped);
+                }
+                None => return Err(ScannerError::Eof),
+            }
+        }
     }
 
     /// Reads bytes while `predicate` returns true and converts them to a string
@@ -238,6 +370,10 @@ impl<'buf> Scanner<'buf> {
         predicate: impl Fn(u8) -> bool,
     ) -> &'buf str {
         let bytes = self.read_while(predicate);
+        debug_assert!(
+            std::str::from_utf8(bytes).is_ok(),
+            "predicate matched bytes that are not valid UTF-8"
+        );
 
         // SAFETY: The caller guarantees that `predicate` only matches bytes forming valid
         // UTF-8.
@@ -267,8 +403,50 @@ impl<'buf> Scanner<'buf> {
         self.next_byte_if(|b| b == expected)
     }
 
+    /// Returns the unread part of the buffer with the `'buf` lifetime.
+    #[inline(always)]
+    fn remaining_buf(&self) -> &'buf [u8] {
+        self.slice(self.index, self.len)
+    }
+
+    /// Returns `buffer[start..end]` without a bounds check in release builds.
+    ///
+    /// Callers only pass offsets previously held by `self.index`, so by the
+    /// type invariant `start <= end <= len`. The check is still performed in
+    /// debug builds to catch a logic error in a new caller.
+    #[inline(always)]
+    fn slice(&self, start: usize, end: usize) -> &'buf [u8] {
+        debug_assert!(
+            start <= end && end <= self.len,
+            "scanner slice {start}..{end} out of bounds (len {})",
+            self.len
+        );
+        // SAFETY: `start <= end <= self.len == self.buffer.len()`, see above.
+        unsafe { self.buffer.get_unchecked(start..end) }
+    }
+
+    /// Consumes the next `n` bytes and returns them, updating the
+    /// position once for all of them.
+    fn consume(&mut self, n: usize) -> &'buf [u8] {
+        let bytes = &self.remaining_buf()[..n];
+
+        self.index += n;
+        match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(last) => {
+                self.position.line += bytes.iter().filter(|&&b| b == b'\n').count();
+                self.position.column = n - last;
+            }
+            None => self.position.column += n,
+        }
+
+        bytes
+    }
+
+    /// Advances past `byte`, which must be the byte at `self.index`.
     #[inline(always)]
     fn bump(&mut self, byte: u8) {
+        debug_assert!(self.index < self.len);
+        debug_assert_eq!(self.buffer[self.index], byte);
         self.index += 1;
         if byte == b'\n' {
             self.position.column = 1;
@@ -279,12 +457,77 @@ impl<'buf> Scanner<'buf> {
     }
 }
 
+/// Inputs shorter than this are searched byte by byte.
+const SWAR_THRESHOLD: usize = 16;
+
+const LO: u64 = u64::from_ne_bytes([0x01; 8]);
+const HI: u64 = u64::from_ne_bytes([0x80; 8]);
+
+/// Returns the index of the first byte equal to `a` or `b`.
+///
+/// Searches 8 bytes at a time using the "has zero byte" trick: for each word,
+/// `(w - LO) & !w & HI` has the high bit set in the lowest byte that is zero.
+#[inline]
+fn find_either(bytes: &[u8], a: u8, b: u8) -> Option<usize> {
+    if bytes.len() < SWAR_THRESHOLD {
+        return bytes.iter().position(|&c| c == a || c == b);
+    }
+    let pattern_a = LO * a as u64;
+    let pattern_b = LO * b as u64;
+    let has_zero = |w: u64| w.wrapping_sub(LO) & !w & HI;
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut offset = 0;
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        let found = has_zero(word ^ pattern_a) | has_zero(word ^ pattern_b);
+        if found != 0 {
+            return Some(offset + (found.trailing_zeros() / 8) as usize);
+        }
+        offset += 8;
+    }
+
+    chunks
+        .remainder()
+        .iter()
+        .position(|&c| c == a || c == b)
+        .map(|i| offset + i)
+}
+
+/// Returns the length of the prefix of `bytes` whose bytes are all in
+/// `table`.
+///
+/// Looks up 8 bytes per iteration and only branches once per word, so the
+/// table lookups of a word can run in parallel.
+#[inline]
+fn span_in(bytes: &[u8], table: &[bool; 256]) -> usize {
+    let scalar = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .position(|&b| !table[b as usize])
+            .unwrap_or(bytes.len())
+    };
+    if bytes.len() < SWAR_THRESHOLD {
+        return scalar(bytes);
+    }
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut offset = 0;
+    for chunk in &mut chunks {
+        let all = chunk.iter().fold(true, |all, &b| all & table[b as usize]);
+        if !all {
+            return offset + scalar(chunk);
+        }
+        offset += 8;
+    }
+
+    offset + scalar(chunks.remainder())
+}
+
 impl AsRef<[u8]> for Scanner<'_> {
     #[inline]
     fn as_ref(&self) -> &[u8] {
-        // SAFETY: `self.index..self.len` is guaranteed to be within the bounds of
-        // `self.buffer`.
-        unsafe { self.buffer.get_unchecked(self.index..self.len) }
+        self.remaining_buf()
     }
 }
 
@@ -422,4 +665,107 @@ mod tests {
         let err = scanner.read_f32().unwrap_err();
         assert_eq!(err, ScannerError::InvalidNumber);
     }
+
+    #[test]
+    fn test_read_until_matches_read_while() {
+        let src = b"Via: SIP/2.0/UDP pc33.atlanta.com;branch=z9hG4bK\nnashds8\r\nrest";
+        for needle in [b';', b'\r', b'k', b'#'] {
+            let mut fast = Scanner::new(src);
+            let mut slow = Scanner::new(src);
+
+            assert_eq!(fast.read_until(needle), slow.read_while(|b| b != needle));
+            assert_eq!(fast.position(), slow.position());
+            assert_eq!(fast.remaining(), slow.remaining());
+        }
+    }
+
+    #[test]
+    fn test_read_while_in_matches_read_while() {
+        let mut table = [false; 256];
+        for b in (b'a'..=b'z').chain(b'0'..=b'9').chain(*b".-\n") {
+            table[b as usize] = true;
+        }
+        let src = b"pc33.atlanta-example.com\nbigbox3.site3.atlanta.com;lr";
+        for len in 0..=src.len() {
+            let mut fast = Scanner::new(&src[..len]);
+            let mut slow = Scanner::new(&src[..len]);
+
+            assert_eq!(
+                fast.read_while_in(&table),
+                slow.read_while(|b| table[b as usize])
+            );
+            assert_eq!(fast.position(), slow.position());
+            assert_eq!(fast.remaining(), slow.remaining());
+        }
+    }
+
+    #[test]
+    fn test_read_until_either_stops_at_first_match() {
+        let mut scanner = Scanner::new(b"Subject: a long subject line\r\nnext");
+
+        assert_eq!(
+            scanner.read_until_either(b'\r', b'\n'),
+            b"Subject: a long subject line"
+        );
+        assert_eq!(scanner.remaining(), b"\r\nnext");
+    }
+
+    #[test]
+    fn test_read_until_crlf_or_lf_accepts_both_terminators() {
+        let mut scanner = Scanner::new(b"To: a\r\nFrom: b\nCall-ID: c\rd\r\n");
+
+        assert_eq!(scanner.read_until_crlf_or_lf(), b"To: a");
+        assert!(scanner.advance_if_crlf_or_lf());
+        assert_eq!(scanner.read_until_crlf_or_lf(), b"From: b");
+        assert!(scanner.advance_if_crlf_or_lf());
+        assert_eq!(scanner.read_until_crlf_or_lf(), b"Call-ID: c\rd");
+        assert!(scanner.advance_if_crlf_or_lf());
+        assert!(!scanner.advance_if_crlf_or_lf());
+        assert_eq!(scanner.position().line, 4);
+        assert!(scanner.remaining().is_empty());
+    }
+
+    #[test]
+    fn test_read_quoted_string_without_escapes_borrows() {
+        let mut scanner = Scanner::new(b"\"Alice\";tag=1");
+        let text = scanner.read_quoted_string().unwrap();
+
+        assert!(matches!(text, Cow::Borrowed("Alice")));
+        assert_eq!(scanner.remaining(), b";tag=1");
+    }
+
+    #[test]
+    fn test_read_quoted_string_unescapes_quote_and_backslash() {
+        let mut scanner = Scanner::new(br#""a \"quoted\" \\ path" rest"#);
+
+        assert_eq!(
+            scanner.read_quoted_string().unwrap(),
+            r#"a "quoted" \ path"#
+        );
+        assert_eq!(scanner.remaining(), b" rest");
+    }
+
+    #[test]
+    fn test_reads_at_end_of_buffer_return_empty() {
+        let mut scanner = Scanner::new(b"ab");
+        scanner.advance_by(10);
+
+        assert_eq!(scanner.offset(), 2);
+        assert_eq!(scanner.remaining(), b"");
+        assert_eq!(scanner.peek_byte(), None);
+        assert_eq!(scanner.peek_bytes(1), None);
+        assert_eq!(scanner.read_while(|_| true), b"");
+        assert_eq!(scanner.read_until(b'x'), b"");
+        assert_eq!(scanner.peek_while(|_| true), b"");
+        assert_eq!(scanner.read_between(b'"'), None);
+        assert_eq!(scanner.must_read_bytes(b"SIP"), Err(ScannerError::Eof));
+        assert_eq!(scanner.offset(), 2);
+    }
+
+    #[test]
+    fn test_read_quoted_string_fails_without_closing_quote() {
+        let mut scanner = Scanner::new(br#""unterminated \""#);
+
+        assert_eq!(scanner.read_quoted_string(), Err(ScannerError::Eof));
+    }
 }